@@ -1,11 +1,21 @@
+pub mod abort;
 pub mod args;
+pub mod clean;
+pub mod clipboard;
 pub mod editor;
+pub mod glyphs;
+pub mod history;
+pub mod output;
 pub mod progress;
 pub mod split_view;
 pub mod ui;
+pub mod verbosity;
 
 pub use args::*;
+pub use clean::*;
+pub use clipboard::*;
 pub use editor::*;
+pub use history::*;
 pub use progress::*;
 pub use split_view::*;
 pub use ui::*;
@@ -0,0 +1,144 @@
+use std::io::IsTerminal;
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Mutex};
+use std::thread::JoinHandle;
+use std::time::Duration;
+
+use ratatui::crossterm::event::{self, Event, KeyCode, KeyEvent, KeyEventKind, KeyModifiers};
+use ratatui::crossterm::terminal;
+
+use super::verbosity::{plain, quiet};
+use crate::domain::Abort;
+
+/// How often the listener checks whether the agents finished
+const POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// What happens to the agents' changes after the run was aborted
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AbortChoice {
+    /// Remove the worktrees with everything the agents changed
+    Discard,
+    /// Save what each agent changed so far as a patch before removing its worktree
+    KeepPartial,
+}
+
+impl AbortChoice {
+    /// The choice made by pressing `key` while agents run: x or Ctrl-C discards, k keeps
+    #[must_use]
+    pub fn for_key(key: KeyEvent) -> Option<Self> {
+        if key.kind != KeyEventKind::Press {
+            return None;
+        }
+        match key.code {
+            KeyCode::Char('c') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                Some(Self::Discard)
+            }
+            KeyCode::Char('x') => Some(Self::Discard),
+            KeyCode::Char('k') => Some(Self::KeepPartial),
+            _ => None,
+        }
+    }
+}
+
+/// Reads keys while agents run, until [`AbortKeys::finish`] or dropped
+///
+/// The terminal is in raw mode meanwhile, so a key works without Enter. Ctrl-C is read
+/// as a key then, and aborts like x instead of killing parari.
+#[derive(Debug)]
+pub struct AbortKeys {
+    done: Arc<AtomicBool>,
+    choice: Arc<Mutex<Option<AbortChoice>>>,
+    thread: Option<JoinHandle<()>>,
+}
+
+impl AbortKeys {
+    /// Stop reading keys and return the choice, if a key aborted the run
+    #[must_use]
+    pub fn finish(mut self) -> Option<AbortChoice> {
+        self.stop();
+        self.choice.lock().ok().and_then(|choice| *choice)
+    }
+
+    fn stop(&mut self) {
+        self.done.store(true, Ordering::Relaxed);
+        if let Some(thread) = self.thread.take() {
+            let _ = thread.join();
+            let _ = terminal::disable_raw_mode();
+        }
+    }
+}
+
+impl Drop for AbortKeys {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Trigger `abort` when x, k, or Ctrl-C is pressed while agents run
+///
+/// Returns `None` without a terminal to read keys from, e.g. in CI or when the
+/// output is piped.
+#[must_use]
+pub fn listen(abort: &Abort) -> Option<AbortKeys> {
+    if plain() || !std::io::stdin().is_terminal() {
+        return None;
+    }
+    terminal::enable_raw_mode().ok()?;
+    if !quiet() {
+        // Raw mode does not return the cursor to the start of the line on \n
+        eprint!("Press x to stop all agents, or k to stop them and keep their changes\r\n\r\n");
+    }
+
+    let done = Arc::new(AtomicBool::new(false));
+    let choice = Arc::new(Mutex::new(None));
+    let thread = {
+        let (done, choice, abort) = (Arc::clone(&done), Arc::clone(&choice), abort.clone());
+        std::thread::spawn(move || {
+            while !done.load(Ordering::Relaxed) {
+                match event::poll(POLL_INTERVAL) {
+                    Ok(true) => {}
+                    Ok(false) => continue,
+                    Err(_) => return,
+                }
+                if let Ok(Event::Key(key)) = event::read()
+                    && let Some(chosen) = AbortChoice::for_key(key)
+                {
+                    if let Ok(mut choice) = choice.lock() {
+                        *choice = Some(chosen);
+                    }
+                    abort.trigger();
+                    return;
+                }
+            }
+        })
+    };
+    Some(AbortKeys {
+        done,
+        choice,
+        thread: Some(thread),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_abort_choice_for_key() {
+        let press = |code, modifiers| AbortChoice::for_key(KeyEvent::new(code, modifiers));
+        assert_eq!(
+            press(KeyCode::Char('x'), KeyModifiers::NONE),
+            Some(AbortChoice::Discard)
+        );
+        assert_eq!(
+            press(KeyCode::Char('c'), KeyModifiers::CONTROL),
+            Some(AbortChoice::Discard)
+        );
+        assert_eq!(
+            press(KeyCode::Char('k'), KeyModifiers::NONE),
+            Some(AbortChoice::KeepPartial)
+        );
+        assert_eq!(press(KeyCode::Char('c'), KeyModifiers::NONE), None);
+        assert_eq!(press(KeyCode::Enter, KeyModifiers::NONE), None);
+    }
+}
@@ -1,27 +1,542 @@
-use clap::Parser;
+use std::path::PathBuf;
+
+use clap::builder::BoolishValueParser;
+use clap::error::ErrorKind;
+use clap::parser::ValueSource;
+use clap::{ArgMatches, CommandFactory, FromArgMatches, Parser, Subcommand, ValueEnum};
+
+use crate::config::Age;
+use crate::domain::RunOptions;
+use crate::integrations::ForgeKind;
 
 /// Run AI CLI tools in parallel using git worktrees
-#[derive(Parser, Debug)]
+#[derive(Parser, Debug, Clone)]
 #[command(name = "parari")]
 #[command(author, version, about, long_about = None)]
+#[command(args_conflicts_with_subcommands = true)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent command-line flags"
+)]
 pub struct Args {
+    #[command(subcommand)]
+    pub command: Option<Commands>,
+
     /// The prompt/task to send to AI CLI tools (opens editor if not provided)
     pub prompt: Option<String>,
 
+    /// Read the prompt from the system clipboard, e.g. after copying a ticket
+    #[arg(long, conflicts_with = "prompt")]
+    pub from_clipboard: bool,
+
+    /// Use the title and description of an issue as the prompt, fetched with `gh` or
+    /// `glab`; a prompt given as well is appended as extra instructions
+    #[arg(long, value_name = "NUMBER|URL", conflicts_with = "from_clipboard")]
+    pub issue: Option<String>,
+
+    /// Attach an image such as a screenshot or mockup to the prompt (repeatable);
+    /// agents that cannot see images are told about it instead
+    #[arg(long, value_name = "PATH")]
+    pub image: Vec<PathBuf>,
+
     /// Working directory (defaults to current directory)
     #[arg(short = 'C', long, default_value = ".")]
     pub directory: String,
 
+    /// Shallow-clone the repository at URL into a temporary directory and run the agents
+    /// there; the applied result is pushed as a branch or written as a patch
+    #[arg(long, value_name = "URL", conflicts_with_all = ["directory", "apply_to"])]
+    pub repo: Option<String>,
+
     /// Comma-separated list of agents to use (e.g., "claude,gemini")
-    /// Available agents: claude, gemini, codex
-    #[arg(short, long, value_delimiter = ',')]
+    /// Available agents: claude, gemini, codex, and agent aliases from the config
+    #[arg(short, long, value_delimiter = ',', env = "PARARI_AGENTS")]
     pub agents: Option<Vec<String>>,
+
+    /// Run the agents of a profile defined in the config (e.g., "thorough")
+    #[arg(short, long, env = "PARARI_PROFILE")]
+    pub profile: Option<String>,
+
+    /// Comma-separated directories to check out in the agents' worktrees
+    /// (sparse checkout; speeds up setup in large repositories)
+    #[arg(long, global = true, value_delimiter = ',', value_name = "PATHS")]
+    pub scope: Option<Vec<String>>,
+
+    /// Do not ask agents to summarize their changes after execution
+    #[arg(
+        long,
+        global = true,
+        env = "PARARI_NO_SUMMARY",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub no_summary: bool,
+
+    /// Disable colors in all output (also enabled by the `NO_COLOR` environment variable)
+    #[arg(long)]
+    pub no_color: bool,
+
+    /// Apply the selected result to another checkout of the same repository
+    /// (e.g. a clean clone used for review) instead of the working directory; if its
+    /// HEAD moved on from the base commit, the result is merged in with `git apply --3way`
+    #[arg(long, value_name = "PATH")]
+    pub apply_to: Option<String>,
+
+    /// Emit newline-delimited JSON events (agents starting and finishing, every line of
+    /// output, ranked results, the applied result) to FILE, kept apart from the progress
+    /// and the split view on the terminal
+    #[arg(long, value_name = "FILE")]
+    pub json_stream: Option<String>,
+
+    /// Run a quick check such as `cargo check` in each worktree after its agent finishes;
+    /// without CMD, the `check` of `.parari.toml` or a default for the repository is used
+    #[arg(
+        long,
+        global = true,
+        value_name = "CMD",
+        num_args = 0..=1,
+        default_missing_value = ""
+    )]
+    pub check_cmd: Option<String>,
+
+    /// Number of score commands run at the same time (defaults to `commands.score_jobs`)
+    #[arg(
+        long,
+        value_name = "N",
+        env = "PARARI_SCORE_JOBS",
+        value_parser = clap::value_parser!(u16).range(1..)
+    )]
+    pub score_jobs: Option<u16>,
+
+    /// Run the score command even in worktrees whose content was scored before, instead
+    /// of reusing the earlier score
+    #[arg(long)]
+    pub no_score_cache: bool,
+
+    /// Show agents that printed nothing for DURATION (e.g. `10m`) as stalled
+    /// (defaults to `commands.stall_timeout`)
+    #[arg(
+        long,
+        value_name = "DURATION",
+        env = "PARARI_STALL_TIMEOUT",
+        value_parser = |value: &str| Age::try_from(value.to_string())
+    )]
+    pub stall_timeout: Option<Age>,
+
+    /// Stop agents once they are stalled instead of waiting for them
+    #[arg(
+        long,
+        global = true,
+        env = "PARARI_KILL_STALLED",
+        value_parser = BoolishValueParser::new()
+    )]
+    pub kill_stalled: bool,
+
+    /// Stop an agent once it spent more than USD US dollars; passed to claude as
+    /// `--max-budget-usd`, and enforced on other agents that report their cost
+    #[arg(
+        long,
+        global = true,
+        value_name = "USD",
+        env = "PARARI_MAX_COST",
+        value_parser = parse_cost
+    )]
+    pub max_cost: Option<f64>,
+
+    /// Stop an agent once it used more than N tokens, as reported in its JSON output
+    #[arg(long, global = true, value_name = "N", env = "PARARI_MAX_TOKENS")]
+    pub max_tokens: Option<u64>,
+
+    /// Run an agent up to N more times, in a fresh worktree and with a growing delay,
+    /// when it fails with a rate limit, server, or network error
+    #[arg(
+        long,
+        global = true,
+        value_name = "N",
+        default_value_t = 0,
+        env = "PARARI_RETRIES"
+    )]
+    pub retries: u8,
+
+    /// Apply a result without opening the split view, chosen by the given strategy
+    /// (`weighted` combines the signals weighted in the `[auto_select]` config table)
+    #[arg(long, value_enum, value_name = "STRATEGY", env = "PARARI_AUTO_SELECT")]
+    pub auto_select: Option<AutoSelect>,
+
+    /// Run and rank the agents without applying any result or opening the split view
+    #[arg(long)]
+    pub no_select: bool,
+
+    /// Which agent failures make the run fail (see the exit codes in the README)
+    #[arg(long, value_enum, default_value_t = Expectation::SuccessAny, env = "PARARI_EXPECT")]
+    pub expect: Expectation,
+
+    /// Commit the applied result on a new branch, push it, and open a pull request
+    /// (a merge request on GitLab) with the forge's CLI: `gh`, `glab` or `tea`
+    #[arg(long, conflicts_with = "no_select")]
+    pub pr: bool,
+
+    /// Forge to open the pull request on and fetch `--issue` from
+    /// (detected from the issue URL or the `origin` remote by default)
+    #[arg(long, value_enum, env = "PARARI_FORGE")]
+    pub forge: Option<ForgeKind>,
+
+    /// After opening the pull request, wait up to DURATION (default `60m`) for its CI
+    /// checks and report whether they passed; failing checks exit with code 7
+    #[arg(
+        long,
+        value_name = "DURATION",
+        num_args = 0..=1,
+        default_missing_value = "60m",
+        requires = "pr",
+        value_parser = |value: &str| Age::try_from(value.to_string())
+    )]
+    pub watch_ci: Option<Age>,
+
+    /// Apply without asking, even if local uncommitted changes would be overwritten
+    #[arg(short, long)]
+    pub force: bool,
+
+    /// Let --auto-select apply a result whose diff looks like it adds a secret, such as
+    /// an API key or private key
+    #[arg(long)]
+    pub allow_secrets: bool,
+
+    /// Only print errors (and the result with `--json`); no banner, spinners, or messages
+    #[arg(short, long)]
+    pub quiet: bool,
+
+    /// Print the outcome of the run as JSON on stdout when it ends
+    #[arg(long)]
+    pub json: bool,
+
+    /// List the results in a random order (also enabled by `shuffle` in the `[ui]` config)
+    #[arg(long)]
+    pub shuffle: bool,
+
+    /// Use plain ASCII instead of emoji and box-drawing glyphs
+    /// (enabled automatically on non-UTF-8 locales and the Linux console)
+    #[arg(long, env = "PARARI_ASCII", value_parser = BoolishValueParser::new())]
+    pub ascii: bool,
+
+    /// Log every git command parari runs, with its directory, duration and exit code
+    #[arg(long, global = true)]
+    pub trace_git: bool,
+
+    /// Only log the git commands that would remove worktrees or prune their metadata,
+    /// instead of running them
+    #[arg(long, global = true)]
+    pub git_dry_run: bool,
+
+    /// Commit to create the worktrees at instead of HEAD, set by `parari rerun`
+    #[arg(skip)]
+    pub base: Option<String>,
+
+    /// Run repeated by this one, set by `parari rerun`
+    #[arg(skip)]
+    pub rerun_of: Option<String>,
+
+    /// Ids of the options given on the command line, which recorded options leave alone
+    #[arg(skip)]
+    command_line: Vec<String>,
+}
+
+/// Agent outcomes a run requires to succeed
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Expectation {
+    /// Every agent must succeed
+    SuccessAll,
+    /// At least one agent must succeed
+    SuccessAny,
+}
+
+/// How `--auto-select` chooses the result to apply
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum AutoSelect {
+    /// Highest weighted score of tests, score, diff size, duration, and agent history
+    Weighted,
+}
+
+/// Subcommands besides running a prompt
+#[derive(Subcommand, Debug, Clone)]
+pub enum Commands {
+    /// List past runs, or show one run with the notes taken while reviewing it
+    #[command(args_conflicts_with_subcommands = true)]
+    History {
+        /// Identifier of the run to show
+        id: Option<String>,
+        #[command(subcommand)]
+        action: Option<HistoryAction>,
+    },
+    /// Write a run of the history, with its logs and diffs, to an archive to share it
+    Export {
+        /// Identifier of the run to export
+        id: String,
+        /// Archive to write, e.g. `run.tar.zst`
+        #[arg(short, long, value_name = "FILE")]
+        output: PathBuf,
+        /// Export without asking even if a diff looks like it adds a secret
+        #[arg(long)]
+        allow_secrets: bool,
+    },
+    /// Run the prompt of a past run again with the same agents, from the same base commit
+    Rerun {
+        /// Identifier of the run to repeat
+        id: String,
+        /// Create the worktrees at the current HEAD instead of the run's base commit
+        #[arg(long)]
+        head: bool,
+    },
+    /// Add a run exported with `parari export` to the history
+    Import {
+        /// Archive written by `parari export`
+        archive: PathBuf,
+        /// Open the imported run in the split view to review and apply its results
+        #[arg(long)]
+        review: bool,
+    },
+    /// Show the disk space used by kept worktrees and the history, and free it
+    Clean {
+        /// Remove the worktrees not in use without asking
+        #[arg(short, long)]
+        yes: bool,
+    },
+    /// Write a commented `.parari.toml` for this repository to start from
+    Init {
+        /// Also add `.parari.toml` to `.gitignore` to keep the settings to yourself
+        #[arg(long)]
+        gitignore: bool,
+        /// Overwrite an existing `.parari.toml`
+        #[arg(short, long)]
+        force: bool,
+    },
+    /// Run the prompt in a file again whenever the file is saved
+    Watch {
+        /// File holding the prompt, e.g. TASK.md
+        file: PathBuf,
+        /// Apply a result chosen by the given strategy after each run, instead of
+        /// only ranking the results
+        #[arg(long, value_enum, value_name = "STRATEGY")]
+        auto_select: Option<AutoSelect>,
+        /// Comma-separated list of agents to use (e.g., "claude,gemini")
+        #[arg(short, long, value_delimiter = ',')]
+        agents: Option<Vec<String>>,
+        /// Run the agents of a profile defined in the config (e.g., "thorough")
+        #[arg(short, long, conflicts_with = "agents")]
+        profile: Option<String>,
+    },
+    /// Read and change the settings in `~/.parari/config.toml`
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+}
+
+/// Actions of `parari history`
+#[derive(Subcommand, Debug, Clone)]
+pub enum HistoryAction {
+    /// Find runs whose prompt, notes, logs, or diffs contain every word of the query
+    Search {
+        /// Words to look for, ignoring case, e.g. `codex tokenizer`
+        #[arg(required = true, num_args = 1..)]
+        query: Vec<String>,
+    },
+    /// Open a run in the split view again, recreating its results from the saved diffs
+    Review {
+        /// Identifier of the run to review
+        id: String,
+    },
+}
+
+/// Actions of `parari config`
+#[derive(Subcommand, Debug, Clone)]
+pub enum ConfigAction {
+    /// Print the value of a setting, e.g. `ui.theme`
+    Get {
+        /// Key of the setting, as `<table>.<field>`
+        key: String,
+    },
+    /// Change a setting after checking the value is valid
+    Set {
+        /// Key of the setting, as `<table>.<field>`
+        key: String,
+        /// New value, e.g. `false`, `10`, or `7d`
+        value: String,
+    },
+    /// Open the config file in $VISUAL or $EDITOR and check it afterwards
+    Edit,
+    /// Print the path of the config file
+    Path,
 }
 
 impl Args {
-    /// Parse arguments from command line
+    /// Parse arguments from command line, falling back to `PARARI_*` environment
+    /// variables for the options not given
     #[must_use]
     pub fn parse_args() -> Self {
-        Self::parse()
+        let matches = Self::command().get_matches();
+        Self::from_matches(&matches).unwrap_or_else(|e| e.exit())
+    }
+
+    /// The arguments of parsed matches, remembering which options came from the
+    /// command line
+    fn from_matches(matches: &ArgMatches) -> Result<Self, clap::Error> {
+        let mut args = Self::from_arg_matches(matches)?;
+        args.command_line = matches
+            .ids()
+            .filter(|id| matches.value_source(id.as_str()) == Some(ValueSource::CommandLine))
+            .map(ToString::to_string)
+            .collect();
+        args.resolve_exclusive(matches)?;
+        Ok(args)
+    }
+
+    /// Settle the options that exclude each other
+    ///
+    /// clap's `conflicts_with` would also reject an option set by an environment
+    /// variable, which a command-line flag should override instead. Two options from
+    /// the same source are still a conflict.
+    fn resolve_exclusive(&mut self, matches: &ArgMatches) -> Result<(), clap::Error> {
+        for (a, b) in EXCLUSIVE {
+            let source = |id: &str| {
+                matches
+                    .value_source(id)
+                    .filter(|source| *source != ValueSource::DefaultValue)
+            };
+            match (source(a), source(b)) {
+                (Some(ValueSource::EnvVariable), Some(ValueSource::CommandLine)) => self.clear(a),
+                (Some(ValueSource::CommandLine), Some(ValueSource::EnvVariable)) => self.clear(b),
+                (Some(_), Some(_)) => {
+                    return Err(Self::command().error(
+                        ErrorKind::ArgumentConflict,
+                        format!(
+                            "the argument '--{}' cannot be used with '--{}'",
+                            b.replace('_', "-"),
+                            a.replace('_', "-")
+                        ),
+                    ));
+                }
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Unset an option of [`EXCLUSIVE`]
+    fn clear(&mut self, id: &str) {
+        match id {
+            "agents" => self.agents = None,
+            "profile" => self.profile = None,
+            "auto_select" => self.auto_select = None,
+            "no_select" => self.no_select = false,
+            _ => {}
+        }
+    }
+
+    /// Whether colors are disabled by `--no-color` or a non-empty `NO_COLOR`
+    #[must_use]
+    pub fn colors_disabled(&self) -> bool {
+        self.no_color || std::env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty())
+    }
+
+    /// The options recorded with a run, for `parari rerun`
+    #[must_use]
+    pub fn run_options(&self) -> RunOptions {
+        RunOptions {
+            scope: self.scope.clone(),
+            check_cmd: self.check_cmd.clone(),
+            no_summary: self.no_summary,
+            retries: self.retries,
+            max_cost: self.max_cost,
+            max_tokens: self.max_tokens,
+            kill_stalled: self.kill_stalled,
+        }
+    }
+
+    /// Use the options recorded with a run, except those given on the command line
+    pub fn set_run_options(&mut self, options: &RunOptions) {
+        let recorded = |id: &str| !self.command_line.iter().any(|given| given == id);
+        if recorded("scope") {
+            self.scope.clone_from(&options.scope);
+        }
+        if recorded("check_cmd") {
+            self.check_cmd.clone_from(&options.check_cmd);
+        }
+        if recorded("no_summary") {
+            self.no_summary = options.no_summary;
+        }
+        if recorded("retries") {
+            self.retries = options.retries;
+        }
+        if recorded("max_cost") {
+            self.max_cost = options.max_cost;
+        }
+        if recorded("max_tokens") {
+            self.max_tokens = options.max_tokens;
+        }
+        if recorded("kill_stalled") {
+            self.kill_stalled = options.kill_stalled;
+        }
+    }
+}
+
+/// Pairs of options that exclude each other, by argument id
+const EXCLUSIVE: [(&str, &str); 2] = [("agents", "profile"), ("auto_select", "no_select")];
+
+/// Parse a positive amount of US dollars, e.g. `2.50`
+fn parse_cost(value: &str) -> Result<f64, String> {
+    match value.trim_start_matches('$').parse::<f64>() {
+        Ok(cost) if cost > 0.0 && cost.is_finite() => Ok(cost),
+        _ => Err(format!(
+            "expected a positive amount of US dollars, e.g. 2.50, not {value}"
+        )),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exclusive_flags_conflict() -> Result<(), clap::Error> {
+        let matches = Args::command().try_get_matches_from([
+            "parari",
+            "--agents",
+            "claude",
+            "--profile",
+            "thorough",
+        ])?;
+        let mut args = Args::from_arg_matches(&matches)?;
+        let error = args.resolve_exclusive(&matches).err();
+        assert_eq!(error.map(|e| e.kind()), Some(ErrorKind::ArgumentConflict));
+
+        let matches = Args::command().try_get_matches_from(["parari", "--no-select"])?;
+        let mut args = Args::from_arg_matches(&matches)?;
+        args.resolve_exclusive(&matches)?;
+        assert!(args.no_select);
+        Ok(())
+    }
+
+    #[test]
+    fn test_recorded_options_keep_command_line() -> Result<(), clap::Error> {
+        let matches = Args::command().try_get_matches_from([
+            "parari",
+            "rerun",
+            "20261016-141502",
+            "--retries",
+            "3",
+            "--max-tokens",
+            "100000",
+        ])?;
+        let mut args = Args::from_matches(&matches)?;
+        args.set_run_options(&RunOptions {
+            retries: 1,
+            max_tokens: Some(5_000),
+            max_cost: Some(2.0),
+            ..RunOptions::default()
+        });
+        assert_eq!(args.retries, 3);
+        assert_eq!(args.max_tokens, Some(100_000));
+        assert_eq!(args.max_cost, Some(2.0));
+        Ok(())
     }
 }
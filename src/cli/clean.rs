@@ -0,0 +1,119 @@
+use console::style;
+
+use crate::cli::glyphs::{glyph, rule};
+use crate::cli::output;
+use crate::domain::DiskUsage;
+use crate::error::Result;
+
+/// Largest worktrees listed by `parari clean`
+const CLEAN_MAX_WORKTREES: usize = 10;
+
+/// Format a number of bytes with a binary unit, e.g. `1.5 GB`
+#[must_use]
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "sizes are only shown with one decimal"
+)]
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["B", "KB", "MB", "GB", "TB"];
+    let mut size = bytes as f64;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    if unit == 0 {
+        format!("{bytes} B")
+    } else {
+        format!("{size:.1} {}", UNITS[unit])
+    }
+}
+
+/// Display how much disk space the parari data directory uses
+pub fn show_disk_usage(usage: &DiskUsage) {
+    println!();
+    println!("{}", style(rule(50)).cyan());
+    println!(
+        "  {} {}",
+        style("Disk usage:").bold().cyan(),
+        style(format_size(usage.total())).bold()
+    );
+    println!("{}", style(rule(50)).cyan());
+    println!(
+        "  {} {} in {} worktree(s)",
+        style("Worktrees:").bold(),
+        format_size(usage.total() - usage.history),
+        usage.worktrees.len()
+    );
+    for worktree in usage.worktrees.iter().take(CLEAN_MAX_WORKTREES) {
+        let name = worktree
+            .path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        let line = format!("    {:>10}  {name}", format_size(worktree.bytes));
+        if worktree.in_use {
+            println!("{}", style(format!("{line} (in use)")).dim());
+        } else {
+            println!("{line}");
+        }
+    }
+    if usage.worktrees.len() > CLEAN_MAX_WORKTREES {
+        let remaining = usage.worktrees.len() - CLEAN_MAX_WORKTREES;
+        println!("    ... and {remaining} more worktrees");
+    }
+    println!(
+        "  {} {}",
+        style("History:  ").bold(),
+        format_size(usage.history)
+    );
+    println!();
+}
+
+/// Ask whether to remove the worktrees no running process uses
+///
+/// # Errors
+///
+/// Returns an error if reading from stdin fails.
+pub fn confirm_clean(count: usize, bytes: u64) -> Result<bool> {
+    print!(
+        "Remove {count} worktree(s) not in use to free {}? [y/N]: ",
+        format_size(bytes)
+    );
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    let answer = input.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// Warn on stderr that the parari data directory uses more than the configured quota,
+/// or hold the warning while the split view is shown
+pub fn show_quota_warning(used: u64, quota: u64) {
+    let message = format!(
+        "parari data uses {} (quota {}); run `parari clean` to free space.",
+        format_size(used),
+        format_size(quota)
+    );
+    if !output::hold(&message) {
+        eprintln!(
+            "{} {}",
+            style(glyph("⚠️", "[!]")).bold(),
+            style(message).yellow()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512 B");
+        assert_eq!(format_size(1536), "1.5 KB");
+        assert_eq!(format_size(3 << 30), "3.0 GB");
+    }
+}
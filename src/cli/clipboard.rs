@@ -0,0 +1,24 @@
+use arboard::Clipboard;
+
+use crate::error::{Error, Result};
+
+/// Read the prompt from the system clipboard
+///
+/// # Errors
+///
+/// Returns an error if the clipboard cannot be accessed, holds no text, or the text
+/// is blank.
+pub fn read_prompt_from_clipboard() -> Result<String> {
+    let text = Clipboard::new()
+        .and_then(|mut clipboard| clipboard.get_text())
+        .map_err(|e| Error::Clipboard {
+            message: e.to_string(),
+        })?;
+    let prompt = text.trim();
+    if prompt.is_empty() {
+        return Err(Error::Clipboard {
+            message: "the clipboard is empty".to_string(),
+        });
+    }
+    Ok(prompt.to_string())
+}
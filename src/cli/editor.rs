@@ -1,64 +1,175 @@
 use std::env;
 use std::fs;
 use std::io::Write;
+use std::path::Path;
 use std::process::Command;
 
+use crate::cli::args::Args;
+use crate::cli::ui::show_progress;
 use crate::error::{Error, Result};
 use tempfile::NamedTempFile;
 
-/// Opens an editor for the user to enter a prompt.
-/// Uses $EDITOR environment variable, falling back to vi.
-/// Returns the entered text, or an error if the editor fails or returns empty input.
+/// Options for a run set by `# parari:` lines in the prompt editor
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PromptDirectives {
+    /// `# parari: agents: claude,codex`
+    pub agents: Option<Vec<String>>,
+    /// `# parari: profile: thorough`
+    pub profile: Option<String>,
+    /// `# parari: scope: crates/core,docs`
+    pub scope: Option<Vec<String>>,
+    /// `# parari: check: cargo check`
+    pub check: Option<String>,
+}
+
+impl PromptDirectives {
+    /// Fill in the options not given on the command line
+    pub fn apply_to(self, args: &mut Args) {
+        if args.agents.is_none() && args.profile.is_none() {
+            args.agents = self.agents;
+            args.profile = self.profile;
+        }
+        args.scope = args.scope.take().or(self.scope);
+        args.check_cmd = args.check_cmd.take().or(self.check);
+    }
+}
+
+/// A prompt written in the editor, with the options set inside it
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EditorPrompt {
+    /// The prompt without comment lines
+    pub prompt: String,
+    /// Options set by `# parari:` lines
+    pub directives: PromptDirectives,
+}
+
+/// Split a `,`-separated directive value into its items
+fn directive_list(value: &str) -> Vec<String> {
+    value
+        .split(',')
+        .map(str::trim)
+        .filter(|item| !item.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+/// Separate the prompt from the comments and `# parari:` lines of an editor buffer
+///
+/// Text left as it was in the template does not count as a prompt. Unknown
+/// directives are returned as the third value.
+fn parse_prompt_buffer(content: &str, template: &str) -> (String, PromptDirectives, Vec<String>) {
+    let text = |content: &str| {
+        content
+            .lines()
+            .filter(|line| !line.starts_with('#'))
+            .collect::<Vec<&str>>()
+            .join("\n")
+            .trim()
+            .to_string()
+    };
+
+    let mut directives = PromptDirectives::default();
+    let mut unknown = Vec::new();
+    for line in content.lines() {
+        let Some(directive) = line.strip_prefix("# parari:") else {
+            continue;
+        };
+        let (key, value) = directive.split_once(':').unwrap_or((directive, ""));
+        let value = value.trim();
+        match key.trim() {
+            "agents" => directives.agents = Some(directive_list(value)),
+            "profile" => directives.profile = Some(value.to_string()),
+            "scope" => directives.scope = Some(directive_list(value)),
+            "check" => directives.check = Some(value.to_string()),
+            _ => unknown.push(directive.trim().to_string()),
+        }
+    }
+
+    let prompt = text(content);
+    let prompt = if prompt == text(template) {
+        String::new()
+    } else {
+        prompt
+    };
+    (prompt, directives, unknown)
+}
+
+/// Editor used when neither `$VISUAL` nor `$EDITOR` is set
+const DEFAULT_EDITOR: &str = "vi";
+
+/// Split an editor command like `code --wait` into the program and its arguments
+///
+/// `visual` takes precedence over `editor`; empty values are skipped.
+fn editor_command_from(visual: Option<String>, editor: Option<String>) -> Result<Vec<String>> {
+    let value = [visual, editor]
+        .into_iter()
+        .flatten()
+        .find(|value| !value.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_EDITOR.to_string());
+    shlex::split(&value)
+        .filter(|words| !words.is_empty())
+        .ok_or_else(|| Error::EditorFailed {
+            message: format!("Cannot parse editor command '{value}'"),
+        })
+}
+
+/// The editor command from `$VISUAL` or `$EDITOR`, falling back to vi
+pub(crate) fn editor_command() -> Result<Vec<String>> {
+    editor_command_from(env::var("VISUAL").ok(), env::var("EDITOR").ok())
+}
+
+/// The text the prompt editor starts with
+///
+/// A `draft`, such as a prompt picked from history, replaces the text of the
+/// template while its comments are kept.
+fn initial_buffer(template: &str, draft: Option<&str>) -> String {
+    let Some(draft) = draft else {
+        return template.to_string();
+    };
+    let comments: Vec<&str> = template
+        .lines()
+        .filter(|line| line.starts_with('#'))
+        .collect();
+    format!("{draft}\n\n{}\n", comments.join("\n"))
+}
+
+/// Opens an editor for the user to enter a prompt, starting with `template`
+/// or, when given, with `draft` followed by the comments of `template`.
+/// Uses $VISUAL or $EDITOR environment variable, falling back to vi.
+/// Returns the entered text with the options set in it, or an error if the editor
+/// fails or returns empty input.
 ///
 /// # Errors
 ///
 /// Returns an error if the temporary file cannot be created, the editor fails to start,
 /// the editor exits with a non-zero status, the file cannot be read, or no prompt is entered.
-pub fn open_editor_for_prompt() -> Result<String> {
-    let editor = env::var("EDITOR").unwrap_or_else(|_| "vi".to_string());
-
+pub fn open_editor_for_prompt(template: &str, draft: Option<&str>) -> Result<EditorPrompt> {
     // Create a temporary file with instructions
     let mut temp_file = NamedTempFile::new().map_err(|e| Error::EditorFailed {
         message: format!("Failed to create temporary file: {e}"),
     })?;
 
-    // Write initial content with instructions
-    let initial_content = "\n# Enter your prompt above this line.\n# Lines starting with '#' will be ignored.\n# Save and exit the editor to continue.\n# Leave empty to cancel.\n";
     temp_file
-        .write_all(initial_content.as_bytes())
+        .write_all(initial_buffer(template, draft).as_bytes())
         .map_err(|e| Error::EditorFailed {
             message: format!("Failed to write to temporary file: {e}"),
         })?;
 
     let temp_path = temp_file.path().to_path_buf();
 
-    // Open the editor
-    let status = Command::new(&editor)
-        .arg(&temp_path)
-        .status()
-        .map_err(|e| Error::EditorFailed {
-            message: format!("Failed to start editor '{editor}': {e}"),
-        })?;
-
-    if !status.success() {
-        return Err(Error::EditorFailed {
-            message: format!("Editor '{editor}' exited with non-zero status"),
-        });
-    }
+    open_editor_for_file(&temp_path)?;
 
     // Read the result
     let content = fs::read_to_string(&temp_path).map_err(|e| Error::EditorFailed {
         message: format!("Failed to read temporary file: {e}"),
     })?;
 
-    // Filter out comment lines and trim
-    let prompt: String = content
-        .lines()
-        .filter(|line| !line.starts_with('#'))
-        .collect::<Vec<&str>>()
-        .join("\n")
-        .trim()
-        .to_string();
+    let (prompt, directives, unknown) = parse_prompt_buffer(&content, template);
+    for directive in unknown {
+        show_progress(&format!(
+            "Ignoring unknown directive \"# parari:{directive}\"; known: agents, profile, scope, check"
+        ));
+    }
 
     if prompt.is_empty() {
         return Err(Error::EditorFailed {
@@ -66,11 +177,40 @@ pub fn open_editor_for_prompt() -> Result<String> {
         });
     }
 
-    Ok(prompt)
+    Ok(EditorPrompt { prompt, directives })
+}
+
+/// Opens an editor on an existing file, such as the config file
+/// Uses $VISUAL or $EDITOR environment variable, falling back to vi.
+///
+/// # Errors
+///
+/// Returns an error if the editor command cannot be parsed, the editor fails to start
+/// or exits with a non-zero status.
+pub fn open_editor_for_file(path: &Path) -> Result<()> {
+    let command = editor_command()?;
+    let editor = command.join(" ");
+
+    let status = Command::new(&command[0])
+        .args(&command[1..])
+        .arg(path)
+        .status()
+        .map_err(|e| Error::EditorFailed {
+            message: format!("Failed to start editor '{editor}': {e}"),
+        })?;
+
+    if !status.success() {
+        return Err(Error::EditorFailed {
+            message: format!("Editor '{editor}' exited with non-zero status"),
+        });
+    }
+    Ok(())
 }
 
 #[cfg(test)]
 mod tests {
+    use super::*;
+    use clap::Parser;
 
     #[test]
     fn test_filter_comments() {
@@ -97,4 +237,75 @@ mod tests {
             .to_string();
         assert!(filtered.is_empty());
     }
+
+    #[test]
+    fn test_parse_directives() {
+        let content = "Fix the parser\n# parari: agents: claude, codex\n# parari: scope: src\n# parari: check: cargo check\n# parari: colour: blue\n# A comment\n";
+        let (prompt, directives, unknown) = parse_prompt_buffer(content, "");
+        assert_eq!(prompt, "Fix the parser");
+        assert_eq!(
+            directives,
+            PromptDirectives {
+                agents: Some(vec!["claude".to_string(), "codex".to_string()]),
+                profile: None,
+                scope: Some(vec!["src".to_string()]),
+                check: Some("cargo check".to_string()),
+            }
+        );
+        assert_eq!(unknown, ["colour: blue"]);
+    }
+
+    #[test]
+    fn test_unchanged_template_is_empty() {
+        let template = "Goal:\n\nConstraints:\n# Fill in the sections\n";
+        let (prompt, _, _) = parse_prompt_buffer(template, template);
+        assert!(prompt.is_empty());
+        let (prompt, _, _) = parse_prompt_buffer("Goal: faster builds\n\nConstraints:\n", template);
+        assert_eq!(prompt, "Goal: faster builds\n\nConstraints:");
+    }
+
+    #[test]
+    fn test_directives_do_not_override_arguments() -> std::result::Result<(), clap::Error> {
+        let mut args = Args::try_parse_from(["parari", "--agents", "gemini"])?;
+        PromptDirectives {
+            agents: Some(vec!["claude".to_string()]),
+            check: Some("make lint".to_string()),
+            ..PromptDirectives::default()
+        }
+        .apply_to(&mut args);
+        assert_eq!(args.agents, Some(vec!["gemini".to_string()]));
+        assert_eq!(args.check_cmd.as_deref(), Some("make lint"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_editor_command() -> Result<()> {
+        let some = |value: &str| Some(value.to_string());
+        assert_eq!(editor_command_from(None, None)?, ["vi"]);
+        assert_eq!(editor_command_from(None, some("nano"))?, ["nano"]);
+        assert_eq!(
+            editor_command_from(some("code --wait"), some("nano"))?,
+            ["code", "--wait"]
+        );
+        assert_eq!(
+            editor_command_from(some("  "), some("subl -w"))?,
+            ["subl", "-w"]
+        );
+        assert_eq!(
+            editor_command_from(some("'/opt/My Editor/edit' -w"), None)?,
+            ["/opt/My Editor/edit", "-w"]
+        );
+        assert!(editor_command_from(some("vim \"unclosed"), None).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_initial_buffer_with_draft() {
+        let template = "Goal:\n# Describe the goal\n";
+        assert_eq!(initial_buffer(template, None), template);
+        let buffer = initial_buffer(template, Some("Fix the importer"));
+        assert_eq!(buffer, "Fix the importer\n\n# Describe the goal\n");
+        let (prompt, _, _) = parse_prompt_buffer(&buffer, template);
+        assert_eq!(prompt, "Fix the importer");
+    }
 }
@@ -0,0 +1,65 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether output is restricted to plain ASCII
+static ASCII_ONLY: AtomicBool = AtomicBool::new(false);
+
+/// Restrict emoji and box-drawing glyphs to plain ASCII equivalents
+///
+/// Like `console::set_colors_enabled`, this is a process-wide switch.
+pub fn set_ascii_only(enabled: bool) {
+    ASCII_ONLY.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether output is restricted to plain ASCII
+#[must_use]
+pub fn ascii_only() -> bool {
+    ASCII_ONLY.load(Ordering::Relaxed)
+}
+
+/// Pick the Unicode glyph, or its ASCII equivalent in ASCII-only mode
+#[must_use]
+pub fn glyph(unicode: &'static str, ascii: &'static str) -> &'static str {
+    if ascii_only() { ascii } else { unicode }
+}
+
+/// Horizontal rule of the given length
+#[must_use]
+pub fn rule(len: usize) -> String {
+    glyph("━", "=").repeat(len)
+}
+
+/// Detect terminals that are unlikely to render emoji and box-drawing glyphs
+///
+/// The Linux virtual console and non-UTF-8 locales fall back to ASCII.
+#[must_use]
+pub fn detect_ascii_only() -> bool {
+    if std::env::var("TERM").is_ok_and(|term| term == "linux") {
+        return true;
+    }
+
+    // The first non-empty locale variable decides, as in setlocale(3)
+    ["LC_ALL", "LC_CTYPE", "LANG"]
+        .iter()
+        .filter_map(|name| std::env::var(name).ok())
+        .find(|value| !value.is_empty())
+        .is_some_and(|locale| !is_utf8_locale(&locale))
+}
+
+/// Whether a locale name uses the UTF-8 encoding
+fn is_utf8_locale(locale: &str) -> bool {
+    let locale = locale.to_lowercase();
+    locale.contains("utf-8") || locale.contains("utf8")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_is_utf8_locale() {
+        assert!(is_utf8_locale("en_US.UTF-8"));
+        assert!(is_utf8_locale("ja_JP.utf8"));
+        assert!(!is_utf8_locale("C"));
+        assert!(!is_utf8_locale("POSIX"));
+    }
+}
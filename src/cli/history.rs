@@ -0,0 +1,336 @@
+use std::fmt;
+use std::io::IsTerminal;
+use std::path::Path;
+
+use chrono::{DateTime, Local};
+use console::style;
+use inquire::{InquireError, Select};
+
+use crate::cli::glyphs::{glyph, rule};
+use crate::cli::output::say;
+use crate::cli::progress::AgentStyle;
+use crate::domain::{RunRecord, SearchHit};
+use crate::error::{Error, Result};
+
+/// Number of past prompts offered before opening the editor
+const RECENT_PROMPTS: usize = 50;
+
+/// First line of a prompt, shortened to `width` characters
+fn prompt_title(prompt: &str, width: usize) -> String {
+    let first = prompt.lines().next().unwrap_or("").trim();
+    if first.chars().count() > width {
+        let short: String = first.chars().take(width.saturating_sub(3)).collect();
+        format!("{short}...")
+    } else {
+        first.to_string()
+    }
+}
+
+/// Display the list of past runs, newest first
+pub fn show_history(runs: &[RunRecord]) {
+    if runs.is_empty() {
+        say!("No runs recorded yet.");
+        return;
+    }
+
+    for run in runs {
+        let agents: Vec<&str> = run.candidates.iter().map(|c| c.agent.as_str()).collect();
+        let rerun_of = run
+            .rerun_of
+            .as_ref()
+            .map(|original| format!("  (rerun of {original})"))
+            .unwrap_or_default();
+        say!(
+            "  {}  {}  {}  {}{}",
+            style(&run.id).bold().cyan(),
+            style(run.started_at.format("%Y-%m-%d %H:%M")).dim(),
+            prompt_title(&run.prompt, 50),
+            style(format!("[{}]", agents.join(", "))).dim(),
+            style(rerun_of).dim()
+        );
+    }
+    say!();
+    say!("Show a run with `parari history <id>`.");
+}
+
+/// Display the runs found by `parari history search`, newest first, with the lines
+/// that matched
+pub fn show_search_results(hits: &[SearchHit]) {
+    if hits.is_empty() {
+        say!("No runs found.");
+        return;
+    }
+
+    for hit in hits {
+        say!(
+            "  {}  {}  {}",
+            style(&hit.run.id).bold().cyan(),
+            style(hit.run.started_at.format("%Y-%m-%d %H:%M")).dim(),
+            prompt_title(&hit.run.prompt, 50)
+        );
+        for found in &hit.matches {
+            say!(
+                "      {} {}",
+                style(format!("{}:", found.source)).dim(),
+                found.line
+            );
+        }
+    }
+    say!();
+    say!("Show a run with `parari history <id>`.");
+}
+
+/// Display one run with the notes taken while reviewing it, and the runs repeating
+/// it with `parari rerun`
+pub fn show_run(run: &RunRecord, reruns: &[String]) {
+    say!();
+    say!("{}", style(rule(50)).cyan());
+    say!("  {} {}", style("Run").bold().cyan(), style(&run.id).bold());
+    say!("{}", style(rule(50)).cyan());
+    say!(
+        "  {} {}",
+        style("Started:  ").bold(),
+        run.started_at.format("%Y-%m-%d %H:%M:%S")
+    );
+    say!(
+        "  {} {}",
+        style("Directory:").bold(),
+        run.working_dir.display()
+    );
+    if let Some(commit) = &run.base_commit {
+        let dirty = run.snapshot.dirty_files.len();
+        if dirty > 0 {
+            say!(
+                "  {} {commit} + {dirty} uncommitted file(s)",
+                style("Base:     ").bold()
+            );
+        } else {
+            say!("  {} {commit}", style("Base:     ").bold());
+        }
+    }
+    if !run.snapshot.agent_versions.is_empty() {
+        let versions: Vec<String> = run
+            .snapshot
+            .agent_versions
+            .iter()
+            .map(|(agent, version)| format!("{agent} {version}"))
+            .collect();
+        say!("  {} {}", style("Agents:   ").bold(), versions.join(", "));
+    }
+    if let Some(issue) = &run.issue {
+        say!("  {} {issue}", style("Issue:    ").bold());
+    }
+    if let Some(original) = &run.rerun_of {
+        say!(
+            "  {} {}",
+            style("Rerun of: ").bold(),
+            style(original).cyan()
+        );
+    }
+    if !reruns.is_empty() {
+        say!(
+            "  {} {}",
+            style("Reruns:   ").bold(),
+            style(reruns.join(", ")).cyan()
+        );
+    }
+    if let Some(decision) = &run.decision {
+        let how = if decision.auto_selected {
+            " (auto-selected)"
+        } else {
+            ""
+        };
+        say!(
+            "  {} {}{how}",
+            style("Applied:  ").bold(),
+            style(&decision.agent).green().bold()
+        );
+    }
+    say!();
+    say!("  {}", style("Prompt:").bold());
+    for line in run.prompt.lines() {
+        say!("    {line}");
+    }
+    say!();
+
+    for candidate in &run.candidates {
+        let agent_style = AgentStyle::for_agent(&candidate.agent);
+        let name = if candidate.rejected {
+            style(format!("{} (rejected)", candidate.agent)).dim()
+        } else if candidate.success {
+            style(candidate.agent.clone()).green().bold()
+        } else if let Some(kind) = candidate.error_kind {
+            style(format!("{} (failed: {})", candidate.agent, kind.label())).red()
+        } else {
+            style(format!("{} (failed)", candidate.agent)).red()
+        };
+        say!(
+            "  {} {}  {} files, +{} -{}",
+            agent_style.emoji,
+            name,
+            candidate.files_changed,
+            candidate.insertions,
+            candidate.deletions
+        );
+        if let Some(error) = &candidate.error {
+            say!("     {}", style(error).red());
+        }
+        if let Some(note) = &candidate.note {
+            say!("     {}", style(format!("\"{note}\"")).italic());
+        }
+    }
+    say!();
+}
+
+/// Warn that uncommitted files differ from when a run being rerun started
+pub fn show_changed_since_run(id: &str, files: &[String]) {
+    say!(
+        "{} {}",
+        style(glyph("⚠️", "[!]")).bold(),
+        style(format!(
+            "{} uncommitted file(s) changed since run {id}; the agents see them as they are now:",
+            files.len()
+        ))
+        .yellow()
+    );
+    for file in files {
+        say!("  {}", style(file).yellow());
+    }
+}
+
+/// Display where a run was exported to
+pub fn show_exported(id: &str, archive: &Path) {
+    say!(
+        "Exported run {} to {}",
+        style(id).bold().cyan(),
+        archive.display()
+    );
+    say!("Import it with `parari import {}`.", archive.display());
+}
+
+/// Display a run added to the history with `parari import`
+pub fn show_imported(run: &RunRecord) {
+    say!(
+        "Imported run {}  {}",
+        style(&run.id).bold().cyan(),
+        prompt_title(&run.prompt, 50)
+    );
+    say!(
+        "Show it with `parari history {0}`, or review it with `parari history review {0}`.",
+        run.id
+    );
+}
+
+/// A past prompt offered before opening the editor
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RecentPrompt {
+    /// The prompt as given to the agents
+    pub prompt: String,
+    /// When it was last run
+    pub started_at: DateTime<Local>,
+}
+
+impl fmt::Display for RecentPrompt {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{}  {}",
+            self.started_at.format("%Y-%m-%d %H:%M"),
+            prompt_title(&self.prompt, 70)
+        )
+    }
+}
+
+/// The distinct prompts of `runs`, newest first, at most `limit` of them
+#[must_use]
+pub fn recent_prompts(runs: &[RunRecord], limit: usize) -> Vec<RecentPrompt> {
+    let mut prompts: Vec<RecentPrompt> = Vec::new();
+    for run in runs {
+        if prompts.len() == limit {
+            break;
+        }
+        if run.prompt.trim().is_empty() || prompts.iter().any(|p| p.prompt == run.prompt) {
+            continue;
+        }
+        prompts.push(RecentPrompt {
+            prompt: run.prompt.clone(),
+            started_at: run.started_at,
+        });
+    }
+    prompts
+}
+
+/// Let the user search the prompts of past runs to start the editor from
+///
+/// Returns `None` when there is no history, the terminal is not interactive, or
+/// the user presses Esc to write a new prompt.
+///
+/// # Errors
+///
+/// Returns an error if the user presses Ctrl-C or the list cannot be shown.
+pub fn pick_recent_prompt(runs: &[RunRecord]) -> Result<Option<String>> {
+    let prompts = recent_prompts(runs, RECENT_PROMPTS);
+    if prompts.is_empty() || !std::io::stdin().is_terminal() || !std::io::stdout().is_terminal() {
+        return Ok(None);
+    }
+
+    match Select::new("Start from a recent prompt:", prompts)
+        .with_help_message("type to search, Enter to edit, Esc to write a new prompt")
+        .with_page_size(10)
+        .prompt_skippable()
+    {
+        Ok(choice) => Ok(choice.map(|recent| recent.prompt)),
+        Err(InquireError::OperationInterrupted) => Err(Error::UserCancelled),
+        Err(e) => Err(Error::EditorFailed {
+            message: format!("Failed to show recent prompts: {e}"),
+        }),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{RunOptions, RunSnapshot};
+    use chrono::TimeZone;
+
+    #[test]
+    fn test_prompt_title() {
+        assert_eq!(prompt_title("Fix the bug\nin detail", 50), "Fix the bug");
+        assert_eq!(prompt_title("abcdefghij", 8), "abcde...");
+        assert_eq!(prompt_title("", 8), "");
+    }
+
+    #[test]
+    fn test_recent_prompts() {
+        let run = |prompt: &str, hour: u32| RunRecord {
+            id: format!("run-{hour}"),
+            prompt: prompt.to_string(),
+            working_dir: std::path::PathBuf::from("/repo"),
+            base_commit: None,
+            started_at: Local
+                .with_ymd_and_hms(2026, 10, 15, hour, 0, 0)
+                .single()
+                .unwrap_or_default(),
+            candidates: vec![],
+            prompt_hash: String::new(),
+            decision: None,
+            issue: None,
+            snapshot: RunSnapshot::default(),
+            options: RunOptions::default(),
+            rerun_of: None,
+        };
+        let runs = [
+            run("Fix the importer", 12),
+            run("Add tests", 11),
+            run("Fix the importer", 10),
+            run("  ", 9),
+            run("Update docs", 8),
+        ];
+
+        let prompts = recent_prompts(&runs, 10);
+        let texts: Vec<&str> = prompts.iter().map(|p| p.prompt.as_str()).collect();
+        assert_eq!(texts, ["Fix the importer", "Add tests", "Update docs"]);
+        assert_eq!(prompts[0].to_string(), "2026-10-15 12:00  Fix the importer");
+        assert_eq!(recent_prompts(&runs, 2).len(), 2);
+    }
+}
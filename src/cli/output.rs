@@ -0,0 +1,121 @@
+use std::sync::Mutex;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether the split view owns the terminal
+static TUI_ACTIVE: AtomicBool = AtomicBool::new(false);
+
+/// Messages sent while the split view owns the terminal, not yet shown
+static PENDING: Mutex<Vec<String>> = Mutex::new(Vec::new());
+
+/// Holds messages for the split view until dropped
+#[derive(Debug)]
+#[must_use = "messages are printed again as soon as the guard is dropped"]
+pub struct TuiGuard(());
+
+impl Drop for TuiGuard {
+    fn drop(&mut self) {
+        TUI_ACTIVE.store(false, Ordering::Relaxed);
+        for message in take_messages() {
+            println!("  {message}");
+        }
+    }
+}
+
+/// Hold `message` for the split view's status area if it owns the terminal
+///
+/// Printing to stdout while the alternate screen is shown garbles the TUI, so
+/// callers print `message` themselves only when this returns `false`.
+pub fn hold(message: &str) -> bool {
+    if !TUI_ACTIVE.load(Ordering::Relaxed) {
+        return false;
+    }
+    if let Ok(mut pending) = PENDING.lock() {
+        pending.push(message.trim().to_string());
+    }
+    true
+}
+
+/// Like `println!`, but holds the line while the split view owns the terminal
+macro_rules! say {
+    () => {
+        $crate::cli::output::say!("")
+    };
+    ($($arg:tt)*) => {{
+        let message = format!($($arg)*);
+        if !$crate::cli::output::hold(&message) {
+            println!("{message}");
+        }
+    }};
+}
+pub(crate) use say;
+
+/// Hold messages for the split view until the returned guard is dropped
+///
+/// All held messages are printed when the guard is dropped, also when the TUI
+/// exits with an error; the split view only shows the latest in its status line.
+pub fn enter_tui() -> TuiGuard {
+    TUI_ACTIVE.store(true, Ordering::Relaxed);
+    TuiGuard(())
+}
+
+/// Print messages directly while `f` runs, e.g. an external program the split view
+/// handed the terminal to
+///
+/// The messages held so far are printed first, so their count starts over afterwards.
+pub fn suspend_tui<T>(f: impl FnOnce() -> T) -> T {
+    drop(TuiGuard(()));
+    let result = f();
+    TUI_ACTIVE.store(true, Ordering::Relaxed);
+    result
+}
+
+/// Whether messages are held for the split view
+#[must_use]
+pub fn tui_active() -> bool {
+    TUI_ACTIVE.load(Ordering::Relaxed)
+}
+
+/// The latest held message, if any were held since `seen` messages
+///
+/// Messages stay held, so `seen` is updated to their number.
+#[must_use]
+pub fn latest_message(seen: &mut usize) -> Option<String> {
+    let pending = PENDING.lock().ok()?;
+    if pending.len() <= *seen {
+        return None;
+    }
+    *seen = pending.len();
+    pending.last().cloned()
+}
+
+/// The messages held so far, oldest first
+fn take_messages() -> Vec<String> {
+    PENDING
+        .lock()
+        .map(|mut pending| std::mem::take(&mut *pending))
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_messages_are_held_while_tui_is_active() {
+        let guard = enter_tui();
+        assert!(tui_active());
+        say!("  Saved the run {}", 1);
+        let mut seen = 0;
+        assert!(latest_message(&mut seen).is_some());
+        assert!(latest_message(&mut seen).is_none());
+        let held = take_messages();
+        // An external program gets the terminal, and messages are held again afterwards
+        assert!(suspend_tui(|| !tui_active()));
+        assert!(tui_active());
+        drop(guard);
+
+        assert!(!tui_active());
+        assert!(held.contains(&"Saved the run 1".to_string()));
+        assert!(!take_messages().contains(&"Saved the run 1".to_string()));
+    }
+}
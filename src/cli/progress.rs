@@ -1,11 +1,15 @@
 use std::collections::HashMap;
+use std::io;
 use std::sync::Arc;
 use std::time::Duration;
 
-use console::style;
-use indicatif::{MultiProgress, ProgressBar, ProgressStyle};
+use console::{Term, style};
+use indicatif::{MultiProgress, ProgressBar, ProgressDrawTarget, ProgressStyle, TermLike};
 use tokio::sync::Mutex;
 
+use super::glyphs::{glyph, rule};
+use super::verbosity::{plain, quiet};
+
 /// Agent emoji and color configuration
 #[derive(Clone)]
 pub struct AgentStyle {
@@ -16,21 +20,23 @@ pub struct AgentStyle {
 impl AgentStyle {
     #[must_use]
     pub fn for_agent(name: &str) -> Self {
-        match name.to_lowercase().as_str() {
+        // Several instances of an agent (`claude-2`) share its style
+        let name = name.to_lowercase();
+        match name.split('-').next().unwrap_or_default() {
             "claude" => AgentStyle {
-                emoji: "🤖",
+                emoji: glyph("🤖", "[C]"),
                 color: "magenta",
             },
             "gemini" => AgentStyle {
-                emoji: "💎",
+                emoji: glyph("💎", "[G]"),
                 color: "cyan",
             },
             "codex" => AgentStyle {
-                emoji: "🧠",
+                emoji: glyph("🧠", "[X]"),
                 color: "green",
             },
             _ => AgentStyle {
-                emoji: "⚡",
+                emoji: glyph("⚡", "[*]"),
                 color: "yellow",
             },
         }
@@ -49,10 +55,10 @@ pub enum AgentStatus {
 impl AgentStatus {
     fn emoji(self) -> &'static str {
         match self {
-            AgentStatus::Pending => "⏳",
-            AgentStatus::Running => "🔄",
-            AgentStatus::Completed => "✅",
-            AgentStatus::Failed => "❌",
+            AgentStatus::Pending => glyph("⏳", "[ ]"),
+            AgentStatus::Running => glyph("🔄", "[~]"),
+            AgentStatus::Completed => glyph("✅", "[+]"),
+            AgentStatus::Failed => glyph("❌", "[x]"),
         }
     }
 }
@@ -67,14 +73,20 @@ impl ProgressTracker {
     /// Create a new progress tracker for the given agent names
     #[must_use]
     pub fn new(agent_names: &[&str]) -> Self {
-        let multi_progress = MultiProgress::new();
+        let multi_progress = if quiet() || plain() {
+            MultiProgress::with_draw_target(ProgressDrawTarget::hidden())
+        } else {
+            MultiProgress::with_draw_target(ProgressDrawTarget::term_like(Box::new(RawStderr(
+                Term::stderr(),
+            ))))
+        };
         let mut bars = HashMap::new();
 
         // Create spinner style with custom characters
         // The template is a constant string, so it should always be valid.
         let spinner_style = ProgressStyle::with_template("{spinner:.bold} {prefix:.bold} {msg}")
             .unwrap_or_else(|_| ProgressStyle::default_spinner())
-            .tick_chars("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏");
+            .tick_chars(glyph("⠋⠙⠹⠸⠼⠴⠦⠧⠇⠏", "|/-\\+"));
 
         for name in agent_names {
             let agent_style = AgentStyle::for_agent(name);
@@ -85,7 +97,9 @@ impl ProgressTracker {
             let prefix = format!("{} {:<6}", agent_style.emoji, name);
             pb.set_prefix(prefix);
             pb.set_message(format!("{} Waiting...", AgentStatus::Pending.emoji()));
-            pb.enable_steady_tick(Duration::from_millis(100));
+            if !plain() {
+                pb.enable_steady_tick(Duration::from_millis(100));
+            }
 
             bars.insert(name.to_string(), pb);
         }
@@ -101,17 +115,17 @@ impl ProgressTracker {
         if let Some(pb) = self.bars.get(agent_name) {
             match status {
                 AgentStatus::Pending => {
-                    pb.set_message(format!("{} Waiting...", status.emoji()));
+                    set_message(pb, format!("{} Waiting...", status.emoji()));
                 }
                 AgentStatus::Running => {
-                    pb.set_message(format!("{} Running...", status.emoji()));
+                    set_message(pb, format!("{} Running...", status.emoji()));
                 }
                 AgentStatus::Completed => {
-                    pb.set_message(format!("{} Completed!", status.emoji()));
+                    set_message(pb, format!("{} Completed!", status.emoji()));
                     pb.finish();
                 }
                 AgentStatus::Failed => {
-                    pb.set_message(format!("{} Failed", status.emoji()));
+                    set_message(pb, format!("{} Failed", status.emoji()));
                     pb.finish();
                 }
             }
@@ -121,7 +135,14 @@ impl ProgressTracker {
     /// Update with a custom message
     pub fn update_message(&self, agent_name: &str, message: &str) {
         if let Some(pb) = self.bars.get(agent_name) {
-            pb.set_message(format!("🔄 {message}"));
+            set_message(pb, format!("{} {message}", AgentStatus::Running.emoji()));
+        }
+    }
+
+    /// Show why an agent has not started yet, e.g. waiting for a free slot
+    pub fn update_queued(&self, agent_name: &str, message: &str) {
+        if let Some(pb) = self.bars.get(agent_name) {
+            set_message(pb, format!("{} {message}", AgentStatus::Pending.emoji()));
         }
     }
 
@@ -139,6 +160,64 @@ impl ProgressTracker {
     }
 }
 
+/// Stderr that also returns to the start of the line on a newline
+///
+/// The terminal is in raw mode while the abort keys are read, where `\n` only moves
+/// down a line.
+#[derive(Debug)]
+struct RawStderr(Term);
+
+impl TermLike for RawStderr {
+    fn width(&self) -> u16 {
+        self.0.size().1
+    }
+
+    fn height(&self) -> u16 {
+        self.0.size().0
+    }
+
+    fn move_cursor_up(&self, n: usize) -> io::Result<()> {
+        self.0.move_cursor_up(n)
+    }
+
+    fn move_cursor_down(&self, n: usize) -> io::Result<()> {
+        self.0.move_cursor_down(n)
+    }
+
+    fn move_cursor_right(&self, n: usize) -> io::Result<()> {
+        self.0.move_cursor_right(n)
+    }
+
+    fn move_cursor_left(&self, n: usize) -> io::Result<()> {
+        self.0.move_cursor_left(n)
+    }
+
+    fn write_line(&self, s: &str) -> io::Result<()> {
+        self.0
+            .write_str(&format!("{}\r\n", s.replace('\n', "\r\n")))
+    }
+
+    fn write_str(&self, s: &str) -> io::Result<()> {
+        self.0.write_str(&s.replace('\n', "\r\n"))
+    }
+
+    fn clear_line(&self) -> io::Result<()> {
+        self.0.clear_line()
+    }
+
+    fn flush(&self) -> io::Result<()> {
+        self.0.flush()
+    }
+}
+
+/// Show a new message on an agent's spinner, or print it as a line in plain mode
+fn set_message(pb: &ProgressBar, message: String) {
+    if plain() && !quiet() && pb.message() != message {
+        eprintln!("{} {message}", pb.prefix());
+    }
+    pb.set_message(message);
+}
+
 /// Shared progress tracker that can be used across async tasks
 pub type SharedProgressTracker = Arc<Mutex<ProgressTracker>>;
 
@@ -148,16 +227,37 @@ pub fn create_shared_tracker(agent_names: &[&str]) -> SharedProgressTracker {
     Arc::new(Mutex::new(ProgressTracker::new(agent_names)))
 }
 
+/// Create a progress bar for the files copied while applying a result
+///
+/// The length is set by the caller once the number of files is known.
+#[must_use]
+pub fn create_apply_progress() -> ProgressBar {
+    // The template is a constant string, so it should always be valid.
+    let bar_style = ProgressStyle::with_template("  {bar:30.cyan} {pos}/{len} files")
+        .unwrap_or_else(|_| ProgressStyle::default_bar())
+        .progress_chars(glyph("█▌░", "#>-"));
+    let pb = if quiet() || plain() {
+        ProgressBar::hidden()
+    } else {
+        ProgressBar::new(0)
+    };
+    pb.set_style(bar_style);
+    pb
+}
+
 /// Display header with colorful styling
 pub fn display_header(agent_names: &[&str]) {
+    if quiet() {
+        return;
+    }
     println!();
-    println!("{}", style("━".repeat(50)).cyan());
+    println!("{}", style(rule(50)).cyan());
     println!(
         "  {} {}",
-        style("🚀").bold(),
+        style(glyph("🚀", ">>")).bold(),
         style("Running AI Agents in Parallel").bold().cyan()
     );
-    println!("{}", style("━".repeat(50)).cyan());
+    println!("{}", style(rule(50)).cyan());
     println!();
 
     for name in agent_names {
@@ -174,13 +274,16 @@ pub fn display_header(agent_names: &[&str]) {
 
 /// Display completion summary
 pub fn display_completion_summary(completed: &[&str], failed: &[&str]) {
+    if quiet() {
+        return;
+    }
     println!();
-    println!("{}", style("━".repeat(50)).cyan());
+    println!("{}", style(rule(50)).cyan());
 
     if !completed.is_empty() {
         println!(
             "  {} {} agent(s) completed successfully",
-            style("✅").green(),
+            style(AgentStatus::Completed.emoji()).green(),
             style(completed.len()).green().bold()
         );
         for name in completed {
@@ -192,7 +295,7 @@ pub fn display_completion_summary(completed: &[&str], failed: &[&str]) {
     if !failed.is_empty() {
         println!(
             "  {} {} agent(s) failed",
-            style("❌").red(),
+            style(AgentStatus::Failed.emoji()).red(),
             style(failed.len()).red().bold()
         );
         for name in failed {
@@ -201,7 +304,7 @@ pub fn display_completion_summary(completed: &[&str], failed: &[&str]) {
         }
     }
 
-    println!("{}", style("━".repeat(50)).cyan());
+    println!("{}", style(rule(50)).cyan());
     println!();
 }
 
@@ -1,63 +1,224 @@
+mod ansi;
 mod app;
+mod clipboard;
+mod compare;
 mod content;
+mod delta;
+mod diff_cache;
+mod external;
+mod highlight;
 mod render;
+mod side_by_side;
+mod theme;
 mod types;
 
+#[cfg(test)]
+mod driver;
 #[cfg(test)]
 mod tests;
 
+use std::sync::Arc;
 use std::time::Duration;
 
 use ratatui::crossterm::event;
+use ratatui::text::Text;
 
 use app::App;
-use content::{get_diff_content_string, get_log_content_string};
+use compare::Comparer;
+use content::{
+    fold_diff_sections, get_checks_content_string, get_log_content_string, get_stat_content_string,
+    get_summary_content_string, get_transcript_content_string, prefix_len,
+};
+use delta::render_with_delta;
+use diff_cache::DIFF_LOADING_PLACEHOLDER;
+use external::{discard_worktree, run_external};
+use highlight::Highlighter;
 use render::render;
 use types::{SplitViewResult, ViewMode};
 
 pub use types::SplitViewResult as SelectionResult;
 
-use crate::domain::ResultInfo;
+use crate::cli::output;
+use crate::cli::ui::{Selection, is_delta_available};
+use crate::config::{Theme, UiSettings};
+use crate::domain::{ResultInfo, Review, RunInfo};
 use crate::error::{Error, Result};
+use crate::executor::Executor;
+
+/// Number of content lines made available to the view at a time
+///
+/// Long logs and diffs are exposed in chunks so searching, folding and styling
+/// only work on the part the user has scrolled to.
+const CONTENT_CHUNK_LINES: usize = 5_000;
+
+/// Full content of the current view of the selected result
+fn view_content(app: &App) -> Option<String> {
+    let info = app.selected_info()?;
+    Some(match app.current_mode {
+        ViewMode::Log => get_log_content_string(
+            info,
+            &app.collapsed_dirs,
+            app.output_filter,
+            app.ansi_colors,
+        ),
+        ViewMode::Diff => match app.diff_cache.get(&info.worktree_path) {
+            Some(diff) => fold_diff_sections(diff, &app.collapsed_files),
+            None => DIFF_LOADING_PLACEHOLDER.to_string(),
+        },
+        ViewMode::Stat => get_stat_content_string(info),
+        ViewMode::Summary => get_summary_content_string(info),
+        ViewMode::Checks => get_checks_content_string(info),
+        ViewMode::Transcript => get_transcript_content_string(info),
+    })
+}
+
+/// Render real diffs through delta when it is enabled, from byte `from` of `content` on
+fn render_delta(app: &App, content: &str, from: usize) -> Option<Text<'static>> {
+    if app.use_delta && app.current_mode == ViewMode::Diff && content.starts_with("diff --git") {
+        render_with_delta(content, from)
+    } else {
+        None
+    }
+}
+
+/// Load the first `lines` lines of `content`, returning their length in bytes
+///
+/// Only the newly loaded chunk is rendered through delta and appended to the rendered part.
+/// If that fails, the whole diff falls back to the built-in styling so the rows do not mix.
+fn load_chunk(app: &mut App, content: &str, loaded_len: usize, lines: usize) -> usize {
+    let len = prefix_len(content, lines);
+    if app.delta_text.is_some() {
+        let chunk = render_delta(app, &content[..len], loaded_len);
+        match (&mut app.delta_text, chunk) {
+            (Some(text), Some(chunk)) => text.lines.extend(chunk.lines),
+            _ => app.delta_text = None,
+        }
+    }
+    len
+}
+
+/// Delete the worktrees the user queued for discarding, reporting the outcome in the footer
+fn discard_pending(app: &mut App) {
+    let pending = std::mem::take(&mut app.pending_discards);
+    let Some(repo) = app.run_info.as_ref().map(|run| run.working_dir.clone()) else {
+        return;
+    };
+    if let Some(comparer) = &mut app.comparer {
+        comparer.stop();
+    }
+
+    let mut deleted = 0;
+    for index in pending {
+        let Some(info) = app.result_infos.get(index) else {
+            continue;
+        };
+        if let Err(e) = discard_worktree(&repo, &info.worktree_path) {
+            app.status_message = Some(format!(
+                "Failed to delete the worktree of {}: {e}",
+                info.executor_name
+            ));
+            return;
+        }
+        app.discarded.insert(index);
+        deleted += 1;
+    }
+    app.status_message = Some(format!("Deleted {deleted} rejected worktree(s)"));
+}
 
 /// Display results in a split view and allow user to select one
 ///
+/// `comparer` writes the comparisons of two candidates asked for with `m`.
+///
 /// # Errors
 ///
 /// Returns an error if no results are available, terminal I/O fails, or the user cancels.
-pub fn select_result_split_view(result_infos: &[ResultInfo]) -> Result<usize> {
+pub fn select_result_split_view(
+    result_infos: &[ResultInfo],
+    run: &RunInfo,
+    settings: &UiSettings,
+    reviews: &mut [Review],
+    comparer: Option<Arc<dyn Executor>>,
+) -> Result<Selection> {
     if result_infos.is_empty() {
         return Err(Error::NoExecutorsAvailable);
     }
 
     let mut terminal = ratatui::init();
+    let _output = output::enter_tui();
     let mut app = App::new(result_infos.to_vec());
-    let mut cached_content = String::new();
+    app.theme = settings.theme;
+    app.large_diff_files = settings.large_diff_files;
+    app.large_diff_lines = settings.large_diff_lines;
+    app.run_info = Some(run.clone());
+    app.comparer = comparer.map(Comparer::new);
+    // Syntax highlighting and delta are pure color, so they are skipped without colors
+    let colors = settings.theme != Theme::NoColor;
+    if colors && settings.syntax_highlighting {
+        app.highlighter = Some(Highlighter::new());
+    }
+    app.use_delta = colors && settings.delta && is_delta_available();
+    app.ansi_colors = colors && settings.ansi_colors;
+
+    // Compute all diffs in the background right away
+    for info in result_infos {
+        app.diff_cache.request(&info.worktree_path);
+    }
+
+    // Full content of the current view; only the first `loaded_lines` lines are shown
+    let mut full_content = String::new();
+    let mut loaded_lines = 0usize;
+    let mut loaded_len = 0usize;
     let mut last_selected = 0usize;
     let mut last_mode = app.current_mode;
+    let mut seen_messages = 0;
 
     loop {
+        // Messages sent elsewhere would garble the screen, so show the latest in the status
+        // line; all of them are printed once the split view exits
+        if let Some(message) = output::latest_message(&mut seen_messages) {
+            app.status_message = Some(message);
+        }
+
+        // Show a diff as soon as its background computation finishes
+        if app.diff_cache.poll() && app.current_mode == ViewMode::Diff {
+            app.refresh_content = true;
+        }
+
+        // Comparisons are shown in their popup as soon as the agent has written them
+        if let Some(comparer) = &mut app.comparer {
+            comparer.poll();
+        }
+
         // Update content cache if selection or mode changed
-        if app.selected_index() != last_selected || app.current_mode != last_mode {
-            if let Some(info) = app.selected_info() {
-                cached_content = match app.current_mode {
-                    ViewMode::Log => get_log_content_string(info),
-                    ViewMode::Diff => get_diff_content_string(&info.worktree_path),
-                };
-                app.content_height =
-                    u16::try_from(cached_content.lines().count()).unwrap_or(u16::MAX);
+        if app.selected_index() != last_selected
+            || app.current_mode != last_mode
+            || app.refresh_content
+        {
+            if let Some(content) = view_content(&app) {
+                full_content = content;
+                loaded_lines = CONTENT_CHUNK_LINES;
+                loaded_len = prefix_len(&full_content, loaded_lines);
+                app.delta_text = render_delta(&app, &full_content[..loaded_len], 0);
             }
             last_selected = app.selected_index();
             last_mode = app.current_mode;
+            app.refresh_content = false;
         }
 
-        let viewport_height = terminal
-            .size()
-            .map(|s| s.height.saturating_sub(4))
-            .unwrap_or(20);
+        let viewport_height = terminal.size().map_or(20, |s| s.height.saturating_sub(4));
+
+        // Load the next chunk of a long content when scrolling close to the loaded end
+        if loaded_len < full_content.len()
+            && usize::from(app.scroll_offset) + 2 * usize::from(viewport_height)
+                >= usize::from(app.content_height)
+        {
+            loaded_lines += CONTENT_CHUNK_LINES;
+            loaded_len = load_chunk(&mut app, &full_content, loaded_len, loaded_lines);
+        }
+        let cached_content = &full_content[..loaded_len];
 
         terminal
-            .draw(|frame| render(frame, &mut app, &cached_content))
+            .draw(|frame| render(frame, &mut app, cached_content))
             .map_err(|e| Error::Io(std::io::Error::other(e.to_string())))?;
 
         if event::poll(Duration::from_millis(100))
@@ -65,16 +226,44 @@ pub fn select_result_split_view(result_infos: &[ResultInfo]) -> Result<usize> {
         {
             let event =
                 event::read().map_err(|e| Error::Io(std::io::Error::other(e.to_string())))?;
-            if app.handle_event(&event, viewport_height, &cached_content) {
+            if app.handle_event(&event, viewport_height, cached_content) {
                 break;
             }
+
+            // Suspend the TUI while an external program owns the terminal
+            if let Some(action) = app.pending_action.take()
+                && let Some(info) = app.selected_info()
+            {
+                ratatui::restore();
+                let _ = output::suspend_tui(|| run_external(action, &info.worktree_path));
+                seen_messages = 0;
+                terminal = ratatui::init();
+                // The program may have edited the worktree
+                let worktree = info.worktree_path.clone();
+                app.diff_cache.invalidate(&worktree);
+                app.diff_cache.request(&worktree);
+                app.refresh_content = true;
+            }
+
+            if !app.pending_discards.is_empty() {
+                discard_pending(&mut app);
+            }
         }
     }
 
     ratatui::restore();
 
+    for (i, review) in reviews.iter_mut().enumerate() {
+        review.rejected = app.rejected.contains(&i);
+        review.note = app.notes.get(&i).cloned();
+    }
+
     match app.result {
-        Some(SplitViewResult::Apply(index)) => Ok(index),
+        Some(SplitViewResult::Apply(index)) => Ok(Selection {
+            index,
+            stash: false,
+        }),
+        Some(SplitViewResult::ApplyStashed(index)) => Ok(Selection { index, stash: true }),
         Some(SplitViewResult::Cancel) | None => Err(Error::UserCancelled),
     }
 }
@@ -1,10 +1,45 @@
+use std::collections::{HashMap, HashSet};
+
+use arboard::Clipboard;
 use ratatui::crossterm::event::{self, Event, KeyCode, KeyEventKind};
+use ratatui::text::Text;
 use ratatui::widgets::ListState;
 
-use super::types::{FocusedPanel, InputMode, SplitViewResult, ViewMode};
-use crate::domain::ResultInfo;
+use super::clipboard::{copy_to_clipboard, yank_text};
+use super::compare::{Comparer, Pair};
+use super::content::{
+    STDERR_MARKER, diff_file_starts, diff_section_path, error_lines, find_fold_dir,
+    fold_diff_sections, get_log_content_string, group_files_by_dir, strip_ansi_codes,
+};
+use super::diff_cache::DiffCache;
+use super::highlight::Highlighter;
+use super::types::{
+    ExternalAction, FocusedPanel, GlobalMatch, InputMode, OutputFilter, SplitViewResult, ViewMode,
+    YankTarget,
+};
+use crate::config::Theme;
+use crate::domain::{ResultInfo, RunInfo};
+
+/// Columns moved by one horizontal scroll step
+const HORIZONTAL_SCROLL_STEP: u16 = 4;
+
+/// Default width of the models panel
+pub const MODELS_PANEL_WIDTH: u16 = 32;
+
+/// Narrowest models panel, which only has room for the agent icons
+pub const MIN_MODELS_PANEL_WIDTH: u16 = 8;
+
+/// Widest models panel
+const MAX_MODELS_PANEL_WIDTH: u16 = 80;
+
+/// Columns added or removed by one resize step
+const MODELS_PANEL_RESIZE_STEP: u16 = 4;
 
 /// Application state
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent toggles of the detail view"
+)]
 pub struct App {
     pub result_infos: Vec<ResultInfo>,
     pub list_state: ListState,
@@ -12,11 +47,81 @@ pub struct App {
     pub focused_panel: FocusedPanel,
     pub input_mode: InputMode,
     pub scroll_offset: u16,
+    /// Width of the models panel in the side-by-side layout
+    pub models_width: u16,
+    /// First visible column when line wrapping is off; `None` means lines are wrapped
+    pub horizontal_scroll: Option<u16>,
     pub content_height: u16,
     pub search_query: String,
     pub search_matches: Vec<u16>,
     pub search_match_index: usize,
     pub result: Option<SplitViewResult>,
+    /// Directories collapsed in the per-file summary of the log view
+    pub collapsed_dirs: HashSet<String>,
+    /// Output streams shown in the Log view
+    pub output_filter: OutputFilter,
+    /// File sections collapsed in the diff view
+    pub collapsed_files: HashSet<String>,
+    /// Whether the cached content must be regenerated
+    pub refresh_content: bool,
+    /// Whether diffs are rendered in two columns (old/new)
+    pub side_by_side: bool,
+    /// Whether the detail view shows a line-number gutter
+    pub line_numbers: bool,
+    /// Line number typed after `:`
+    pub goto_input: String,
+    /// Syntax highlighter for the diff view, if enabled
+    pub highlighter: Option<Highlighter>,
+    /// Whether diffs are rendered through delta
+    pub use_delta: bool,
+    /// Whether the agents' own ANSI colors are shown in the log view
+    pub ansi_colors: bool,
+    /// Diff of the selected result as rendered by delta
+    pub delta_text: Option<Text<'static>>,
+    /// Side-by-side rendering of the diff, with the content and width it was built for
+    pub side_by_side_text: Option<(String, u16, Text<'static>)>,
+    /// Line of the content each row of `side_by_side_text` starts at
+    pub side_by_side_lines: Vec<usize>,
+    /// Content line to scroll to once the content it refers to is rendered
+    pub pending_line: Option<usize>,
+    /// Diffs of all worktrees, computed in the background
+    pub diff_cache: DiffCache,
+    /// Candidates marked as rejected while reviewing
+    pub rejected: HashSet<usize>,
+    /// Rejected candidates whose worktrees have been deleted
+    pub discarded: HashSet<usize>,
+    /// Candidates whose worktrees should be deleted by the main loop
+    pub pending_discards: Vec<usize>,
+    /// Notes about candidates, kept in the run history
+    pub notes: HashMap<usize, String>,
+    /// Note being typed for the selected candidate
+    pub note_input: String,
+    /// Prompt and metadata of the run the results come from
+    pub run_info: Option<RunInfo>,
+    /// Agent comparing two candidates on request; `None` when no agent is available
+    pub comparer: Option<Comparer>,
+    /// Candidate marked to be compared with the next one marked
+    pub compare_mark: Option<usize>,
+    /// Candidates whose comparison is shown
+    pub comparison: Option<Pair>,
+    /// Query of the search across all results
+    pub global_query: String,
+    /// Matches of the search across all results, grouped by result
+    pub global_matches: Vec<GlobalMatch>,
+    /// Selected entry of `global_matches`
+    pub global_selected: usize,
+    /// External program to run once the TUI is suspended
+    pub pending_action: Option<ExternalAction>,
+    /// Color theme applied to every frame
+    pub theme: Theme,
+    /// Files changed above which a candidate is flagged as a large diff (0 disables)
+    pub large_diff_files: usize,
+    /// Lines changed above which a candidate is flagged as a large diff (0 disables)
+    pub large_diff_lines: usize,
+    /// One-off message shown in the footer until the next key press
+    pub status_message: Option<String>,
+    /// System clipboard, created on the first yank
+    clipboard: Option<Clipboard>,
 }
 
 impl App {
@@ -33,11 +138,46 @@ impl App {
             focused_panel: FocusedPanel::Models,
             input_mode: InputMode::Normal,
             scroll_offset: 0,
+            models_width: MODELS_PANEL_WIDTH,
+            horizontal_scroll: None,
             content_height: 0,
             search_query: String::new(),
             search_matches: Vec::new(),
             search_match_index: 0,
             result: None,
+            collapsed_dirs: HashSet::new(),
+            output_filter: OutputFilter::All,
+            collapsed_files: HashSet::new(),
+            refresh_content: true,
+            side_by_side: false,
+            line_numbers: false,
+            goto_input: String::new(),
+            highlighter: None,
+            use_delta: false,
+            ansi_colors: false,
+            delta_text: None,
+            side_by_side_text: None,
+            side_by_side_lines: Vec::new(),
+            pending_line: None,
+            diff_cache: DiffCache::new(),
+            rejected: HashSet::new(),
+            discarded: HashSet::new(),
+            pending_discards: Vec::new(),
+            notes: HashMap::new(),
+            note_input: String::new(),
+            run_info: None,
+            comparer: None,
+            compare_mark: None,
+            comparison: None,
+            global_query: String::new(),
+            global_matches: Vec::new(),
+            global_selected: 0,
+            pending_action: None,
+            theme: Theme::Default,
+            large_diff_files: 0,
+            large_diff_lines: 0,
+            status_message: None,
+            clipboard: None,
         }
     }
 
@@ -65,22 +205,18 @@ impl App {
         };
         self.list_state.select(Some(i));
         self.scroll_offset = 0;
+        self.reset_horizontal_scroll();
         self.clear_search();
     }
 
     pub fn previous_model(&mut self) {
         let i = match self.list_state.selected() {
-            Some(i) => {
-                if i > 0 {
-                    i - 1
-                } else {
-                    0
-                }
-            }
+            Some(i) => i.saturating_sub(1),
             None => 0,
         };
         self.list_state.select(Some(i));
         self.scroll_offset = 0;
+        self.reset_horizontal_scroll();
         self.clear_search();
     }
 
@@ -92,6 +228,62 @@ impl App {
         self.scroll_offset = self.scroll_offset.saturating_sub(lines);
     }
 
+    /// Switch between wrapped lines and horizontal scrolling
+    pub fn toggle_wrap(&mut self) {
+        self.horizontal_scroll = match self.horizontal_scroll {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    pub fn scroll_left(&mut self) {
+        if let Some(column) = self.horizontal_scroll.as_mut() {
+            *column = column.saturating_sub(HORIZONTAL_SCROLL_STEP);
+        }
+    }
+
+    pub fn scroll_right(&mut self) {
+        if let Some(column) = self.horizontal_scroll.as_mut() {
+            *column = column.saturating_add(HORIZONTAL_SCROLL_STEP);
+        }
+    }
+
+    /// Move back to the first column, keeping the wrap setting
+    fn reset_horizontal_scroll(&mut self) {
+        if self.horizontal_scroll.is_some() {
+            self.horizontal_scroll = Some(0);
+        }
+    }
+
+    /// Rows of the side-by-side diff mapped to content lines, while it is shown
+    fn row_lines(&self) -> Option<&[usize]> {
+        (self.current_mode == ViewMode::Diff && self.side_by_side)
+            .then_some(self.side_by_side_lines.as_slice())
+    }
+
+    /// The content line shown at the top of the viewport
+    ///
+    /// Scrolling counts rendered rows, while navigation and search count lines of
+    /// the content; side-by-side rows pair removed and added lines.
+    pub fn top_line(&self) -> usize {
+        let row = usize::from(self.scroll_offset);
+        match self.row_lines() {
+            Some(lines) => lines.get(row).or(lines.last()).copied().unwrap_or(0),
+            None => row,
+        }
+    }
+
+    /// Scroll so the row showing a content line is at the top of the viewport
+    pub fn scroll_to_line(&mut self, line: usize) {
+        let row = match self.row_lines() {
+            Some(lines) => lines
+                .partition_point(|start| *start <= line)
+                .saturating_sub(1),
+            None => line,
+        };
+        self.scroll_offset = u16::try_from(row).unwrap_or(u16::MAX);
+    }
+
     pub fn scroll_to_top(&mut self) {
         self.scroll_offset = 0;
     }
@@ -114,10 +306,23 @@ impl App {
         if self.current_mode != mode {
             self.current_mode = mode;
             self.scroll_offset = 0;
+            self.reset_horizontal_scroll();
             self.clear_search();
         }
     }
 
+    pub fn widen_models_panel(&mut self) {
+        self.models_width =
+            (self.models_width + MODELS_PANEL_RESIZE_STEP).min(MAX_MODELS_PANEL_WIDTH);
+    }
+
+    pub fn narrow_models_panel(&mut self) {
+        self.models_width = self
+            .models_width
+            .saturating_sub(MODELS_PANEL_RESIZE_STEP)
+            .max(MIN_MODELS_PANEL_WIDTH);
+    }
+
     pub fn toggle_focus(&mut self) {
         self.focused_panel = match self.focused_panel {
             FocusedPanel::Models => FocusedPanel::Details,
@@ -125,6 +330,170 @@ impl App {
         };
     }
 
+    /// Collapse or expand the section at the top of the viewport
+    ///
+    /// In the log view this folds a directory of the per-file summary, in the diff
+    /// view it folds a whole file section.
+    pub fn toggle_fold(&mut self, content: &str) {
+        let top = self.top_line();
+        let (target, collapsed) = match self.current_mode {
+            ViewMode::Log => (find_fold_dir(content, top), &mut self.collapsed_dirs),
+            ViewMode::Diff => (
+                content
+                    .lines()
+                    .take(top + 1)
+                    .filter_map(diff_section_path)
+                    .last()
+                    .map(str::to_string),
+                &mut self.collapsed_files,
+            ),
+            _ => return,
+        };
+        if let Some(target) = target {
+            if !collapsed.remove(&target) {
+                collapsed.insert(target);
+            }
+            self.refresh_content = true;
+        }
+    }
+
+    /// Collapse all sections of the current view, or expand them if any are collapsed
+    pub fn toggle_all_folds(&mut self, content: &str) {
+        let (all, collapsed): (Vec<String>, _) = match self.current_mode {
+            ViewMode::Log => (
+                self.selected_info()
+                    .and_then(|info| info.change_summary.as_ref())
+                    .map(|summary| {
+                        group_files_by_dir(&summary.changed_files)
+                            .into_keys()
+                            .collect()
+                    })
+                    .unwrap_or_default(),
+                &mut self.collapsed_dirs,
+            ),
+            ViewMode::Diff => (
+                content
+                    .lines()
+                    .filter_map(diff_section_path)
+                    .map(str::to_string)
+                    .collect(),
+                &mut self.collapsed_files,
+            ),
+            _ => return,
+        };
+        if collapsed.is_empty() {
+            collapsed.extend(all);
+        } else {
+            collapsed.clear();
+        }
+        self.refresh_content = true;
+    }
+
+    /// Show the Log view with the next output filter (both, stderr only, stdout only)
+    pub fn cycle_output_filter(&mut self) {
+        self.set_mode(ViewMode::Log);
+        self.output_filter = self.output_filter.next();
+        self.scroll_offset = 0;
+        self.clear_search();
+        self.refresh_content = true;
+    }
+
+    /// Switch between unified and side-by-side diff rendering
+    pub fn toggle_side_by_side(&mut self) {
+        self.side_by_side = !self.side_by_side;
+        self.scroll_offset = 0;
+    }
+
+    /// Jump to the next file section in the diff view
+    pub fn next_file(&mut self, content: &str) {
+        let top = self.top_line();
+        if let Some(start) = diff_file_starts(content)
+            .into_iter()
+            .find(|start| usize::from(*start) > top)
+        {
+            self.scroll_to_line(usize::from(start));
+        }
+    }
+
+    /// Jump to the previous file section in the diff view
+    pub fn previous_file(&mut self, content: &str) {
+        let top = self.top_line();
+        if let Some(start) = diff_file_starts(content)
+            .into_iter()
+            .rev()
+            .find(|start| usize::from(*start) < top)
+        {
+            self.scroll_to_line(usize::from(start));
+        }
+    }
+
+    /// Jump to the next error line in the log view
+    pub fn next_error(&mut self, content: &str) {
+        if self.current_mode != ViewMode::Log {
+            return;
+        }
+        if let Some(line) = error_lines(content)
+            .into_iter()
+            .find(|line| *line > self.scroll_offset)
+        {
+            self.scroll_offset = line;
+        }
+    }
+
+    /// Jump to the previous error line in the log view
+    pub fn previous_error(&mut self, content: &str) {
+        if self.current_mode != ViewMode::Log {
+            return;
+        }
+        if let Some(line) = error_lines(content)
+            .into_iter()
+            .rev()
+            .find(|line| *line < self.scroll_offset)
+        {
+            self.scroll_offset = line;
+        }
+    }
+
+    /// Request an external program for the selected worktree
+    pub fn request_external(&mut self, action: ExternalAction) {
+        if self.selected_info().is_some() {
+            self.pending_action = Some(action);
+        }
+    }
+
+    pub fn start_yank(&mut self) {
+        self.input_mode = InputMode::Yank;
+    }
+
+    /// Copy part of the selected result to the system clipboard
+    pub fn yank(&mut self, target: YankTarget) {
+        let Some(info) = self.selected_info() else {
+            return;
+        };
+        let text = yank_text(info, target);
+        self.status_message = Some(match copy_to_clipboard(&mut self.clipboard, text) {
+            Ok(()) => format!("Copied {} to clipboard", target.label()),
+            Err(e) => format!("Failed to copy to clipboard: {e}"),
+        });
+    }
+
+    pub fn toggle_line_numbers(&mut self) {
+        self.line_numbers = !self.line_numbers;
+    }
+
+    pub fn start_goto_line(&mut self) {
+        self.input_mode = InputMode::GotoLine;
+        self.goto_input.clear();
+    }
+
+    /// Scroll to the 1-based line number typed after `:`
+    pub fn execute_goto_line(&mut self) {
+        self.input_mode = InputMode::Normal;
+        if let Ok(line) = self.goto_input.parse::<usize>() {
+            self.scroll_to_line(line.saturating_sub(1));
+        }
+    }
+
     pub fn start_search(&mut self) {
         self.input_mode = InputMode::Search;
         self.search_query.clear();
@@ -159,7 +528,7 @@ impl App {
 
         if !self.search_matches.is_empty() {
             self.search_match_index = 0;
-            self.scroll_offset = self.search_matches[0];
+            self.scroll_to_line(usize::from(self.search_matches[0]));
         }
     }
 
@@ -168,7 +537,7 @@ impl App {
             return;
         }
         self.search_match_index = (self.search_match_index + 1) % self.search_matches.len();
-        self.scroll_offset = self.search_matches[self.search_match_index];
+        self.scroll_to_line(usize::from(self.search_matches[self.search_match_index]));
     }
 
     pub fn previous_search_match(&mut self) {
@@ -180,15 +549,241 @@ impl App {
         } else {
             self.search_match_index -= 1;
         }
-        self.scroll_offset = self.search_matches[self.search_match_index];
+        self.scroll_to_line(usize::from(self.search_matches[self.search_match_index]));
+    }
+
+    /// Show the run metadata popup, if the run is known
+    pub fn show_run_info(&mut self) {
+        if self.run_info.is_some() {
+            self.input_mode = InputMode::RunInfo;
+        }
+    }
+
+    /// Mark the selected candidate for comparison, or compare it with the marked one
+    pub fn mark_for_comparison(&mut self) {
+        if self.comparer.is_none() {
+            self.status_message = Some("No agent is available to compare candidates".to_string());
+            return;
+        }
+        let Some(name) = self.selected_info().map(|info| info.executor_name.clone()) else {
+            return;
+        };
+        let index = self.selected_index();
+        match self.compare_mark.take() {
+            None => {
+                self.compare_mark = Some(index);
+                self.status_message = Some(format!(
+                    "Select another candidate and press m to compare it with {name}"
+                ));
+            }
+            Some(marked) if marked == index => {
+                self.status_message = Some(format!("{name} is no longer marked for comparison"));
+            }
+            Some(marked) => self.show_comparison((marked.min(index), marked.max(index))),
+        }
+    }
+
+    /// Show the comparison of two candidates, asking the agent for it if needed
+    pub fn show_comparison(&mut self, pair: Pair) {
+        let (Some(comparer), Some(a), Some(b)) = (
+            self.comparer.as_mut(),
+            self.result_infos.get(pair.0),
+            self.result_infos.get(pair.1),
+        ) else {
+            return;
+        };
+        comparer.request(
+            pair,
+            (&a.executor_name, &a.worktree_path),
+            (&b.executor_name, &b.worktree_path),
+        );
+        self.comparison = Some(pair);
+        self.input_mode = InputMode::Compare;
+    }
+
+    pub fn start_global_search(&mut self) {
+        self.input_mode = InputMode::GlobalSearch;
+        self.global_query.clear();
+    }
+
+    /// Search the log and diff of every result for the global query
+    pub fn execute_global_search(&mut self) {
+        self.global_matches.clear();
+        self.global_selected = 0;
+        if self.global_query.is_empty() {
+            self.input_mode = InputMode::Normal;
+            return;
+        }
+
+        let query_lower = self.global_query.to_lowercase();
+        for index in 0..self.result_infos.len() {
+            let info = &self.result_infos[index];
+            let log = get_log_content_string(
+                info,
+                &self.collapsed_dirs,
+                self.output_filter,
+                self.ansi_colors,
+            );
+            let worktree = info.worktree_path.clone();
+            let diff = fold_diff_sections(
+                self.diff_cache.get_or_load(&worktree),
+                &self.collapsed_files,
+            );
+
+            for (mode, content) in [(ViewMode::Log, log), (ViewMode::Diff, diff)] {
+                for (line, text) in content.lines().enumerate() {
+                    let text = text.trim_start_matches(STDERR_MARKER);
+                    if text.to_lowercase().contains(&query_lower) {
+                        self.global_matches.push(GlobalMatch {
+                            result_index: index,
+                            mode,
+                            line,
+                            text: strip_ansi_codes(text),
+                        });
+                    }
+                }
+            }
+        }
+
+        // Diffs that finished loading meanwhile must be picked up by the view
+        self.refresh_content = true;
+        self.input_mode = InputMode::GlobalResults;
+    }
+
+    pub fn next_global_match(&mut self) {
+        if self.global_selected + 1 < self.global_matches.len() {
+            self.global_selected += 1;
+        }
+    }
+
+    pub fn previous_global_match(&mut self) {
+        self.global_selected = self.global_selected.saturating_sub(1);
+    }
+
+    /// Show the selected global match in its result and view
+    pub fn jump_to_global_match(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let Some(found) = self.global_matches.get(self.global_selected).cloned() else {
+            return;
+        };
+
+        self.list_state.select(Some(found.result_index));
+        self.clear_search();
+        self.current_mode = found.mode;
+        self.focused_panel = FocusedPanel::Details;
+        self.scroll_to_line(found.line);
+        // Side-by-side rows of the result's diff are only known once it is rendered
+        if self.row_lines().is_some() {
+            self.pending_line = Some(found.line);
+        }
+        self.reset_horizontal_scroll();
+        self.refresh_content = true;
     }
 
     pub fn apply(&mut self) {
         self.result = Some(SplitViewResult::Apply(self.selected_index()));
     }
 
+    /// Apply the selected result after stashing the local changes it conflicts with
+    ///
+    /// Does nothing if the selected result does not conflict with local changes.
+    pub fn apply_stashed(&mut self) -> bool {
+        if self
+            .selected_info()
+            .is_none_or(|info| info.conflicts.is_empty())
+        {
+            return false;
+        }
+        self.result = Some(SplitViewResult::ApplyStashed(self.selected_index()));
+        true
+    }
+
     pub fn start_confirm(&mut self) {
-        self.input_mode = InputMode::Confirm;
+        if self.discarded.contains(&self.selected_index()) {
+            self.status_message = Some("This candidate's worktree was deleted".to_string());
+            return;
+        }
+        self.input_mode = if self
+            .selected_info()
+            .is_some_and(|info| !info.secrets.is_empty())
+        {
+            InputMode::ConfirmSecrets
+        } else {
+            self.confirm_mode_after_secrets()
+        };
+    }
+
+    /// The confirmation following the one for likely secrets
+    fn confirm_mode_after_secrets(&self) -> InputMode {
+        if self.selected_is_large_diff() {
+            InputMode::ConfirmLargeDiff
+        } else {
+            InputMode::Confirm
+        }
+    }
+
+    /// Whether a candidate exceeds the large-diff limits
+    pub fn is_large_diff(&self, info: &ResultInfo) -> bool {
+        info.is_large_diff(self.large_diff_files, self.large_diff_lines)
+    }
+
+    /// Whether the selected candidate exceeds the large-diff limits
+    pub fn selected_is_large_diff(&self) -> bool {
+        self.selected_info()
+            .is_some_and(|info| self.is_large_diff(info))
+    }
+
+    /// Mark or unmark the selected candidate as rejected
+    pub fn toggle_rejected(&mut self) {
+        if self.selected_info().is_none() {
+            return;
+        }
+        let index = self.selected_index();
+        if !self.rejected.remove(&index) {
+            self.rejected.insert(index);
+        }
+    }
+
+    /// Start editing the note of the selected candidate
+    pub fn start_note(&mut self) {
+        if self.selected_info().is_none() {
+            return;
+        }
+        self.note_input = self
+            .notes
+            .get(&self.selected_index())
+            .cloned()
+            .unwrap_or_default();
+        self.input_mode = InputMode::Note;
+    }
+
+    /// Save the typed note; an empty note removes it
+    pub fn save_note(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let index = self.selected_index();
+        let note = self.note_input.trim();
+        if note.is_empty() {
+            self.notes.remove(&index);
+        } else {
+            self.notes.insert(index, note.to_string());
+        }
+    }
+
+    /// Ask to delete the worktrees of the rejected candidates
+    pub fn start_confirm_discard(&mut self) {
+        if self.rejected.iter().any(|i| !self.discarded.contains(i)) {
+            self.input_mode = InputMode::ConfirmDiscard;
+        } else {
+            self.status_message = Some("Mark candidates as rejected with r first".to_string());
+        }
+    }
+
+    /// Queue the worktrees of the rejected candidates for deletion
+    pub fn confirm_discard(&mut self) {
+        self.input_mode = InputMode::Normal;
+        let mut pending: Vec<usize> = self.rejected.difference(&self.discarded).copied().collect();
+        pending.sort_unstable();
+        self.pending_discards = pending;
     }
 
     pub fn cancel_confirm(&mut self) {
@@ -219,15 +814,94 @@ impl App {
         viewport_height: u16,
         content: &str,
     ) -> bool {
+        self.status_message = None;
+        // Raw mode turns Ctrl-C into a key, so it asks to quit from anywhere; a second one quits
+        if key.code == KeyCode::Char('c') && key.modifiers.contains(event::KeyModifiers::CONTROL) {
+            if self.input_mode == InputMode::ConfirmCancel {
+                self.cancel();
+                return true;
+            }
+            self.start_confirm_cancel();
+            return false;
+        }
         match self.input_mode {
             InputMode::Confirm => self.handle_confirm_key(key),
+            InputMode::ConfirmSecrets => {
+                match key.code {
+                    KeyCode::Char('y' | 'Y') => {
+                        self.input_mode = self.confirm_mode_after_secrets();
+                    }
+                    KeyCode::Char('n' | 'N') | KeyCode::Esc => self.cancel_confirm(),
+                    _ => {}
+                }
+                false
+            }
+            InputMode::ConfirmLargeDiff => {
+                match key.code {
+                    KeyCode::Char('y' | 'Y') => self.input_mode = InputMode::Confirm,
+                    KeyCode::Char('n' | 'N') | KeyCode::Esc => self.cancel_confirm(),
+                    _ => {}
+                }
+                false
+            }
             InputMode::ConfirmCancel => self.handle_confirm_cancel_key(key),
+            InputMode::ConfirmDiscard => {
+                match key.code {
+                    KeyCode::Char('y' | 'Y') => self.confirm_discard(),
+                    KeyCode::Char('n' | 'N') | KeyCode::Esc => self.cancel_confirm(),
+                    _ => {}
+                }
+                false
+            }
             InputMode::Search => {
                 self.handle_search_key(key, content);
                 false
             }
+            InputMode::Yank => {
+                self.handle_yank_key(key);
+                false
+            }
+            InputMode::GlobalSearch => {
+                self.handle_global_search_key(key);
+                false
+            }
+            InputMode::GlobalResults => {
+                self.handle_global_results_key(key);
+                false
+            }
+            InputMode::GotoLine => {
+                self.handle_goto_line_key(key);
+                false
+            }
+            InputMode::Note => {
+                match key.code {
+                    KeyCode::Enter => self.save_note(),
+                    KeyCode::Esc => self.input_mode = InputMode::Normal,
+                    KeyCode::Backspace => {
+                        self.note_input.pop();
+                    }
+                    KeyCode::Char(c) => self.note_input.push(c),
+                    _ => {}
+                }
+                false
+            }
+            InputMode::Compare => {
+                if matches!(key.code, KeyCode::Esc | KeyCode::Enter | KeyCode::Char('q')) {
+                    self.input_mode = InputMode::Normal;
+                }
+                false
+            }
+            InputMode::RunInfo => {
+                if matches!(
+                    key.code,
+                    KeyCode::Esc | KeyCode::Enter | KeyCode::Char('i' | 'q')
+                ) {
+                    self.input_mode = InputMode::Normal;
+                }
+                false
+            }
             InputMode::Normal => {
-                self.handle_normal_key(key, viewport_height);
+                self.handle_normal_key(key, viewport_height, content);
                 false
             }
         }
@@ -239,6 +913,7 @@ impl App {
                 self.apply();
                 true
             }
+            KeyCode::Char('s' | 'S') => self.apply_stashed(),
             KeyCode::Char('n' | 'N') | KeyCode::Esc => {
                 self.cancel_confirm();
                 false
@@ -261,6 +936,51 @@ impl App {
         }
     }
 
+    fn handle_yank_key(&mut self, key: ratatui::crossterm::event::KeyEvent) {
+        self.input_mode = InputMode::Normal;
+        let target = match key.code {
+            KeyCode::Char('p') => YankTarget::Path,
+            KeyCode::Char('d') => YankTarget::Diff,
+            KeyCode::Char('l') => YankTarget::Log,
+            _ => return,
+        };
+        self.yank(target);
+    }
+
+    fn handle_global_search_key(&mut self, key: ratatui::crossterm::event::KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.execute_global_search(),
+            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Backspace => {
+                self.global_query.pop();
+            }
+            KeyCode::Char(c) => self.global_query.push(c),
+            _ => {}
+        }
+    }
+
+    fn handle_global_results_key(&mut self, key: ratatui::crossterm::event::KeyEvent) {
+        match key.code {
+            KeyCode::Char('j') | KeyCode::Down => self.next_global_match(),
+            KeyCode::Char('k') | KeyCode::Up => self.previous_global_match(),
+            KeyCode::Enter => self.jump_to_global_match(),
+            KeyCode::Esc | KeyCode::Char('q') => self.input_mode = InputMode::Normal,
+            _ => {}
+        }
+    }
+
+    fn handle_goto_line_key(&mut self, key: ratatui::crossterm::event::KeyEvent) {
+        match key.code {
+            KeyCode::Enter => self.execute_goto_line(),
+            KeyCode::Esc => self.input_mode = InputMode::Normal,
+            KeyCode::Backspace => {
+                self.goto_input.pop();
+            }
+            KeyCode::Char(c) if c.is_ascii_digit() => self.goto_input.push(c),
+            _ => {}
+        }
+    }
+
     fn handle_search_key(&mut self, key: ratatui::crossterm::event::KeyEvent, content: &str) {
         match key.code {
             KeyCode::Enter => {
@@ -283,10 +1003,11 @@ impl App {
         &mut self,
         key: ratatui::crossterm::event::KeyEvent,
         viewport_height: u16,
+        content: &str,
     ) {
         match self.focused_panel {
             FocusedPanel::Models => self.handle_models_panel_key(key),
-            FocusedPanel::Details => self.handle_details_panel_key(key, viewport_height),
+            FocusedPanel::Details => self.handle_details_panel_key(key, viewport_height, content),
         }
     }
 
@@ -304,6 +1025,31 @@ impl App {
             // Mode switching
             KeyCode::Char('L') => self.set_mode(ViewMode::Log),
             KeyCode::Char('D') => self.set_mode(ViewMode::Diff),
+            KeyCode::Char('T') => self.set_mode(ViewMode::Stat),
+            KeyCode::Char('S') => self.set_mode(ViewMode::Summary),
+            KeyCode::Char('C') => self.set_mode(ViewMode::Checks),
+            KeyCode::Char('R') => self.set_mode(ViewMode::Transcript),
+            KeyCode::Char('v') => self.toggle_side_by_side(),
+            KeyCode::Char('O') => self.cycle_output_filter(),
+
+            // External programs
+            KeyCode::Char('p') => self.request_external(ExternalAction::Pager),
+            KeyCode::Char('x') => self.request_external(ExternalAction::Difftool),
+            KeyCode::Char('o') => self.request_external(ExternalAction::Editor),
+            KeyCode::Char('s' | '!') => self.request_external(ExternalAction::Shell),
+            KeyCode::Char('y') => self.start_yank(),
+            KeyCode::Char('F') => self.start_global_search(),
+            KeyCode::Char('i') => self.show_run_info(),
+
+            // Reviewing candidates
+            KeyCode::Char('r') => self.toggle_rejected(),
+            KeyCode::Char('c') => self.start_note(),
+            KeyCode::Char('m') => self.mark_for_comparison(),
+            KeyCode::Char('X') => self.start_confirm_discard(),
+
+            // Panel resizing
+            KeyCode::Char('<') => self.narrow_models_panel(),
+            KeyCode::Char('>') => self.widen_models_panel(),
 
             // Actions
             KeyCode::Char('a') | KeyCode::Enter => {
@@ -321,6 +1067,7 @@ impl App {
         &mut self,
         key: ratatui::crossterm::event::KeyEvent,
         viewport_height: u16,
+        content: &str,
     ) {
         match key.code {
             // Vim-style scrolling
@@ -345,9 +1092,46 @@ impl App {
 
             // Search
             KeyCode::Char('/') => self.start_search(),
+            KeyCode::Char(':') => self.start_goto_line(),
+            KeyCode::Char('#') => self.toggle_line_numbers(),
             KeyCode::Char('n') => self.next_search_match(),
             KeyCode::Char('N') => self.previous_search_match(),
 
+            // Folding of directories (log view) and files (diff view)
+            KeyCode::Char('z') => self.toggle_fold(content),
+            KeyCode::Char('Z') => self.toggle_all_folds(content),
+
+            // File navigation in the diff view
+            KeyCode::Char(']') => self.next_file(content),
+            KeyCode::Char('[') => self.previous_file(content),
+            KeyCode::Char('e') => self.next_error(content),
+            KeyCode::Char('E') => self.previous_error(content),
+            KeyCode::Char('v') => self.toggle_side_by_side(),
+            KeyCode::Char('O') => self.cycle_output_filter(),
+
+            // External programs
+            KeyCode::Char('p') => self.request_external(ExternalAction::Pager),
+            KeyCode::Char('x') => self.request_external(ExternalAction::Difftool),
+            KeyCode::Char('o') => self.request_external(ExternalAction::Editor),
+            KeyCode::Char('!') => self.request_external(ExternalAction::Shell),
+            KeyCode::Char('y') => self.start_yank(),
+            KeyCode::Char('F') => self.start_global_search(),
+            KeyCode::Char('i') => self.show_run_info(),
+            KeyCode::Char('m') => self.mark_for_comparison(),
+
+            // Panel resizing
+            KeyCode::Char('<') => self.narrow_models_panel(),
+            KeyCode::Char('>') => self.widen_models_panel(),
+
+            // Line wrapping and horizontal scrolling (h/l scroll only while wrapping is off)
+            KeyCode::Char('w') => self.toggle_wrap(),
+            KeyCode::Char('h') | KeyCode::Left if self.horizontal_scroll.is_some() => {
+                self.scroll_left();
+            }
+            KeyCode::Char('l') | KeyCode::Right if self.horizontal_scroll.is_some() => {
+                self.scroll_right();
+            }
+
             // Focus switch
             KeyCode::Tab | KeyCode::Char('h') | KeyCode::Left => {
                 self.toggle_focus();
@@ -361,6 +1145,10 @@ impl App {
                 self.set_mode(ViewMode::Diff);
             }
             KeyCode::Char('D') => self.set_mode(ViewMode::Diff),
+            KeyCode::Char('t' | 'T') => self.set_mode(ViewMode::Stat),
+            KeyCode::Char('s' | 'S') => self.set_mode(ViewMode::Summary),
+            KeyCode::Char('C') => self.set_mode(ViewMode::Checks),
+            KeyCode::Char('R') => self.set_mode(ViewMode::Transcript),
 
             // Actions (also available in detail view)
             KeyCode::Char('a') => {
@@ -0,0 +1,34 @@
+use arboard::Clipboard;
+
+use super::content::{get_diff_content_string, strip_ansi_codes};
+use super::types::YankTarget;
+use crate::domain::ResultInfo;
+use crate::executor::OutputLine;
+
+/// Plain text of a result to copy to the clipboard
+pub fn yank_text(info: &ResultInfo, target: YankTarget) -> String {
+    match target {
+        YankTarget::Path => info.worktree_path.display().to_string(),
+        YankTarget::Diff => get_diff_content_string(&info.worktree_path),
+        YankTarget::Log => info
+            .output_lines
+            .iter()
+            .map(|line| match line {
+                OutputLine::Stdout(text) | OutputLine::Stderr(text) => strip_ansi_codes(text),
+            })
+            .collect::<Vec<_>>()
+            .join("\n"),
+    }
+}
+
+/// Copy text to the system clipboard
+///
+/// The clipboard handle is created on first use and kept alive, since on X11 the
+/// copied text is only served while the owning handle exists.
+pub fn copy_to_clipboard(slot: &mut Option<Clipboard>, text: String) -> Result<(), arboard::Error> {
+    let clipboard = match slot.take() {
+        Some(clipboard) => clipboard,
+        None => Clipboard::new()?,
+    };
+    slot.insert(clipboard).set_text(text)
+}
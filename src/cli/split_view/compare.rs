@@ -0,0 +1,133 @@
+use std::collections::{HashMap, HashSet};
+use std::path::Path;
+use std::sync::Arc;
+use std::sync::mpsc::{Receiver, Sender, channel};
+use std::thread::JoinHandle;
+
+use tokio::sync::watch;
+
+use crate::domain::compare_changes;
+use crate::executor::Executor;
+
+/// Two candidates by index, the lower one first
+pub type Pair = (usize, usize);
+
+/// Comparisons of two candidates written by an agent, each produced once on a background thread
+///
+/// Agents take a while to answer, so the TUI stays usable and shows the comparison
+/// when it arrives. Comparisons still running when the comparer is dropped are
+/// cancelled and waited for, so no agent reads a worktree while it is removed.
+pub struct Comparer {
+    executor: Arc<dyn Executor>,
+    comparisons: HashMap<Pair, String>,
+    pending: HashSet<Pair>,
+    sender: Sender<(Pair, String)>,
+    receiver: Receiver<(Pair, String)>,
+    cancel: watch::Sender<bool>,
+    threads: Vec<JoinHandle<()>>,
+}
+
+impl Comparer {
+    pub fn new(executor: Arc<dyn Executor>) -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            executor,
+            comparisons: HashMap::new(),
+            pending: HashSet::new(),
+            sender,
+            receiver,
+            cancel: watch::Sender::new(false),
+            threads: Vec::new(),
+        }
+    }
+
+    /// Name of the agent writing the comparisons
+    pub fn agent(&self) -> &str {
+        self.executor.name()
+    }
+
+    /// Start comparing two candidates, given by agent name and worktree, unless the
+    /// comparison is done or in progress
+    pub fn request(&mut self, pair: Pair, a: (&str, &Path), b: (&str, &Path)) {
+        if self.comparisons.contains_key(&pair) || !self.pending.insert(pair) {
+            return;
+        }
+
+        let executor = Arc::clone(&self.executor);
+        let sender = self.sender.clone();
+        let cancelled = self.cancel.subscribe();
+        let (name_a, worktree_a) = (a.0.to_string(), a.1.to_path_buf());
+        let (name_b, worktree_b) = (b.0.to_string(), b.1.to_path_buf());
+        // The TUI blocks the runtime it was started from, so the agent runs on its own
+        let thread = std::thread::spawn(move || {
+            let a = (name_a.as_str(), worktree_a.as_path());
+            let b = (name_b.as_str(), worktree_b.as_path());
+            let comparison = run_comparison(executor.as_ref(), a, b, cancelled.clone());
+            // A cancelled comparison is left to be requested again
+            if *cancelled.borrow() {
+                return;
+            }
+            let comparison = comparison.unwrap_or_else(|| {
+                format!(
+                    "{} could not compare the changes of {name_a} and {name_b}.",
+                    executor.name()
+                )
+            });
+            // The receiver is gone once the TUI has exited
+            let _ = sender.send((pair, comparison));
+        });
+        self.threads.push(thread);
+    }
+
+    /// The comparison of two candidates, if it has been written
+    pub fn get(&self, pair: Pair) -> Option<&str> {
+        self.comparisons.get(&pair).map(String::as_str)
+    }
+
+    /// Collect finished comparisons
+    pub fn poll(&mut self) {
+        while let Ok((pair, comparison)) = self.receiver.try_recv() {
+            self.pending.remove(&pair);
+            self.comparisons.insert(pair, comparison);
+        }
+        self.threads.retain(|thread| !thread.is_finished());
+    }
+
+    /// Cancel the comparisons in progress and wait until their agents have stopped,
+    /// before worktrees are removed; they can be requested again afterwards
+    pub fn stop(&mut self) {
+        self.cancel.send_replace(true);
+        for thread in self.threads.drain(..) {
+            let _ = thread.join();
+        }
+        self.pending.clear();
+        self.cancel = watch::Sender::new(false);
+    }
+}
+
+impl Drop for Comparer {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+/// Compare two worktrees on a runtime of the current thread, stopping the agent
+/// once `cancelled` turns true
+fn run_comparison(
+    executor: &dyn Executor,
+    a: (&str, &Path),
+    b: (&str, &Path),
+    mut cancelled: watch::Receiver<bool>,
+) -> Option<String> {
+    let rt = tokio::runtime::Builder::new_current_thread()
+        .enable_all()
+        .build()
+        .ok()?;
+    rt.block_on(async {
+        tokio::select! {
+            comparison = compare_changes(executor, a, b) => comparison,
+            // Dropping the comparison kills the agent
+            _ = cancelled.wait_for(|cancelled| *cancelled) => None,
+        }
+    })
+}
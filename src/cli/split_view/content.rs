@@ -1,13 +1,18 @@
+use std::collections::{BTreeMap, HashSet};
 use std::fmt::Write as FmtWrite;
 use std::path::Path;
 use std::process::Command;
 
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::{Line, Span, Text};
+use ratatui::text::{Line, Span};
+use unicode_width::UnicodeWidthStr;
 
-use super::types::ViewMode;
-use crate::domain::ResultInfo;
+use super::ansi::ansi_to_line;
+use super::types::{OutputFilter, ViewMode};
+use crate::cli::clean::format_size;
+use crate::domain::{Diff, FileDiff, ResultInfo, TranscriptStep, transcript};
 use crate::executor::OutputLine;
+use crate::git::{self, FileChange};
 
 /// Special marker for stderr lines (invisible character used for detection in `style_log_line`)
 pub const STDERR_MARKER: &str = "\x01STDERR\x02";
@@ -52,7 +57,106 @@ pub fn strip_ansi_codes(s: &str) -> String {
     result
 }
 
-pub fn get_log_content_string(info: &ResultInfo) -> String {
+/// Maximum width of the +/- bar in the stat view
+const STAT_BAR_WIDTH: usize = 40;
+
+/// Most lines of a tool's output shown in the transcript view
+const TRANSCRIPT_OUTPUT_LINES: usize = 6;
+
+/// Shown in the transcript view for agents that printed plain text
+const NO_TRANSCRIPT: &str = "(no structured output: run claude with `--output-format stream-json --verbose` or codex with `--json`, e.g. in an agent alias, to see a transcript)";
+
+/// Marker for an expanded directory in the per-file summary
+pub const EXPANDED_MARKER: &str = "\u{25BE}";
+
+/// Marker for a collapsed directory in the per-file summary
+pub const COLLAPSED_MARKER: &str = "\u{25B8}";
+
+/// Group changed files by their parent directory (`./` for the repository root)
+pub fn group_files_by_dir(files: &[FileChange]) -> BTreeMap<String, Vec<&FileChange>> {
+    let mut groups: BTreeMap<String, Vec<&FileChange>> = BTreeMap::new();
+    for file in files {
+        let dir = match file.path.rsplit_once('/') {
+            Some((dir, _)) => format!("{dir}/"),
+            None => "./".to_string(),
+        };
+        groups.entry(dir).or_default().push(file);
+    }
+    for files in groups.values_mut() {
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+    }
+    groups
+}
+
+/// Find the directory of the fold header at or above the given line
+///
+/// Only file entries and headers of the per-file summary are considered, so lines
+/// outside of it never resolve to a directory.
+pub fn find_fold_dir(content: &str, line: usize) -> Option<String> {
+    let lines: Vec<&str> = content.lines().take(line + 1).collect();
+    lines
+        .iter()
+        .rev()
+        .take_while(|l| l.starts_with("  "))
+        .find_map(|l| {
+            let rest = l
+                .strip_prefix(&format!("  {EXPANDED_MARKER} "))
+                .or_else(|| l.strip_prefix(&format!("  {COLLAPSED_MARKER} ")))?;
+            rest.split_once(" (").map(|(dir, _)| dir.to_string())
+        })
+}
+
+fn write_file_tree(content: &mut String, files: &[FileChange], collapsed: &HashSet<String>) {
+    for (dir, files) in group_files_by_dir(files) {
+        let insertions: usize = files.iter().map(|f| f.insertions).sum();
+        let deletions: usize = files.iter().map(|f| f.deletions).sum();
+        let is_collapsed = collapsed.contains(&dir);
+        let marker = if is_collapsed {
+            COLLAPSED_MARKER
+        } else {
+            EXPANDED_MARKER
+        };
+
+        let _ = writeln!(
+            content,
+            "  {marker} {dir} ({} files, +{insertions} \u{2212}{deletions})",
+            files.len()
+        );
+
+        if is_collapsed {
+            continue;
+        }
+
+        for file in files {
+            let name = file
+                .path
+                .rsplit_once('/')
+                .map_or(file.path.as_str(), |(_, n)| n);
+            if file.binary {
+                let _ = writeln!(content, "    {} {name}  binary", file.status.symbol());
+                continue;
+            }
+            let _ = writeln!(
+                content,
+                "    {} {name}  +{} \u{2212}{}",
+                file.status.symbol(),
+                file.insertions,
+                file.deletions
+            );
+        }
+    }
+}
+
+/// Build the log content, collapsing the given directories in the per-file summary
+/// and showing only the output lines that pass `filter`
+///
+/// With `ansi`, the agents' own color sequences are kept so the view can render them.
+pub fn get_log_content_string(
+    info: &ResultInfo,
+    collapsed: &HashSet<String>,
+    filter: OutputFilter,
+    ansi: bool,
+) -> String {
     let mut content = String::new();
 
     // Header
@@ -73,8 +177,43 @@ pub fn get_log_content_string(info: &ResultInfo) -> String {
     // Summary
     content.push_str("Summary:\n");
     let _ = writeln!(content, "  Files changed: {}", info.files_changed);
+    for (attempt, reason) in info.retries.iter().enumerate() {
+        let _ = writeln!(content, "  Attempt {} failed: {reason}", attempt + 1);
+    }
+    if let Some(ref original) = info.duplicate_of {
+        let _ = writeln!(content, "  Same changes as: {original}");
+    }
+    if !info.similarity.is_empty() {
+        let row: Vec<String> = info
+            .similarity
+            .iter()
+            .map(|(name, similarity)| format!("{name} {:.0}%", similarity * 100.0))
+            .collect();
+        let _ = writeln!(content, "  Similarity: {}", row.join(", "));
+    }
+    for violation in &info.policy {
+        let _ = writeln!(content, "  Policy: {violation}");
+    }
+    for secret in &info.secrets {
+        let _ = writeln!(content, "  Possible secret: {secret}");
+    }
+    if let Some(ref check) = info.check {
+        let status = if check.passed { "passed" } else { "failed" };
+        let _ = writeln!(content, "  Check: {status}");
+    }
+    if let Some(ref score) = info.score {
+        let _ = writeln!(content, "  Score: {}", score.label());
+    }
+    if let Some(ref breakdown) = info.breakdown {
+        let _ = writeln!(content, "  Weighted: {}", breakdown.label());
+    }
 
     if let Some(ref summary) = info.change_summary {
+        let _ = writeln!(
+            content,
+            "  Lines changed: +{} \u{2212}{}",
+            summary.insertions, summary.deletions
+        );
         if summary.files_added > 0 {
             let _ = writeln!(content, "  + {} added", summary.files_added);
         }
@@ -84,6 +223,10 @@ pub fn get_log_content_string(info: &ResultInfo) -> String {
         if summary.files_deleted > 0 {
             let _ = writeln!(content, "  - {} deleted", summary.files_deleted);
         }
+        if !summary.changed_files.is_empty() {
+            content.push('\n');
+            write_file_tree(&mut content, &summary.changed_files, collapsed);
+        }
     }
     content.push('\n');
 
@@ -94,21 +237,28 @@ pub fn get_log_content_string(info: &ResultInfo) -> String {
     content.push_str(&"-".repeat(50));
     content.push('\n');
 
-    if info.output_lines.is_empty() {
-        content.push_str("(no output)\n");
+    let mut output_lines = info
+        .output_lines
+        .iter()
+        .filter(|line| filter.includes(line))
+        .peekable();
+    if output_lines.peek().is_none() {
+        content.push_str(match filter {
+            OutputFilter::All => "(no output)\n",
+            OutputFilter::Stderr => "(no stderr output)\n",
+            OutputFilter::Stdout => "(no stdout output)\n",
+        });
     } else {
-        for output_line in &info.output_lines {
+        for output_line in output_lines {
             match output_line {
                 OutputLine::Stdout(line) => {
-                    let cleaned = strip_ansi_codes(line);
-                    content.push_str(&cleaned);
+                    content.push_str(&log_text(line, ansi));
                     content.push('\n');
                 }
                 OutputLine::Stderr(line) => {
                     // Add marker for stderr lines so style_log_line can detect them
-                    let cleaned = strip_ansi_codes(line);
                     content.push_str(STDERR_MARKER);
-                    content.push_str(&cleaned);
+                    content.push_str(&log_text(line, ansi));
                     content.push('\n');
                 }
             }
@@ -118,153 +268,469 @@ pub fn get_log_content_string(info: &ResultInfo) -> String {
     content
 }
 
-pub fn get_diff_content_string(worktree_path: &Path) -> String {
-    let diff_output = Command::new("git")
-        .args(["diff", "HEAD"])
-        .current_dir(worktree_path)
-        .output();
+/// Text of an output line; escape sequences are kept for `style_log_line` if `ansi` is set
+fn log_text(line: &str, ansi: bool) -> String {
+    if ansi {
+        line.to_string()
+    } else {
+        strip_ansi_codes(line)
+    }
+}
 
-    match diff_output {
-        Ok(output) => {
-            let diff_str = String::from_utf8_lossy(&output.stdout);
-            if diff_str.is_empty() {
-                get_untracked_files_string(worktree_path)
-            } else {
-                diff_str.to_string()
+/// Number of stderr lines in the agent output
+pub fn stderr_line_count(info: &ResultInfo) -> usize {
+    info.output_lines
+        .iter()
+        .filter(|line| matches!(line, OutputLine::Stderr(_)))
+        .count()
+}
+
+pub fn get_summary_content_string(info: &ResultInfo) -> String {
+    let mut content = String::new();
+
+    // Header
+    let emoji = get_agent_emoji(&info.executor_name);
+    let _ = writeln!(
+        content,
+        "{} {} - Summary",
+        emoji,
+        info.executor_name.to_uppercase()
+    );
+    content.push_str(&"=".repeat(50));
+    content.push('\n');
+    content.push('\n');
+
+    match info.summary {
+        Some(ref summary) => {
+            for line in summary.lines() {
+                content.push_str(line);
+                content.push('\n');
             }
         }
-        Err(e) => format!("Error getting diff: {e}"),
+        None => content.push_str("(no summary available)\n"),
     }
+
+    content
 }
 
-pub fn get_untracked_files_string(worktree_path: &Path) -> String {
-    let status_output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(worktree_path)
-        .output();
+/// Build the content of the transcript view: the agent's conversation, step by step
+pub fn get_transcript_content_string(info: &ResultInfo) -> String {
+    let mut content = String::new();
 
-    match status_output {
-        Ok(status) => {
-            let status_str = String::from_utf8_lossy(&status.stdout);
-            if status_str.is_empty() {
-                "No changes detected.".to_string()
-            } else {
-                let mut content = String::new();
-                content.push_str("New/Untracked files:\n\n");
-
-                for line in status_str.lines() {
-                    if line.starts_with("??") || line.starts_with("A ") {
-                        let file = line.split_whitespace().last().unwrap_or("");
-                        let file_path = worktree_path.join(file);
-
-                        if file_path.exists() && file_path.is_file() {
-                            let _ = writeln!(content, "+ {file}");
-                            content.push_str(&"-".repeat(40));
-                            content.push('\n');
-
-                            if let Ok(file_content) = std::fs::read_to_string(&file_path) {
-                                for (i, file_line) in file_content.lines().enumerate().take(100) {
-                                    let _ = writeln!(content, "{:4} | +{file_line}", i + 1);
-                                }
-                                if file_content.lines().count() > 100 {
-                                    content.push_str("... (truncated)\n");
-                                }
-                            }
-                            content.push('\n');
-                        }
-                    }
+    let emoji = get_agent_emoji(&info.executor_name);
+    let _ = writeln!(
+        content,
+        "{} {} - Transcript",
+        emoji,
+        info.executor_name.to_uppercase()
+    );
+    content.push_str(&"=".repeat(50));
+    content.push('\n');
+
+    let Some(steps) = transcript(&info.output_lines) else {
+        content.push('\n');
+        content.push_str(NO_TRANSCRIPT);
+        content.push('\n');
+        return content;
+    };
+    for step in steps {
+        match step {
+            TranscriptStep::User(text) => push_transcript_text(&mut content, "User:", &text),
+            TranscriptStep::Assistant(text) => {
+                push_transcript_text(&mut content, "Assistant:", &text);
+            }
+            TranscriptStep::Result(text) => push_transcript_text(&mut content, "Result:", &text),
+            TranscriptStep::ToolCall { tool, target } => {
+                let _ = writeln!(content, "> {tool}: {}", target.lines().next().unwrap_or(""));
+            }
+            TranscriptStep::ToolResult { output, failed } => {
+                let marker = if failed { "  ! " } else { "  | " };
+                let lines: Vec<&str> = output.lines().collect();
+                for line in lines.iter().take(TRANSCRIPT_OUTPUT_LINES) {
+                    let _ = writeln!(content, "{marker}{line}");
+                }
+                if lines.len() > TRANSCRIPT_OUTPUT_LINES {
+                    let more = lines.len() - TRANSCRIPT_OUTPUT_LINES;
+                    let _ = writeln!(content, "{marker}... {more} more lines");
                 }
-                content
             }
         }
-        Err(e) => format!("Error getting status: {e}"),
+    }
+
+    content
+}
+
+/// Add a message of the transcript under its role
+fn push_transcript_text(content: &mut String, role: &str, text: &str) {
+    let _ = writeln!(content, "\n{role}");
+    for line in text.trim().lines() {
+        let _ = writeln!(content, "  {line}");
     }
 }
 
-pub fn get_styled_content(content: &str, mode: ViewMode) -> Text<'static> {
-    let mut lines = Vec::new();
+/// Build the content of the checks view: the output of the check and score commands
+pub fn get_checks_content_string(info: &ResultInfo) -> String {
+    let mut content = String::new();
 
-    for line in content.lines() {
-        let styled_line = match mode {
-            ViewMode::Log => style_log_line(line),
-            ViewMode::Diff => style_diff_line(line),
+    let emoji = get_agent_emoji(&info.executor_name);
+    let _ = writeln!(
+        content,
+        "{} {} - Checks",
+        emoji,
+        info.executor_name.to_uppercase()
+    );
+    content.push_str(&"=".repeat(50));
+    content.push('\n');
+
+    let sections = [
+        ("Check", info.check.as_ref().map(|c| (c.passed, &c.output))),
+        ("Score", info.score.as_ref().map(|s| (s.passed, &s.output))),
+    ];
+    if sections.iter().all(|(_, section)| section.is_none()) {
+        content.push('\n');
+        content.push_str("(no check or score command was run)\n");
+        return content;
+    }
+    for (name, section) in sections {
+        let Some((passed, output)) = section else {
+            continue;
         };
-        lines.push(styled_line);
+        let status = if passed { "passed" } else { "failed" };
+        let _ = writeln!(content, "\n{name}: {status}");
+        content.push_str(&"-".repeat(50));
+        content.push('\n');
+        for line in output.lines() {
+            content.push_str(line);
+            content.push('\n');
+        }
     }
 
-    Text::from(lines)
+    content
 }
 
-pub fn get_styled_content_with_search(content: &str, mode: ViewMode, query: &str) -> Text<'static> {
-    let mut lines = Vec::new();
-    let query_lower = query.to_lowercase();
-
-    for line in content.lines() {
-        // Handle stderr marker
-        let (actual_line, is_stderr) = if let Some(stripped) = line.strip_prefix(STDERR_MARKER) {
-            (stripped, true)
+/// Scale insertion/deletion counts to fit within `STAT_BAR_WIDTH`
+pub fn scale_stat_bar(insertions: usize, deletions: usize, max_changes: usize) -> (usize, usize) {
+    if max_changes <= STAT_BAR_WIDTH {
+        return (insertions, deletions);
+    }
+    let scale = |n: usize| {
+        if n == 0 {
+            0
         } else {
-            (line, false)
-        };
+            (n * STAT_BAR_WIDTH / max_changes).max(1)
+        }
+    };
+    (scale(insertions), scale(deletions))
+}
 
-        let line_lower = actual_line.to_lowercase();
-        if line_lower.contains(&query_lower) {
-            // Highlight search matches
-            let mut spans = Vec::new();
-            let mut last_end = 0;
+/// Build `git diff --stat`-style content from the change summary
+pub fn get_stat_content_string(info: &ResultInfo) -> String {
+    let Some(summary) = info
+        .change_summary
+        .as_ref()
+        .filter(|summary| !summary.changed_files.is_empty())
+    else {
+        return "No changes detected.".to_string();
+    };
+
+    let mut files: Vec<&FileChange> = summary.changed_files.iter().collect();
+    files.sort_by(|a, b| a.path.cmp(&b.path));
+
+    let name_width = files.iter().map(|f| f.path.width()).max().unwrap_or(0);
+    let max_changes = files
+        .iter()
+        .map(|f| f.insertions + f.deletions)
+        .max()
+        .unwrap_or(0);
+    let count_width = max_changes.to_string().len();
 
-            // Base style for stderr lines
-            let base_style = if is_stderr {
-                Style::new().fg(Color::Red)
+    let mut content = String::new();
+    for file in files {
+        let total = file.insertions + file.deletions;
+        let (plus, minus) = scale_stat_bar(file.insertions, file.deletions, max_changes);
+        // Pad by display width so paths with wide characters stay aligned
+        let padding = " ".repeat(name_width - file.path.width());
+        if file.binary {
+            let _ = writeln!(content, " {}{padding} | Bin", file.path);
+            continue;
+        }
+        let _ = writeln!(
+            content,
+            " {}{padding} | {total:>count_width$} {}{}",
+            file.path,
+            "+".repeat(plus),
+            "-".repeat(minus)
+        );
+    }
+    let _ = writeln!(
+        content,
+        " {} files changed, {} insertions(+), {} deletions(-)",
+        summary.changed_files.len(),
+        summary.insertions,
+        summary.deletions
+    );
+
+    content
+}
+
+/// Replace git's `Binary files ... differ` lines with the file sizes before and after
+///
+/// `sizes` returns the old and new size of a file, `None` where it does not exist.
+pub fn describe_binary_changes(
+    diff: &str,
+    sizes: impl Fn(&str) -> (Option<u64>, Option<u64>),
+) -> String {
+    let parsed = Diff::parse(diff);
+    if !parsed.files.iter().any(FileDiff::is_binary) {
+        return diff.to_string();
+    }
+    let size = |bytes: Option<u64>| bytes.map_or_else(|| "none".to_string(), format_size);
+
+    let mut content = String::new();
+    for line in &parsed.preamble {
+        content.push_str(line);
+        content.push('\n');
+    }
+    for file in &parsed.files {
+        if !file.is_binary() {
+            content.push_str(&file.to_patch());
+            continue;
+        }
+        let (old, new) = sizes(file.path);
+        for line in &file.header {
+            if line.starts_with("Binary files ") {
+                let _ = writeln!(
+                    content,
+                    "Binary file changed (size {} \u{2192} {})",
+                    size(old),
+                    size(new)
+                );
             } else {
-                Style::new()
-            };
-
-            for (start, _) in line_lower.match_indices(&query_lower) {
-                if start > last_end {
-                    spans.push(Span::styled(
-                        actual_line[last_end..start].to_string(),
-                        base_style,
-                    ));
-                }
-                spans.push(Span::styled(
-                    actual_line[start..start + query.len()].to_string(),
-                    Style::new()
-                        .fg(Color::Black)
-                        .bg(Color::Yellow)
-                        .add_modifier(Modifier::BOLD),
-                ));
-                last_end = start + query.len();
+                content.push_str(line);
+                content.push('\n');
             }
+        }
+    }
+    content
+}
+
+/// Diff of a worktree against HEAD, with new files marked intent-to-add so they show as
+/// regular additions
+pub fn get_diff_content_string(worktree_path: &Path) -> String {
+    let _ = git::add_intent_to_add_blocking(worktree_path);
+    let diff_output = git::traced_blocking(
+        Command::new("git")
+            .args(["diff", "HEAD"])
+            .current_dir(worktree_path),
+    );
 
-            if last_end < actual_line.len() {
-                spans.push(Span::styled(
-                    actual_line[last_end..].to_string(),
-                    base_style,
-                ));
+    match diff_output {
+        Ok(output) => {
+            let diff_str = String::from_utf8_lossy(&output.stdout);
+            if diff_str.is_empty() {
+                "No changes detected.".to_string()
+            } else {
+                describe_binary_changes(&diff_str, |path| {
+                    let old = git::traced_blocking(
+                        Command::new("git")
+                            .args(["cat-file", "-s", &format!("HEAD:{path}")])
+                            .current_dir(worktree_path),
+                    )
+                    .ok()
+                    .filter(|output| output.status.success())
+                    .and_then(|output| String::from_utf8_lossy(&output.stdout).trim().parse().ok());
+                    let new = std::fs::metadata(worktree_path.join(path))
+                        .ok()
+                        .map(|metadata| metadata.len());
+                    (old, new)
+                })
             }
+        }
+        Err(e) => format!("Error getting diff: {e}"),
+    }
+}
+
+/// Byte length of the first `lines` lines of `content`, including their line breaks
+pub fn prefix_len(content: &str, lines: usize) -> usize {
+    if lines == 0 {
+        return 0;
+    }
+    content
+        .match_indices('\n')
+        .nth(lines - 1)
+        .map_or(content.len(), |(index, _)| index + 1)
+}
 
-            lines.push(Line::from(spans));
+/// Extract the file path from a `diff --git a/<path> b/<path>` section header
+pub use crate::domain::section_path as diff_section_path;
+
+/// Line numbers of all file section headers in a unified diff
+pub fn diff_file_starts(content: &str) -> Vec<u16> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| diff_section_path(line).is_some())
+        .filter_map(|(i, _)| u16::try_from(i).ok())
+        .collect()
+}
+
+/// Whether a log line looks like a failure: stderr output, errors, panics or tracebacks
+pub fn is_error_line(line: &str) -> bool {
+    if line.starts_with(STDERR_MARKER) {
+        return true;
+    }
+    let lower = line.to_lowercase();
+    lower.contains("error") || lower.contains("panic") || line.contains("Traceback")
+}
+
+/// Line numbers of the error lines in the log content
+pub fn error_lines(content: &str) -> Vec<u16> {
+    content
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| is_error_line(line))
+        .filter_map(|(i, _)| u16::try_from(i).ok())
+        .collect()
+}
+
+/// Position of the file section at the given line as (current, total), 1-based
+pub fn diff_file_position(content: &str, line: u16) -> Option<(usize, usize)> {
+    let starts = diff_file_starts(content);
+    if starts.is_empty() {
+        return None;
+    }
+    let current = starts.iter().filter(|start| **start <= line).count().max(1);
+    Some((current, starts.len()))
+}
+
+/// Replace the body of collapsed file sections with a single folded marker line
+pub fn fold_diff_sections(diff: &str, collapsed: &HashSet<String>) -> String {
+    let diff = Diff::parse(diff);
+    let mut content = String::new();
+    for line in &diff.preamble {
+        content.push_str(line);
+        content.push('\n');
+    }
+    for file in &diff.files {
+        if collapsed.contains(file.path) {
+            content.push_str(file.header[0]);
+            content.push('\n');
+            let _ = writeln!(
+                content,
+                "  {COLLAPSED_MARKER} {} lines folded",
+                file.line_count() - 1
+            );
         } else {
-            let styled_line = match mode {
-                ViewMode::Log => style_log_line(line),
-                ViewMode::Diff => style_diff_line(line),
-            };
-            lines.push(styled_line);
+            content.push_str(&file.to_patch());
+        }
+    }
+    content
+}
+
+/// Style a single line of the detail content for the given view mode
+pub fn style_line(line: &str, mode: ViewMode) -> Line<'static> {
+    match mode {
+        ViewMode::Log => style_log_line(line),
+        ViewMode::Diff => style_diff_line(line),
+        ViewMode::Stat => style_stat_line(line),
+        ViewMode::Summary => style_summary_line(line),
+        ViewMode::Checks => style_checks_line(line),
+        ViewMode::Transcript => style_transcript_line(line),
+    }
+}
+
+/// Style a single line, highlighting case-insensitive matches of `query`
+pub fn style_line_with_search(line: &str, mode: ViewMode, query: &str) -> Line<'static> {
+    let query_lower = query.to_lowercase();
+
+    // Matches are highlighted on the plain text, without the agent's own colors
+    if line.contains('\x1b') {
+        let plain = strip_ansi_codes(line);
+        if plain.to_lowercase().contains(&query_lower) {
+            return style_line_with_search(&plain, mode, query);
+        }
+        return style_line(line, mode);
+    }
+
+    // Handle stderr marker
+    let (actual_line, is_stderr) = if let Some(stripped) = line.strip_prefix(STDERR_MARKER) {
+        (stripped, true)
+    } else {
+        (line, false)
+    };
+
+    let line_lower = actual_line.to_lowercase();
+    if !line_lower.contains(&query_lower) {
+        return style_line(line, mode);
+    }
+
+    // Highlight search matches
+    let mut spans = Vec::new();
+    let mut last_end = 0;
+
+    // Base style for stderr lines
+    let base_style = if is_stderr {
+        Style::new().fg(Color::Red)
+    } else {
+        Style::new()
+    };
+
+    for (start, _) in line_lower.match_indices(&query_lower) {
+        if start > last_end {
+            spans.push(Span::styled(
+                actual_line[last_end..start].to_string(),
+                base_style,
+            ));
         }
+        spans.push(Span::styled(
+            actual_line[start..start + query.len()].to_string(),
+            Style::new()
+                .fg(Color::Black)
+                .bg(Color::Yellow)
+                .add_modifier(Modifier::BOLD),
+        ));
+        last_end = start + query.len();
+    }
+
+    if last_end < actual_line.len() {
+        spans.push(Span::styled(
+            actual_line[last_end..].to_string(),
+            base_style,
+        ));
     }
 
-    Text::from(lines)
+    Line::from(spans)
 }
 
 pub fn style_log_line(line: &str) -> Line<'static> {
     // Check for stderr marker first - display in red and remove the marker
     if let Some(content) = line.strip_prefix(STDERR_MARKER) {
+        if content.contains('\x1b') {
+            return ansi_to_line(content).style(Style::new().fg(Color::Red));
+        }
         return Line::styled(content.to_string(), Style::new().fg(Color::Red));
     }
 
+    // Agent output with its own colors
+    if line.contains('\x1b') {
+        return ansi_to_line(line);
+    }
+
     if line.starts_with("Output:") || line.starts_with("Summary:") {
         Line::styled(line.to_string(), Style::new().add_modifier(Modifier::BOLD))
+    } else if line.starts_with(&format!("  {EXPANDED_MARKER} "))
+        || line.starts_with(&format!("  {COLLAPSED_MARKER} "))
+    {
+        Line::styled(
+            line.to_string(),
+            Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD),
+        )
+    } else if line.starts_with("    +") {
+        Line::styled(line.to_string(), Style::new().fg(Color::Green))
+    } else if line.starts_with("    ~") {
+        Line::styled(line.to_string(), Style::new().fg(Color::Yellow))
+    } else if line.starts_with("    -") {
+        Line::styled(line.to_string(), Style::new().fg(Color::Red))
     } else if line.starts_with("  +") {
         Line::styled(line.to_string(), Style::new().fg(Color::Green))
     } else if line.starts_with("  ~") {
@@ -299,18 +765,96 @@ pub fn style_diff_line(line: &str) -> Line<'static> {
         Line::styled(line.to_string(), Style::new().fg(Color::Red))
     } else if line.starts_with("@@") || line.starts_with("diff ") || line.starts_with("index ") {
         Line::styled(line.to_string(), Style::new().fg(Color::Cyan))
-    } else if line.starts_with("New/Untracked") {
+    } else if line.starts_with(&format!("  {COLLAPSED_MARKER} ")) || line.starts_with("Binary file")
+    {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
+    } else {
+        Line::raw(line.to_string())
+    }
+}
+
+pub fn style_stat_line(line: &str) -> Line<'static> {
+    if line.contains(" files changed, ") {
+        return Line::styled(line.to_string(), Style::new().add_modifier(Modifier::BOLD));
+    }
+
+    let marks_len = line
+        .chars()
+        .rev()
+        .take_while(|c| *c == '+' || *c == '-')
+        .count();
+    if marks_len == 0 || !line.contains(" | ") {
+        return Line::raw(line.to_string());
+    }
+
+    // '+' and '-' are single-byte, so the char count equals the byte count
+    let (prefix, marks) = line.split_at(line.len() - marks_len);
+    let plus = marks.chars().filter(|c| *c == '+').count();
+    Line::from(vec![
+        Span::raw(prefix.to_string()),
+        Span::styled("+".repeat(plus), Style::new().fg(Color::Green)),
+        Span::styled("-".repeat(marks_len - plus), Style::new().fg(Color::Red)),
+    ])
+}
+
+pub fn style_summary_line(line: &str) -> Line<'static> {
+    if line.starts_with('=') {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
+    } else if line.ends_with("- Summary") {
+        Line::styled(line.to_string(), Style::new().add_modifier(Modifier::BOLD))
+    } else if line == "(no summary available)" {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
+    } else {
+        Line::raw(line.to_string())
+    }
+}
+
+pub fn style_transcript_line(line: &str) -> Line<'static> {
+    if line.starts_with('=') {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
+    } else if line.ends_with("- Transcript") {
+        Line::styled(line.to_string(), Style::new().add_modifier(Modifier::BOLD))
+    } else if matches!(line, "User:" | "Assistant:" | "Result:") {
         Line::styled(
             line.to_string(),
             Style::new().fg(Color::Cyan).add_modifier(Modifier::BOLD),
         )
+    } else if line.starts_with("> ") {
+        Line::styled(line.to_string(), Style::new().fg(Color::Yellow))
+    } else if line.starts_with("  | ") || line == NO_TRANSCRIPT {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
+    } else if line.starts_with("  ! ") {
+        Line::styled(line.to_string(), Style::new().fg(Color::Red))
+    } else {
+        Line::raw(line.to_string())
+    }
+}
+
+pub fn style_checks_line(line: &str) -> Line<'static> {
+    if line.starts_with('=') || line.starts_with("-----") {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
+    } else if line.ends_with("- Checks") {
+        Line::styled(line.to_string(), Style::new().add_modifier(Modifier::BOLD))
+    } else if line.ends_with(": passed") {
+        Line::styled(
+            line.to_string(),
+            Style::new().fg(Color::Green).add_modifier(Modifier::BOLD),
+        )
+    } else if line.ends_with(": failed") {
+        Line::styled(
+            line.to_string(),
+            Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+        )
+    } else if line == "(no check or score command was run)" {
+        Line::styled(line.to_string(), Style::new().fg(Color::DarkGray))
     } else {
         Line::raw(line.to_string())
     }
 }
 
 pub fn get_agent_emoji(name: &str) -> &'static str {
-    match name.to_lowercase().as_str() {
+    let name = name.to_lowercase();
+    match name.split('-').next().unwrap_or_default() {
         "claude" => "\u{1F916}", // Robot
         "gemini" => "\u{2728}",  // Sparkles
         "codex" => "\u{1F4E6}",  // Package
@@ -0,0 +1,136 @@
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use ratatui::text::{Line, Text};
+
+use super::ansi::ansi_to_text;
+use super::content::style_diff_line;
+use crate::domain::section_path;
+
+/// Render a unified diff through `delta`, keeping its colors
+///
+/// Navigation and search count lines of the raw diff, so the result has exactly one
+/// row per line of `diff`: delta only renders the changed and context lines, with its
+/// file and hunk decorations turned off, and the other lines keep the built-in styling.
+///
+/// Only the part of `diff` from byte `from` on is rendered, so a long diff loaded in
+/// chunks runs delta once per chunk. The file and hunk headers the chunk continues are
+/// passed to delta for highlighting and left out of the result.
+///
+/// Returns `None` if delta cannot be run, fails, or prints a different number of lines,
+/// so the caller can fall back to the built-in styling.
+pub fn render_with_delta(diff: &str, from: usize) -> Option<Text<'static>> {
+    let (before, chunk) = diff.split_at_checked(from)?;
+    let context = chunk_context(before);
+    let lines: Vec<&str> = context.iter().copied().chain(chunk.lines()).collect();
+    let body = body_lines(&lines);
+
+    // Delta needs the file and hunk headers to highlight the lines, but prints nothing for them
+    let mut input = String::new();
+    for (line, is_body) in lines.iter().zip(&body) {
+        if *is_body || section_path(line).is_some() || is_file_name(line) || line.starts_with("@@")
+        {
+            input.push_str(line);
+            input.push('\n');
+        }
+    }
+    let rendered = run_delta(&input)?;
+    let mut text = merge_rendered(&lines, &body, rendered)?;
+    text.lines.drain(..context.len());
+    Some(text)
+}
+
+/// The file header and last hunk header in effect at the end of `before`
+pub fn chunk_context(before: &str) -> Vec<&str> {
+    let lines: Vec<&str> = before.lines().collect();
+    let body = body_lines(&lines);
+    let mut context = Vec::new();
+    for (line, is_body) in lines.into_iter().zip(body) {
+        if is_body {
+            continue;
+        }
+        if section_path(line).is_some() {
+            context.clear();
+            context.push(line);
+        } else if is_file_name(line) {
+            context.push(line);
+        } else if line.starts_with("@@") {
+            context.retain(|line: &&str| !line.starts_with("@@"));
+            context.push(line);
+        }
+    }
+    context
+}
+
+/// Lines delta prints nothing for, that name the file it highlights
+fn is_file_name(line: &str) -> bool {
+    line.starts_with("--- ") || line.starts_with("+++ ")
+}
+
+/// Whether each line is a changed or context line of a hunk
+pub fn body_lines(lines: &[&str]) -> Vec<bool> {
+    let mut in_hunk = false;
+    lines
+        .iter()
+        .map(|line| {
+            if line.starts_with("@@") {
+                in_hunk = true;
+                return false;
+            }
+            in_hunk &= line.is_empty() || line.starts_with([' ', '+', '-', '\\']);
+            in_hunk && !line.starts_with('\\')
+        })
+        .collect()
+}
+
+/// Put the lines rendered by delta in place of the body lines, styling all others
+///
+/// Returns `None` unless delta rendered exactly one line per body line.
+pub fn merge_rendered(
+    lines: &[&str],
+    body: &[bool],
+    rendered: Text<'static>,
+) -> Option<Text<'static>> {
+    if rendered.lines.len() != body.iter().filter(|is_body| **is_body).count() {
+        return None;
+    }
+    let mut rendered = rendered.lines.into_iter();
+    let merged: Vec<Line<'static>> = lines
+        .iter()
+        .zip(body)
+        .map(|(line, is_body)| {
+            let delta_line = if *is_body { rendered.next() } else { None };
+            delta_line.unwrap_or_else(|| style_diff_line(line))
+        })
+        .collect();
+    Some(Text::from(merged))
+}
+
+/// Run delta on `input` without file and hunk decorations
+fn run_delta(input: &str) -> Option<Text<'static>> {
+    let mut child = Command::new("delta")
+        .args([
+            "--paging=never",
+            "--width=variable",
+            "--file-style=omit",
+            "--hunk-header-style=omit",
+        ])
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .stderr(Stdio::null())
+        .spawn()
+        .ok()?;
+
+    // Feed stdin from a separate thread so large diffs cannot fill the pipe and deadlock
+    let mut stdin = child.stdin.take()?;
+    let input = input.to_string();
+    let writer = std::thread::spawn(move || stdin.write_all(input.as_bytes()));
+
+    let output = child.wait_with_output().ok()?;
+    writer.join().ok()?.ok()?;
+
+    output
+        .status
+        .success()
+        .then(|| ansi_to_text(&String::from_utf8_lossy(&output.stdout)))
+}
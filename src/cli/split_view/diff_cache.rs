@@ -0,0 +1,95 @@
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{Receiver, Sender, channel};
+
+use super::content::get_diff_content_string;
+
+/// Shown in the diff view while the diff is still being computed
+pub const DIFF_LOADING_PLACEHOLDER: &str = "Loading diff...";
+
+/// Diffs of the worktrees, computed once each on background threads
+///
+/// Running `git diff` can take seconds on large repositories, so the TUI shows
+/// a placeholder until the diff arrives instead of blocking on every selection.
+pub struct DiffCache {
+    diffs: HashMap<PathBuf, String>,
+    pending: HashSet<PathBuf>,
+    /// How often the diff of each worktree was invalidated; diffs computed before
+    /// the latest invalidation are dropped when they arrive
+    generations: HashMap<PathBuf, u64>,
+    sender: Sender<(PathBuf, u64, String)>,
+    receiver: Receiver<(PathBuf, u64, String)>,
+}
+
+impl Default for DiffCache {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DiffCache {
+    pub fn new() -> Self {
+        let (sender, receiver) = channel();
+        Self {
+            diffs: HashMap::new(),
+            pending: HashSet::new(),
+            generations: HashMap::new(),
+            sender,
+            receiver,
+        }
+    }
+
+    /// Start computing the diff of a worktree unless it is cached or in progress
+    pub fn request(&mut self, worktree: &Path) {
+        if self.diffs.contains_key(worktree) || !self.pending.insert(worktree.to_path_buf()) {
+            return;
+        }
+
+        let sender = self.sender.clone();
+        let generation = self.generation(worktree);
+        let worktree = worktree.to_path_buf();
+        std::thread::spawn(move || {
+            let diff = get_diff_content_string(&worktree);
+            // The receiver is gone once the TUI has exited
+            let _ = sender.send((worktree, generation, diff));
+        });
+    }
+
+    /// Forget the diff of a worktree, e.g. after it was edited in an external program
+    pub fn invalidate(&mut self, worktree: &Path) {
+        self.diffs.remove(worktree);
+        self.pending.remove(worktree);
+        *self.generations.entry(worktree.to_path_buf()).or_default() += 1;
+    }
+
+    fn generation(&self, worktree: &Path) -> u64 {
+        self.generations.get(worktree).copied().unwrap_or_default()
+    }
+
+    /// The diff of a worktree, if it has been computed
+    pub fn get(&self, worktree: &Path) -> Option<&str> {
+        self.diffs.get(worktree).map(String::as_str)
+    }
+
+    /// The diff of a worktree, computed on the spot if it has not arrived yet
+    pub fn get_or_load(&mut self, worktree: &Path) -> &str {
+        self.poll();
+        self.diffs
+            .entry(worktree.to_path_buf())
+            .or_insert_with(|| get_diff_content_string(worktree))
+    }
+
+    /// Collect finished diffs; returns whether any arrived
+    pub fn poll(&mut self) -> bool {
+        let mut received = false;
+        while let Ok((worktree, generation, diff)) = self.receiver.try_recv() {
+            if generation != self.generation(&worktree) {
+                continue;
+            }
+            self.pending.remove(&worktree);
+            self.diffs.insert(worktree, diff);
+            received = true;
+        }
+        received
+    }
+}
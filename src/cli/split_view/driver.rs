@@ -0,0 +1,149 @@
+//! Plays scripted key presses into the split view for tests of whole interactions
+//!
+//! ```text
+//! let mut driver = Driver::new(result_infos, 100, 30)?;
+//! driver.press("j<Tab>/Made<Enter>")?;
+//! assert_snapshot!(driver.backend());
+//! ```
+
+use ratatui::Terminal;
+use ratatui::backend::TestBackend;
+use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+use super::app::App;
+use super::render::render;
+use super::types::SplitViewResult;
+use super::view_content;
+use crate::domain::ResultInfo;
+
+type TestResult<T> = Result<T, Box<dyn std::error::Error>>;
+
+/// The split view on a test terminal, redrawn after every key like the real loop
+pub struct Driver {
+    pub app: App,
+    pub terminal: Terminal<TestBackend>,
+    /// Content of the current view, as the real loop passes it to the app
+    content: String,
+    /// Whether a key closed the view
+    finished: bool,
+}
+
+impl Driver {
+    /// Open the split view on the results in a `width` x `height` terminal
+    pub fn new(result_infos: Vec<ResultInfo>, width: u16, height: u16) -> TestResult<Self> {
+        let mut driver = Self {
+            app: App::new(result_infos),
+            terminal: Terminal::new(TestBackend::new(width, height))?,
+            content: String::new(),
+            finished: false,
+        };
+        driver.draw()?;
+        Ok(driver)
+    }
+
+    /// Press the keys of `script` one by one
+    ///
+    /// Characters are typed as they are; `<Enter>`, `<Esc>`, `<Tab>`, `<BackTab>`,
+    /// `<Backspace>`, `<Space>`, `<Up>`, `<Down>`, `<Left>`, `<Right>`, `<Home>`,
+    /// `<End>`, `<lt>` and `<C-x>` name the other keys. Fails if the view closes
+    /// before the script ends.
+    pub fn press(&mut self, script: &str) -> TestResult<&mut Self> {
+        for key in parse_keys(script)? {
+            if self.finished {
+                return Err(format!("the view closed before the end of {script:?}").into());
+            }
+            let viewport_height = self.terminal.size()?.height.saturating_sub(4);
+            self.finished = self
+                .app
+                .handle_event(&Event::Key(key), viewport_height, &self.content);
+            self.draw()?;
+        }
+        Ok(self)
+    }
+
+    /// The terminal as last drawn, for snapshots
+    pub fn backend(&self) -> &TestBackend {
+        self.terminal.backend()
+    }
+
+    /// How the view was closed, once a key closed it
+    pub fn result(&self) -> Option<&SplitViewResult> {
+        if self.finished {
+            self.app.result.as_ref()
+        } else {
+            None
+        }
+    }
+
+    fn draw(&mut self) -> TestResult<()> {
+        if let Some(content) = view_content(&self.app) {
+            self.content = content;
+        }
+        self.terminal
+            .draw(|frame| render(frame, &mut self.app, &self.content))?;
+        Ok(())
+    }
+}
+
+/// The key presses of a script, see [`Driver::press`]
+fn parse_keys(script: &str) -> Result<Vec<KeyEvent>, String> {
+    let mut keys = Vec::new();
+    let mut chars = script.chars();
+    while let Some(c) = chars.next() {
+        if c != '<' {
+            keys.push(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+            continue;
+        }
+        let name: String = chars.by_ref().take_while(|&c| c != '>').collect();
+        let key = match name.as_str() {
+            "Enter" => KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+            "Esc" => KeyEvent::new(KeyCode::Esc, KeyModifiers::NONE),
+            "Tab" => KeyEvent::new(KeyCode::Tab, KeyModifiers::NONE),
+            "BackTab" => KeyEvent::new(KeyCode::BackTab, KeyModifiers::SHIFT),
+            "Backspace" => KeyEvent::new(KeyCode::Backspace, KeyModifiers::NONE),
+            "Space" => KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+            "Up" => KeyEvent::new(KeyCode::Up, KeyModifiers::NONE),
+            "Down" => KeyEvent::new(KeyCode::Down, KeyModifiers::NONE),
+            "Left" => KeyEvent::new(KeyCode::Left, KeyModifiers::NONE),
+            "Right" => KeyEvent::new(KeyCode::Right, KeyModifiers::NONE),
+            "Home" => KeyEvent::new(KeyCode::Home, KeyModifiers::NONE),
+            "End" => KeyEvent::new(KeyCode::End, KeyModifiers::NONE),
+            "lt" => KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+            _ => match name
+                .strip_prefix("C-")
+                .map(|rest| rest.chars().collect::<Vec<_>>())
+            {
+                Some(rest) if rest.len() == 1 => {
+                    KeyEvent::new(KeyCode::Char(rest[0]), KeyModifiers::CONTROL)
+                }
+                _ => return Err(format!("unknown key <{name}> in {script:?}")),
+            },
+        };
+        keys.push(key);
+    }
+    Ok(keys)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_keys() -> Result<(), String> {
+        assert_eq!(
+            parse_keys("j/a b<Enter><C-d><lt>")?,
+            [
+                KeyEvent::new(KeyCode::Char('j'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('/'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('a'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char(' '), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('b'), KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Enter, KeyModifiers::NONE),
+                KeyEvent::new(KeyCode::Char('d'), KeyModifiers::CONTROL),
+                KeyEvent::new(KeyCode::Char('<'), KeyModifiers::NONE),
+            ]
+        );
+        assert!(parse_keys("<Nope>").is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,150 @@
+use std::path::Path;
+use std::process::Command;
+
+use super::types::ExternalAction;
+use crate::cli::editor::editor_command;
+use crate::cli::output::say;
+use crate::cli::ui::is_delta_available;
+use crate::error::{Error, Result};
+use crate::git::{skipped_by_dry_run, traced_blocking, traced_status};
+
+/// Pager used when neither delta nor `$PAGER` is available
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Pager command for `git diff`: delta if installed, otherwise `$PAGER`
+fn pager_command() -> String {
+    if is_delta_available() {
+        return "delta --paging=always".to_string();
+    }
+    std::env::var("PAGER")
+        .ok()
+        .filter(|pager| !pager.trim().is_empty())
+        .unwrap_or_else(|| DEFAULT_PAGER.to_string())
+}
+
+/// Split an interactive shell command like `zsh -l` into the program and its arguments
+///
+/// Falls back to `sh` when `shell` is unset or empty.
+fn shell_command_from(shell: Option<String>) -> Result<Vec<String>> {
+    let value = shell
+        .filter(|shell| !shell.trim().is_empty())
+        .unwrap_or_else(|| "sh".to_string());
+    shlex::split(&value)
+        .filter(|words| !words.is_empty())
+        .ok_or_else(|| Error::Config {
+            message: format!("Cannot parse shell command '{value}'"),
+        })
+}
+
+/// Interactive shell from `$SHELL`, falling back to `sh`
+fn shell_command() -> Result<Vec<String>> {
+    shell_command_from(std::env::var("SHELL").ok())
+}
+
+/// Run an external program for the given worktree
+///
+/// The caller is responsible for leaving the alternate screen before and
+/// restoring the TUI afterwards.
+///
+/// # Errors
+///
+/// Returns an error if the program cannot be started.
+pub fn run_external(action: ExternalAction, worktree: &Path) -> Result<()> {
+    let mut cmd = match action {
+        ExternalAction::Pager => {
+            let mut cmd = Command::new("git");
+            cmd.args(["-c", "color.diff=always", "diff", "HEAD"])
+                .env("GIT_PAGER", pager_command());
+            cmd
+        }
+        // `--dir-diff` hands the whole change set to the configured `diff.tool` (e.g. meld)
+        ExternalAction::Difftool => {
+            let mut cmd = Command::new("git");
+            cmd.args(["difftool", "--dir-diff", "--no-prompt", "HEAD"]);
+            cmd
+        }
+        ExternalAction::Editor => {
+            let editor = editor_command()?;
+            let mut cmd = Command::new(&editor[0]);
+            cmd.args(&editor[1..]).arg(worktree);
+            cmd
+        }
+        ExternalAction::Shell => {
+            let shell = shell_command()?;
+            say!(
+                "Opening a shell in {}. Type `exit` to return to parari.",
+                worktree.display()
+            );
+            let mut cmd = Command::new(&shell[0]);
+            cmd.args(&shell[1..]);
+            cmd
+        }
+    };
+
+    cmd.current_dir(worktree);
+    let status = match action {
+        ExternalAction::Pager | ExternalAction::Difftool => traced_status(&mut cmd),
+        ExternalAction::Editor | ExternalAction::Shell => cmd.status(),
+    };
+    status.map_err(|e| match action {
+        ExternalAction::Editor => Error::EditorFailed {
+            message: format!("Failed to start editor: {e}"),
+        },
+        _ => Error::GitCommand {
+            message: format!("Failed to run {action:?}: {e}"),
+        },
+    })?;
+
+    Ok(())
+}
+
+/// Delete a rejected candidate's worktree right away to free disk space
+///
+/// Like `git::remove_worktree`, but blocking so it can run from the TUI loop.
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started or the directory cannot be removed.
+pub fn discard_worktree(repo: &Path, worktree: &Path) -> Result<()> {
+    let mut remove = Command::new("git");
+    remove
+        .args(["worktree", "remove", "--force"])
+        .arg(worktree)
+        .current_dir(repo);
+    if skipped_by_dry_run(&remove) {
+        return Ok(());
+    }
+    let output = traced_blocking(&mut remove)?;
+
+    if !output.status.success() {
+        if worktree.exists() {
+            std::fs::remove_dir_all(worktree)?;
+        }
+        let _ = traced_blocking(
+            Command::new("git")
+                .args(["worktree", "prune"])
+                .current_dir(repo),
+        );
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_shell_command() -> Result<()> {
+        let some = |value: &str| Some(value.to_string());
+        assert_eq!(shell_command_from(None)?, ["sh"]);
+        assert_eq!(shell_command_from(some(" "))?, ["sh"]);
+        assert_eq!(shell_command_from(some("/bin/zsh -l"))?, ["/bin/zsh", "-l"]);
+        assert_eq!(
+            shell_command_from(some("'/opt/My Shell/fish'"))?,
+            ["/opt/My Shell/fish"]
+        );
+        assert!(shell_command_from(some("bash \"unclosed")).is_err());
+        Ok(())
+    }
+}
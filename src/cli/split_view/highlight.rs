@@ -0,0 +1,141 @@
+use std::cell::{Ref, RefCell};
+use std::path::Path;
+
+use ratatui::style::{Color, Style};
+use ratatui::text::{Line, Span, Text};
+use syntect::easy::HighlightLines;
+use syntect::highlighting::{Theme, ThemeSet};
+use syntect::parsing::{SyntaxReference, SyntaxSet};
+
+use super::content::{diff_section_path, style_diff_line};
+
+/// Theme used for highlighting code
+const THEME_NAME: &str = "base16-ocean.dark";
+
+/// Background tint of added lines
+const ADDED_BACKGROUND: Color = Color::Rgb(20, 50, 20);
+
+/// Background tint of removed lines
+const REMOVED_BACKGROUND: Color = Color::Rgb(60, 20, 20);
+
+/// Syntax highlighter for the diff view
+///
+/// The language of each file section is detected from its file extension.
+pub struct Highlighter {
+    syntax_set: SyntaxSet,
+    theme: Theme,
+    /// Last highlighted content and its styled text, reused across frames
+    cache: RefCell<(String, Text<'static>)>,
+}
+
+impl Default for Highlighter {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Highlighter {
+    pub fn new() -> Self {
+        let theme = ThemeSet::load_defaults()
+            .themes
+            .remove(THEME_NAME)
+            .unwrap_or_default();
+
+        Self {
+            syntax_set: SyntaxSet::load_defaults_nonewlines(),
+            theme,
+            cache: RefCell::new((String::new(), Text::default())),
+        }
+    }
+
+    /// Highlight diff content
+    ///
+    /// The result is cached, so highlighting the same content again is free.
+    pub fn highlight(&self, content: &str) -> Ref<'_, Text<'static>> {
+        if self.cache.borrow().0 != content {
+            let text = self.highlight_uncached(content);
+            *self.cache.borrow_mut() = (content.to_string(), text);
+        }
+        Ref::map(self.cache.borrow(), |(_, text)| text)
+    }
+
+    /// Find the syntax for a file by its extension, falling back to plain text
+    fn syntax_for(&self, path: &str) -> &SyntaxReference {
+        let path = Path::new(path);
+        path.extension()
+            .and_then(|ext| ext.to_str())
+            .and_then(|ext| self.syntax_set.find_syntax_by_extension(ext))
+            // Files such as `Makefile` are matched by their full name
+            .or_else(|| {
+                path.file_name()
+                    .and_then(|name| name.to_str())
+                    .and_then(|name| self.syntax_set.find_syntax_by_extension(name))
+            })
+            .unwrap_or_else(|| self.syntax_set.find_syntax_plain_text())
+    }
+
+    fn highlight_uncached(&self, content: &str) -> Text<'static> {
+        let mut current: Option<HighlightLines> = None;
+        let mut lines = Vec::new();
+
+        for line in content.lines() {
+            // A new file section switches the language
+            if let Some(path) = diff_section_path(line) {
+                current = Some(HighlightLines::new(self.syntax_for(path), &self.theme));
+                lines.push(style_diff_line(line));
+                continue;
+            }
+
+            let highlighted = current
+                .as_mut()
+                .and_then(|highlighter| self.highlight_line(highlighter, line));
+            lines.push(highlighted.unwrap_or_else(|| style_diff_line(line)));
+        }
+
+        Text::from(lines)
+    }
+
+    /// Highlight a single code line, or `None` if the line is not code
+    fn highlight_line(
+        &self,
+        highlighter: &mut HighlightLines,
+        line: &str,
+    ) -> Option<Line<'static>> {
+        // File headers keep the default diff styling
+        if line.starts_with("+++") || line.starts_with("---") {
+            return None;
+        }
+        let (marker_style, background) = match line.chars().next()? {
+            '+' => (Style::new().fg(Color::Green), Some(ADDED_BACKGROUND)),
+            '-' => (Style::new().fg(Color::Red), Some(REMOVED_BACKGROUND)),
+            ' ' => (Style::new(), None),
+            _ => return None,
+        };
+
+        let (marker, code) = line.split_at(1);
+        let mut spans = vec![Span::styled(marker.to_string(), marker_style)];
+        spans.extend(self.highlight_code(highlighter, code).into_iter().map(
+            |span| match background {
+                Some(bg) => span.patch_style(Style::new().bg(bg)),
+                None => span,
+            },
+        ));
+        Some(Line::from(spans))
+    }
+
+    fn highlight_code(&self, highlighter: &mut HighlightLines, code: &str) -> Vec<Span<'static>> {
+        match highlighter.highlight_line(code, &self.syntax_set) {
+            Ok(ranges) => ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let fg = style.foreground;
+                    Span::styled(
+                        text.to_string(),
+                        Style::new().fg(Color::Rgb(fg.r, fg.g, fg.b)),
+                    )
+                })
+                .collect(),
+            Err(_) => vec![Span::raw(code.to_string())],
+        }
+    }
+}
@@ -1,16 +1,45 @@
 use ratatui::Frame;
-use ratatui::layout::{Constraint, Layout, Position};
+use ratatui::layout::{Constraint, Layout, Margin, Position, Rect};
 use ratatui::style::{Color, Modifier, Style};
-use ratatui::text::{Line, Span};
-use ratatui::widgets::{Block, List, ListItem, Paragraph, Wrap};
+use ratatui::text::{Line, Span, Text};
+use ratatui::widgets::{
+    Block, Clear, List, ListItem, ListState, Paragraph, Scrollbar, ScrollbarOrientation,
+    ScrollbarState, Wrap,
+};
 
-use super::app::App;
-use super::content::{get_agent_emoji, get_styled_content, get_styled_content_with_search};
-use super::types::{FocusedPanel, InputMode, ViewMode};
+use super::app::{App, MIN_MODELS_PANEL_WIDTH};
+use super::content::{
+    diff_file_position, error_lines, get_agent_emoji, stderr_line_count, style_line,
+    style_line_with_search,
+};
+use super::side_by_side::{render_side_by_side, row_lines};
+use super::theme::{apply_ascii_glyphs, apply_theme};
+use super::types::{FocusedPanel, InputMode, OutputFilter, ViewMode};
+use crate::cli::glyphs::ascii_only;
+use crate::domain::ResultInfo;
+
+/// Scrollbar marker of an error line in the log
+const ERROR_MARKER: &str = "\u{25A0}";
+
+/// Lines styled beyond the bottom of the detail pane (wrapped lines take several rows)
+const RENDER_MARGIN: usize = 8;
+
+/// Terminals narrower than this stack the models panel above the details
+const STACKED_LAYOUT_WIDTH: u16 = 80;
+
+/// Terminals narrower than this collapse the models panel to agent icons
+const COMPACT_LAYOUT_WIDTH: u16 = 100;
+
+/// Models panels narrower than this only show agent icons and status
+const COMPACT_MODELS_WIDTH: u16 = 20;
 
 pub fn render(frame: &mut Frame, app: &mut App, content: &str) {
     // Main layout: body + search bar (if searching) + footer
-    let layout = if app.input_mode == InputMode::Search {
+    let searching = matches!(
+        app.input_mode,
+        InputMode::Search | InputMode::GlobalSearch | InputMode::GotoLine | InputMode::Note
+    );
+    let layout = if searching {
         Layout::vertical([
             Constraint::Fill(1),
             Constraint::Length(1),
@@ -24,37 +53,141 @@ pub fn render(frame: &mut Frame, app: &mut App, content: &str) {
     let body = layout[0];
     let footer_idx = layout.len() - 1;
 
-    // Body layout: left panel (models) + right panel (details)
-    let [left_panel, right_panel] =
-        Layout::horizontal([Constraint::Length(32), Constraint::Fill(1)]).areas(body);
+    // Body layout: models + details, stacked vertically on narrow terminals
+    let (models_panel, details_panel, compact) = if body.width < STACKED_LAYOUT_WIDTH {
+        let list_height = app
+            .result_infos
+            .iter()
+            .enumerate()
+            .map(|(i, info)| {
+                if app.rejected.contains(&i) {
+                    1
+                } else {
+                    1 + u16::from(info.change_summary.is_some())
+                        + u16::from(app.notes.contains_key(&i))
+                }
+            })
+            .sum::<u16>()
+            .saturating_add(2)
+            .min(body.height / 2);
+        let [top, bottom] =
+            Layout::vertical([Constraint::Length(list_height), Constraint::Fill(1)]).areas(body);
+        (top, bottom, false)
+    } else {
+        let width = if body.width < COMPACT_LAYOUT_WIDTH {
+            MIN_MODELS_PANEL_WIDTH
+        } else {
+            app.models_width
+        };
+        let [left, right] =
+            Layout::horizontal([Constraint::Length(width), Constraint::Fill(1)]).areas(body);
+        (left, right, width < COMPACT_MODELS_WIDTH)
+    };
 
     // Render model list
-    render_model_list(frame, app, left_panel);
+    render_model_list(frame, app, models_panel, compact);
 
     // Render detail panel
-    render_detail_panel(frame, app, right_panel, content);
+    render_detail_panel(frame, app, details_panel, content);
 
     // Render search bar if in search mode
-    if app.input_mode == InputMode::Search {
+    if searching {
         render_search_bar(frame, app, layout[1]);
     }
 
+    // Render matches of a search across all results on top of the body
+    if app.input_mode == InputMode::GlobalResults {
+        render_global_results(frame, app, body);
+    }
+
+    // Render the run metadata on top of the body
+    if app.input_mode == InputMode::RunInfo {
+        render_run_info(frame, app, body);
+    }
+
+    // Render the comparison of two candidates on top of the body
+    if app.input_mode == InputMode::Compare {
+        render_comparison(frame, app, body);
+    }
+
+    // Warn about local edits that applying would overwrite
+    if app.input_mode == InputMode::Confirm {
+        render_conflicts(frame, app, body);
+    }
+
     // Render help footer
     render_footer(frame, app, layout[footer_idx]);
+
+    apply_theme(app.theme, frame.buffer_mut());
+    if ascii_only() {
+        apply_ascii_glyphs(frame.buffer_mut());
+    }
 }
 
-fn render_model_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::Rect) {
+/// Render the model list; `compact` shows only the agent icon and status
+fn render_model_list(frame: &mut Frame, app: &mut App, area: Rect, compact: bool) {
+    let rejected_style = Style::new()
+        .fg(Color::DarkGray)
+        .add_modifier(Modifier::CROSSED_OUT);
     let items: Vec<ListItem> = app
         .result_infos
         .iter()
-        .map(|info| {
+        .enumerate()
+        .map(|(i, info)| {
             let emoji = get_agent_emoji(&info.executor_name);
             let status = if info.success { "+" } else { "x" };
+            let rejected = app.rejected.contains(&i);
+            if compact {
+                let item = ListItem::new(format!("{emoji}{status}"));
+                return if rejected {
+                    item.style(rejected_style)
+                } else {
+                    item
+                };
+            }
+            if rejected {
+                let mark = if app.discarded.contains(&i) {
+                    "deleted"
+                } else {
+                    "rejected"
+                };
+                return ListItem::new(format!("{emoji} {} [{mark}]", info.executor_name))
+                    .style(rejected_style);
+            }
+            // Candidates with the same changes as an earlier one collapse to one line
+            if let Some(ref original) = info.duplicate_of {
+                return ListItem::new(Line::from(vec![
+                    Span::raw(format!("{emoji} {} ", info.executor_name)),
+                    Span::styled(
+                        format!("= same as {original}"),
+                        Style::new().fg(Color::DarkGray),
+                    ),
+                ]));
+            }
             let label = format!(
                 "{} {} [{}] ({} files)",
                 emoji, info.executor_name, status, info.files_changed
             );
-            ListItem::new(label)
+            let mut lines = vec![if app.compare_mark == Some(i) {
+                Line::from(vec![
+                    Span::raw(label),
+                    Span::styled(" [compare]", Style::new().fg(Color::Cyan)),
+                ])
+            } else {
+                Line::raw(label)
+            }];
+            if let Some(stats) = model_stats(app, info) {
+                lines.push(stats);
+            }
+            if let Some(note) = app.notes.get(&i) {
+                lines.push(Line::styled(
+                    format!("   \"{note}\""),
+                    Style::new()
+                        .fg(Color::DarkGray)
+                        .add_modifier(Modifier::ITALIC),
+                ));
+            }
+            ListItem::new(lines)
         })
         .collect();
 
@@ -65,10 +198,11 @@ fn render_model_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::Re
         Style::new().fg(Color::DarkGray)
     };
 
-    let title = if is_focused {
-        "▶ Models "
-    } else {
-        " Models "
+    let title = match (is_focused, compact) {
+        (true, false) => "▶ Models ",
+        (false, false) => " Models ",
+        (true, true) => "▶ ",
+        (false, true) => "",
     };
 
     let list = List::new(items)
@@ -84,12 +218,116 @@ fn render_model_list(frame: &mut Frame, app: &mut App, area: ratatui::layout::Re
     frame.render_stateful_widget(list, area, &mut app.list_state);
 }
 
-fn render_detail_panel(frame: &mut Frame, app: &App, area: ratatui::layout::Rect, content: &str) {
+/// The stats line under a model: its diff size and badges for errors, checks and warnings
+fn model_stats(app: &App, info: &ResultInfo) -> Option<Line<'static>> {
+    let alert = Style::new().fg(Color::Red).add_modifier(Modifier::BOLD);
+    let mut badges = Vec::new();
+    if let Some(kind) = info.error_kind {
+        badges.push(Span::styled(kind.label(), alert));
+    }
+    if let Some(ref check) = info.check {
+        badges.push(if check.passed {
+            Span::styled("check ok", Style::new().fg(Color::Green))
+        } else {
+            Span::styled("check failed", alert)
+        });
+    }
+    if !info.policy.is_empty() {
+        badges.push(Span::styled(
+            format!("policy: {}", info.policy.len()),
+            alert,
+        ));
+    }
+    if !info.secrets.is_empty() {
+        badges.push(Span::styled("secrets?", alert));
+    }
+    if app.is_large_diff(info) {
+        badges.push(Span::styled(
+            "large diff",
+            Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ));
+    }
+    let agreements = info.agreements();
+    if agreements > 0 {
+        let others = if agreements == 1 { "other" } else { "others" };
+        badges.push(Span::styled(
+            format!("agrees with {agreements} {others}"),
+            Style::new().fg(Color::Cyan),
+        ));
+    }
+
+    let mut stats = vec![Span::raw("   ")];
+    if let Some(ref summary) = info.change_summary {
+        stats.extend([
+            Span::styled(
+                format!("+{}", summary.insertions),
+                Style::new().fg(Color::Green),
+            ),
+            Span::raw(" "),
+            Span::styled(
+                format!("\u{2212}{}", summary.deletions),
+                Style::new().fg(Color::Red),
+            ),
+        ]);
+    }
+    for badge in badges {
+        if stats.len() > 1 {
+            stats.push(Span::raw(" "));
+        }
+        stats.push(badge);
+    }
+    (stats.len() > 1).then(|| Line::from(stats))
+}
+
+/// Title of the detail panel: the view mode and the position within the content
+fn detail_title(app: &App, content: &str, side_by_side: bool) -> String {
     let mode_name = match app.current_mode {
-        ViewMode::Log => "Log",
+        ViewMode::Log => match app.output_filter {
+            OutputFilter::All => "Log",
+            OutputFilter::Stderr => "Log (stderr only)",
+            OutputFilter::Stdout => "Log (stdout only)",
+        },
+        ViewMode::Diff if side_by_side => "Diff (side-by-side)",
         ViewMode::Diff => "Diff",
+        ViewMode::Stat => "Stat",
+        ViewMode::Summary => "Summary",
+        ViewMode::Checks => "Checks",
+        ViewMode::Transcript => "Transcript",
+    };
+
+    let is_focused = app.focused_panel == FocusedPanel::Details;
+    let title = if is_focused {
+        format!("▶ {mode_name} ")
+    } else {
+        format!(" {mode_name} ")
     };
 
+    // Show the current file position in the diff view
+    let top = u16::try_from(app.top_line()).unwrap_or(u16::MAX);
+    let title = match diff_file_position(content, top) {
+        Some((current, total)) if app.current_mode == ViewMode::Diff => {
+            format!("{title}[file {current}/{total}] ")
+        }
+        _ => title,
+    };
+
+    // Count stderr lines in the log view so failures stand out
+    let stderr_lines = app.selected_info().map_or(0, stderr_line_count);
+    let title = if app.current_mode == ViewMode::Log && stderr_lines > 0 {
+        format!("{title}[stderr: {stderr_lines}] ")
+    } else {
+        title
+    };
+
+    // Show the first visible column while line wrapping is off
+    match app.horizontal_scroll {
+        Some(column) => format!("{title}[col {}] ", column.saturating_add(1)),
+        None => title,
+    }
+}
+
+fn render_detail_panel(frame: &mut Frame, app: &mut App, area: Rect, content: &str) {
+    let side_by_side = app.current_mode == ViewMode::Diff && app.side_by_side;
     let is_focused = app.focused_panel == FocusedPanel::Details;
     let border_style = if is_focused {
         Style::new().fg(Color::Cyan)
@@ -97,18 +335,38 @@ fn render_detail_panel(frame: &mut Frame, app: &App, area: ratatui::layout::Rect
         Style::new().fg(Color::DarkGray)
     };
 
-    let title = if is_focused {
-        format!("▶ {mode_name} ")
-    } else {
-        format!(" {mode_name} ")
-    };
+    let title = detail_title(app, content, side_by_side);
 
-    // Build styled content with search highlighting
-    let text = if app.search_query.is_empty() {
-        get_styled_content(content, app.current_mode)
+    let inner = area.inner(Margin::new(1, 1));
+    // Side-by-side rows are already fitted to the column width
+    let wrap = !side_by_side && app.horizontal_scroll.is_none();
+
+    // The line-number gutter (digits plus a space) narrows the text area
+    let gutter = content.lines().count().max(1).to_string().len();
+    let text_area = if app.line_numbers {
+        Rect {
+            width: inner
+                .width
+                .saturating_sub(u16::try_from(gutter + 1).unwrap_or(u16::MAX)),
+            ..inner
+        }
     } else {
-        get_styled_content_with_search(content, app.current_mode, &app.search_query)
+        inner
     };
+    if side_by_side {
+        update_side_by_side_text(app, content, text_area.width);
+    }
+    if let Some(line) = app.pending_line.take() {
+        app.scroll_to_line(line);
+    }
+
+    let mut window = detail_window(app, content, side_by_side, text_area, wrap);
+    if app.line_numbers {
+        let rows = side_by_side.then_some(app.side_by_side_lines.as_slice());
+        number_lines(&mut window.lines, window.scroll, gutter, rows);
+    }
+    app.content_height = u16::try_from(window.total).unwrap_or(u16::MAX);
+    app.scroll_offset = u16::try_from(window.scroll).unwrap_or(u16::MAX);
 
     // Show search match count if searching
     let title_with_search = if !app.search_matches.is_empty() {
@@ -124,22 +382,209 @@ fn render_detail_panel(frame: &mut Frame, app: &App, area: ratatui::layout::Rect
         title
     };
 
-    let paragraph = Paragraph::new(text)
+    // Show the scroll position when the content does not fit
+    let title_with_position = match (window.scroll * 100).checked_div(window.max_scroll) {
+        Some(percent) => format!("{title_with_search} {percent}% "),
+        None => title_with_search,
+    };
+
+    let mut paragraph = Paragraph::new(window.lines)
         .block(
             Block::bordered()
-                .title(title_with_search)
+                .title(title_with_position)
                 .border_style(border_style),
         )
-        .wrap(Wrap { trim: false })
-        .scroll((app.scroll_offset, 0));
+        .scroll((0, app.horizontal_scroll.unwrap_or(0)));
+    if wrap {
+        paragraph = paragraph.wrap(Wrap { trim: false });
+    }
 
     frame.render_widget(paragraph, area);
+
+    if window.max_scroll > 0 {
+        let mut scrollbar_state = ScrollbarState::new(window.max_scroll).position(window.scroll);
+        frame.render_stateful_widget(
+            Scrollbar::new(ScrollbarOrientation::VerticalRight)
+                .begin_symbol(None)
+                .end_symbol(None),
+            area.inner(Margin::new(0, 1)),
+            &mut scrollbar_state,
+        );
+
+        // Mark where the error lines of the log are along the scrollbar
+        if app.current_mode == ViewMode::Log {
+            mark_error_lines(frame, content, area.inner(Margin::new(0, 1)), window.total);
+        }
+    }
+}
+
+/// Draw a marker on the scrollbar track for every error line of the log
+fn mark_error_lines(frame: &mut Frame, content: &str, track: Rect, total: usize) {
+    let Some(x) = track.right().checked_sub(1) else {
+        return;
+    };
+    if total == 0 || track.height == 0 {
+        return;
+    }
+    let buf = frame.buffer_mut();
+    for line in error_lines(content) {
+        let row = usize::from(line) * usize::from(track.height) / total;
+        let y = track.y + u16::try_from(row).unwrap_or(u16::MAX).min(track.height - 1);
+        if let Some(cell) = buf.cell_mut(Position::new(x, y)) {
+            cell.set_symbol(ERROR_MARKER).set_fg(Color::Red);
+        }
+    }
+}
+
+/// Prefix each line with its 1-based line number, right-aligned to `width`
+///
+/// `rows` maps rendered rows to content lines, so side-by-side rows show the number
+/// of the line they start at, which `:` jumps to.
+fn number_lines(lines: &mut [Line<'static>], scroll: usize, width: usize, rows: Option<&[usize]>) {
+    for (i, line) in lines.iter_mut().enumerate() {
+        let row = scroll + i;
+        let content_line = rows.map_or(row, |rows| rows.get(row).copied().unwrap_or(row));
+        let number = Span::styled(
+            format!("{:>width$} ", content_line + 1),
+            Style::new().fg(Color::DarkGray),
+        );
+        line.spans.insert(0, number);
+    }
+}
+
+/// The part of the detail content that is actually rendered
+struct DetailWindow {
+    /// Styled lines starting at `scroll`
+    lines: Vec<Line<'static>>,
+    /// Total number of content lines
+    total: usize,
+    /// Largest scroll offset that still fills the viewport
+    max_scroll: usize,
+    /// Scroll offset (first visible line), clamped to `max_scroll`
+    scroll: usize,
 }
 
-fn render_search_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
+/// Style only the visible window of the detail content
+///
+/// Views whose styling depends on neighbouring lines (delta, syntax highlighting,
+/// side-by-side) use their cached full rendering; all others are styled line by line.
+fn detail_window(
+    app: &App,
+    content: &str,
+    side_by_side: bool,
+    inner: Rect,
+    wrap: bool,
+) -> DetailWindow {
+    let diff = app.current_mode == ViewMode::Diff && app.search_query.is_empty();
+    let height = usize::from(inner.height);
+
+    let highlighted;
+    let full_text: Option<&Text<'static>> = if side_by_side {
+        app.side_by_side_text.as_ref().map(|(_, _, text)| text)
+    } else if diff && app.delta_text.is_some() {
+        app.delta_text.as_ref()
+    } else if diff && let Some(highlighter) = app.highlighter.as_ref() {
+        highlighted = highlighter.highlight(content);
+        Some(&*highlighted)
+    } else {
+        None
+    };
+
+    let (total, max_scroll) = if let Some(text) = full_text {
+        let tail = text.lines.iter().rev().take(height).cloned();
+        (
+            text.lines.len(),
+            last_page_start(tail, text.lines.len(), inner, wrap),
+        )
+    } else {
+        let total = content.lines().count();
+        let tail = content.lines().rev().take(height).map(Line::raw);
+        (total, last_page_start(tail, total, inner, wrap))
+    };
+    let scroll = usize::from(app.scroll_offset).min(max_scroll);
+    let visible = height + RENDER_MARGIN;
+
+    let lines = match full_text {
+        Some(text) => text
+            .lines
+            .iter()
+            .skip(scroll)
+            .take(visible)
+            .cloned()
+            .collect(),
+        None => content
+            .lines()
+            .skip(scroll)
+            .take(visible)
+            .map(|line| {
+                if app.search_query.is_empty() {
+                    style_line(line, app.current_mode)
+                } else {
+                    style_line_with_search(line, app.current_mode, &app.search_query)
+                }
+            })
+            .collect(),
+    };
+
+    DetailWindow {
+        lines,
+        total,
+        max_scroll,
+        scroll,
+    }
+}
+
+/// First line from which the remaining lines fill the viewport
+///
+/// `tail` yields the last lines of the content in reverse order.
+fn last_page_start<'a>(
+    tail: impl Iterator<Item = Line<'a>>,
+    total: usize,
+    inner: Rect,
+    wrap: bool,
+) -> usize {
+    let mut rows = 0;
+    let mut fitting = 0;
+    for line in tail {
+        rows += if wrap {
+            Paragraph::new(line)
+                .wrap(Wrap { trim: false })
+                .line_count(inner.width)
+                .max(1)
+        } else {
+            1
+        };
+        if rows > usize::from(inner.height) {
+            break;
+        }
+        fitting += 1;
+    }
+    total.saturating_sub(fitting)
+}
+
+/// Rebuild the cached side-by-side rendering if the content or width changed
+fn update_side_by_side_text(app: &mut App, content: &str, width: u16) {
+    let stale = app
+        .side_by_side_text
+        .as_ref()
+        .is_none_or(|(cached, cached_width, _)| cached != content || *cached_width != width);
+    if stale {
+        let text = render_side_by_side(content, width);
+        app.side_by_side_text = Some((content.to_string(), width, text));
+        app.side_by_side_lines = row_lines(content);
+    }
+}
+
+fn render_search_bar(frame: &mut Frame, app: &App, area: Rect) {
+    let (prompt, query) = match app.input_mode {
+        InputMode::GlobalSearch => ("All/", &app.global_query),
+        InputMode::GotoLine => (":", &app.goto_input),
+        InputMode::Note => ("Note: ", &app.note_input),
+        _ => ("/", &app.search_query),
+    };
     let search_line = Line::from(vec![
-        Span::styled("/", Style::new().fg(Color::Yellow)),
-        Span::raw(&app.search_query),
+        Span::styled(prompt, Style::new().fg(Color::Yellow)),
+        Span::raw(query),
         Span::styled("_", Style::new().add_modifier(Modifier::SLOW_BLINK)),
     ]);
 
@@ -147,19 +592,339 @@ fn render_search_bar(frame: &mut Frame, app: &App, area: ratatui::layout::Rect)
     frame.render_widget(search_bar, area);
 
     // Set cursor position
-    let query_len = u16::try_from(app.search_query.len()).unwrap_or(u16::MAX);
-    frame.set_cursor_position(Position::new(area.x + 1 + query_len, area.y));
+    let prompt_len = u16::try_from(prompt.len()).unwrap_or(u16::MAX);
+    let query_len = u16::try_from(query.len()).unwrap_or(u16::MAX);
+    frame.set_cursor_position(Position::new(
+        area.x.saturating_add(prompt_len).saturating_add(query_len),
+        area.y,
+    ));
 }
 
-fn render_footer(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
-    let help_spans = match app.input_mode {
-        InputMode::Confirm => {
-            let name = app
-                .selected_info()
-                .map_or("unknown", |info| info.executor_name.as_str());
+/// Render the prompt, directory, base commit and start time of the run as a popup
+fn render_run_info(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(run) = &app.run_info else {
+        return;
+    };
+    let popup = area.inner(Margin::new(area.width / 8, area.height / 8));
+
+    let label = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let field = |name: &'static str, value: String| {
+        Line::from(vec![
+            Span::styled(format!("{name:<11}"), label),
+            Span::raw(value),
+        ])
+    };
+    let mut lines = vec![
+        field(
+            "Started",
+            run.started_at.format("%Y-%m-%d %H:%M:%S").to_string(),
+        ),
+        field("Directory", run.working_dir.display().to_string()),
+        field(
+            "Base",
+            run.base_commit
+                .clone()
+                .unwrap_or_else(|| "(no commits)".to_string()),
+        ),
+        Line::raw(""),
+        Line::styled("Prompt", label),
+    ];
+    lines.extend(run.prompt.lines().map(|line| Line::raw(line.to_string())));
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .title(" Run ")
+                .border_style(Style::new().fg(Color::Cyan)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Render the agent-written comparison of two candidates as a popup
+fn render_comparison(frame: &mut Frame, app: &App, area: Rect) {
+    let (Some(comparer), Some((a, b))) = (&app.comparer, app.comparison) else {
+        return;
+    };
+    let name = |index: usize| {
+        app.result_infos
+            .get(index)
+            .map_or("unknown", |info| info.executor_name.as_str())
+    };
+    let popup = area.inner(Margin::new(area.width / 8, area.height / 8));
+
+    let heading = Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD);
+    let lines: Vec<Line> = match comparer.get((a, b)) {
+        Some(comparison) => comparison
+            .lines()
+            .map(|line| {
+                // Lines that are not bullet points are the agent's headings
+                let item = line.trim_start();
+                if ["- ", "* ", "• "]
+                    .iter()
+                    .any(|bullet| item.starts_with(bullet))
+                {
+                    Line::raw(line.to_string())
+                } else {
+                    Line::styled(line.to_string(), heading)
+                }
+            })
+            .collect(),
+        None => vec![Line::styled(
+            format!("{} is comparing the changes...", comparer.agent()),
+            Style::new().fg(Color::DarkGray),
+        )],
+    };
+
+    let paragraph = Paragraph::new(lines)
+        .block(
+            Block::bordered()
+                .title(format!(" {} vs {} ", name(a), name(b)))
+                .border_style(Style::new().fg(Color::Cyan)),
+        )
+        .wrap(Wrap { trim: false });
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Render the local files the selected candidate would overwrite, if any
+fn render_conflicts(frame: &mut Frame, app: &App, area: Rect) {
+    let Some(info) = app.selected_info() else {
+        return;
+    };
+    if info.conflicts.is_empty() {
+        return;
+    }
+    let height = u16::try_from(info.conflicts.len() + 4)
+        .unwrap_or(u16::MAX)
+        .min(area.height);
+    let popup = Rect {
+        x: area.x + area.width / 8,
+        y: area.y + area.height.saturating_sub(height) / 2,
+        width: area.width - area.width / 4,
+        height,
+    };
+
+    let mut lines = vec![
+        Line::styled(
+            format!(
+                "{} file(s) with uncommitted local changes will be overwritten:",
+                info.conflicts.len()
+            ),
+            Style::new().fg(Color::Yellow),
+        ),
+        Line::raw(""),
+    ];
+    lines.extend(
+        info.conflicts
+            .iter()
+            .map(|file| Line::styled(format!("  {file}"), Style::new().fg(Color::Red))),
+    );
+
+    let paragraph = Paragraph::new(lines).block(
+        Block::bordered()
+            .title(" Local changes ")
+            .border_style(Style::new().fg(Color::Red)),
+    );
+
+    frame.render_widget(Clear, popup);
+    frame.render_widget(paragraph, popup);
+}
+
+/// Render the matches of a search across all results as a popup, grouped by agent
+fn render_global_results(frame: &mut Frame, app: &App, area: Rect) {
+    let popup = area.inner(Margin::new(area.width / 8, area.height / 8));
+
+    let mut items = Vec::new();
+    let mut selected = None;
+    let mut current_result = None;
+    for (i, found) in app.global_matches.iter().enumerate() {
+        if current_result != Some(found.result_index) {
+            current_result = Some(found.result_index);
+            if let Some(info) = app.result_infos.get(found.result_index) {
+                let count = app.global_matches[i..]
+                    .iter()
+                    .take_while(|m| m.result_index == found.result_index)
+                    .count();
+                items.push(ListItem::new(Line::styled(
+                    format!(
+                        "{} {} ({count})",
+                        get_agent_emoji(&info.executor_name),
+                        info.executor_name
+                    ),
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                )));
+            }
+        }
+        if i == app.global_selected {
+            selected = Some(items.len());
+        }
+        let mode = match found.mode {
+            ViewMode::Log => "Log",
+            ViewMode::Diff => "Diff",
+            ViewMode::Stat => "Stat",
+            ViewMode::Summary => "Summary",
+            ViewMode::Checks => "Checks",
+            ViewMode::Transcript => "Transcript",
+        };
+        items.push(ListItem::new(Line::from(vec![
+            Span::styled(
+                format!("  {mode}:{} ", found.line + 1),
+                Style::new().fg(Color::DarkGray),
+            ),
+            Span::raw(found.text.trim().to_string()),
+        ])));
+    }
+    if items.is_empty() {
+        items.push(ListItem::new(Line::styled(
+            "  No matches",
+            Style::new().fg(Color::DarkGray),
+        )));
+    }
+
+    let title = format!(
+        " Search all: {} ({} matches) ",
+        app.global_query,
+        app.global_matches.len()
+    );
+    let list = List::new(items)
+        .block(
+            Block::bordered()
+                .title(title)
+                .border_style(Style::new().fg(Color::Cyan)),
+        )
+        .highlight_style(
+            Style::new()
+                .fg(Color::Black)
+                .bg(Color::Cyan)
+                .add_modifier(Modifier::BOLD),
+        );
+
+    let mut state = ListState::default().with_selected(selected);
+    frame.render_widget(Clear, popup);
+    frame.render_stateful_widget(list, popup, &mut state);
+}
+
+/// Key hints of the focused panel
+fn normal_help(panel: FocusedPanel) -> Vec<Span<'static>> {
+    match panel {
+        FocusedPanel::Models => vec![
+            Span::styled(" j/k ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Select  "),
+            Span::styled(" Tab/l ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Details  "),
+            Span::styled(" L ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Log  "),
+            Span::styled(" D ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Diff  "),
+            Span::styled(" T ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Stat  "),
+            Span::styled(" S ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Summary  "),
+            Span::styled(" a/Enter ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Apply  "),
+            Span::styled(" q ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Quit"),
+        ],
+        FocusedPanel::Details => vec![
+            Span::styled(" j/k ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Scroll  "),
+            Span::styled(" Tab/h ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Models  "),
+            Span::styled(" / ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Search  "),
+            Span::styled(" n/N ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Next/Prev  "),
+            Span::styled(" l ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Log  "),
+            Span::styled(" d ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Diff  "),
+            Span::styled(" a ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Apply  "),
+            Span::styled(" q ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Quit"),
+        ],
+    }
+}
+
+/// The question whether to apply the selected result, offering to stash conflicting changes
+fn apply_confirm_spans(app: &App) -> Vec<Span<'static>> {
+    let name = app
+        .selected_info()
+        .map_or("unknown", |info| info.executor_name.as_str());
+    let conflicts = app
+        .selected_info()
+        .is_some_and(|info| !info.conflicts.is_empty());
+    let mut spans = vec![
+        Span::styled(
+            format!(" Apply changes from {name}? "),
+            Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+        ),
+        Span::styled(" y ", Style::new().fg(Color::Black).bg(Color::Green)),
+        Span::raw(if conflicts { " Overwrite  " } else { " Yes  " }),
+    ];
+    if conflicts {
+        spans.push(Span::styled(
+            " s ",
+            Style::new().fg(Color::Black).bg(Color::Cyan),
+        ));
+        spans.push(Span::raw(" Stash, apply and restore  "));
+    }
+    spans.push(Span::styled(
+        " n/Esc ",
+        Style::new().fg(Color::Black).bg(Color::Red),
+    ));
+    spans.push(Span::raw(" No"));
+    spans
+}
+
+/// The question and answer keys of a pending confirmation
+fn confirm_spans(app: &App) -> Vec<Span<'static>> {
+    match app.input_mode {
+        InputMode::Confirm => apply_confirm_spans(app),
+        InputMode::ConfirmSecrets => {
+            let (name, found, more) =
+                app.selected_info()
+                    .map_or(("unknown", String::new(), 0), |info| {
+                        (
+                            info.executor_name.as_str(),
+                            info.secrets
+                                .first()
+                                .map(ToString::to_string)
+                                .unwrap_or_default(),
+                            info.secrets.len().saturating_sub(1),
+                        )
+                    });
+            let more = if more > 0 {
+                format!(" and {more} more")
+            } else {
+                String::new()
+            };
             vec![
                 Span::styled(
-                    format!(" Apply changes from {name}? "),
+                    format!(" {name} adds a possible {found}{more}. Apply anyway? "),
+                    Style::new().fg(Color::Red).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" y ", Style::new().fg(Color::Black).bg(Color::Green)),
+                Span::raw(" Yes  "),
+                Span::styled(" n/Esc ", Style::new().fg(Color::Black).bg(Color::Red)),
+                Span::raw(" No"),
+            ]
+        }
+        InputMode::ConfirmLargeDiff => {
+            let (name, files, lines) = app.selected_info().map_or(("unknown", 0, 0), |info| {
+                (
+                    info.executor_name.as_str(),
+                    info.files_changed,
+                    info.changed_line_count(),
+                )
+            });
+            vec![
+                Span::styled(
+                    format!(" {name} changed {files} files and {lines} lines. Continue? "),
                     Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
                 ),
                 Span::styled(" y ", Style::new().fg(Color::Black).bg(Color::Green)),
@@ -180,6 +945,89 @@ fn render_footer(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
                 Span::raw(" No"),
             ]
         }
+        InputMode::ConfirmDiscard => {
+            let count = app.rejected.difference(&app.discarded).count();
+            vec![
+                Span::styled(
+                    format!(" Delete the worktrees of {count} rejected candidate(s)? "),
+                    Style::new().fg(Color::Yellow).add_modifier(Modifier::BOLD),
+                ),
+                Span::styled(" y ", Style::new().fg(Color::Black).bg(Color::Green)),
+                Span::raw(" Yes  "),
+                Span::styled(" n/Esc ", Style::new().fg(Color::Black).bg(Color::Red)),
+                Span::raw(" No"),
+            ]
+        }
+        _ => Vec::new(),
+    }
+}
+
+fn render_footer(frame: &mut Frame, app: &App, area: Rect) {
+    if let (InputMode::Normal, Some(message)) = (app.input_mode, &app.status_message) {
+        let status = Paragraph::new(Line::styled(
+            format!(" {message}"),
+            Style::new().fg(Color::Yellow),
+        ));
+        frame.render_widget(status, area);
+        return;
+    }
+
+    let help_spans = match app.input_mode {
+        InputMode::Confirm
+        | InputMode::ConfirmSecrets
+        | InputMode::ConfirmLargeDiff
+        | InputMode::ConfirmCancel
+        | InputMode::ConfirmDiscard => confirm_spans(app),
+        InputMode::Yank => vec![
+            Span::styled(" Copy: ", Style::new().fg(Color::Yellow)),
+            Span::styled(" p ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Worktree path  "),
+            Span::styled(" d ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Diff  "),
+            Span::styled(" l ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Log  "),
+            Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Cancel"),
+        ],
+        InputMode::GlobalSearch => vec![
+            Span::styled(" Search all results: ", Style::new().fg(Color::Yellow)),
+            Span::raw(&app.global_query),
+            Span::styled(" Enter ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Execute  "),
+            Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Cancel"),
+        ],
+        InputMode::GlobalResults => vec![
+            Span::styled(" j/k ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Select  "),
+            Span::styled(" Enter ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Jump  "),
+            Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Close"),
+        ],
+        InputMode::RunInfo => vec![
+            Span::styled(" Esc/i ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Close"),
+        ],
+        InputMode::Compare => vec![
+            Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Close"),
+        ],
+        InputMode::Note => vec![
+            Span::styled(" Note (#tags allowed): ", Style::new().fg(Color::Yellow)),
+            Span::styled(" Enter ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Save (empty removes)  "),
+            Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Cancel"),
+        ],
+        InputMode::GotoLine => vec![
+            Span::styled(" Go to line: ", Style::new().fg(Color::Yellow)),
+            Span::raw(&app.goto_input),
+            Span::styled(" Enter ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Jump  "),
+            Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
+            Span::raw(" Cancel"),
+        ],
         InputMode::Search => vec![
             Span::styled(" Search: ", Style::new().fg(Color::Yellow)),
             Span::raw(&app.search_query),
@@ -188,40 +1036,7 @@ fn render_footer(frame: &mut Frame, app: &App, area: ratatui::layout::Rect) {
             Span::styled(" Esc ", Style::new().fg(Color::Black).bg(Color::Cyan)),
             Span::raw(" Cancel"),
         ],
-        InputMode::Normal => match app.focused_panel {
-            FocusedPanel::Models => vec![
-                Span::styled(" j/k ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Select  "),
-                Span::styled(" Tab/l ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Details  "),
-                Span::styled(" L ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Log  "),
-                Span::styled(" D ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Diff  "),
-                Span::styled(" a/Enter ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Apply  "),
-                Span::styled(" q ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Quit"),
-            ],
-            FocusedPanel::Details => vec![
-                Span::styled(" j/k ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Scroll  "),
-                Span::styled(" Tab/h ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Models  "),
-                Span::styled(" / ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Search  "),
-                Span::styled(" n/N ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Next/Prev  "),
-                Span::styled(" l ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Log  "),
-                Span::styled(" d ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Diff  "),
-                Span::styled(" a ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Apply  "),
-                Span::styled(" q ", Style::new().fg(Color::Black).bg(Color::Cyan)),
-                Span::raw(" Quit"),
-            ],
-        },
+        InputMode::Normal => normal_help(app.focused_panel),
     };
 
     let help_line = Line::from(help_spans);
@@ -0,0 +1,223 @@
+use ratatui::style::{Color, Modifier, Style};
+use ratatui::text::{Line, Span, Text};
+use unicode_width::UnicodeWidthChar;
+
+use super::content::style_diff_line;
+use crate::domain::DiffLine;
+
+/// Separator drawn between the old and new columns
+const COLUMN_SEPARATOR: &str = " \u{2502} ";
+
+/// A row of the side-by-side diff
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Row<'a> {
+    /// A line spanning both columns (file headers, hunk headers, ...)
+    Header(&'a str),
+    /// An unchanged line shown on both sides
+    Context(&'a str),
+    /// A removed and/or added line shown next to each other
+    Change(Option<&'a str>, Option<&'a str>),
+}
+
+/// Split a unified diff into side-by-side rows
+///
+/// Consecutive removed lines are paired with the added lines that follow them.
+pub fn build_rows(diff: &str) -> Vec<Row<'_>> {
+    indexed_rows(diff).into_iter().map(|(_, row)| row).collect()
+}
+
+/// The line of the unified diff each side-by-side row starts at
+///
+/// Navigation and search count lines of the unified diff, so jumps go through this
+/// map. The lines only increase, as rows keep the order of the diff.
+pub fn row_lines(diff: &str) -> Vec<usize> {
+    indexed_rows(diff)
+        .into_iter()
+        .map(|(line, _)| line)
+        .collect()
+}
+
+/// Side-by-side rows, each with the index of the diff line it starts at
+fn indexed_rows(diff: &str) -> Vec<(usize, Row<'_>)> {
+    type Lines<'a> = Vec<(usize, &'a str)>;
+    fn flush<'a>(rows: &mut Vec<(usize, Row<'a>)>, removed: &mut Lines<'a>, added: &mut Lines<'a>) {
+        let len = removed.len().max(added.len());
+        for i in 0..len {
+            let (old, new) = (removed.get(i), added.get(i));
+            let Some((line, _)) = old.or(new) else {
+                continue;
+            };
+            rows.push((*line, Row::Change(old.map(|l| l.1), new.map(|l| l.1))));
+        }
+        removed.clear();
+        added.clear();
+    }
+
+    let mut rows = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let mut in_hunk = false;
+
+    for (index, line) in diff.lines().enumerate() {
+        if line.starts_with("@@") {
+            flush(&mut rows, &mut removed, &mut added);
+            in_hunk = true;
+            rows.push((index, Row::Header(line)));
+            continue;
+        }
+        let parsed = if in_hunk { DiffLine::parse(line) } else { None };
+        match parsed {
+            Some(DiffLine::Removed(old)) => removed.push((index, old)),
+            Some(DiffLine::Added(new)) => added.push((index, new)),
+            Some(DiffLine::Context(context)) => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push((index, Row::Context(context)));
+            }
+            Some(DiffLine::NoNewline(marker)) => {
+                flush(&mut rows, &mut removed, &mut added);
+                rows.push((index, Row::Header(marker)));
+            }
+            None => {
+                flush(&mut rows, &mut removed, &mut added);
+                in_hunk = false;
+                rows.push((index, Row::Header(line)));
+            }
+        }
+    }
+    flush(&mut rows, &mut removed, &mut added);
+
+    rows
+}
+
+/// Length in chars of the common prefix and common suffix of two lines
+///
+/// The prefix and suffix never overlap, so the remaining middle parts are the changed text.
+pub fn common_affixes(old: &str, new: &str) -> (usize, usize) {
+    let old: Vec<char> = old.chars().collect();
+    let new: Vec<char> = new.chars().collect();
+
+    let prefix = old
+        .iter()
+        .zip(new.iter())
+        .take_while(|(a, b)| a == b)
+        .count();
+    let max_suffix = old.len().min(new.len()) - prefix;
+    let suffix = old
+        .iter()
+        .rev()
+        .zip(new.iter().rev())
+        .take(max_suffix)
+        .take_while(|(a, b)| a == b)
+        .count();
+
+    (prefix, suffix)
+}
+
+/// Build the spans of one column, emphasizing the changed middle part and padding to `width`
+///
+/// `width` is measured in terminal cells, so wide (CJK, emoji) characters count twice.
+///
+/// `affixes` are the common prefix/suffix lengths from `common_affixes`; `None` means the
+/// line has no counterpart and is shown without emphasis.
+fn column_spans(
+    text: &str,
+    affixes: Option<(usize, usize)>,
+    width: usize,
+    base: Style,
+    emphasis: Style,
+) -> Vec<Span<'static>> {
+    let full_len = text.chars().count();
+    let mut used = 0;
+    let chars: Vec<char> = text
+        .chars()
+        .take_while(|c| {
+            let char_width = c.width().unwrap_or(0);
+            if used + char_width > width {
+                return false;
+            }
+            used += char_width;
+            true
+        })
+        .collect();
+    let (prefix, suffix) = affixes.unwrap_or((full_len, 0));
+    let prefix = prefix.min(chars.len());
+    let middle_end = full_len.saturating_sub(suffix).clamp(prefix, chars.len());
+
+    let mut spans = Vec::new();
+    let parts = [
+        (&chars[..prefix], base),
+        (&chars[prefix..middle_end], emphasis),
+        (&chars[middle_end..], base),
+    ];
+    for (part, style) in parts {
+        if !part.is_empty() {
+            spans.push(Span::styled(part.iter().collect::<String>(), style));
+        }
+    }
+    if used < width {
+        spans.push(Span::raw(" ".repeat(width - used)));
+    }
+    spans
+}
+
+/// Render a unified diff as two columns (old on the left, new on the right)
+///
+/// `width` is the total width available; each column gets half of it.
+pub fn render_side_by_side(diff: &str, width: u16) -> Text<'static> {
+    let column_width = usize::from(width).saturating_sub(COLUMN_SEPARATOR.chars().count()) / 2;
+
+    let removed = Style::new().fg(Color::Red);
+    let removed_emphasis = Style::new()
+        .fg(Color::White)
+        .bg(Color::Red)
+        .add_modifier(Modifier::BOLD);
+    let added = Style::new().fg(Color::Green);
+    let added_emphasis = Style::new()
+        .fg(Color::Black)
+        .bg(Color::Green)
+        .add_modifier(Modifier::BOLD);
+    let separator = Style::new().fg(Color::DarkGray);
+
+    let lines: Vec<Line<'static>> = build_rows(diff)
+        .into_iter()
+        .map(|row| match row {
+            Row::Header(line) => style_diff_line(line),
+            Row::Context(line) => {
+                let mut spans = column_spans(line, None, column_width, Style::new(), Style::new());
+                spans.push(Span::styled(COLUMN_SEPARATOR, separator));
+                spans.extend(column_spans(
+                    line,
+                    None,
+                    column_width,
+                    Style::new(),
+                    Style::new(),
+                ));
+                Line::from(spans)
+            }
+            Row::Change(old, new) => {
+                let affixes = match (old, new) {
+                    (Some(old), Some(new)) => Some(common_affixes(old, new)),
+                    _ => None,
+                };
+                let mut spans = column_spans(
+                    old.unwrap_or(""),
+                    affixes,
+                    column_width,
+                    removed,
+                    removed_emphasis,
+                );
+                spans.push(Span::styled(COLUMN_SEPARATOR, separator));
+                spans.extend(column_spans(
+                    new.unwrap_or(""),
+                    affixes,
+                    column_width,
+                    added,
+                    added_emphasis,
+                ));
+                Line::from(spans)
+            }
+        })
+        .collect();
+
+    Text::from(lines)
+}
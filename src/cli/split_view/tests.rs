@@ -1,11 +1,47 @@
+use std::collections::HashSet;
+
 use super::app::App;
-use super::content::{get_agent_emoji, get_log_content_string, strip_ansi_codes};
+use super::content::{
+    diff_file_position, diff_file_starts, diff_section_path, find_fold_dir, fold_diff_sections,
+    get_agent_emoji, get_checks_content_string, get_log_content_string, get_stat_content_string,
+    get_summary_content_string, get_transcript_content_string, group_files_by_dir, scale_stat_bar,
+    strip_ansi_codes,
+};
 use super::render::render;
-use super::types::{FocusedPanel, InputMode, ViewMode};
+use super::types::{FocusedPanel, InputMode, OutputFilter, SplitViewResult, ViewMode};
 
-use crate::domain::ResultInfo;
+use crate::domain::{CheckResult, ResultInfo, Score, SecretFinding};
 use crate::executor::OutputLine;
 
+/// A successful result of `executor_name` without output or changes, for tests to
+/// fill in the fields they need
+fn test_info(executor_name: &str) -> ResultInfo {
+    ResultInfo {
+        executor_name: executor_name.to_string(),
+        success: true,
+        files_changed: 0,
+        change_summary: None,
+        summary: None,
+        worktree_path: std::path::PathBuf::from("/tmp/test1"),
+        stdout: String::new(),
+        stderr: String::new(),
+        output_lines: vec![],
+        conflicts: vec![],
+        score: None,
+        check: None,
+        duration: None,
+        breakdown: None,
+        diff_hash: None,
+        duplicate_of: None,
+        changed_lines: vec![],
+        similarity: vec![],
+        retries: vec![],
+        error_kind: None,
+        secrets: vec![],
+        policy: vec![],
+    }
+}
+
 #[test]
 fn test_strip_ansi_codes() {
     assert_eq!(strip_ansi_codes("\x1b[31mred\x1b[0m"), "red");
@@ -23,39 +59,42 @@ fn test_get_agent_emoji() {
     assert_eq!(get_agent_emoji("Claude"), "\u{1F916}");
     assert_eq!(get_agent_emoji("gemini"), "\u{2728}");
     assert_eq!(get_agent_emoji("codex"), "\u{1F4E6}");
+    assert_eq!(get_agent_emoji("claude-2"), "\u{1F916}");
     assert_eq!(get_agent_emoji("unknown"), "\u{1F4BB}");
 }
 
 #[test]
 fn test_app_navigation() {
-    use crate::git::ChangeSummary;
+    use crate::git::{ChangeSummary, FileChange, FileStatus};
     use std::path::PathBuf;
 
     let result_infos = vec![
         ResultInfo {
-            executor_name: "claude".to_string(),
-            success: true,
             stdout: "output".to_string(),
-            stderr: String::new(),
             output_lines: vec![OutputLine::Stdout("output".to_string())],
             files_changed: 1,
-            worktree_path: PathBuf::from("/tmp/test1"),
             change_summary: Some(ChangeSummary {
                 files_added: 1,
                 files_modified: 0,
                 files_deleted: 0,
-                changed_files: vec!["test.rs".to_string()],
+                insertions: 5,
+                deletions: 0,
+                changed_files: vec![FileChange {
+                    path: "test.rs".to_string(),
+                    status: FileStatus::Added,
+                    insertions: 5,
+                    deletions: 0,
+                    binary: false,
+                }],
             }),
+            ..test_info("claude")
         },
         ResultInfo {
-            executor_name: "gemini".to_string(),
-            success: true,
             stdout: "output".to_string(),
-            stderr: String::new(),
             output_lines: vec![OutputLine::Stdout("output".to_string())],
             files_changed: 2,
             worktree_path: PathBuf::from("/tmp/test2"),
-            change_summary: None,
+            ..test_info("gemini")
         },
     ];
 
@@ -83,10 +122,967 @@ fn test_app_mode_switching() {
     app.set_mode(ViewMode::Diff);
     assert_eq!(app.current_mode, ViewMode::Diff);
 
+    app.set_mode(ViewMode::Summary);
+    assert_eq!(app.current_mode, ViewMode::Summary);
+
     app.set_mode(ViewMode::Log);
     assert_eq!(app.current_mode, ViewMode::Log);
 }
 
+#[test]
+fn test_get_summary_content_string() {
+    let mut info = ResultInfo {
+        files_changed: 1,
+        summary: Some("Added a parser\nUpdated tests".to_string()),
+        ..test_info("claude")
+    };
+
+    let content = get_summary_content_string(&info);
+    assert!(content.starts_with("\u{1F916} CLAUDE - Summary\n"));
+    assert!(content.contains("Added a parser\nUpdated tests\n"));
+
+    info.summary = None;
+    let content = get_summary_content_string(&info);
+    assert!(content.contains("(no summary available)"));
+
+    let content = get_checks_content_string(&info);
+    assert!(content.starts_with("\u{1F916} CLAUDE - Checks\n"));
+    assert!(content.contains("(no check or score command was run)"));
+
+    info.check = Some(CheckResult {
+        passed: false,
+        output: "error[E0308]: mismatched types\n".to_string(),
+    });
+    let content = get_checks_content_string(&info);
+    assert!(content.contains("\nCheck: failed\n"));
+    assert!(content.contains("error[E0308]: mismatched types\n"));
+    assert!(!content.contains("Score:"));
+}
+
+#[test]
+fn test_get_transcript_content_string() {
+    let mut info = ResultInfo {
+        output_lines: vec![OutputLine::Stdout("Done.".to_string())],
+        ..test_info("claude")
+    };
+    let content = get_transcript_content_string(&info);
+    assert!(content.starts_with("\u{1F916} CLAUDE - Transcript\n"));
+    assert!(content.contains("(no structured output"));
+
+    let output = (1..=8).map(|i| format!("line {i}")).collect::<Vec<_>>();
+    info.output_lines = [
+        serde_json::json!({"type": "assistant", "message": {"content": [
+            {"type": "text", "text": "Running the tests."},
+            {"type": "tool_use", "name": "Bash", "input": {"command": "cargo test"}},
+        ]}}),
+        serde_json::json!({"type": "user", "message": {"content": [
+            {"type": "tool_result", "content": output.join("\n"), "is_error": true},
+        ]}}),
+    ]
+    .iter()
+    .map(|event| OutputLine::Stdout(event.to_string()))
+    .collect();
+    let content = get_transcript_content_string(&info);
+    assert!(
+        content.contains("\nAssistant:\n  Running the tests.\n> Bash: cargo test\n  ! line 1\n")
+    );
+    assert!(content.contains("  ! line 6\n  ! ... 2 more lines\n"));
+}
+
+#[test]
+fn test_group_files_by_dir() {
+    use crate::git::{FileChange, FileStatus};
+
+    let files: Vec<FileChange> = ["src/main.rs", "README.md", "src/cli/args.rs", "src/lib.rs"]
+        .iter()
+        .map(|path| FileChange {
+            path: (*path).to_string(),
+            status: FileStatus::Modified,
+            insertions: 1,
+            deletions: 0,
+            binary: false,
+        })
+        .collect();
+
+    let groups = group_files_by_dir(&files);
+    let dirs: Vec<&str> = groups.keys().map(String::as_str).collect();
+    assert_eq!(dirs, vec!["./", "src/", "src/cli/"]);
+
+    let src: Vec<&str> = groups["src/"].iter().map(|f| f.path.as_str()).collect();
+    assert_eq!(src, vec!["src/lib.rs", "src/main.rs"]);
+}
+
+#[test]
+fn test_find_fold_dir() {
+    let content = "Summary:\n  Files changed: 2\n\n  \u{25BE} src/ (2 files, +3 \u{2212}0)\n    + a.rs  +1 \u{2212}0\n    + b.rs  +2 \u{2212}0\n  \u{25B8} tests/ (1 files, +1 \u{2212}0)\n\nOutput:";
+
+    assert_eq!(find_fold_dir(content, 1), None);
+    assert_eq!(find_fold_dir(content, 3), Some("src/".to_string()));
+    assert_eq!(find_fold_dir(content, 5), Some("src/".to_string()));
+    assert_eq!(find_fold_dir(content, 6), Some("tests/".to_string()));
+    assert_eq!(find_fold_dir(content, 8), None);
+}
+
+#[test]
+fn test_app_fold_toggle() {
+    let content = "Summary:\n\n  \u{25BE} src/ (1 files, +1 \u{2212}0)\n    + a.rs  +1 \u{2212}0";
+    let mut app = App::new(vec![]);
+    app.refresh_content = false;
+
+    app.scroll_offset = 3;
+    app.toggle_fold(content);
+    assert!(app.collapsed_dirs.contains("src/"));
+    assert!(app.refresh_content);
+
+    app.toggle_fold(content);
+    assert!(app.collapsed_dirs.is_empty());
+
+    // Folding only applies to the log view
+    app.set_mode(ViewMode::Diff);
+    app.toggle_fold(content);
+    assert!(app.collapsed_dirs.is_empty());
+}
+
+#[test]
+fn test_scale_stat_bar() {
+    assert_eq!(scale_stat_bar(3, 2, 5), (3, 2));
+    assert_eq!(scale_stat_bar(80, 0, 80), (40, 0));
+    assert_eq!(scale_stat_bar(100, 1, 200), (20, 1));
+}
+
+#[test]
+fn test_get_stat_content_string() {
+    use crate::git::{ChangeSummary, FileChange, FileStatus};
+
+    let mut info = ResultInfo {
+        files_changed: 2,
+        change_summary: Some(ChangeSummary {
+            files_added: 1,
+            files_modified: 1,
+            files_deleted: 0,
+            insertions: 12,
+            deletions: 3,
+            changed_files: vec![
+                FileChange {
+                    path: "src/main.rs".to_string(),
+                    status: FileStatus::Modified,
+                    insertions: 2,
+                    deletions: 3,
+                    binary: false,
+                },
+                FileChange {
+                    path: "src/lib.rs".to_string(),
+                    status: FileStatus::Added,
+                    insertions: 10,
+                    deletions: 0,
+                    binary: false,
+                },
+            ],
+        }),
+        ..test_info("claude")
+    };
+
+    assert_eq!(
+        get_stat_content_string(&info),
+        " src/lib.rs  | 10 ++++++++++\n src/main.rs |  5 ++---\n 2 files changed, 12 insertions(+), 3 deletions(-)\n"
+    );
+
+    info.change_summary = None;
+    assert_eq!(get_stat_content_string(&info), "No changes detected.");
+}
+
+const MULTI_FILE_DIFF: &str = "diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1 +1 @@
+-old
++new
+diff --git a/src/main.rs b/src/main.rs
+index 3333333..4444444 100644
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1,2 @@
+ fn main() {}
++fn run() {}
+";
+
+#[test]
+fn test_diff_section_path() {
+    assert_eq!(
+        diff_section_path("diff --git a/src/lib.rs b/src/lib.rs"),
+        Some("src/lib.rs")
+    );
+    assert_eq!(diff_section_path("+++ b/src/lib.rs"), None);
+}
+
+#[test]
+fn test_diff_file_position() {
+    assert_eq!(diff_file_starts(MULTI_FILE_DIFF), vec![0, 7]);
+    assert_eq!(diff_file_position(MULTI_FILE_DIFF, 0), Some((1, 2)));
+    assert_eq!(diff_file_position(MULTI_FILE_DIFF, 8), Some((2, 2)));
+    assert_eq!(diff_file_position("No changes detected.", 0), None);
+}
+
+#[test]
+fn test_fold_diff_sections() {
+    let mut collapsed = HashSet::new();
+    assert_eq!(
+        fold_diff_sections(MULTI_FILE_DIFF, &collapsed),
+        MULTI_FILE_DIFF
+    );
+
+    collapsed.insert("src/lib.rs".to_string());
+    let folded = fold_diff_sections(MULTI_FILE_DIFF, &collapsed);
+    assert!(folded.starts_with(
+        "diff --git a/src/lib.rs b/src/lib.rs\n  \u{25B8} 6 lines folded\ndiff --git a/src/main.rs"
+    ));
+    assert!(folded.ends_with("+fn run() {}\n"));
+}
+
+#[test]
+fn test_app_file_navigation() {
+    let mut app = App::new(vec![]);
+    app.set_mode(ViewMode::Diff);
+
+    app.next_file(MULTI_FILE_DIFF);
+    assert_eq!(app.scroll_offset, 7);
+
+    app.next_file(MULTI_FILE_DIFF);
+    assert_eq!(app.scroll_offset, 7); // Should stay at last file
+
+    app.previous_file(MULTI_FILE_DIFF);
+    assert_eq!(app.scroll_offset, 0);
+
+    app.scroll_offset = 8;
+    app.toggle_fold(MULTI_FILE_DIFF);
+    assert!(app.collapsed_files.contains("src/main.rs"));
+
+    app.toggle_all_folds(MULTI_FILE_DIFF);
+    assert!(app.collapsed_files.is_empty());
+
+    app.toggle_all_folds(MULTI_FILE_DIFF);
+    assert_eq!(app.collapsed_files.len(), 2);
+}
+
+#[test]
+fn test_app_error_navigation() {
+    use super::content::{STDERR_MARKER, is_error_line};
+
+    assert!(is_error_line("error[E0308]: mismatched types"));
+    assert!(is_error_line("thread 'main' panicked at src/main.rs:2:5"));
+    assert!(is_error_line("Traceback (most recent call last):"));
+    assert!(is_error_line(&format!("{STDERR_MARKER}warning")));
+    assert!(!is_error_line("Compiling parari"));
+
+    let log = "start\nError: first\nok\nok\npanic: second\nend";
+    let mut app = App::new(vec![]);
+    app.next_error(log);
+    assert_eq!(app.scroll_offset, 1);
+    app.next_error(log);
+    assert_eq!(app.scroll_offset, 4);
+    app.next_error(log);
+    assert_eq!(app.scroll_offset, 4); // Should stay at the last error
+    app.previous_error(log);
+    assert_eq!(app.scroll_offset, 1);
+
+    // Only the log view has error lines
+    app.set_mode(ViewMode::Diff);
+    app.next_error(log);
+    assert_eq!(app.scroll_offset, 0);
+}
+
+#[test]
+fn test_app_goto_line() {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = App::new(vec![]);
+    app.focused_panel = FocusedPanel::Details;
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    app.handle_event(&key(KeyCode::Char(':')), 20, "");
+    assert_eq!(app.input_mode, InputMode::GotoLine);
+    for c in "4x2".chars() {
+        app.handle_event(&key(KeyCode::Char(c)), 20, "");
+    }
+    assert_eq!(app.goto_input, "42");
+    app.handle_event(&key(KeyCode::Enter), 20, "");
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert_eq!(app.scroll_offset, 41);
+
+    app.handle_event(&key(KeyCode::Char('#')), 20, "");
+    assert!(app.line_numbers);
+}
+
+#[test]
+fn test_app_reject_and_discard() {
+    let info = |name: &str| ResultInfo { ..test_info(name) };
+    let mut app = App::new(vec![info("claude"), info("gemini"), info("codex")]);
+
+    // Nothing to discard until a candidate is rejected
+    app.start_confirm_discard();
+    assert_eq!(app.input_mode, InputMode::Normal);
+
+    app.toggle_rejected();
+    app.next_model();
+    app.next_model();
+    app.toggle_rejected();
+    assert_eq!(app.rejected, HashSet::from([0, 2]));
+
+    app.start_confirm_discard();
+    assert_eq!(app.input_mode, InputMode::ConfirmDiscard);
+    app.confirm_discard();
+    assert_eq!(app.pending_discards, vec![0, 2]);
+
+    // Deleted candidates can no longer be applied
+    app.discarded.insert(2);
+    app.start_confirm();
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert!(app.status_message.is_some());
+
+    app.toggle_rejected();
+    assert_eq!(app.rejected, HashSet::from([0]));
+}
+
+#[test]
+fn test_app_large_diff_needs_extra_confirmation() {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let info = |name: &str, files_changed: usize| ResultInfo {
+        files_changed,
+        ..test_info(name)
+    };
+    let mut app = App::new(vec![info("claude", 120), info("gemini", 3)]);
+    app.large_diff_files = 50;
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    app.start_confirm();
+    assert_eq!(app.input_mode, InputMode::ConfirmLargeDiff);
+    app.handle_event(&key(KeyCode::Esc), 20, "");
+    assert_eq!(app.input_mode, InputMode::Normal);
+
+    app.start_confirm();
+    app.handle_event(&key(KeyCode::Char('y')), 20, "");
+    assert_eq!(app.input_mode, InputMode::Confirm);
+    assert!(app.result.is_none());
+
+    app.cancel_confirm();
+    app.next_model();
+    app.start_confirm();
+    assert_eq!(app.input_mode, InputMode::Confirm);
+
+    // A limit of 0 turns the check off
+    app.large_diff_files = 0;
+    assert!(!app.is_large_diff(&info("claude", 120)));
+}
+
+#[test]
+fn test_app_secrets_need_extra_confirmation() {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = App::new(vec![ResultInfo {
+        secrets: vec![SecretFinding {
+            file: ".env".to_string(),
+            line: 3,
+            kind: "AWS access key",
+        }],
+        files_changed: 120,
+        ..test_info("codex")
+    }]);
+    app.large_diff_files = 50;
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    app.start_confirm();
+    assert_eq!(app.input_mode, InputMode::ConfirmSecrets);
+    app.handle_event(&key(KeyCode::Char('n')), 20, "");
+    assert_eq!(app.input_mode, InputMode::Normal);
+
+    // Each warning is confirmed on its own before the final confirmation
+    app.start_confirm();
+    app.handle_event(&key(KeyCode::Char('y')), 20, "");
+    assert_eq!(app.input_mode, InputMode::ConfirmLargeDiff);
+    app.handle_event(&key(KeyCode::Char('y')), 20, "");
+    assert_eq!(app.input_mode, InputMode::Confirm);
+    assert!(app.result.is_none());
+}
+
+#[test]
+fn test_app_notes() {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = App::new(vec![ResultInfo {
+        ..test_info("claude")
+    }]);
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    app.handle_event(&key(KeyCode::Char('c')), 20, "");
+    assert_eq!(app.input_mode, InputMode::Note);
+    for c in "broke API".chars() {
+        app.handle_event(&key(KeyCode::Char(c)), 20, "");
+    }
+    app.handle_event(&key(KeyCode::Enter), 20, "");
+    assert_eq!(app.notes.get(&0).map(String::as_str), Some("broke API"));
+
+    // Editing starts from the existing note; clearing it removes the note
+    app.start_note();
+    assert_eq!(app.note_input, "broke API");
+    app.note_input.clear();
+    app.save_note();
+    assert!(app.notes.is_empty());
+}
+
+#[test]
+fn test_app_apply_stashed_needs_conflicts() {
+    let info = |name: &str, conflicts: Vec<String>| ResultInfo {
+        conflicts,
+        ..test_info(name)
+    };
+    let mut app = App::new(vec![
+        info("claude", vec![]),
+        info("gemini", vec!["src/lib.rs".to_string()]),
+    ]);
+
+    assert!(!app.apply_stashed());
+    assert!(app.result.is_none());
+
+    app.next_model();
+    assert!(app.apply_stashed());
+    assert!(matches!(app.result, Some(SplitViewResult::ApplyStashed(1))));
+}
+
+#[test]
+fn test_discard_worktree_removes_directory() -> Result<(), Box<dyn std::error::Error>> {
+    use super::external::discard_worktree;
+
+    let repo = tempfile::tempdir()?;
+    let worktree = repo.path().join("worktree");
+    std::fs::create_dir_all(worktree.join("src"))?;
+    discard_worktree(repo.path(), &worktree)?;
+    assert!(!worktree.exists());
+    Ok(())
+}
+
+#[test]
+fn test_describe_binary_changes() {
+    use super::content::describe_binary_changes;
+
+    let diff = "diff --git a/logo.png b/logo.png\nindex 1111111..2222222 100644\nBinary files a/logo.png and b/logo.png differ\n";
+    let content = describe_binary_changes(diff, |path| {
+        assert_eq!(path, "logo.png");
+        (Some(2048), Some(512))
+    });
+    assert_eq!(
+        content,
+        "diff --git a/logo.png b/logo.png\nindex 1111111..2222222 100644\nBinary file changed (size 2.0 KB \u{2192} 512 B)\n"
+    );
+    assert_eq!(
+        describe_binary_changes(MULTI_FILE_DIFF, |_| (None, None)),
+        MULTI_FILE_DIFF
+    );
+}
+
+#[test]
+fn test_side_by_side_rows() {
+    use super::side_by_side::{Row, build_rows};
+
+    let rows = build_rows(MULTI_FILE_DIFF);
+    assert_eq!(rows[0], Row::Header("diff --git a/src/lib.rs b/src/lib.rs"));
+    assert_eq!(rows[4], Row::Header("@@ -1 +1 @@"));
+    assert_eq!(rows[5], Row::Change(Some("old"), Some("new")));
+    assert_eq!(rows[11], Row::Context("fn main() {}"));
+    assert_eq!(rows[12], Row::Change(None, Some("fn run() {}")));
+    assert_eq!(rows.len(), 13);
+}
+
+#[test]
+fn test_delta_keeps_one_row_per_line() {
+    use super::delta::{body_lines, merge_rendered};
+    use ratatui::text::Text;
+
+    let lines: Vec<&str> = MULTI_FILE_DIFF.lines().collect();
+    let body = body_lines(&lines);
+    assert_eq!(
+        body,
+        [
+            false, false, false, false, false, true, true, false, false, false, false, false, true,
+            true
+        ]
+    );
+
+    let rendered = Text::from("old\nnew\nfn main() {}\nfn run() {}");
+    let text = merge_rendered(&lines, &body, rendered);
+    assert_eq!(
+        text.as_ref().map(|text| text.lines.len()),
+        Some(lines.len())
+    );
+    assert_eq!(
+        text.map(|text| text.lines[12].to_string()),
+        Some("fn main() {}".to_string())
+    );
+
+    // Decorations delta added anyway would shift the rows, so they are not used
+    let decorated = Text::from("src/lib.rs\nold\nnew\nfn main() {}\nfn run() {}");
+    assert!(merge_rendered(&lines, &body, decorated).is_none());
+}
+
+#[test]
+fn test_delta_chunk_context() {
+    use super::delta::chunk_context;
+
+    let lines: Vec<&str> = MULTI_FILE_DIFF.lines().collect();
+    let before = |count: usize| lines[..count].join("\n");
+
+    assert!(chunk_context("").is_empty());
+    assert_eq!(
+        chunk_context(&before(6)),
+        [lines[0], lines[2], lines[3], lines[4]]
+    );
+    // The headers of the first file no longer apply once the second one starts
+    assert_eq!(
+        chunk_context(&before(13)),
+        [lines[7], lines[9], lines[10], lines[11]]
+    );
+}
+
+#[test]
+fn test_side_by_side_jumps_to_rows() {
+    use super::side_by_side::row_lines;
+
+    let mut app = App::new(vec![]);
+    app.current_mode = ViewMode::Diff;
+    app.side_by_side = true;
+    app.side_by_side_lines = row_lines(MULTI_FILE_DIFF);
+    // "-old" and "+new" share a row, so every later row is one line ahead
+    assert_eq!(app.side_by_side_lines[..7], [0, 1, 2, 3, 4, 5, 7]);
+
+    app.next_file(MULTI_FILE_DIFF);
+    assert_eq!(app.scroll_offset, 6);
+    assert_eq!(app.top_line(), 7);
+    app.previous_file(MULTI_FILE_DIFF);
+    assert_eq!(app.scroll_offset, 0);
+
+    app.goto_input = "13".to_string();
+    app.execute_goto_line();
+    assert_eq!(app.scroll_offset, 11);
+
+    // The added line of a pair is shown on the row of the removed one
+    app.scroll_to_line(6);
+    assert_eq!(app.scroll_offset, 5);
+}
+
+#[test]
+fn test_side_by_side_common_affixes() {
+    use super::side_by_side::common_affixes;
+
+    assert_eq!(common_affixes("let x = 1;", "let x = 2;"), (8, 1));
+    assert_eq!(common_affixes("abc", "abc"), (3, 0));
+    assert_eq!(common_affixes("aa", "aaa"), (2, 0));
+    assert_eq!(common_affixes("", "new"), (0, 0));
+}
+
+#[test]
+fn test_render_side_by_side_width() {
+    use super::side_by_side::render_side_by_side;
+
+    let text = render_side_by_side(MULTI_FILE_DIFF, 23);
+    // "old" and "new" are padded to 10 columns each, plus the 3-column separator
+    assert_eq!(text.lines[5].width(), 23);
+}
+
+#[test]
+fn test_render_side_by_side_wide_characters() {
+    use super::side_by_side::render_side_by_side;
+
+    let diff = "@@ -1 +1 @@\n-\u{65E5}\u{672C}\u{8A9E}\u{306E}\u{6587}\n+\u{1F600} emoji\n";
+    let text = render_side_by_side(diff, 23);
+    // Wide characters take two cells, so the row still fits exactly
+    assert_eq!(text.lines[1].width(), 23);
+}
+
+#[test]
+fn test_log_keeps_agent_colors() {
+    use super::content::style_log_line;
+    use ratatui::style::Color;
+
+    let info = ResultInfo {
+        output_lines: vec![OutputLine::Stdout("\x1b[32mok\x1b[0m".to_string())],
+        ..test_info("claude")
+    };
+    let plain = get_log_content_string(&info, &HashSet::new(), OutputFilter::All, false);
+    assert!(!plain.contains('\x1b'));
+
+    let colored = get_log_content_string(&info, &HashSet::new(), OutputFilter::All, true);
+    let line = colored
+        .lines()
+        .find(|line| line.contains("ok"))
+        .map(style_log_line)
+        .unwrap_or_default();
+    assert_eq!(line.spans[0].content, "ok");
+    assert_eq!(line.spans[0].style.fg, Some(Color::Green));
+}
+
+#[test]
+fn test_highlighter_colors_code_by_extension() {
+    use super::highlight::Highlighter;
+    use ratatui::style::Color;
+
+    let highlighter = Highlighter::new();
+    let text = highlighter.highlight(MULTI_FILE_DIFF);
+    assert_eq!(text.lines.len(), MULTI_FILE_DIFF.lines().count());
+
+    // Code lines are split into syntax-colored spans after the diff marker
+    let added = text
+        .lines
+        .iter()
+        .find(|line| line.spans.len() > 1 && line.spans[0].content == "+")
+        .map(|line| line.spans[1].style.fg);
+    assert!(matches!(added, Some(Some(Color::Rgb(..)))));
+}
+
+#[test]
+fn test_ansi_to_text_styles() {
+    use super::ansi::ansi_to_text;
+    use ratatui::style::{Color, Modifier};
+
+    let text = ansi_to_text("\x1b[1;32m+added\x1b[0m tail\n\x1b[38;2;10;20;30mrgb\x1b[39m\n");
+    assert_eq!(text.lines.len(), 2);
+
+    let first = &text.lines[0];
+    assert_eq!(first.spans[0].content, "+added");
+    assert_eq!(first.spans[0].style.fg, Some(Color::Green));
+    assert!(first.spans[0].style.add_modifier.contains(Modifier::BOLD));
+    assert_eq!(first.spans[1].content, " tail");
+    assert_eq!(first.spans[1].style.fg, None);
+
+    assert_eq!(
+        text.lines[1].spans[0].style.fg,
+        Some(Color::Rgb(10, 20, 30))
+    );
+}
+
+#[test]
+fn test_ansi_to_text_drops_other_sequences() {
+    use super::ansi::ansi_to_text;
+
+    let text = ansi_to_text("\x1b]8;;file:///a\x1b\\link\x1b]8;;\x1b\\\x1b[2Kdone");
+    let line: String = text.lines[0]
+        .spans
+        .iter()
+        .map(|s| s.content.as_ref())
+        .collect();
+    assert_eq!(line, "linkdone");
+}
+
+#[test]
+fn test_app_request_external() {
+    use super::types::ExternalAction;
+
+    let mut app = App::new(vec![]);
+    app.request_external(ExternalAction::Pager);
+    assert_eq!(app.pending_action, None);
+
+    let mut app = App::new(vec![ResultInfo {
+        ..test_info("claude")
+    }]);
+    app.request_external(ExternalAction::Difftool);
+    assert_eq!(app.pending_action, Some(ExternalAction::Difftool));
+
+    app.request_external(ExternalAction::Shell);
+    assert_eq!(app.pending_action, Some(ExternalAction::Shell));
+}
+
+#[test]
+fn test_yank_text() {
+    use super::clipboard::yank_text;
+    use super::types::YankTarget;
+
+    let info = ResultInfo {
+        output_lines: vec![
+            OutputLine::Stdout("\x1b[32mdone\x1b[0m".to_string()),
+            OutputLine::Stderr("warning".to_string()),
+        ],
+        ..test_info("claude")
+    };
+    assert_eq!(yank_text(&info, YankTarget::Path), "/tmp/test1");
+    assert_eq!(yank_text(&info, YankTarget::Log), "done\nwarning");
+}
+
+#[test]
+fn test_app_yank_mode() {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let mut app = App::new(vec![]);
+    let press = |c| Event::Key(KeyEvent::new(KeyCode::Char(c), KeyModifiers::NONE));
+
+    app.handle_event(&press('y'), 20, "");
+    assert_eq!(app.input_mode, InputMode::Yank);
+
+    // Unknown targets cancel the yank
+    app.handle_event(&press('q'), 20, "");
+    assert_eq!(app.input_mode, InputMode::Normal);
+
+    app.status_message = Some("Copied".to_string());
+    app.handle_event(&press('j'), 20, "");
+    assert_eq!(app.status_message, None);
+}
+
+#[test]
+fn test_app_horizontal_scroll() {
+    let mut app = App::new(vec![]);
+
+    // Horizontal scrolling only applies while wrapping is off
+    app.scroll_right();
+    assert_eq!(app.horizontal_scroll, None);
+
+    app.toggle_wrap();
+    assert_eq!(app.horizontal_scroll, Some(0));
+    app.scroll_right();
+    app.scroll_right();
+    assert_eq!(app.horizontal_scroll, Some(8));
+    app.scroll_left();
+    assert_eq!(app.horizontal_scroll, Some(4));
+
+    // Switching modes returns to the first column
+    app.set_mode(ViewMode::Diff);
+    assert_eq!(app.horizontal_scroll, Some(0));
+
+    app.toggle_wrap();
+    assert_eq!(app.horizontal_scroll, None);
+}
+
+#[test]
+fn test_app_resize_models_panel() {
+    use super::app::{MIN_MODELS_PANEL_WIDTH, MODELS_PANEL_WIDTH};
+
+    let mut app = App::new(vec![]);
+    assert_eq!(app.models_width, MODELS_PANEL_WIDTH);
+
+    app.widen_models_panel();
+    assert_eq!(app.models_width, MODELS_PANEL_WIDTH + 4);
+
+    for _ in 0..20 {
+        app.narrow_models_panel();
+    }
+    assert_eq!(app.models_width, MIN_MODELS_PANEL_WIDTH);
+}
+
+#[test]
+fn test_theme_color() {
+    use super::theme::theme_color;
+    use crate::config::Theme;
+    use ratatui::style::Color;
+
+    assert_eq!(theme_color(Theme::Default, Color::Cyan), Color::Cyan);
+    assert_eq!(
+        theme_color(Theme::HighContrast, Color::Cyan),
+        Color::LightCyan
+    );
+    assert_eq!(
+        theme_color(Theme::HighContrast, Color::DarkGray),
+        Color::Gray
+    );
+    assert_eq!(theme_color(Theme::NoColor, Color::Green), Color::Reset);
+}
+
+#[test]
+fn test_apply_no_color_theme() {
+    use super::theme::apply_theme;
+    use crate::config::Theme;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::{Color, Modifier, Style};
+
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 2, 1));
+    buffer.set_string(0, 0, "a", Style::new().fg(Color::Red));
+    buffer.set_string(1, 0, "b", Style::new().fg(Color::Black).bg(Color::Cyan));
+    apply_theme(Theme::NoColor, &mut buffer);
+
+    assert_eq!(buffer[(0, 0)].fg, Color::Reset);
+    assert!(!buffer[(0, 0)].modifier.contains(Modifier::REVERSED));
+    // Highlighted cells keep their emphasis through reverse video
+    assert_eq!(buffer[(1, 0)].bg, Color::Reset);
+    assert!(buffer[(1, 0)].modifier.contains(Modifier::REVERSED));
+}
+
+#[test]
+fn test_apply_ascii_glyphs() {
+    use super::theme::apply_ascii_glyphs;
+    use ratatui::buffer::Buffer;
+    use ratatui::layout::Rect;
+    use ratatui::style::Style;
+
+    let mut buffer = Buffer::empty(Rect::new(0, 0, 6, 1));
+    buffer.set_string(0, 0, "\u{25B6} \u{1F916}a\u{2212}", Style::new());
+    apply_ascii_glyphs(&mut buffer);
+
+    let row: String = (0..6).map(|x| buffer[(x, 0)].symbol()).collect();
+    assert_eq!(row, "> C a-");
+}
+
+#[test]
+fn test_prefix_len() {
+    use super::content::prefix_len;
+
+    let content = "one\ntwo\nthree\n";
+    assert_eq!(prefix_len(content, 0), 0);
+    assert_eq!(&content[..prefix_len(content, 2)], "one\ntwo\n");
+    assert_eq!(prefix_len(content, 10), content.len());
+}
+
+#[test]
+fn test_diff_cache_loads_in_background() -> Result<(), Box<dyn std::error::Error>> {
+    use super::diff_cache::DiffCache;
+    use std::time::{Duration, Instant};
+
+    let dir = tempfile::tempdir()?;
+    let mut cache = DiffCache::new();
+    cache.request(dir.path());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while !cache.poll() {
+        assert!(Instant::now() < deadline, "diff was never computed");
+        std::thread::sleep(Duration::from_millis(10));
+    }
+
+    // Not a git repository, so there is nothing to show
+    assert_eq!(cache.get(dir.path()), Some("No changes detected."));
+    Ok(())
+}
+
+#[test]
+fn test_diff_cache_invalidate() -> Result<(), Box<dyn std::error::Error>> {
+    use super::diff_cache::DiffCache;
+    use crate::testing::TestRepo;
+    use std::time::{Duration, Instant};
+
+    let repo = TestRepo::new()?;
+    let mut cache = DiffCache::new();
+    assert_eq!(cache.get_or_load(repo.path()), "No changes detected.");
+
+    // An external program edits the worktree, so the cached diff is out of date
+    repo.write("README.md", "# Edited\n")?;
+    cache.invalidate(repo.path());
+    assert_eq!(cache.get(repo.path()), None);
+    cache.request(repo.path());
+
+    let deadline = Instant::now() + Duration::from_secs(10);
+    while cache.get(repo.path()).is_none() {
+        assert!(Instant::now() < deadline, "diff was never computed");
+        cache.poll();
+        std::thread::sleep(Duration::from_millis(10));
+    }
+    assert!(
+        cache
+            .get(repo.path())
+            .is_some_and(|diff| diff.contains("+# Edited"))
+    );
+    Ok(())
+}
+
+#[test]
+fn test_app_global_search() -> Result<(), Box<dyn std::error::Error>> {
+    use ratatui::crossterm::event::{Event, KeyCode, KeyEvent, KeyModifiers};
+
+    let dir = tempfile::tempdir()?;
+    let result = |name: &str, lines: Vec<OutputLine>| ResultInfo {
+        output_lines: lines,
+        worktree_path: dir.path().to_path_buf(),
+        ..test_info(name)
+    };
+    let mut app = App::new(vec![
+        result(
+            "claude",
+            vec![OutputLine::Stdout("nothing here".to_string())],
+        ),
+        result(
+            "gemini",
+            vec![
+                OutputLine::Stdout("first".to_string()),
+                OutputLine::Stderr("Needle in stderr".to_string()),
+            ],
+        ),
+    ]);
+    let key = |code| Event::Key(KeyEvent::new(code, KeyModifiers::NONE));
+
+    app.handle_event(&key(KeyCode::Char('F')), 20, "");
+    assert_eq!(app.input_mode, InputMode::GlobalSearch);
+    for c in "needle".chars() {
+        app.handle_event(&key(KeyCode::Char(c)), 20, "");
+    }
+    app.handle_event(&key(KeyCode::Enter), 20, "");
+    assert_eq!(app.input_mode, InputMode::GlobalResults);
+    assert_eq!(app.global_matches.len(), 1);
+    let found = app.global_matches[0].clone();
+    assert_eq!(found.result_index, 1);
+    assert_eq!(found.mode, ViewMode::Log);
+    assert_eq!(found.text, "Needle in stderr");
+
+    app.handle_event(&key(KeyCode::Enter), 20, "");
+    assert_eq!(app.input_mode, InputMode::Normal);
+    assert_eq!(app.selected_index(), 1);
+    assert_eq!(app.focused_panel, FocusedPanel::Details);
+    assert_eq!(usize::from(app.scroll_offset), found.line);
+    Ok(())
+}
+
+#[test]
+fn test_log_output_filter() {
+    use super::content::stderr_line_count;
+
+    let info = ResultInfo {
+        success: false,
+        output_lines: vec![
+            OutputLine::Stdout("working".to_string()),
+            OutputLine::Stderr("broken".to_string()),
+        ],
+        ..test_info("claude")
+    };
+    assert_eq!(stderr_line_count(&info), 1);
+
+    let all = get_log_content_string(&info, &HashSet::new(), OutputFilter::All, false);
+    assert!(all.contains("working") && all.contains("broken"));
+
+    let stderr = get_log_content_string(&info, &HashSet::new(), OutputFilter::Stderr, false);
+    assert!(!stderr.contains("working") && stderr.contains("broken"));
+
+    let stdout = get_log_content_string(&info, &HashSet::new(), OutputFilter::Stdout, false);
+    assert!(stdout.contains("working") && !stdout.contains("broken"));
+
+    let mut scored = info.clone();
+    scored.score = Some(Score {
+        passed: true,
+        value: Some(0.9),
+        output: String::new(),
+    });
+    scored.duplicate_of = Some("codex".to_string());
+    scored.similarity = vec![("codex".to_string(), 1.0), ("gemini".to_string(), 0.25)];
+    scored.retries = vec!["API Error: 529 overloaded".to_string()];
+    let log = get_log_content_string(&scored, &HashSet::new(), OutputFilter::All, false);
+    assert!(log.contains("Score: 0.9 (passed)"));
+    assert!(log.contains("Attempt 1 failed: API Error: 529 overloaded"));
+    assert!(log.contains("Same changes as: codex"));
+    assert!(log.contains("Similarity: codex 100%, gemini 25%"));
+
+    let mut app = App::new(vec![info]);
+    app.set_mode(ViewMode::Diff);
+    app.cycle_output_filter();
+    assert_eq!(app.current_mode, ViewMode::Log);
+    assert_eq!(app.output_filter, OutputFilter::Stderr);
+    app.cycle_output_filter();
+    assert_eq!(app.output_filter, OutputFilter::Stdout);
+    app.cycle_output_filter();
+    assert_eq!(app.output_filter, OutputFilter::All);
+}
+
+#[test]
+fn test_app_toggle_side_by_side() {
+    let mut app = App::new(vec![]);
+    app.scroll_offset = 5;
+    app.toggle_side_by_side();
+    assert!(app.side_by_side);
+    assert_eq!(app.scroll_offset, 0);
+}
+
 #[test]
 fn test_app_scrolling() {
     let mut app = App::new(vec![]);
@@ -148,67 +1144,126 @@ fn test_app_search() {
 
 mod snapshot_tests {
     use super::*;
-    use crate::git::ChangeSummary;
+    use crate::cli::split_view::driver::Driver;
+    use crate::git::{ChangeSummary, FileChange, FileStatus};
     use insta::assert_snapshot;
     use ratatui::{Terminal, backend::TestBackend};
     use std::path::PathBuf;
 
+    /// A result of `executor_name` with the given output and nothing else
+    fn test_result_info(
+        executor_name: &str,
+        success: bool,
+        output_lines: Vec<OutputLine>,
+    ) -> ResultInfo {
+        let text = |stderr: bool| {
+            output_lines
+                .iter()
+                .filter_map(|line| match line {
+                    OutputLine::Stderr(line) if stderr => Some(line.as_str()),
+                    OutputLine::Stdout(line) if !stderr => Some(line.as_str()),
+                    _ => None,
+                })
+                .collect::<Vec<_>>()
+                .join("\n")
+        };
+        ResultInfo {
+            success,
+            stdout: text(false),
+            stderr: text(true),
+            output_lines,
+            worktree_path: PathBuf::from(format!("/tmp/worktree-{executor_name}")),
+            ..test_info(executor_name)
+        }
+    }
+
     /// Create test result infos for snapshot tests
     fn create_test_result_infos() -> Vec<ResultInfo> {
         vec![
             ResultInfo {
-                executor_name: "claude".to_string(),
-                success: true,
-                stdout: "Analyzing the code...\nMade changes to src/main.rs".to_string(),
-                stderr: String::new(),
-                output_lines: vec![
-                    OutputLine::Stdout("Analyzing the code...".to_string()),
-                    OutputLine::Stdout("Made changes to src/main.rs".to_string()),
-                ],
                 files_changed: 2,
-                worktree_path: PathBuf::from("/tmp/worktree-claude"),
+                summary: Some("Added a greeting to main".to_string()),
                 change_summary: Some(ChangeSummary {
                     files_added: 1,
                     files_modified: 1,
                     files_deleted: 0,
-                    changed_files: vec!["src/main.rs".to_string(), "src/lib.rs".to_string()],
+                    insertions: 12,
+                    deletions: 3,
+                    changed_files: vec![
+                        FileChange {
+                            path: "src/main.rs".to_string(),
+                            status: FileStatus::Modified,
+                            insertions: 2,
+                            deletions: 3,
+                            binary: false,
+                        },
+                        FileChange {
+                            path: "src/lib.rs".to_string(),
+                            status: FileStatus::Added,
+                            insertions: 10,
+                            deletions: 0,
+                            binary: false,
+                        },
+                    ],
                 }),
+                ..test_result_info(
+                    "claude",
+                    true,
+                    vec![
+                        OutputLine::Stdout("Analyzing the code...".to_string()),
+                        OutputLine::Stdout("Made changes to src/main.rs".to_string()),
+                    ],
+                )
             },
             ResultInfo {
-                executor_name: "gemini".to_string(),
-                success: true,
-                stdout: "Processing request...\nUpdated 3 files".to_string(),
-                stderr: String::new(),
-                output_lines: vec![
-                    OutputLine::Stdout("Processing request...".to_string()),
-                    OutputLine::Stdout("Updated 3 files".to_string()),
-                ],
                 files_changed: 3,
-                worktree_path: PathBuf::from("/tmp/worktree-gemini"),
                 change_summary: Some(ChangeSummary {
                     files_added: 2,
                     files_modified: 1,
                     files_deleted: 0,
+                    insertions: 40,
+                    deletions: 0,
                     changed_files: vec![
-                        "src/main.rs".to_string(),
-                        "src/utils.rs".to_string(),
-                        "tests/test.rs".to_string(),
+                        FileChange {
+                            path: "src/main.rs".to_string(),
+                            status: FileStatus::Modified,
+                            insertions: 5,
+                            deletions: 0,
+                            binary: false,
+                        },
+                        FileChange {
+                            path: "src/utils.rs".to_string(),
+                            status: FileStatus::Added,
+                            insertions: 20,
+                            deletions: 0,
+                            binary: false,
+                        },
+                        FileChange {
+                            path: "tests/test.rs".to_string(),
+                            status: FileStatus::Added,
+                            insertions: 15,
+                            deletions: 0,
+                            binary: false,
+                        },
                     ],
                 }),
+                ..test_result_info(
+                    "gemini",
+                    true,
+                    vec![
+                        OutputLine::Stdout("Processing request...".to_string()),
+                        OutputLine::Stdout("Updated 3 files".to_string()),
+                    ],
+                )
             },
-            ResultInfo {
-                executor_name: "codex".to_string(),
-                success: false,
-                stdout: "Starting task...".to_string(),
-                stderr: "Error: Something went wrong".to_string(),
-                output_lines: vec![
+            test_result_info(
+                "codex",
+                false,
+                vec![
                     OutputLine::Stdout("Starting task...".to_string()),
                     OutputLine::Stderr("Error: Something went wrong".to_string()),
                 ],
-                files_changed: 0,
-                worktree_path: PathBuf::from("/tmp/worktree-codex"),
-                change_summary: None,
-            },
+            ),
         ]
     }
 
@@ -218,7 +1273,7 @@ mod snapshot_tests {
         let mut app = App::new(result_infos);
         let content = app
             .selected_info()
-            .map(get_log_content_string)
+            .map(|info| get_log_content_string(info, &HashSet::new(), OutputFilter::All, false))
             .unwrap_or_default();
 
         let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
@@ -250,7 +1305,7 @@ mod snapshot_tests {
         app.toggle_focus(); // Focus on details panel
         let content = app
             .selected_info()
-            .map(get_log_content_string)
+            .map(|info| get_log_content_string(info, &HashSet::new(), OutputFilter::All, false))
             .unwrap_or_default();
 
         let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
@@ -268,7 +1323,7 @@ mod snapshot_tests {
         app.search_query = "code".to_string();
         let content = app
             .selected_info()
-            .map(get_log_content_string)
+            .map(|info| get_log_content_string(info, &HashSet::new(), OutputFilter::All, false))
             .unwrap_or_default();
 
         let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
@@ -285,7 +1340,7 @@ mod snapshot_tests {
         app.next_model(); // Select gemini
         let content = app
             .selected_info()
-            .map(get_log_content_string)
+            .map(|info| get_log_content_string(info, &HashSet::new(), OutputFilter::All, false))
             .unwrap_or_default();
 
         let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
@@ -303,7 +1358,7 @@ mod snapshot_tests {
         app.next_model(); // Select codex (failed)
         let content = app
             .selected_info()
-            .map(get_log_content_string)
+            .map(|info| get_log_content_string(info, &HashSet::new(), OutputFilter::All, false))
             .unwrap_or_default();
 
         let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
@@ -332,7 +1387,7 @@ mod snapshot_tests {
         app.input_mode = InputMode::Confirm;
         let content = app
             .selected_info()
-            .map(get_log_content_string)
+            .map(|info| get_log_content_string(info, &HashSet::new(), OutputFilter::All, false))
             .unwrap_or_default();
 
         let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
@@ -341,4 +1396,234 @@ mod snapshot_tests {
         assert_snapshot!(terminal.backend());
         Ok(())
     }
+
+    /// Buffer rows of a rendered frame as plain strings
+    fn buffer_rows(terminal: &Terminal<TestBackend>) -> Vec<String> {
+        let buffer = terminal.backend().buffer();
+        (0..buffer.area.height)
+            .map(|y| {
+                (0..buffer.area.width)
+                    .map(|x| buffer[(x, y)].symbol())
+                    .collect()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn test_render_narrow_terminal_stacks_panels() -> Result<(), Box<dyn std::error::Error>> {
+        let mut app = App::new(create_test_result_infos());
+        let mut terminal = Terminal::new(TestBackend::new(60, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, "content"))?;
+
+        // The models panel spans the full width above the details panel
+        let rows = buffer_rows(&terminal);
+        assert!(rows[0].contains("Models"));
+        let details_row = rows
+            .iter()
+            .position(|row| row.contains(" Log "))
+            .ok_or("details panel not found")?;
+        assert!(details_row > 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_line_numbers() -> Result<(), Box<dyn std::error::Error>> {
+        use std::fmt::Write;
+
+        let mut app = App::new(create_test_result_infos());
+        app.toggle_line_numbers();
+        let content = (1..=12).fold(String::new(), |mut content, i| {
+            let _ = writeln!(content, "line {i}");
+            content
+        });
+        let mut terminal = Terminal::new(TestBackend::new(120, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, &content))?;
+
+        let rows = buffer_rows(&terminal);
+        assert!(rows[1].contains(" 1 line 1 "));
+        assert!(rows[12].contains("12 line 12"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_run_info_popup() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::domain::RunInfo;
+
+        let mut app = App::new(create_test_result_infos());
+        app.show_run_info();
+        assert_eq!(app.input_mode, InputMode::Normal); // Nothing to show yet
+
+        app.run_info = Some(
+            RunInfo::new("Add a --verbose flag", "/tmp/repo")
+                .with_base_commit(Some("abc1234".to_string())),
+        );
+        app.show_run_info();
+        assert_eq!(app.input_mode, InputMode::RunInfo);
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, "content"))?;
+        let rows = buffer_rows(&terminal);
+        assert!(rows.iter().any(|row| row.contains("Add a --verbose flag")));
+        assert!(rows.iter().any(|row| row.contains("Base       abc1234")));
+        assert!(rows.iter().any(|row| row.contains("Directory  /tmp/repo")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_comparison_popup() -> Result<(), Box<dyn std::error::Error>> {
+        use crate::cli::split_view::compare::Comparer;
+        use crate::executor::MockExecutor;
+        use std::sync::Arc;
+        use std::time::{Duration, Instant};
+
+        let mut app = App::new(create_test_result_infos());
+        app.mark_for_comparison();
+        assert_eq!(
+            app.status_message.as_deref(),
+            Some("No agent is available to compare candidates")
+        );
+
+        app.comparer = Some(Comparer::new(Arc::new(MockExecutor::new("reviewer"))));
+        app.mark_for_comparison();
+        assert_eq!(app.compare_mark, Some(0));
+        app.next_model();
+        app.mark_for_comparison();
+        assert_eq!(app.input_mode, InputMode::Compare);
+        assert_eq!(app.comparison, Some((0, 1)));
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, "content"))?;
+        let rows = buffer_rows(&terminal);
+        assert!(rows.iter().any(|row| row.contains(" claude vs gemini ")));
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("reviewer is comparing the changes..."))
+        );
+
+        let deadline = Instant::now() + Duration::from_secs(10);
+        while app.comparer.as_mut().is_some_and(|comparer| {
+            comparer.poll();
+            comparer.get((0, 1)).is_none()
+        }) {
+            assert!(Instant::now() < deadline, "comparison never arrived");
+            std::thread::sleep(Duration::from_millis(10));
+        }
+
+        // The worktrees are not repositories, so there is nothing to compare
+        terminal.draw(|frame| render(frame, &mut app, "content"))?;
+        let rows = buffer_rows(&terminal);
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("reviewer could not compare the changes"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_confirm_lists_local_conflicts() -> Result<(), Box<dyn std::error::Error>> {
+        let mut infos = create_test_result_infos();
+        infos[0].conflicts = vec!["src/lib.rs".to_string(), "README.md".to_string()];
+        let mut app = App::new(infos);
+        app.start_confirm();
+
+        let mut terminal = Terminal::new(TestBackend::new(120, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, "content"))?;
+        let rows = buffer_rows(&terminal);
+        assert!(
+            rows.iter()
+                .any(|row| row.contains("2 file(s) with uncommitted"))
+        );
+        assert!(rows.iter().any(|row| row.contains("src/lib.rs")));
+        assert!(rows.iter().any(|row| row.contains("README.md")));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_compact_models_panel() -> Result<(), Box<dyn std::error::Error>> {
+        let mut app = App::new(create_test_result_infos());
+        let mut terminal = Terminal::new(TestBackend::new(90, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, "content"))?;
+
+        // Only icons fit, so agent names are not shown
+        let rows = buffer_rows(&terminal);
+        assert!(!rows.iter().any(|row| row.contains("claude")));
+        assert!(rows[0].contains("Log"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_render_clamps_scroll_and_shows_scrollbar() -> Result<(), Box<dyn std::error::Error>> {
+        use std::fmt::Write;
+
+        let mut app = App::new(create_test_result_infos());
+        app.scroll_offset = 500;
+        let content = (1..=100).fold(String::new(), |mut content, i| {
+            let _ = writeln!(content, "line {i}");
+            content
+        });
+
+        let mut terminal = Terminal::new(TestBackend::new(100, 30))?;
+        terminal.draw(|frame| render(frame, &mut app, &content))?;
+
+        // 100 lines in a 27-line viewport can scroll at most 73 lines
+        assert_eq!(app.scroll_offset, 73);
+        assert_eq!(app.content_height, 100);
+        let rows = buffer_rows(&terminal);
+        assert!(rows[0].contains("100%"));
+        assert!(rows[27].contains("line 100"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drive_select_then_confirm() -> Result<(), Box<dyn std::error::Error>> {
+        let mut driver = Driver::new(create_test_result_infos(), 100, 30)?;
+        driver.press("ja")?;
+        assert_snapshot!(driver.backend());
+
+        driver.press("y")?;
+        assert!(matches!(driver.result(), Some(SplitViewResult::Apply(1))));
+        assert!(driver.press("j").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_drive_search_then_apply() -> Result<(), Box<dyn std::error::Error>> {
+        let mut driver = Driver::new(create_test_result_infos(), 100, 30)?;
+        driver.press("<Tab>/made<Enter>")?;
+        assert_eq!(driver.app.input_mode, InputMode::Normal);
+        assert_eq!(driver.app.search_matches.len(), 1);
+
+        // Declining the confirmation keeps the view open
+        driver.press("<Tab>a<Esc>")?;
+        assert_eq!(driver.app.input_mode, InputMode::Normal);
+        assert!(driver.result().is_none());
+
+        driver.press("<Enter>y")?;
+        assert!(matches!(driver.result(), Some(SplitViewResult::Apply(0))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drive_quit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut driver = Driver::new(create_test_result_infos(), 100, 30)?;
+        driver.press("qn")?;
+        assert!(driver.result().is_none());
+        driver.press("qy")?;
+        assert!(matches!(driver.result(), Some(SplitViewResult::Cancel)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_drive_interrupt() -> Result<(), Box<dyn std::error::Error>> {
+        let mut driver = Driver::new(create_test_result_infos(), 100, 30)?;
+        // Ctrl-C asks first, even while typing a note
+        driver.press("c<C-c>")?;
+        assert_eq!(driver.app.input_mode, InputMode::ConfirmCancel);
+        assert!(driver.app.note_input.is_empty());
+        driver.press("<Esc>")?;
+        assert!(driver.result().is_none());
+        driver.press("<C-c><C-c>")?;
+        assert!(matches!(driver.result(), Some(SplitViewResult::Cancel)));
+        Ok(())
+    }
 }
@@ -0,0 +1,75 @@
+use ratatui::buffer::Buffer;
+use ratatui::style::{Color, Modifier};
+
+use crate::config::Theme;
+
+/// ASCII replacements for the glyphs drawn by the split view
+const ASCII_GLYPHS: &[(&str, &str)] = &[
+    ("\u{25B6}", ">"), // Selection and focus marker
+    ("\u{25BE}", "v"), // Expanded section
+    ("\u{25B8}", ">"), // Collapsed section
+    ("\u{2212}", "-"), // Minus sign of line counts
+    ("\u{2502}", "|"),
+    ("\u{2551}", "|"), // Scrollbar track
+    ("\u{2588}", "#"), // Scrollbar thumb
+    ("\u{25A0}", "!"), // Error line marker
+    ("\u{2500}", "-"),
+    ("\u{250C}", "+"),
+    ("\u{2510}", "+"),
+    ("\u{2514}", "+"),
+    ("\u{2518}", "+"),
+    ("\u{1F916}", "C"), // Agent icons
+    ("\u{2728}", "G"),
+    ("\u{1F4E6}", "X"),
+    ("\u{1F4BB}", "*"),
+];
+
+/// Map a color of the base palette to the given theme
+pub fn theme_color(theme: Theme, color: Color) -> Color {
+    match theme {
+        Theme::Default => color,
+        Theme::HighContrast => match color {
+            Color::DarkGray => Color::Gray,
+            Color::Gray => Color::White,
+            Color::Red => Color::LightRed,
+            Color::Green => Color::LightGreen,
+            Color::Yellow => Color::LightYellow,
+            Color::Blue => Color::LightBlue,
+            Color::Magenta => Color::LightMagenta,
+            Color::Cyan => Color::LightCyan,
+            other => other,
+        },
+        Theme::NoColor => Color::Reset,
+    }
+}
+
+/// Apply a theme to a rendered buffer
+///
+/// All views are styled with the base palette; the theme remaps the colors of
+/// every cell afterwards. Without colors, cells that had a background (selection,
+/// key hints, emphasized changes) are shown in reverse video instead.
+pub fn apply_theme(theme: Theme, buffer: &mut Buffer) {
+    if theme == Theme::Default {
+        return;
+    }
+
+    for cell in &mut buffer.content {
+        if theme == Theme::NoColor && cell.bg != Color::Reset {
+            cell.modifier.insert(Modifier::REVERSED);
+        }
+        cell.fg = theme_color(theme, cell.fg);
+        cell.bg = theme_color(theme, cell.bg);
+    }
+}
+
+/// Replace Unicode glyphs (markers, borders, agent icons) in a rendered buffer with ASCII
+pub fn apply_ascii_glyphs(buffer: &mut Buffer) {
+    for cell in &mut buffer.content {
+        if let Some((_, ascii)) = ASCII_GLYPHS
+            .iter()
+            .find(|(unicode, _)| *unicode == cell.symbol())
+        {
+            cell.set_symbol(ascii);
+        }
+    }
+}
@@ -1,8 +1,49 @@
+use crate::executor::OutputLine;
+
 /// Mode for the detail view
 #[derive(Debug, Clone, Copy, PartialEq)]
 pub enum ViewMode {
     Log,
     Diff,
+    Stat,
+    Summary,
+    /// Output of the check and score commands
+    Checks,
+    /// The agent's conversation, reconstructed from its structured output
+    Transcript,
+}
+
+/// Output streams shown in the Log view
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub enum OutputFilter {
+    /// Stdout and stderr interleaved
+    #[default]
+    All,
+    /// Only stderr lines
+    Stderr,
+    /// Only stdout lines
+    Stdout,
+}
+
+impl OutputFilter {
+    /// The filter selected after this one when cycling
+    pub fn next(self) -> Self {
+        match self {
+            OutputFilter::All => OutputFilter::Stderr,
+            OutputFilter::Stderr => OutputFilter::Stdout,
+            OutputFilter::Stdout => OutputFilter::All,
+        }
+    }
+
+    /// Whether an output line passes the filter
+    pub fn includes(self, line: &OutputLine) -> bool {
+        match (self, line) {
+            (OutputFilter::All, _)
+            | (OutputFilter::Stderr, OutputLine::Stderr(_))
+            | (OutputFilter::Stdout, OutputLine::Stdout(_)) => true,
+            (OutputFilter::Stderr | OutputFilter::Stdout, _) => false,
+        }
+    }
 }
 
 /// Which panel is focused
@@ -18,12 +59,82 @@ pub enum InputMode {
     Normal,
     Search,
     Confirm,
+    /// Confirming that a diff with likely secrets should be applied, before `Confirm`
+    ConfirmSecrets,
+    /// Confirming that an unusually large diff should be applied, before `Confirm`
+    ConfirmLargeDiff,
     ConfirmCancel,
+    /// Confirming deletion of the rejected candidates' worktrees
+    ConfirmDiscard,
+    /// Waiting for the target of a yank (`y`) command
+    Yank,
+    /// Typing a query to search all results
+    GlobalSearch,
+    /// Browsing the matches of a search across all results
+    GlobalResults,
+    /// Typing a line number to jump to (`:`)
+    GotoLine,
+    /// Showing the prompt and metadata of the run
+    RunInfo,
+    /// Showing an agent-written comparison of two candidates
+    Compare,
+    /// Typing a note about the selected candidate
+    Note,
+}
+
+/// A line matching a search across all results
+#[derive(Debug, Clone, PartialEq)]
+pub struct GlobalMatch {
+    /// Index of the result the line belongs to
+    pub result_index: usize,
+    /// View the line appears in
+    pub mode: ViewMode,
+    /// Line number within that view
+    pub line: usize,
+    /// Text of the matching line
+    pub text: String,
+}
+
+/// Program run outside the TUI for the selected worktree
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum ExternalAction {
+    /// Page the diff with delta or `$PAGER`
+    Pager,
+    /// Open the diff in `git difftool`
+    Difftool,
+    /// Open the worktree in `$VISUAL` or VS Code
+    Editor,
+    /// Start an interactive shell in the worktree
+    Shell,
+}
+
+/// What a yank command copies to the clipboard
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum YankTarget {
+    /// Path of the selected worktree
+    Path,
+    /// Full diff of the selected worktree
+    Diff,
+    /// Agent output log
+    Log,
+}
+
+impl YankTarget {
+    /// Human-readable name used in status messages
+    pub fn label(self) -> &'static str {
+        match self {
+            YankTarget::Path => "worktree path",
+            YankTarget::Diff => "diff",
+            YankTarget::Log => "log",
+        }
+    }
 }
 
 /// Result from the split view selection
 #[derive(Debug, Clone)]
 pub enum SplitViewResult {
     Apply(usize),
+    /// Apply after stashing the local changes, restoring them afterwards
+    ApplyStashed(usize),
     Cancel,
 }
@@ -1,11 +1,23 @@
-use std::path::Path;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::process::{Command, Stdio};
+use std::sync::Arc;
 
 use console::style;
 
+use crate::cli::glyphs::{glyph, rule};
+use crate::cli::output::{self, say};
 use crate::cli::progress::AgentStyle;
-use crate::domain::{ResultInfo, TaskResult};
+use crate::cli::verbosity::quiet;
+use crate::config::UiSettings;
+use crate::domain::{
+    FailedAgent, PromptWarning, ResultInfo, Review, RunInfo, ScoreBreakdown, SecretFinding,
+    TaskResult,
+};
 use crate::error::{Error, Result};
+use crate::executor::Executor;
+use crate::git::{ApplySummary, traced_blocking, traced_status};
+use crate::integrations::CiStatus;
 
 /// Check if delta command is available
 #[must_use]
@@ -26,100 +38,46 @@ pub fn is_delta_available() -> bool {
 pub fn show_diff_with_delta(worktree_path: &Path) -> Result<()> {
     let use_delta = is_delta_available();
 
-    // Get diff from the worktree
-    let diff_output = Command::new("git")
-        .args(["diff", "HEAD"])
-        .current_dir(worktree_path)
-        .output()
-        .map_err(|e| Error::GitCommand {
-            message: format!("Failed to get diff: {e}"),
-        })?;
+    // New files are marked intent-to-add so they show up as regular additions
+    crate::git::add_intent_to_add_blocking(worktree_path)?;
+    let diff_output = traced_blocking(
+        Command::new("git")
+            .args(["diff", "HEAD"])
+            .current_dir(worktree_path),
+    )
+    .map_err(|e| Error::GitCommand {
+        message: format!("Failed to get diff: {e}"),
+    })?;
 
     let diff_str = String::from_utf8_lossy(&diff_output.stdout);
-
     if diff_str.is_empty() {
-        // Try to get diff including untracked files
-        let status_output = Command::new("git")
-            .args(["status", "--porcelain"])
-            .current_dir(worktree_path)
-            .output()
-            .map_err(|e| Error::GitCommand {
-                message: format!("Failed to get status: {e}"),
-            })?;
-
-        let status_str = String::from_utf8_lossy(&status_output.stdout);
-        if status_str.is_empty() {
-            println!("\nNo changes detected.");
-            return Ok(());
-        }
-
-        // Show new files with delta if available
-        println!("\nNew/Untracked files:");
-        for line in status_str.lines() {
-            if line.starts_with("??") || line.starts_with("A ") {
-                let file = line.split_whitespace().last().unwrap_or("");
-                let file_path = worktree_path.join(file);
-
-                if file_path.exists() && file_path.is_file() {
-                    if use_delta {
-                        // Use git diff --no-index with delta for new files
-                        let _ = Command::new("git")
-                            .args([
-                                "-c",
-                                "core.pager=delta --paging=never",
-                                "-c",
-                                "color.diff=always",
-                                "diff",
-                                "--no-index",
-                                "/dev/null",
-                            ])
-                            .arg(&file_path)
-                            .current_dir(worktree_path)
-                            .stdout(Stdio::inherit())
-                            .stderr(Stdio::inherit())
-                            .status();
-                    } else {
-                        // Fallback: show plain diff
-                        println!("  + {file}");
-                        if let Ok(content) = std::fs::read_to_string(&file_path) {
-                            println!("\n--- /dev/null");
-                            println!("+++ {file}");
-                            for line in content.lines().take(50) {
-                                println!("+{line}");
-                            }
-                            if content.lines().count() > 50 {
-                                println!("... (truncated)");
-                            }
-                        }
-                    }
-                }
-            }
-        }
+        say!("\nNo changes detected.");
         return Ok(());
     }
 
     if use_delta {
         // Use git with delta as pager, forcing color output
-        Command::new("git")
-            .args([
-                "-c",
-                "core.pager=delta --paging=never",
-                "-c",
-                "color.diff=always",
-                "diff",
-                "HEAD",
-            ])
-            .current_dir(worktree_path)
-            .stdout(Stdio::inherit())
-            .stderr(Stdio::inherit())
-            .status()
-            .map_err(|e| Error::GitCommand {
-                message: format!("Failed to run git diff with delta: {e}"),
-            })?;
+        traced_status(
+            Command::new("git")
+                .args([
+                    "-c",
+                    "core.pager=delta --paging=never",
+                    "-c",
+                    "color.diff=always",
+                    "diff",
+                    "HEAD",
+                ])
+                .current_dir(worktree_path)
+                .stdout(Stdio::inherit())
+                .stderr(Stdio::inherit()),
+        )
+        .map_err(|e| Error::GitCommand {
+            message: format!("Failed to run git diff with delta: {e}"),
+        })?;
     } else {
         // Fallback: show plain diff
-        println!("\n(Tip: Install 'delta' for better diff output)");
-        println!("{diff_str}");
+        say!("\n(Tip: Install 'delta' for better diff output)");
+        say!("{diff_str}");
     }
 
     Ok(())
@@ -132,99 +90,390 @@ pub fn show_diff_with_delta(worktree_path: &Path) -> Result<()> {
 /// - Right panel: details (Output/Diff) for the selected model
 /// - Tab: switch between Output and Diff modes
 /// - 'a': apply the selected result
+/// - 'm' on two results: show a comparison of them written by `comparer`
 /// - Esc/q: cancel
 ///
+/// Rejections and notes made while reviewing are written to `reviews`, also when
+/// the user cancels.
+///
 /// # Errors
 ///
 /// Returns an error if no results are available or the user cancels.
-pub fn select_result(results: &[TaskResult], result_infos: &[ResultInfo]) -> Result<usize> {
+pub fn select_result(
+    results: &[TaskResult],
+    result_infos: &[ResultInfo],
+    run: &RunInfo,
+    settings: &UiSettings,
+    reviews: &mut [Review],
+    comparer: Option<Arc<dyn Executor>>,
+) -> Result<Selection> {
     if results.is_empty() {
         return Err(Error::NoExecutorsAvailable);
     }
 
     // Use the new split view
-    super::split_view::select_result_split_view(result_infos)
+    super::split_view::select_result_split_view(result_infos, run, settings, reviews, comparer)
+}
+
+/// The result chosen for applying and how to apply it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Selection {
+    /// Index of the chosen result
+    pub index: usize,
+    /// Stash conflicting local changes before applying and restore them afterwards
+    pub stash: bool,
+}
+
+/// Display the outcome of restoring stashed local changes after applying
+pub fn show_stash_restored(conflicts: &[String]) {
+    if conflicts.is_empty() {
+        if quiet() {
+            return;
+        }
+        say!("  Local changes were restored on top of the applied result.");
+        return;
+    }
+
+    say!(
+        "  {} Local changes conflict with the applied result in {} file(s):",
+        style(glyph("⚠️", "[!]")).bold(),
+        conflicts.len()
+    );
+    for file in conflicts {
+        say!("{}", style(format!("    {file}")).red());
+    }
+    say!(
+        "  Resolve them with the local versions kept in the stash, then drop it with `git stash drop`."
+    );
 }
 
 /// Display a message when applying changes
 pub fn show_applying_message(executor_name: &str) {
+    if quiet() {
+        return;
+    }
     let agent_style = AgentStyle::for_agent(executor_name);
-    println!();
-    println!(
+    say!();
+    say!(
         "  {} {} Applying changes from {}...",
-        style("🔧").bold(),
+        style(glyph("🔧", ">>")).bold(),
         agent_style.emoji,
         style(executor_name.to_uppercase()).bold().cyan()
     );
 }
 
+/// Maximum number of files listed per category in the apply summary
+const APPLY_SUMMARY_MAX_FILES: usize = 10;
+
+/// Display the files created, updated and deleted by applying a result
+pub fn show_apply_summary(summary: &ApplySummary) {
+    if quiet() {
+        return;
+    }
+    if summary.is_empty() {
+        say!("  No files changed in the target directory.");
+        return;
+    }
+
+    say!(
+        "  {} created, {} updated, {} deleted",
+        style(summary.created.len()).green().bold(),
+        style(summary.updated.len()).yellow().bold(),
+        style(summary.deleted.len()).red().bold()
+    );
+    // Binary files are listed on their own, as their changes cannot be shown as a diff
+    let text = |files: &[String]| -> Vec<String> {
+        files
+            .iter()
+            .filter(|file| !summary.binary.contains(file))
+            .cloned()
+            .collect()
+    };
+    let groups = [
+        ("+", text(&summary.created)),
+        ("~", text(&summary.updated)),
+        ("-", summary.deleted.clone()),
+        ("b", summary.binary.clone()),
+    ];
+    for (marker, files) in groups {
+        for file in files.iter().take(APPLY_SUMMARY_MAX_FILES) {
+            let line = format!("    {marker} {file}");
+            match marker {
+                "+" => say!("{}", style(line).green()),
+                "~" => say!("{}", style(line).yellow()),
+                "b" => say!("{}", style(line).magenta()),
+                _ => say!("{}", style(line).red()),
+            }
+        }
+        if files.len() > APPLY_SUMMARY_MAX_FILES {
+            let remaining = files.len() - APPLY_SUMMARY_MAX_FILES;
+            say!("    ... and {remaining} more files");
+        }
+    }
+    if !summary.conflicts.is_empty() {
+        say!(
+            "  {} The result conflicts with newer commits in {} file(s):",
+            style(glyph("⚠️", "[!]")).bold(),
+            summary.conflicts.len()
+        );
+        for file in &summary.conflicts {
+            say!("{}", style(format!("    {file}")).red());
+        }
+        say!("  Resolve the conflict markers before committing.");
+    }
+}
+
+/// Display the results of a run that applies nothing, best score first
+pub fn show_ranking(result_infos: &[ResultInfo], scores: &[ScoreBreakdown]) {
+    let mut ranked: Vec<(&ResultInfo, &ScoreBreakdown)> = result_infos.iter().zip(scores).collect();
+    ranked.sort_by(|a, b| b.1.total.total_cmp(&a.1.total));
+
+    say!();
+    say!("{}", style(rule(50)).cyan());
+    for (rank, (info, score)) in ranked.iter().enumerate() {
+        let agent_style = AgentStyle::for_agent(&info.executor_name);
+        let (insertions, deletions) = info
+            .change_summary
+            .as_ref()
+            .map_or((0, 0), |s| (s.insertions, s.deletions));
+        let outcome = if info.success {
+            format!(
+                "{} file(s), {} {}",
+                info.files_changed,
+                style(format!("+{insertions}")).green(),
+                style(format!("-{deletions}")).red()
+            )
+        } else {
+            style(
+                info.failure_reason()
+                    .unwrap_or_else(|| "failed".to_string()),
+            )
+            .red()
+            .to_string()
+        };
+        say!(
+            "  {}. {} {}  {}  {outcome}",
+            rank + 1,
+            agent_style.emoji,
+            style(&info.executor_name).bold(),
+            style(format!("{:.2}", score.total)).cyan()
+        );
+    }
+    say!("{}", style(rule(50)).cyan());
+    say!();
+}
+
 /// Display a success message
 pub fn show_success_message() {
-    println!();
-    println!("{}", style("━".repeat(50)).green());
-    println!(
+    if quiet() {
+        return;
+    }
+    say!();
+    say!("{}", style(rule(50)).green());
+    say!(
         "  {} {}",
-        style("✅").bold(),
+        style(glyph("✅", "[+]")).bold(),
         style("Changes applied successfully!").bold().green()
     );
-    println!("{}", style("━".repeat(50)).green());
-    println!();
+    say!("{}", style(rule(50)).green());
+    say!();
+}
+
+/// Report how the CI checks of the pull request at `url` ended
+pub fn show_ci_status(status: CiStatus, url: &str) {
+    match status {
+        CiStatus::Passed => show_progress(&format!("CI passed for {url}")),
+        CiStatus::NoChecks => show_progress(&format!("No CI checks ran for {url}")),
+        CiStatus::Pending => {
+            show_progress(&format!("Stopped waiting; CI is still running for {url}"));
+        }
+        CiStatus::Failed => eprintln!(
+            "\n{} {}",
+            style(glyph("❌", "[x]")).bold(),
+            style(format!("CI failed for {url}")).bold().red()
+        ),
+    }
+}
+
+/// Report an aborted run, with the patches of what the agents changed so far
+pub fn show_aborted(patches: &[(String, PathBuf)]) {
+    eprintln!(
+        "\n{} {}",
+        style(glyph("⏹", "[-]")).bold(),
+        style("Stopped all agents").bold().yellow()
+    );
+    for (agent, patch) in patches {
+        eprintln!("  {agent}: {}", patch.display());
+    }
+    if !patches.is_empty() {
+        eprintln!("  {}", style("Apply one with `git apply <patch>`").dim());
+    }
 }
 
 /// Display an error message
 pub fn show_error(error: &Error) {
     eprintln!();
-    eprintln!("{}", style("━".repeat(50)).red());
+    eprintln!("{}", style(rule(50)).red());
     eprintln!(
         "  {} {} {}",
-        style("❌").bold(),
+        style(glyph("❌", "[x]")).bold(),
         style("Error:").bold().red(),
         style(error).red()
     );
-    eprintln!("{}", style("━".repeat(50)).red());
+    eprintln!("{}", style(rule(50)).red());
+    eprintln!();
+}
+
+/// Display why every agent failed, with the saved logs and what to check
+pub fn show_all_agents_failed(failures: &[FailedAgent]) {
+    eprintln!();
+    eprintln!("{}", style(rule(50)).red());
+    eprintln!(
+        "  {} {}",
+        style(glyph("❌", "[x]")).bold(),
+        style("Every agent failed").bold().red()
+    );
+    eprintln!("{}", style(rule(50)).red());
+    for failure in failures {
+        let agent_style = AgentStyle::for_agent(&failure.agent);
+        let exit = failure
+            .exit_code
+            .map(|code| format!(" (exit code {code})"))
+            .unwrap_or_default();
+        eprintln!();
+        eprintln!(
+            "  {} {}{}",
+            agent_style.emoji,
+            style(&failure.agent).bold(),
+            style(exit).dim()
+        );
+        eprintln!(
+            "     {}",
+            style(failure.error.as_deref().unwrap_or("(no output)")).red()
+        );
+        if let Some(log) = &failure.log {
+            eprintln!("     {} {}", style("Log:").dim(), log.display());
+        }
+        if let Some(hint) = &failure.hint {
+            eprintln!("     {} {hint}", style("Check:").yellow());
+        }
+    }
     eprintln!();
 }
 
+/// Warn on stderr that `--max-tokens` cannot stop agents whose output does not report
+/// the tokens they use
+pub fn show_untracked_tokens_warning(agents: &[&str]) {
+    let message = format!(
+        "--max-tokens only stops agents with structured output; {} report no usage.",
+        agents.join(", ")
+    );
+    if !output::hold(&message) {
+        eprintln!(
+            "{} {}",
+            style(glyph("⚠️", "[!]")).bold(),
+            style(message).yellow()
+        );
+    }
+}
+
 /// Display progress message
 pub fn show_progress(message: &str) {
-    println!("  {} {message}", style("ℹ️").bold());
+    if quiet() || output::hold(message) {
+        return;
+    }
+    say!("  {} {message}", style(glyph("ℹ️", "[i]")).bold());
 }
 
 /// Display waiting message while executors are running
 pub fn show_running_message(executor_names: &[&str]) {
-    println!("\nRunning AI CLI tools in parallel:");
+    if quiet() {
+        return;
+    }
+    say!("\nRunning AI CLI tools in parallel:");
     for name in executor_names {
-        println!("  - {name}");
+        say!("  - {name}");
     }
-    println!("\nThis may take a while...\n");
+    say!("\nThis may take a while...\n");
+}
+
+/// What to do with a prompt that is long for some agents
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LongPrompt {
+    /// Run the agents on the whole prompt
+    Run,
+    /// Leave out the middle of the prompt so it fits
+    Truncate,
+    /// Do not run the agents
+    Cancel,
 }
 
-/// Display warning about uncommitted changes and ask for confirmation
+/// Warn on stderr that the prompt is long for some agents and, with `ask`, what to do
+///
+/// Without a terminal to ask on, the agents run on the whole prompt.
 ///
 /// # Errors
 ///
-/// Returns an error if stdin/stdout operations fail.
-pub fn confirm_overwrite_uncommitted(uncommitted_files: &[String]) -> Result<bool> {
-    use crossterm::style::Stylize;
-
-    println!(
+/// Returns an error if stdin/stderr operations fail.
+pub fn confirm_long_prompt(warnings: &[PromptWarning], ask: bool) -> Result<LongPrompt> {
+    eprintln!(
         "\n{}",
-        "Warning: You have uncommitted changes!".yellow().bold()
+        style("Warning: The prompt is long for some agents!")
+            .yellow()
+            .bold()
     );
-    println!("The following files will be overwritten:\n");
+    for warning in warnings {
+        eprintln!(
+            "  {} about {} tokens, more than half of its context window ({} tokens)",
+            style(&warning.agent).yellow(),
+            warning.tokens,
+            warning.context_window
+        );
+    }
+    eprintln!("They may run out of context while working and fail before finishing.");
 
-    for file in uncommitted_files.iter().take(10) {
-        println!("  {}", file.as_str().yellow());
+    if !ask || !std::io::stdin().is_terminal() {
+        return Ok(LongPrompt::Run);
     }
+    eprint!("\n[r]un anyway, [t]runcate the middle of the prompt, or [C]ancel? ");
+    std::io::Write::flush(&mut std::io::stderr())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
 
-    if uncommitted_files.len() > 10 {
-        let remaining = uncommitted_files.len() - 10;
-        println!("  ... and {remaining} more files");
+    Ok(match input.trim().to_lowercase().as_str() {
+        "r" | "run" | "y" | "yes" => LongPrompt::Run,
+        "t" | "truncate" => LongPrompt::Truncate,
+        _ => LongPrompt::Cancel,
+    })
+}
+
+/// Warn that the diffs of a run look like they add secrets and ask whether to export it
+/// anyway
+///
+/// Without a terminal to ask on, the export is refused.
+///
+/// # Errors
+///
+/// Returns an error if stdin/stdout operations fail.
+pub fn confirm_export_secrets(secrets: &[(String, SecretFinding)]) -> Result<bool> {
+    eprintln!(
+        "\n{}",
+        style("Warning: Some diffs look like they add secrets!")
+            .red()
+            .bold()
+    );
+    for (agent, secret) in secrets {
+        eprintln!("  {}: {secret}", style(agent).yellow());
     }
 
-    println!();
-    print!("Do you want to continue? [y/N]: ");
-    std::io::Write::flush(&mut std::io::stdout())?;
+    if !std::io::stdin().is_terminal() {
+        eprintln!("Pass --allow-secrets to export the run anyway.");
+        return Ok(false);
+    }
+    eprintln!();
+    eprint!("Export the run anyway? [y/N]: ");
+    std::io::Write::flush(&mut std::io::stderr())?;
 
     let mut input = String::new();
     std::io::stdin().read_line(&mut input)?;
@@ -233,6 +482,65 @@ pub fn confirm_overwrite_uncommitted(uncommitted_files: &[String]) -> Result<boo
     Ok(answer == "y" || answer == "yes")
 }
 
+/// Show the diff of the selected worktree after formatting and ask whether to apply it
+///
+/// # Errors
+///
+/// Returns an error if the diff cannot be shown or stdin/stdout operations fail.
+pub fn confirm_formatted_diff(worktree_path: &Path, command: &str) -> Result<bool> {
+    eprintln!(
+        "\n{}",
+        style(format!("Changes after running `{command}`:")).bold()
+    );
+    show_diff_with_delta(worktree_path)?;
+
+    eprint!("\nApply these changes? [y/N]: ");
+    std::io::Write::flush(&mut std::io::stderr())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    let answer = input.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
+/// What to do with a result applied to a clone of a remote repository
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RemoteDelivery {
+    /// Push it to a new branch of the remote
+    Push,
+    /// Write it to a patch file in the current directory
+    Patch,
+    /// Throw it away with the clone
+    Discard,
+}
+
+/// Ask whether to push the applied result to `branch` or write it to `patch`
+///
+/// # Errors
+///
+/// Returns an error if stdin/stdout operations fail.
+pub fn choose_remote_delivery(url: &str, branch: &str, patch: &Path) -> Result<RemoteDelivery> {
+    say!(
+        "\nThe result was applied to a temporary clone of {}, removed when parari exits.",
+        style(url).bold()
+    );
+    print!(
+        "[p]ush it to branch {branch}, [W]rite it to {}, or [d]iscard it? ",
+        patch.display()
+    );
+    std::io::Write::flush(&mut std::io::stdout())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    Ok(match input.trim().to_lowercase().as_str() {
+        "p" | "push" => RemoteDelivery::Push,
+        "d" | "discard" => RemoteDelivery::Discard,
+        _ => RemoteDelivery::Patch,
+    })
+}
+
 /// Display warning about conflicting files and ask for confirmation
 ///
 /// # Errors
@@ -241,24 +549,24 @@ pub fn confirm_overwrite_uncommitted(uncommitted_files: &[String]) -> Result<boo
 pub fn confirm_apply_with_conflicts(conflicts: &[String]) -> Result<bool> {
     use crossterm::style::Stylize;
 
-    println!(
+    say!(
         "\n{}",
         "Warning: The following files have conflicts!".red().bold()
     );
-    println!(
+    say!(
         "These files have been modified both in your working directory and the selected result:\n"
     );
 
     for file in conflicts.iter().take(10) {
-        println!("  {}", file.as_str().red());
+        say!("  {}", file.as_str().red());
     }
 
     if conflicts.len() > 10 {
         let remaining = conflicts.len() - 10;
-        println!("  ... and {remaining} more files");
+        say!("  ... and {remaining} more files");
     }
 
-    println!("\nApplying will overwrite your local changes in these files.");
+    say!("\nApplying will overwrite your local changes in these files.");
     print!("Do you want to continue? [y/N]: ");
     std::io::Write::flush(&mut std::io::stdout())?;
 
@@ -269,6 +577,35 @@ pub fn confirm_apply_with_conflicts(conflicts: &[String]) -> Result<bool> {
     Ok(answer == "y" || answer == "yes")
 }
 
+/// Warn that the auto-selected candidate exceeds the large-diff limits and ask
+/// whether to apply it anyway
+///
+/// # Errors
+///
+/// Returns an error if stdin/stderr operations fail.
+pub fn confirm_large_diff(info: &ResultInfo) -> Result<bool> {
+    eprintln!(
+        "\n{}",
+        style(format!(
+            "Warning: {} changed {} files and {} lines!",
+            info.executor_name,
+            info.files_changed,
+            info.changed_line_count()
+        ))
+        .yellow()
+        .bold()
+    );
+    eprintln!("That is more than `ui.large_diff_files` or `ui.large_diff_lines` allow.");
+    eprint!("Apply it anyway? [y/N]: ");
+    std::io::Write::flush(&mut std::io::stderr())?;
+
+    let mut input = String::new();
+    std::io::stdin().read_line(&mut input)?;
+
+    let answer = input.trim().to_lowercase();
+    Ok(answer == "y" || answer == "yes")
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
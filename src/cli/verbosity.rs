@@ -0,0 +1,35 @@
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether progress is printed as plain lines
+static PLAIN: AtomicBool = AtomicBool::new(false);
+
+/// Whether informational output is suppressed
+static QUIET: AtomicBool = AtomicBool::new(false);
+
+/// Suppress the banner, progress spinners, and informational messages
+///
+/// Like `glyphs::set_ascii_only`, this is a process-wide switch. Errors, warnings,
+/// and questions are still printed.
+pub fn set_quiet(enabled: bool) {
+    QUIET.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether informational output is suppressed
+#[must_use]
+pub fn quiet() -> bool {
+    QUIET.load(Ordering::Relaxed)
+}
+
+/// Print progress as plain lines instead of redrawn spinners
+///
+/// Used when the output is not a terminal, e.g. piped into `tee`, where the
+/// control sequences of spinners would end up as garbage in the output.
+pub fn set_plain(enabled: bool) {
+    PLAIN.store(enabled, Ordering::Relaxed);
+}
+
+/// Whether progress is printed as plain lines
+#[must_use]
+pub fn plain() -> bool {
+    PLAIN.load(Ordering::Relaxed)
+}
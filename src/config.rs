@@ -1,3 +1,9 @@
+pub mod edit;
 pub mod paths;
+pub mod repo;
+pub mod settings;
 
+pub use edit::*;
 pub use paths::*;
+pub use repo::*;
+pub use settings::*;
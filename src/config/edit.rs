@@ -0,0 +1,161 @@
+use toml_edit::{DocumentMut, Item, Value};
+
+use super::settings::Settings;
+use crate::error::{Error, Result};
+
+/// Keys of every setting, as written to `parari config get` and `parari config set`
+pub const KEYS: [&str; 23] = [
+    "ui.syntax_highlighting",
+    "ui.delta",
+    "ui.ansi_colors",
+    "ui.theme",
+    "ui.shuffle",
+    "ui.large_diff_files",
+    "ui.large_diff_lines",
+    "worktrees.max_count",
+    "worktrees.max_age",
+    "storage.quota",
+    "storage.save_logs",
+    "storage.logs_max_age",
+    "auto_select.tests",
+    "auto_select.check",
+    "auto_select.score",
+    "auto_select.diff_size",
+    "auto_select.duration",
+    "auto_select.history",
+    "commands.score_jobs",
+    "commands.stall_timeout",
+    "commands.kill_stalled",
+    "editor.template",
+    "editor.template_file",
+];
+
+/// Split a key like `ui.theme` into its table and field, rejecting unknown keys
+fn split_key(key: &str) -> Result<(&str, &str)> {
+    if !KEYS.contains(&key) {
+        return Err(Error::Config {
+            message: format!(
+                "unknown setting \"{key}\"; valid settings are: {}",
+                KEYS.join(", ")
+            ),
+        });
+    }
+    key.split_once('.').ok_or_else(|| Error::Config {
+        message: format!("invalid setting \"{key}\""),
+    })
+}
+
+/// Read the effective value of a setting, falling back to its default
+///
+/// Returns `None` for optional settings that are not set.
+///
+/// # Errors
+///
+/// Returns an error if the key is unknown.
+pub fn get_setting(settings: &Settings, key: &str) -> Result<Option<String>> {
+    let (table, field) = split_key(key)?;
+    let values = toml::Value::try_from(settings).map_err(|e| Error::Config {
+        message: e.to_string(),
+    })?;
+    Ok(values
+        .get(table)
+        .and_then(|table| table.get(field))
+        .map(|value| match value {
+            toml::Value::String(text) => text.clone(),
+            other => other.to_string(),
+        }))
+}
+
+/// Set a setting in the content of a config file and return the new content
+///
+/// Comments and the layout of the rest of the file are kept. The value is read as
+/// a TOML value when it is one (`false`, `10`) and as a string otherwise (`7d`).
+///
+/// # Errors
+///
+/// Returns an error if the key is unknown, the content is not valid TOML, or the
+/// value is invalid for the setting.
+pub fn set_setting(content: &str, key: &str, value: &str) -> Result<String> {
+    let (table, field) = split_key(key)?;
+    let mut document = content.parse::<DocumentMut>().map_err(|e| Error::Config {
+        message: e.to_string(),
+    })?;
+    let value = value
+        .parse::<Value>()
+        .unwrap_or_else(|_| Value::from(value));
+    let section = document
+        .entry(table)
+        .or_insert(toml_edit::table())
+        .as_table_mut()
+        .ok_or_else(|| Error::Config {
+            message: format!("\"{table}\" is not a table"),
+        })?;
+    section[field] = Item::Value(value);
+
+    let updated = document.to_string();
+    Settings::parse(&updated).map_err(|e| match e {
+        Error::Config { message } => Error::Config {
+            message: format!("invalid value for \"{key}\": {message}"),
+        },
+        other => other,
+    })?;
+    Ok(updated)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Theme;
+
+    #[test]
+    fn test_get_setting() -> Result<()> {
+        let settings = Settings::parse("[ui]\ntheme = \"no-color\"\n")?;
+        assert_eq!(
+            get_setting(&settings, "ui.theme")?,
+            Some("no-color".to_string())
+        );
+        assert_eq!(
+            get_setting(&settings, "ui.delta")?,
+            Some("true".to_string())
+        );
+        assert_eq!(get_setting(&settings, "worktrees.max_age")?, None);
+        assert!(get_setting(&settings, "ui.colour").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_setting_keeps_comments() -> Result<()> {
+        let content = "# My settings\n[ui]\n# Too slow here\nsyntax_highlighting = false\n";
+        let updated = set_setting(content, "ui.theme", "high-contrast")?;
+        assert!(updated.contains("# Too slow here"));
+        let settings = Settings::parse(&updated)?;
+        assert!(!settings.ui.syntax_highlighting);
+        assert_eq!(settings.ui.theme, Theme::HighContrast);
+
+        let updated = set_setting(&updated, "worktrees.max_count", "5")?;
+        assert_eq!(Settings::parse(&updated)?.worktrees.max_count, 5);
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_setting_validates() {
+        assert!(set_setting("", "ui.delta", "maybe").is_err());
+        assert!(set_setting("", "worktrees.max_age", "7 days").is_err());
+        assert!(set_setting("", "ui.colour", "red").is_err());
+    }
+
+    #[test]
+    fn test_keys_cover_settings() -> Result<()> {
+        let values = toml::Value::try_from(Settings::default()).map_err(|e| Error::Config {
+            message: e.to_string(),
+        })?;
+        let tables = values.as_table().into_iter().flatten();
+        for (table, fields) in tables {
+            for field in fields.as_table().into_iter().flat_map(|t| t.keys()) {
+                let key = format!("{table}.{field}");
+                assert!(KEYS.contains(&key.as_str()), "{key} is missing from KEYS");
+            }
+        }
+        Ok(())
+    }
+}
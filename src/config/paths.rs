@@ -18,7 +18,31 @@ pub fn worktrees_dir() -> PathBuf {
     base_dir().join("worktrees")
 }
 
-/// Maximum number of worktrees to keep
+/// Path of the user configuration file
+///
+/// Returns `$HOME/.parari/config.toml`
+#[must_use]
+pub fn config_file() -> PathBuf {
+    base_dir().join("config.toml")
+}
+
+/// Directory for storing the history of runs
+///
+/// Returns `$HOME/.parari/history`
+#[must_use]
+pub fn history_dir() -> PathBuf {
+    base_dir().join("history")
+}
+
+/// Directory for storing the results of score commands, by worktree content
+///
+/// Returns `$HOME/.parari/cache/scores`
+#[must_use]
+pub fn score_cache_dir() -> PathBuf {
+    base_dir().join("cache").join("scores")
+}
+
+/// Default maximum number of worktrees kept per repository
 pub const MAX_WORKTREES: usize = 20;
 
 #[cfg(test)]
@@ -38,4 +62,18 @@ mod tests {
         assert!(worktrees.starts_with(&base));
         assert!(worktrees.ends_with("worktrees"));
     }
+
+    #[test]
+    fn test_history_dir_is_under_base() {
+        let history = history_dir();
+        assert!(history.starts_with(base_dir()));
+        assert!(history.ends_with("history"));
+    }
+
+    #[test]
+    fn test_config_file_is_under_base() {
+        let config = config_file();
+        assert!(config.starts_with(base_dir()));
+        assert!(config.ends_with("config.toml"));
+    }
 }
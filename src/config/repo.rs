@@ -0,0 +1,364 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::error::{Error, Result};
+
+/// Name of the per-repository configuration file, at the repository root
+pub const REPO_CONFIG_FILE: &str = ".parari.toml";
+
+/// Commented configuration written by `parari init`
+pub const REPO_CONFIG_TEMPLATE: &str = r#"# parari configuration for this repository.
+# Commit it to share the setup with your team, or list it in .gitignore
+# (`parari init --gitignore`) to keep it to yourself.
+
+# Agents to run when --agents is not given ("claude", "gemini", "codex").
+# Leave unset to run every agent that is installed.
+# agents = ["claude", "codex"]
+
+# Command run in each agent's worktree before the agent starts, e.g. to
+# install dependencies. An agent whose setup fails is reported as failed.
+# setup = "npm ci"
+
+# Quick sanity check run in each agent's worktree after the agent finishes,
+# shown as a badge next to each result. Defaults to `cargo check`, `go build`, or
+# `tsc` depending on the repository when parari is run with --check-cmd.
+# check = "cargo check --quiet"
+
+# Command run in each agent's worktree after the agent finishes, to score its
+# result. It passes when it exits with status 0; if the last line it prints is
+# a number, that number is shown as the score.
+# score = "cargo test --quiet"
+
+# Formatter run in the selected agent's worktree before its changes are
+# applied, so formatting differences between agents do not end up in your diff.
+# The formatted diff is shown for a final confirmation.
+# format = "cargo fmt"
+
+# Generated files besides the usual lockfiles (Cargo.lock, package-lock.json,
+# yarn.lock, ...). Local edits to them never block applying a result; the
+# candidate's version is taken, and the regenerate command is run afterwards.
+# generated = ["src/generated/"]
+# regenerate = "npm install --package-lock-only"
+
+# Files that are never applied from a worktree, such as build output and
+# dependencies installed by the setup command. Patterns without a "/" match a
+# file or directory name anywhere; "*" matches any characters except "/".
+ignore = [
+    "node_modules/",
+    "target/",
+    "*.log",
+]
+
+# Policy checked in each agent's worktree after the agent finishes. Violations
+# are shown as a badge next to each result and listed in its log view.
+# [policy]
+# Require an SPDX-License-Identifier header in new source files
+# spdx_header = true
+# Command that exits with a non-zero status when the policy is broken
+# command = "reuse lint"
+
+# Guardrails for proprietary code. Text matching a pattern (a regular
+# expression) is replaced with [redacted] in the prompt before it is sent to the
+# agents, and in the output and errors kept in the run history. Uncommitted files
+# matching a file pattern (same syntax as `ignore`) are not copied into the
+# agents' worktrees, and are refused as --image attachments.
+# [redact]
+# patterns = ["sk-[A-Za-z0-9]{20,}", "/home/[a-z]+/"]
+# files = [".env*", "*.pem", "secrets/"]
+"#;
+
+/// Settings shared by everyone working on a repository, read from `.parari.toml`
+///
+/// Every field is optional in the file; missing values fall back to their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RepoSettings {
+    /// Agents to run when `--agents` is not given
+    pub agents: Option<Vec<String>>,
+    /// Shell command run in each worktree before the agent starts
+    pub setup: Option<String>,
+    /// Quick shell command run in each worktree after the agent finishes, e.g. `cargo check`
+    pub check: Option<String>,
+    /// Shell command run in each worktree after the agent finishes to score its result
+    pub score: Option<String>,
+    /// Formatter run in the selected worktree before applying, e.g. `cargo fmt`
+    pub format: Option<String>,
+    /// Patterns of generated files whose conflicts are resolved without asking, besides
+    /// the usual lockfiles
+    pub generated: Vec<String>,
+    /// Shell command run in the target after applying changes to generated files
+    pub regenerate: Option<String>,
+    /// Patterns of files that are never applied from a worktree
+    pub ignore: Vec<String>,
+    /// Secrets stripped from what is sent to agents and kept in the history
+    pub redact: RedactSettings,
+    /// License and header rules checked in each worktree
+    pub policy: PolicySettings,
+}
+
+/// Rules a candidate must follow, checked in its worktree after the agent finishes
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct PolicySettings {
+    /// Shell command that fails when the policy is broken, e.g. `reuse lint`
+    pub command: Option<String>,
+    /// Whether new source files must start with an `SPDX-License-Identifier` header
+    pub spdx_header: bool,
+}
+
+impl PolicySettings {
+    /// Whether any rule is configured
+    #[must_use]
+    pub fn is_enabled(&self) -> bool {
+        self.command.is_some() || self.spdx_header
+    }
+}
+
+/// Secrets and paths to keep away from the agents and out of the run history
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct RedactSettings {
+    /// Regular expressions of text replaced with `[redacted]` in the prompt and in the
+    /// logs saved with runs
+    pub patterns: Vec<String>,
+    /// Patterns of files never copied into a worktree or attached to the prompt
+    pub files: Vec<String>,
+}
+
+/// Lockfiles of common package managers, treated as generated files
+pub const LOCKFILES: [&str; 11] = [
+    "Cargo.lock",
+    "package-lock.json",
+    "npm-shrinkwrap.json",
+    "yarn.lock",
+    "pnpm-lock.yaml",
+    "bun.lockb",
+    "poetry.lock",
+    "uv.lock",
+    "Gemfile.lock",
+    "composer.lock",
+    "go.sum",
+];
+
+/// Quick check command suited to the repository at `repo_root`, based on its build files
+#[must_use]
+pub fn default_check_command(repo_root: &Path) -> Option<&'static str> {
+    [
+        ("Cargo.toml", "cargo check --quiet"),
+        ("go.mod", "go build ./..."),
+        ("tsconfig.json", "npx --no-install tsc --noEmit"),
+    ]
+    .into_iter()
+    .find(|(file, _)| repo_root.join(file).is_file())
+    .map(|(_, command)| command)
+}
+
+/// Add `.parari.toml` to the `.gitignore` at `repo_root`, creating it if needed
+///
+/// Returns false if it was already listed.
+///
+/// # Errors
+///
+/// Returns an error if `.gitignore` cannot be read or written.
+pub fn add_to_gitignore(repo_root: &Path) -> Result<bool> {
+    let path = repo_root.join(".gitignore");
+    let mut content = match std::fs::read_to_string(&path) {
+        Ok(content) => content,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+        Err(e) => return Err(e.into()),
+    };
+    let entry = format!("/{REPO_CONFIG_FILE}");
+    if content
+        .lines()
+        .any(|line| line.trim() == entry || line.trim() == REPO_CONFIG_FILE)
+    {
+        return Ok(false);
+    }
+    if !content.is_empty() && !content.ends_with('\n') {
+        content.push('\n');
+    }
+    content.push_str(&entry);
+    content.push('\n');
+    std::fs::write(&path, content)?;
+    Ok(true)
+}
+
+impl RepoSettings {
+    /// Load the settings of the repository rooted at `repo_root`
+    ///
+    /// Returns the default settings if the repository has no `.parari.toml`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid TOML.
+    pub fn load(repo_root: &Path) -> Result<Self> {
+        let path = repo_root.join(REPO_CONFIG_FILE);
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(&path)?;
+        toml::from_str(&content).map_err(|e| Error::Config {
+            message: format!("{}: {e}", path.display()),
+        })
+    }
+
+    /// Whether `path`, relative to the repository root, is a lockfile or matches a
+    /// generated-file pattern
+    #[must_use]
+    pub fn is_generated(&self, path: &str) -> bool {
+        let name = path.rsplit('/').next().unwrap_or(path);
+        LOCKFILES.contains(&name)
+            || self
+                .generated
+                .iter()
+                .any(|pattern| matches_pattern(pattern, path))
+    }
+
+    /// Whether `path`, relative to the repository root, matches an ignore pattern
+    #[must_use]
+    pub fn is_ignored(&self, path: &str) -> bool {
+        self.ignore
+            .iter()
+            .any(|pattern| matches_pattern(pattern, path))
+    }
+
+    /// Whether `path` matches a secret file pattern of `[redact]`
+    #[must_use]
+    pub fn is_secret(&self, path: &str) -> bool {
+        self.redact
+            .files
+            .iter()
+            .any(|pattern| matches_pattern(pattern, path))
+    }
+}
+
+/// Match a path against a gitignore-style pattern
+///
+/// A trailing `/` matches directories only, a pattern without a `/` matches any
+/// component of the path, and `*` matches any characters except `/`.
+pub(crate) fn matches_pattern(pattern: &str, path: &str) -> bool {
+    let (pattern, directory) = match pattern.strip_suffix('/') {
+        Some(pattern) => (pattern, true),
+        None => (pattern, false),
+    };
+    let components: Vec<&str> = path.split('/').collect();
+    // Directory patterns never match the file name itself
+    let candidates = if directory {
+        &components[..components.len() - 1]
+    } else {
+        &components[..]
+    };
+
+    match pattern
+        .strip_prefix('/')
+        .or_else(|| pattern.contains('/').then_some(pattern))
+    {
+        // Anchored at the repository root: match a leading run of components
+        Some(anchored) => {
+            let depth = anchored.split('/').count();
+            depth <= candidates.len() && wildcard(anchored, &candidates[..depth].join("/"))
+        }
+        None => candidates
+            .iter()
+            .any(|component| wildcard(pattern, component)),
+    }
+}
+
+/// Match `text` against `pattern`, where `*` matches any characters except `/`
+fn wildcard(pattern: &str, text: &str) -> bool {
+    match pattern.split_once('*') {
+        None => pattern == text,
+        Some((prefix, rest)) => {
+            let Some(text) = text.strip_prefix(prefix) else {
+                return false;
+            };
+            text.char_indices()
+                .map(|(i, _)| i)
+                .chain(std::iter::once(text.len()))
+                .take_while(|&i| !text[..i].contains('/'))
+                .any(|i| wildcard(rest, &text[i..]))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_template_parses() -> Result<()> {
+        let settings: RepoSettings =
+            toml::from_str(REPO_CONFIG_TEMPLATE).map_err(|e| Error::Config {
+                message: e.to_string(),
+            })?;
+        assert_eq!(settings.agents, None);
+        assert_eq!(settings.ignore.len(), 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() -> Result<()> {
+        let dir = std::env::temp_dir().join("parari_repo_settings_missing");
+        assert_eq!(RepoSettings::load(&dir)?, RepoSettings::default());
+        Ok(())
+    }
+
+    #[test]
+    fn test_add_to_gitignore() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        std::fs::write(dir.path().join(".gitignore"), "target")?;
+        assert!(add_to_gitignore(dir.path())?);
+        assert!(!add_to_gitignore(dir.path())?);
+        assert_eq!(
+            std::fs::read_to_string(dir.path().join(".gitignore"))?,
+            "target\n/.parari.toml\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_default_check_command() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(default_check_command(dir.path()), None);
+        std::fs::write(dir.path().join("go.mod"), "module example.com/app")?;
+        assert_eq!(default_check_command(dir.path()), Some("go build ./..."));
+        Ok(())
+    }
+
+    #[test]
+    fn test_generated_files() {
+        let settings = RepoSettings {
+            generated: vec!["src/generated/".to_string()],
+            ..RepoSettings::default()
+        };
+        assert!(settings.is_generated("Cargo.lock"));
+        assert!(settings.is_generated("web/package-lock.json"));
+        assert!(settings.is_generated("src/generated/api.rs"));
+        assert!(!settings.is_generated("src/main.rs"));
+        assert!(!settings.is_generated("Cargo.toml"));
+    }
+
+    #[test]
+    fn test_ignore_patterns() {
+        let settings = RepoSettings {
+            ignore: vec![
+                "node_modules/".to_string(),
+                "*.log".to_string(),
+                "/dist".to_string(),
+                "docs/*.html".to_string(),
+            ],
+            ..RepoSettings::default()
+        };
+        assert!(settings.is_ignored("node_modules/left-pad/index.js"));
+        assert!(settings.is_ignored("web/node_modules/a.js"));
+        assert!(!settings.is_ignored("node_modules"));
+        assert!(settings.is_ignored("build.log"));
+        assert!(settings.is_ignored("logs/build.log"));
+        assert!(settings.is_ignored("dist/app.js"));
+        assert!(!settings.is_ignored("web/dist/app.js"));
+        assert!(settings.is_ignored("docs/index.html"));
+        assert!(!settings.is_ignored("docs/api/index.html"));
+        assert!(!settings.is_ignored("src/main.rs"));
+    }
+}
@@ -0,0 +1,735 @@
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use super::paths::{MAX_WORKTREES, config_file};
+use crate::error::{Error, Result};
+
+/// User settings loaded from `~/.parari/config.toml`
+///
+/// Every field is optional in the file; missing values fall back to their defaults.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Settings {
+    /// Split view settings
+    pub ui: UiSettings,
+    /// Retention of the worktrees kept in `~/.parari/worktrees`
+    pub worktrees: WorktreeSettings,
+    /// Disk space of the `~/.parari` data directory
+    pub storage: StorageSettings,
+    /// Named sets of agents selectable with `--profile`
+    pub profiles: BTreeMap<String, Profile>,
+    /// Agents defined under their own name, usable like the built-in ones
+    pub agents: BTreeMap<String, AgentAlias>,
+    /// Weights of the signals used by `--auto-select weighted`
+    pub auto_select: ScoringWeights,
+    /// Commands run in the worktrees after the agents finish
+    pub commands: CommandSettings,
+    /// The buffer opened in the editor to write a prompt
+    pub editor: EditorSettings,
+    /// Rate limits of the agent CLIs' providers, keyed by CLI: "claude", "gemini", or "codex"
+    pub providers: BTreeMap<String, ProviderSettings>,
+}
+
+/// Text the prompt editor starts with when no template is configured
+pub const DEFAULT_PROMPT_TEMPLATE: &str = "
+# Enter your prompt above this line.
+# Lines starting with '#' will be ignored.
+# Lines like '# parari: agents: claude,codex' set options for this run
+# (agents, profile, scope, check).
+# Save and exit the editor to continue.
+# Leave empty to cancel.
+";
+
+/// Settings of the buffer opened in the editor to write a prompt
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct EditorSettings {
+    /// Text the editor starts with, e.g. with "Goal:", "Constraints:", and "Files:" sections
+    pub template: Option<String>,
+    /// File to read the template from; takes precedence over `template`
+    pub template_file: Option<PathBuf>,
+}
+
+impl EditorSettings {
+    /// The text the prompt editor starts with
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the template file cannot be read.
+    pub fn template(&self) -> Result<String> {
+        if let Some(path) = &self.template_file {
+            return std::fs::read_to_string(path).map_err(|e| Error::Config {
+                message: format!("cannot read template file {}: {e}", path.display()),
+            });
+        }
+        Ok(self
+            .template
+            .clone()
+            .unwrap_or_else(|| DEFAULT_PROMPT_TEMPLATE.to_string()))
+    }
+}
+
+/// Limits of the commands run in the worktrees after the agents finish
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct CommandSettings {
+    /// Score commands running at the same time, as test suites can use a lot of memory
+    pub score_jobs: usize,
+    /// Agents printing nothing for this long are shown as stalled
+    pub stall_timeout: Option<Age>,
+    /// Stop stalled agents instead of only flagging them
+    pub kill_stalled: bool,
+}
+
+impl Default for CommandSettings {
+    fn default() -> Self {
+        Self {
+            score_jobs: 1,
+            stall_timeout: None,
+            kill_stalled: false,
+        }
+    }
+}
+
+/// How agents of one CLI are started, set in `[providers.<cli>]` to stay clear of the
+/// provider's rate limits when several of them run
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ProviderSettings {
+    /// Agents of the CLI running at the same time; the others wait for a free slot
+    pub max_concurrent: Option<usize>,
+    /// Seconds between the starts of two agents of the CLI
+    pub stagger_secs: u64,
+}
+
+impl ProviderSettings {
+    /// Time between the starts of two agents of the CLI
+    #[must_use]
+    pub fn stagger(&self) -> Duration {
+        Duration::from_secs(self.stagger_secs)
+    }
+}
+
+/// Weights of the signals combined into a candidate's score by `--auto-select weighted`
+///
+/// A weight of 0 ignores the signal.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct ScoringWeights {
+    /// The repository's score command passed
+    pub tests: u32,
+    /// The quick check command passed
+    pub check: u32,
+    /// Number printed by the score command, e.g. from a judge
+    pub score: u32,
+    /// Fewer changed lines
+    pub diff_size: u32,
+    /// Faster agent
+    pub duration: u32,
+    /// The agent's past candidates were kept
+    pub history: u32,
+}
+
+impl Default for ScoringWeights {
+    fn default() -> Self {
+        Self {
+            tests: 4,
+            check: 2,
+            score: 2,
+            diff_size: 1,
+            duration: 1,
+            history: 1,
+        }
+    }
+}
+
+/// An agent CLI run under another name with extra flags, defined in `[agents.<name>]`
+///
+/// Lets the same CLI take part in a run several times, e.g. with different models.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct AgentAlias {
+    /// The agent's CLI: "claude", "gemini", or "codex"
+    pub agent: String,
+    /// Extra flags passed to the CLI, e.g. `["--model", "opus"]`
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Environment variables set for the CLI, e.g. the API key of another account;
+    /// a value like `$WORK_ANTHROPIC_API_KEY` is read from parari's own environment
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl AgentAlias {
+    /// The environment variables to set for the CLI of the agent `name`, with `$NAME`
+    /// values read from parari's environment
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a referenced variable is not set.
+    pub fn environment(&self, name: &str) -> Result<BTreeMap<String, String>> {
+        self.env
+            .iter()
+            .map(|(variable, value)| {
+                let Some(source) = value.strip_prefix('$') else {
+                    return Ok((variable.clone(), value.clone()));
+                };
+                std::env::var(source)
+                    .map(|value| (variable.clone(), value))
+                    .map_err(|_| Error::Config {
+                        message: format!(
+                            "{variable} of agent \"{name}\" is read from ${source}, which is not set"
+                        ),
+                    })
+            })
+            .collect()
+    }
+}
+
+/// A named set of agents, each with its own flags, selected with `--profile`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct Profile {
+    /// Agents run by the profile
+    pub agents: Vec<ProfileAgent>,
+}
+
+/// An agent of a profile
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct ProfileAgent {
+    /// The agent: "claude", "gemini", "codex", or the name of an agent alias
+    pub agent: String,
+    /// Extra flags passed to the CLI, e.g. `["--model", "opus"]`
+    #[serde(default)]
+    pub args: Vec<String>,
+    /// Number of instances to run, each in its own worktree
+    #[serde(default = "ProfileAgent::default_count")]
+    pub count: usize,
+    /// Environment variables set for the CLI, like those of an agent alias
+    #[serde(default)]
+    pub env: BTreeMap<String, String>,
+}
+
+impl ProfileAgent {
+    fn default_count() -> usize {
+        1
+    }
+}
+
+impl Profile {
+    /// Expand the profile into one named entry per instance to run
+    ///
+    /// Repeated agents are numbered from their second instance on, so two Claude
+    /// instances are named `claude` and `claude-2`.
+    #[must_use]
+    pub fn instances(&self) -> Vec<(String, &ProfileAgent)> {
+        let mut seen: BTreeMap<&str, usize> = BTreeMap::new();
+        let mut instances = Vec::new();
+        for agent in &self.agents {
+            for _ in 0..agent.count {
+                let index = seen.entry(agent.agent.as_str()).or_insert(0);
+                *index += 1;
+                let name = match *index {
+                    1 => agent.agent.clone(),
+                    n => format!("{}-{n}", agent.agent),
+                };
+                instances.push((name, agent));
+            }
+        }
+        instances
+    }
+}
+
+/// Disk space limits of the parari data directory
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct StorageSettings {
+    /// Warn before a run when the data directory uses more than this
+    pub quota: Option<Size>,
+    /// Save the output and diff of each candidate with the run, for searching,
+    /// exporting, and reviewing it later
+    pub save_logs: bool,
+    /// Remove the saved outputs and diffs of runs older than this; the runs stay in
+    /// the history
+    pub logs_max_age: Option<Age>,
+}
+
+impl Default for StorageSettings {
+    fn default() -> Self {
+        Self {
+            quota: None,
+            save_logs: true,
+            logs_max_age: None,
+        }
+    }
+}
+
+/// An amount of bytes written as a number and a binary unit: `500M`, `20G`, or `1TB`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Size(u64);
+
+impl Size {
+    const UNITS: [(char, u64); 4] = [
+        ('T', 1 << 40),
+        ('G', 1 << 30),
+        ('M', 1 << 20),
+        ('K', 1 << 10),
+    ];
+
+    /// The size in bytes
+    #[must_use]
+    pub fn bytes(self) -> u64 {
+        self.0
+    }
+}
+
+impl TryFrom<String> for Size {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let invalid = || format!("invalid size \"{value}\", expected e.g. \"500M\" or \"20G\"");
+        let trimmed = value.trim_end_matches(['B', 'b']);
+        let unit = trimmed.chars().last().ok_or_else(invalid)?;
+        let (number, multiplier) = match Self::UNITS
+            .iter()
+            .find(|(name, _)| name.eq_ignore_ascii_case(&unit))
+        {
+            Some((_, multiplier)) => (&trimmed[..trimmed.len() - 1], *multiplier),
+            None => (trimmed, 1),
+        };
+        let amount: u64 = number.trim().parse().map_err(|_| invalid())?;
+        Ok(Self(amount.saturating_mul(multiplier)))
+    }
+}
+
+impl From<Size> for String {
+    fn from(size: Size) -> Self {
+        match Size::UNITS
+            .iter()
+            .find(|(_, multiplier)| size.0 != 0 && size.0.is_multiple_of(*multiplier))
+        {
+            Some((unit, multiplier)) => format!("{}{unit}", size.0 / multiplier),
+            None => size.0.to_string(),
+        }
+    }
+}
+
+/// Retention policy for the worktrees of past runs
+///
+/// Worktrees of a parari process that is still running are never removed.
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+pub struct WorktreeSettings {
+    /// Maximum number of worktrees kept per repository
+    pub max_count: usize,
+    /// Remove worktrees older than this, in any repository
+    pub max_age: Option<Age>,
+}
+
+impl Default for WorktreeSettings {
+    fn default() -> Self {
+        Self {
+            max_count: MAX_WORKTREES,
+            max_age: None,
+        }
+    }
+}
+
+/// A duration written as a number and a unit: `30m`, `12h`, `7d`, or `2w`
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(try_from = "String", into = "String")]
+pub struct Age(Duration);
+
+impl Age {
+    const UNITS: [(char, u64); 4] = [('w', 604_800), ('d', 86_400), ('h', 3_600), ('m', 60)];
+
+    /// The age as a duration
+    #[must_use]
+    pub fn duration(self) -> Duration {
+        self.0
+    }
+}
+
+impl TryFrom<String> for Age {
+    type Error = String;
+
+    fn try_from(value: String) -> std::result::Result<Self, Self::Error> {
+        let invalid = || format!("invalid age \"{value}\", expected e.g. \"7d\" or \"12h\"");
+        let unit = value.chars().last().ok_or_else(invalid)?;
+        let seconds = Self::UNITS
+            .iter()
+            .find(|(name, _)| *name == unit)
+            .map(|(_, seconds)| *seconds)
+            .ok_or_else(invalid)?;
+        let amount: u64 = value[..value.len() - 1].parse().map_err(|_| invalid())?;
+        Ok(Self(Duration::from_secs(amount.saturating_mul(seconds))))
+    }
+}
+
+impl From<Age> for String {
+    fn from(age: Age) -> Self {
+        let seconds = age.0.as_secs();
+        let (unit, size) = Age::UNITS
+            .iter()
+            .find(|(_, size)| seconds.is_multiple_of(*size))
+            .copied()
+            .unwrap_or(('m', 60));
+        format!("{}{unit}", seconds / size)
+    }
+}
+
+/// Settings for the split view
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(default)]
+#[expect(
+    clippy::struct_excessive_bools,
+    reason = "independent display settings"
+)]
+pub struct UiSettings {
+    /// Syntax-highlight code in the Diff view (disable for very large diffs or slow terminals)
+    pub syntax_highlighting: bool,
+    /// Render diffs through `delta` when it is installed
+    pub delta: bool,
+    /// Show the colors agents print in their output instead of stripping them
+    pub ansi_colors: bool,
+    /// Color theme of the split view
+    pub theme: Theme,
+    /// List the results in a random order in every run
+    pub shuffle: bool,
+    /// Candidates changing more files than this are flagged and need an extra confirmation (0 disables)
+    pub large_diff_files: usize,
+    /// Candidates changing more lines than this are flagged and need an extra confirmation (0 disables)
+    pub large_diff_lines: usize,
+}
+
+/// Color theme of the split view
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Theme {
+    /// The standard terminal palette
+    #[default]
+    Default,
+    /// Brighter colors for low-contrast terminals
+    HighContrast,
+    /// No colors at all; emphasis uses bold/reverse video only
+    NoColor,
+}
+
+impl Default for UiSettings {
+    fn default() -> Self {
+        Self {
+            syntax_highlighting: true,
+            delta: true,
+            ansi_colors: true,
+            theme: Theme::Default,
+            shuffle: false,
+            large_diff_files: 50,
+            large_diff_lines: 2000,
+        }
+    }
+}
+
+impl Settings {
+    /// Load settings from the user configuration file
+    ///
+    /// Returns the default settings if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid TOML.
+    pub fn load() -> Result<Self> {
+        Self::load_from(&config_file())
+    }
+
+    /// Load settings from the given path
+    ///
+    /// Returns the default settings if the file does not exist.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or is not valid TOML.
+    pub fn load_from(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Ok(Self::default());
+        }
+        let content = std::fs::read_to_string(path)?;
+        Self::parse(&content)
+    }
+
+    /// Resolve an agent name to the CLI and flags to run, following agent aliases
+    #[must_use]
+    pub fn resolve_agent(&self, name: &str) -> AgentAlias {
+        self.agents
+            .get(name)
+            .cloned()
+            .unwrap_or_else(|| AgentAlias {
+                agent: name.to_string(),
+                args: Vec::new(),
+                env: BTreeMap::new(),
+            })
+    }
+
+    /// Look up a profile by name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error listing the defined profiles if there is none named `name`.
+    pub fn profile(&self, name: &str) -> Result<&Profile> {
+        self.profiles.get(name).ok_or_else(|| {
+            let defined: Vec<&str> = self.profiles.keys().map(String::as_str).collect();
+            Error::Config {
+                message: if defined.is_empty() {
+                    format!("unknown profile \"{name}\"; no profiles are defined in the config")
+                } else {
+                    format!(
+                        "unknown profile \"{name}\"; defined profiles are: {}",
+                        defined.join(", ")
+                    )
+                },
+            }
+        })
+    }
+
+    /// Parse settings from a TOML string
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the string is not valid TOML or has invalid values.
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| Error::Config {
+            message: e.to_string(),
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_empty_config_uses_defaults() -> Result<()> {
+        let settings = Settings::parse("")?;
+        assert_eq!(settings, Settings::default());
+        assert!(settings.ui.syntax_highlighting);
+        assert!(settings.ui.delta);
+        Ok(())
+    }
+
+    #[test]
+    fn test_disable_syntax_highlighting() -> Result<()> {
+        let settings = Settings::parse("[ui]\nsyntax_highlighting = false\n")?;
+        assert!(!settings.ui.syntax_highlighting);
+        Ok(())
+    }
+
+    #[test]
+    fn test_theme() -> Result<()> {
+        let settings = Settings::parse("[ui]\ntheme = \"high-contrast\"\n")?;
+        assert_eq!(settings.ui.theme, Theme::HighContrast);
+        assert!(Settings::parse("[ui]\ntheme = \"neon\"\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_worktree_retention() -> Result<()> {
+        let settings = Settings::parse("")?;
+        assert_eq!(settings.worktrees.max_count, MAX_WORKTREES);
+        assert_eq!(settings.worktrees.max_age, None);
+
+        let settings = Settings::parse("[worktrees]\nmax_count = 5\nmax_age = \"7d\"\n")?;
+        assert_eq!(settings.worktrees.max_count, 5);
+        let age = settings.worktrees.max_age.ok_or(Error::Config {
+            message: "missing max_age".to_string(),
+        })?;
+        assert_eq!(age.duration(), Duration::from_hours(7 * 24));
+        assert_eq!(String::from(age), "1w");
+
+        assert!(Settings::parse("[worktrees]\nmax_age = \"7 days\"\n").is_err());
+        assert!(Settings::parse("[worktrees]\nmax_age = \"d\"\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_storage_quota() -> Result<()> {
+        assert_eq!(Settings::parse("")?.storage.quota, None);
+
+        let settings = Settings::parse("[storage]\nquota = \"20G\"\n")?;
+        let quota = settings.storage.quota.map(Size::bytes);
+        assert_eq!(quota, Some(20 << 30));
+        assert_eq!(
+            Size::try_from("512MB".to_string()).map(Size::bytes),
+            Ok(512 << 20)
+        );
+        assert_eq!(
+            Size::try_from("1024".to_string()).map(Size::bytes),
+            Ok(1024)
+        );
+        assert_eq!(String::from(Size(2048 << 20)), "2G");
+        assert!(Settings::parse("[storage]\nquota = \"lots\"\n").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_storage_logs() -> Result<()> {
+        let settings = Settings::parse("")?;
+        assert!(settings.storage.save_logs);
+        assert_eq!(settings.storage.logs_max_age, None);
+
+        let settings = Settings::parse("[storage]\nsave_logs = false\nlogs_max_age = \"30d\"\n")?;
+        assert!(!settings.storage.save_logs);
+        assert_eq!(
+            settings.storage.logs_max_age.map(Age::duration),
+            Some(Duration::from_hours(30 * 24))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_jobs() -> Result<()> {
+        assert_eq!(Settings::parse("")?.commands.score_jobs, 1);
+        let settings = Settings::parse("[commands]\nscore_jobs = 3\n")?;
+        assert_eq!(settings.commands.score_jobs, 3);
+        Ok(())
+    }
+
+    #[test]
+    fn test_editor_template() -> Result<()> {
+        let settings = Settings::parse("")?;
+        assert_eq!(settings.editor.template()?, DEFAULT_PROMPT_TEMPLATE);
+
+        let settings = Settings::parse("[editor]\ntemplate = \"Goal:\\n\\nConstraints:\\n\"\n")?;
+        assert_eq!(settings.editor.template()?, "Goal:\n\nConstraints:\n");
+
+        let dir = tempfile::tempdir()?;
+        let file = dir.path().join("prompt.md");
+        std::fs::write(&file, "Files:\n")?;
+        let mut editor = settings.editor;
+        editor.template_file = Some(file);
+        assert_eq!(editor.template()?, "Files:\n");
+        editor.template_file = Some(dir.path().join("missing.md"));
+        assert!(editor.template().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_stall_timeout() -> Result<()> {
+        let settings = Settings::parse("")?;
+        assert_eq!(settings.commands.stall_timeout, None);
+        assert!(!settings.commands.kill_stalled);
+        let settings =
+            Settings::parse("[commands]\nstall_timeout = \"10m\"\nkill_stalled = true\n")?;
+        assert_eq!(
+            settings.commands.stall_timeout.map(Age::duration),
+            Some(Duration::from_mins(10))
+        );
+        assert!(settings.commands.kill_stalled);
+        Ok(())
+    }
+
+    #[test]
+    fn test_provider_settings() -> Result<()> {
+        let settings =
+            Settings::parse("[providers.claude]\nmax_concurrent = 2\nstagger_secs = 15\n")?;
+        let claude = &settings.providers["claude"];
+        assert_eq!(claude.max_concurrent, Some(2));
+        assert_eq!(claude.stagger(), Duration::from_secs(15));
+        assert!(!settings.providers.contains_key("codex"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_profiles() -> Result<()> {
+        let settings = Settings::parse(
+            r#"
+[profiles.thorough]
+agents = [
+    { agent = "claude", args = ["--model", "opus"], count = 2 },
+    { agent = "codex" },
+]
+"#,
+        )?;
+        let instances = settings.profile("thorough")?.instances();
+        let names: Vec<&str> = instances.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["claude", "claude-2", "codex"]);
+        assert_eq!(instances[1].1.args, ["--model", "opus"]);
+        assert!(instances[2].1.args.is_empty());
+
+        let missing = settings.profile("fast");
+        assert!(
+            matches!(missing, Err(Error::Config { message }) if message.ends_with("are: thorough"))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_agent_aliases() -> Result<()> {
+        let settings = Settings::parse(
+            r#"
+[agents.claude-opus]
+agent = "claude"
+args = ["--model", "opus"]
+"#,
+        )?;
+        let alias = settings.resolve_agent("claude-opus");
+        assert_eq!(alias.agent, "claude");
+        assert_eq!(alias.args, ["--model", "opus"]);
+
+        let builtin = settings.resolve_agent("codex");
+        assert_eq!(builtin.agent, "codex");
+        assert!(builtin.args.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_agent_alias_environment() -> Result<()> {
+        let settings = Settings::parse(
+            r#"
+[agents.claude-work]
+agent = "claude"
+env = { ANTHROPIC_API_KEY = "$PATH", CLAUDE_CONFIG_DIR = "/home/me/.claude-work" }
+
+[agents.claude-missing]
+agent = "claude"
+env = { ANTHROPIC_API_KEY = "$PARARI_TEST_UNSET_VARIABLE" }
+"#,
+        )?;
+        let env = settings
+            .resolve_agent("claude-work")
+            .environment("claude-work")?;
+        assert_eq!(
+            env.get("ANTHROPIC_API_KEY"),
+            std::env::var("PATH").ok().as_ref()
+        );
+        assert_eq!(env["CLAUDE_CONFIG_DIR"], "/home/me/.claude-work");
+
+        let missing = settings
+            .resolve_agent("claude-missing")
+            .environment("claude-missing");
+        assert!(
+            matches!(missing, Err(Error::Config { message }) if message.contains("\"claude-missing\""))
+        );
+        assert!(
+            settings
+                .resolve_agent("codex")
+                .environment("codex")?
+                .is_empty()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        let result = Settings::parse("[ui]\nsyntax_highlighting = \"yes\"\n");
+        assert!(matches!(result, Err(Error::Config { .. })));
+    }
+
+    #[test]
+    fn test_missing_file_uses_defaults() -> Result<()> {
+        let path = std::env::temp_dir().join("parari_settings_missing/config.toml");
+        assert_eq!(Settings::load_from(&path)?, Settings::default());
+        Ok(())
+    }
+}
@@ -1,7 +1,53 @@
+pub mod archive;
+pub mod budget;
+pub mod diff;
+pub mod events;
+pub mod history;
+pub mod hooks;
+pub mod policy;
+pub mod prompt;
+pub mod ranking;
+pub mod redact;
+pub mod remote;
 pub mod result;
+pub mod retry;
+pub mod run;
+pub mod schedule;
+pub mod score_cache;
+pub mod search;
+pub mod secrets;
+pub mod snapshot;
+pub mod storage;
+pub mod store;
+pub mod summary;
 pub mod task;
+pub mod transcript;
+pub mod watch;
 pub mod worktree;
 
+pub use archive::*;
+pub use budget::*;
+pub use diff::*;
+pub use events::*;
+pub use history::*;
+pub use hooks::*;
+pub use policy::*;
+pub use prompt::*;
+pub use ranking::*;
+pub use redact::*;
+pub use remote::*;
 pub use result::*;
+pub use retry::*;
+pub use run::*;
+pub use schedule::*;
+pub use score_cache::*;
+pub use search::*;
+pub use secrets::*;
+pub use snapshot::*;
+pub use storage::*;
+pub use store::*;
+pub use summary::*;
 pub use task::*;
+pub use transcript::*;
+pub use watch::*;
 pub use worktree::*;
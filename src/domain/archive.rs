@@ -0,0 +1,412 @@
+use std::fs::File;
+use std::io::Read;
+use std::path::{Component, Path};
+use std::time::Duration;
+
+use super::history::{RunRecord, load_run_from};
+use super::secrets::{SecretFinding, find_secrets_in};
+use super::store::RunStore;
+use super::task::TaskResult;
+use super::worktree::WorktreeManager;
+use crate::config::history_dir;
+use crate::error::{Error, Result};
+use crate::executor::{ExecutionResult, OutputLine};
+use crate::git;
+
+/// Name of the run's metadata in an archive, next to the logs and patches
+const RUN_FILE: &str = "run.json";
+
+/// Compression level of archives; zstd's default, fast with a good ratio
+const COMPRESSION_LEVEL: i32 = 3;
+
+/// Write a run of the user's history to a `.tar.zst` archive
+///
+/// See [`export_run_from`].
+///
+/// # Errors
+///
+/// Returns an error if the run does not exist or the archive cannot be written.
+pub fn export_run(id: &str, output: &Path) -> Result<()> {
+    export_run_from(&history_dir(), id, output)
+}
+
+/// Write a run of the given history directory to a `.tar.zst` archive
+///
+/// The archive holds `<run id>/run.json` with the prompt, candidates and notes, and
+/// the `<agent>.log` and `<agent>.patch` files saved with the run.
+///
+/// # Errors
+///
+/// Returns an error if the run does not exist or the archive cannot be written.
+pub fn export_run_from(dir: &Path, id: &str, output: &Path) -> Result<()> {
+    let record = load_run_from(dir, id)?;
+    let json = serde_json::to_vec_pretty(&record).map_err(|e| Error::History {
+        message: e.to_string(),
+    })?;
+
+    let encoder = zstd::Encoder::new(File::create(output)?, COMPRESSION_LEVEL)?;
+    let mut builder = tar::Builder::new(encoder);
+    let mut header = tar::Header::new_gnu();
+    header.set_size(u64::try_from(json.len()).unwrap_or(u64::MAX));
+    header.set_mode(0o644);
+    header.set_mtime(u64::try_from(record.started_at.timestamp()).unwrap_or(0));
+    header.set_cksum();
+    builder.append_data(&mut header, format!("{id}/{RUN_FILE}"), json.as_slice())?;
+
+    for (name, path) in saved_files(&dir.join(id)) {
+        builder.append_path_with_name(path, format!("{id}/{name}"))?;
+    }
+    builder.into_inner()?.finish()?;
+    Ok(())
+}
+
+/// The possible secrets in the diffs saved with a run of the user's history, by agent
+///
+/// See [`run_secrets_in`].
+#[must_use]
+pub fn run_secrets(id: &str) -> Vec<(String, SecretFinding)> {
+    run_secrets_in(&history_dir(), id)
+}
+
+/// The possible secrets in the diffs saved with a run of the given history directory,
+/// by agent, checked before the run is exported
+#[must_use]
+pub fn run_secrets_in(dir: &Path, id: &str) -> Vec<(String, SecretFinding)> {
+    saved_files(&dir.join(id))
+        .into_iter()
+        .filter_map(|(name, path)| {
+            let agent = name.strip_suffix(".patch")?.to_string();
+            let diff = std::fs::read_to_string(path).ok()?;
+            Some(
+                find_secrets_in(&diff)
+                    .into_iter()
+                    .map(move |finding| (agent.clone(), finding)),
+            )
+        })
+        .flatten()
+        .collect()
+}
+
+/// Add the run in a `.tar.zst` archive to the user's history
+///
+/// See [`import_run_into`].
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or is not a parari run.
+pub fn import_run(archive: &Path) -> Result<RunRecord> {
+    import_run_into(&history_dir(), archive)
+}
+
+/// Add the run in a `.tar.zst` archive written by [`export_run`] to the given history
+/// directory, replacing a run with the same id
+///
+/// Only the run's metadata, logs and patches are taken from the archive; other entries
+/// are ignored.
+///
+/// # Errors
+///
+/// Returns an error if the archive cannot be read or is not a parari run.
+pub fn import_run_into(dir: &Path, archive: &Path) -> Result<RunRecord> {
+    let invalid = |reason: String| Error::InvalidArchive {
+        path: archive.to_path_buf(),
+        reason,
+    };
+
+    let mut entries = tar::Archive::new(zstd::Decoder::new(File::open(archive)?)?);
+    let mut record = None;
+    let mut files = Vec::new();
+    for entry in entries.entries()? {
+        let mut entry = entry?;
+        if !entry.header().entry_type().is_file() {
+            continue;
+        }
+        // Entries are `<run id>/<file>`; anything else could write outside the history
+        let path = entry.path()?.into_owned();
+        let components: Vec<_> = path.components().collect();
+        let [Component::Normal(_), Component::Normal(name)] = components.as_slice() else {
+            continue;
+        };
+        let name = name.to_string_lossy().into_owned();
+        let mut content = Vec::new();
+        entry.read_to_end(&mut content)?;
+        if name == RUN_FILE {
+            record = Some(
+                serde_json::from_slice::<RunRecord>(&content)
+                    .map_err(|e| invalid(format!("{RUN_FILE}: {e}")))?,
+            );
+        } else if is_saved_file(&name) {
+            files.push((name, content));
+        }
+    }
+
+    let record = record.ok_or_else(|| invalid(format!("no {RUN_FILE} found")))?;
+    if !is_safe_id(&record.id) {
+        return Err(invalid(format!("invalid run id `{}`", record.id)));
+    }
+    // Agent names become worktree and file names when the run is reviewed again
+    if let Some(candidate) = record
+        .candidates
+        .iter()
+        .find(|candidate| !is_plain_name(&candidate.agent))
+    {
+        return Err(invalid(format!("invalid agent name `{}`", candidate.agent)));
+    }
+    RunStore::open(dir)?.save(&record)?;
+    let run_dir = dir.join(&record.id);
+    std::fs::create_dir_all(&run_dir)?;
+    for (name, content) in files {
+        std::fs::write(run_dir.join(name), content)?;
+    }
+    Ok(record)
+}
+
+/// Recreate the candidates of a run in new worktrees, to review them again
+///
+/// Each worktree is checked out at the run's base commit and the agent's saved patch,
+/// if any, is applied to it. The saved log stands in for the agent's output.
+///
+/// # Errors
+///
+/// Returns an error if the run has no base commit, the repository does not have it,
+/// or a worktree cannot be created or patched.
+pub async fn restore_candidates(
+    record: &RunRecord,
+    manager: &mut WorktreeManager,
+) -> Result<Vec<TaskResult>> {
+    restore_candidates_from(&history_dir(), record, manager).await
+}
+
+/// Recreate the candidates of a run of the given history directory in new worktrees
+///
+/// See [`restore_candidates`].
+///
+/// # Errors
+///
+/// Returns an error if the run has no base commit, the repository does not have it,
+/// or a worktree cannot be created or patched.
+pub async fn restore_candidates_from(
+    dir: &Path,
+    record: &RunRecord,
+    manager: &mut WorktreeManager,
+) -> Result<Vec<TaskResult>> {
+    let cannot_review = |reason: String| Error::CannotReview {
+        id: record.id.clone(),
+        reason,
+    };
+    let base = record
+        .base_commit
+        .as_deref()
+        .ok_or_else(|| cannot_review("the run was not based on a commit".to_string()))?;
+    if !git::has_commit(manager.repo_path(), base).await {
+        return Err(cannot_review(format!(
+            "commit {base} is not in this repository; fetch it first"
+        )));
+    }
+
+    let names: Vec<&str> = record.candidates.iter().map(|c| c.agent.as_str()).collect();
+    if let Some(name) = names.iter().find(|name| !is_plain_name(name)) {
+        return Err(cannot_review(format!("invalid agent name `{name}`")));
+    }
+    manager.create_worktrees(&names).await?;
+
+    let run_dir = dir.join(&record.id);
+    let mut results = Vec::new();
+    for candidate in &record.candidates {
+        let worktree = manager
+            .get_worktree(&candidate.agent)
+            .ok_or_else(|| cannot_review(format!("no worktree for {}", candidate.agent)))?
+            .path
+            .clone();
+        git::checkout_commit(&worktree, base).await?;
+        let patch = run_dir.join(format!("{}.patch", candidate.agent));
+        if patch.exists() {
+            git::apply_patch(&worktree, &patch).await?;
+        }
+        let change_summary = git::get_change_summary(manager.repo_path(), &worktree)
+            .await
+            .ok();
+
+        let log = std::fs::read_to_string(run_dir.join(format!("{}.log", candidate.agent)))
+            .unwrap_or_default();
+        results.push(TaskResult {
+            execution: ExecutionResult {
+                executor_name: candidate.agent.clone(),
+                success: candidate.success,
+                output_lines: log
+                    .lines()
+                    .map(|line| OutputLine::Stdout(line.to_string()))
+                    .collect(),
+                stdout: log,
+                stderr: candidate.error.clone().unwrap_or_default(),
+                exit_code: None,
+                error_kind: candidate.error_kind,
+            },
+            worktree_path: worktree,
+            change_summary,
+            summary: None,
+            score: None,
+            check: None,
+            duration: Duration::ZERO,
+            first_output: None,
+            retries: Vec::new(),
+            policy: candidate.policy.clone(),
+        });
+    }
+    Ok(results)
+}
+
+/// Logs and patches saved with a run, by file name, sorted
+fn saved_files(run_dir: &Path) -> Vec<(String, std::path::PathBuf)> {
+    let Ok(entries) = std::fs::read_dir(run_dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| path.is_file())
+        .filter_map(|path| {
+            let name = path.file_name()?.to_str()?.to_string();
+            is_saved_file(&name).then_some((name, path))
+        })
+        .collect();
+    files.sort();
+    files
+}
+
+/// Whether a file name is an agent's log or patch
+fn is_saved_file(name: &str) -> bool {
+    Path::new(name)
+        .extension()
+        .is_some_and(|ext| ext == "log" || ext == "patch")
+}
+
+/// Whether an agent name is a single plain path component, so that the worktree and
+/// files named after it stay where they belong
+fn is_plain_name(name: &str) -> bool {
+    let mut components = Path::new(name).components();
+    matches!(
+        (components.next(), components.next()),
+        (Some(Component::Normal(_)), None)
+    ) && !name.contains(['/', '\\'])
+}
+
+/// Whether a run id can be used as a directory name in the history
+fn is_safe_id(id: &str) -> bool {
+    !id.is_empty()
+        && id
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || c == '-' || c == '_')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{CandidateRecord, RunInfo, save_agent_patch_to};
+
+    #[test]
+    fn test_export_and_import_run() -> Result<()> {
+        let source = tempfile::tempdir()?;
+        let mut info = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        info.id = "20250101-120000".to_string();
+        let mut record = RunRecord::new(&info, &[], &[]);
+        record.candidates.push(CandidateRecord {
+            agent: "codex".to_string(),
+            success: true,
+            files_changed: 1,
+            insertions: 1,
+            deletions: 0,
+            rejected: false,
+            note: Some("handles empty input #edge-cases".to_string()),
+            tags: vec!["edge-cases".to_string()],
+            error: None,
+            error_kind: None,
+            policy: vec!["src/lexer.rs has no SPDX-License-Identifier header".to_string()],
+        });
+        record.save_to(source.path())?;
+        let diff = "+++ b/src/lexer.rs\n+    if input.is_empty() { return Vec::new(); }\n";
+        save_agent_patch_to(source.path(), &record.id, "codex", diff)?;
+        assert!(run_secrets_in(source.path(), &record.id).is_empty());
+
+        let archive = source.path().join("run.tar.zst");
+        export_run_from(source.path(), &record.id, &archive)?;
+
+        let target = tempfile::tempdir()?;
+        assert_eq!(import_run_into(target.path(), &archive)?, record);
+        assert_eq!(load_run_from(target.path(), &record.id)?, record);
+        assert_eq!(
+            std::fs::read_to_string(target.path().join(&record.id).join("codex.patch"))?,
+            diff
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_rejects_other_archives() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let archive = dir.path().join("other.tar.zst");
+        let mut builder = tar::Builder::new(zstd::Encoder::new(File::create(&archive)?, 0)?);
+        let content = b"not a run";
+        let mut header = tar::Header::new_gnu();
+        header.set_size(u64::try_from(content.len()).unwrap_or(u64::MAX));
+        header.set_cksum();
+        builder.append_data(&mut header, "notes/readme.txt", content.as_slice())?;
+        builder.into_inner()?.finish()?;
+
+        assert!(matches!(
+            import_run_into(dir.path(), &archive),
+            Err(Error::InvalidArchive { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_import_rejects_agent_paths() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut info = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        info.id = "20250101-120000".to_string();
+        let mut record = RunRecord::new(&info, &[], &[]);
+        record.candidates.push(CandidateRecord {
+            agent: "../../../.ssh/authorized_keys".to_string(),
+            success: true,
+            files_changed: 1,
+            insertions: 1,
+            deletions: 0,
+            rejected: false,
+            note: None,
+            tags: vec![],
+            error: None,
+            error_kind: None,
+            policy: Vec::new(),
+        });
+        let json = serde_json::to_vec(&record).map_err(|e| Error::History {
+            message: e.to_string(),
+        })?;
+
+        let archive = dir.path().join("evil.tar.zst");
+        let mut builder = tar::Builder::new(zstd::Encoder::new(File::create(&archive)?, 0)?);
+        let mut header = tar::Header::new_gnu();
+        header.set_size(u64::try_from(json.len()).unwrap_or(u64::MAX));
+        header.set_cksum();
+        builder.append_data(
+            &mut header,
+            format!("{}/{RUN_FILE}", record.id),
+            json.as_slice(),
+        )?;
+        builder.into_inner()?.finish()?;
+
+        let history = dir.path().join("history");
+        assert!(matches!(
+            import_run_into(&history, &archive),
+            Err(Error::InvalidArchive { .. })
+        ));
+        assert!(load_run_from(&history, &record.id).is_err());
+
+        for name in ["codex", "claude-2"] {
+            assert!(is_plain_name(name));
+        }
+        for name in ["", ".", "..", "../codex", "a/b", "/tmp/x", "codex/", "a\\b"] {
+            assert!(!is_plain_name(name), "{name}");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,234 @@
+use std::sync::Mutex;
+
+use serde_json::Value;
+use tokio::sync::watch;
+
+use crate::executor::traits::OutputLine;
+
+/// Tokens and cost an agent reported in its structured output
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Usage {
+    /// Input and output tokens, without input read from the prompt cache
+    pub tokens: u64,
+    /// Cost in US dollars, if the tool reported it
+    pub cost: Option<f64>,
+}
+
+/// Limits on what a single agent may spend
+#[derive(Debug, Clone, Copy, Default, PartialEq)]
+pub struct Budget {
+    /// Most US dollars an agent may spend
+    pub max_cost: Option<f64>,
+    /// Most tokens an agent may use
+    pub max_tokens: Option<u64>,
+}
+
+impl Budget {
+    /// Whether neither limit is set
+    #[must_use]
+    pub fn is_unlimited(&self) -> bool {
+        self.max_cost.is_none() && self.max_tokens.is_none()
+    }
+
+    /// Why `usage` is over the budget, or `None` while it is within it
+    #[must_use]
+    pub fn exceeded_by(&self, usage: &Usage) -> Option<String> {
+        if let Some(max) = self.max_tokens
+            && usage.tokens > max
+        {
+            return Some(format!(
+                "Truncated after using {} tokens, over the budget of {max}",
+                usage.tokens
+            ));
+        }
+        match (self.max_cost, usage.cost) {
+            (Some(max), Some(cost)) if cost > max => Some(format!(
+                "Truncated after spending ${cost:.2}, over the budget of ${max:.2}"
+            )),
+            _ => None,
+        }
+    }
+}
+
+/// Adds up the usage an agent reports while it runs, and tells when it goes over budget
+#[derive(Debug)]
+pub struct Meter {
+    budget: Budget,
+    state: Mutex<MeterState>,
+    exceeded: watch::Sender<Option<String>>,
+}
+
+/// What the meter has counted so far
+#[derive(Debug, Default)]
+struct MeterState {
+    usage: Usage,
+    /// Claude repeats the usage of a message for each of its content blocks
+    message_id: Option<String>,
+}
+
+impl Meter {
+    /// Create a meter for an agent that has not reported any usage yet
+    #[must_use]
+    pub fn new(budget: Budget) -> Self {
+        Self {
+            budget,
+            state: Mutex::new(MeterState::default()),
+            exceeded: watch::Sender::new(None),
+        }
+    }
+
+    /// Count the usage reported on a line of output
+    pub fn record(&self, line: &OutputLine) {
+        let OutputLine::Stdout(line) = line else {
+            return;
+        };
+        // Usage is only reported in structured output, a JSON object per line
+        if !line.starts_with('{') {
+            return;
+        }
+        let Ok(event) = serde_json::from_str::<Value>(line) else {
+            return;
+        };
+        let Ok(mut state) = self.state.lock() else {
+            return;
+        };
+        record_event(&mut state, &event);
+        if let Some(reason) = self.budget.exceeded_by(&state.usage) {
+            self.exceeded.send_if_modified(|exceeded| {
+                if exceeded.is_some() {
+                    return false;
+                }
+                *exceeded = Some(reason);
+                true
+            });
+        }
+    }
+
+    /// The usage counted so far
+    #[must_use]
+    pub fn usage(&self) -> Usage {
+        self.state
+            .lock()
+            .map(|state| state.usage)
+            .unwrap_or_default()
+    }
+
+    /// Wait until the agent goes over budget, returning why
+    ///
+    /// Never returns for an agent within its budget.
+    pub async fn exceeded(&self) -> String {
+        let mut exceeded = self.exceeded.subscribe();
+        match exceeded.wait_for(Option::is_some).await {
+            Ok(reason) => reason.clone().unwrap_or_default(),
+            // The sender lives as long as `self`, so this is not reached
+            Err(_) => std::future::pending().await,
+        }
+    }
+}
+
+/// Add the usage in one event of Claude's, Codex's, or Gemini's JSON output
+fn record_event(state: &mut MeterState, event: &Value) {
+    match event["type"].as_str() {
+        // Claude: usage of each model response, and the totals when done
+        Some("assistant") => {
+            let message = &event["message"];
+            let id = message["id"].as_str().map(str::to_string);
+            if id.is_some() && id == state.message_id {
+                return;
+            }
+            state.message_id = id;
+            state.usage.tokens += tokens(&message["usage"]);
+        }
+        Some("result") => {
+            let total =
+                tokens(&event["usage"]).max(event["stats"]["total_tokens"].as_u64().unwrap_or(0));
+            state.usage.tokens = state.usage.tokens.max(total);
+            if let Some(cost) = event["total_cost_usd"].as_f64() {
+                state.usage.cost = Some(cost);
+            }
+        }
+        // Codex: usage of each turn
+        Some("turn.completed") => state.usage.tokens += tokens(&event["usage"]),
+        _ => {}
+    }
+}
+
+/// Tokens in a usage object, without input read from the prompt cache
+fn tokens(usage: &Value) -> u64 {
+    let count = |field: &str| usage[field].as_u64().unwrap_or(0);
+    let input = count("input_tokens").saturating_sub(count("cached_input_tokens"));
+    input + count("cache_creation_input_tokens") + count("output_tokens")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdout(line: &str) -> OutputLine {
+        OutputLine::Stdout(line.to_string())
+    }
+
+    #[test]
+    fn test_meter_counts_claude_messages_once() {
+        let meter = Meter::new(Budget::default());
+        let message = r#"{"type":"assistant","message":{"id":"msg_1","usage":{"input_tokens":100,"cache_read_input_tokens":5000,"output_tokens":20}}}"#;
+        meter.record(&stdout(message));
+        meter.record(&stdout(message));
+        assert_eq!(meter.usage().tokens, 120);
+
+        meter.record(&stdout(
+            r#"{"type":"result","total_cost_usd":0.42,"usage":{"input_tokens":300,"output_tokens":50}}"#,
+        ));
+        assert_eq!(
+            meter.usage(),
+            Usage {
+                tokens: 350,
+                cost: Some(0.42)
+            }
+        );
+    }
+
+    #[test]
+    fn test_meter_counts_codex_turns() {
+        let meter = Meter::new(Budget::default());
+        let turn = r#"{"type":"turn.completed","usage":{"input_tokens":1000,"cached_input_tokens":800,"output_tokens":50}}"#;
+        meter.record(&stdout(turn));
+        meter.record(&stdout(turn));
+        meter.record(&stdout("plain text"));
+        assert_eq!(meter.usage().tokens, 500);
+    }
+
+    #[test]
+    fn test_budget_exceeded_by() {
+        let budget = Budget {
+            max_cost: Some(1.0),
+            max_tokens: Some(1000),
+        };
+        let usage = |tokens, cost| Usage { tokens, cost };
+        assert_eq!(budget.exceeded_by(&usage(1000, Some(1.0))), None);
+        assert_eq!(
+            budget.exceeded_by(&usage(1001, None)).as_deref(),
+            Some("Truncated after using 1001 tokens, over the budget of 1000")
+        );
+        assert_eq!(
+            budget.exceeded_by(&usage(10, Some(1.5))).as_deref(),
+            Some("Truncated after spending $1.50, over the budget of $1.00")
+        );
+        assert!(Budget::default().is_unlimited());
+    }
+
+    #[tokio::test]
+    async fn test_meter_reports_exceeded_budget() {
+        let meter = Meter::new(Budget {
+            max_cost: None,
+            max_tokens: Some(100),
+        });
+        meter.record(&stdout(
+            r#"{"type":"turn.completed","usage":{"input_tokens":90,"output_tokens":20}}"#,
+        ));
+        assert_eq!(
+            meter.exceeded().await,
+            "Truncated after using 110 tokens, over the budget of 100"
+        );
+    }
+}
@@ -0,0 +1,396 @@
+use std::fmt::Write;
+
+use super::history::hash_text;
+
+/// A unified diff, as printed by `git diff`, split into files, hunks, and lines
+///
+/// The parts borrow from the diff text, so parsing copies nothing.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Diff<'a> {
+    /// Lines outside of any file, such as a message in place of a diff
+    pub preamble: Vec<&'a str>,
+    /// The changed files in the order they appear
+    pub files: Vec<FileDiff<'a>>,
+}
+
+/// The changes to a single file
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct FileDiff<'a> {
+    /// Path of the file after the change, relative to the repository root
+    pub path: &'a str,
+    /// Header lines from `diff --git` up to the first hunk
+    pub header: Vec<&'a str>,
+    /// The changed regions of the file
+    pub hunks: Vec<Hunk<'a>>,
+}
+
+/// A changed region of a file, starting with an `@@` line
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Hunk<'a> {
+    /// The `@@ -a,b +c,d @@` line
+    pub header: &'a str,
+    /// First line of the region in the old file
+    pub old_start: usize,
+    /// First line of the region in the new file
+    pub new_start: usize,
+    /// Lines of the region
+    pub lines: Vec<DiffLine<'a>>,
+}
+
+/// A line of a hunk, without its `+`, `-`, or ` ` prefix
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DiffLine<'a> {
+    Context(&'a str),
+    Added(&'a str),
+    Removed(&'a str),
+    /// The `\ No newline at end of file` marker
+    NoNewline(&'a str),
+}
+
+impl DiffLine<'_> {
+    /// Parse a line inside a hunk, or `None` if the line ends the hunk
+    #[must_use]
+    pub fn parse(line: &str) -> Option<DiffLine<'_>> {
+        if line.is_empty() {
+            // Some tools strip the trailing space of empty context lines
+            return Some(DiffLine::Context(""));
+        }
+        let (prefix, text) = line.split_at(1);
+        match prefix {
+            " " => Some(DiffLine::Context(text)),
+            "+" => Some(DiffLine::Added(text)),
+            "-" => Some(DiffLine::Removed(text)),
+            "\\" => Some(DiffLine::NoNewline(line)),
+            _ => None,
+        }
+    }
+
+    /// Write the line back in unified diff form
+    fn write_to(self, out: &mut String) {
+        let _ = match self {
+            DiffLine::Context(text) => writeln!(out, " {text}"),
+            DiffLine::Added(text) => writeln!(out, "+{text}"),
+            DiffLine::Removed(text) => writeln!(out, "-{text}"),
+            DiffLine::NoNewline(line) => writeln!(out, "{line}"),
+        };
+    }
+}
+
+impl<'a> Hunk<'a> {
+    /// Start a hunk from its `@@` line
+    fn new(header: &'a str) -> Self {
+        // @@ -12,3 +12,4 @@ fn context
+        let start = |sign: char| {
+            header
+                .split_whitespace()
+                .find_map(|part| part.strip_prefix(sign))
+                .and_then(|range| range.split(',').next()?.parse().ok())
+                .unwrap_or(0)
+        };
+        Self {
+            header,
+            old_start: start('-'),
+            new_start: start('+'),
+            lines: Vec::new(),
+        }
+    }
+
+    /// Number of added lines
+    #[must_use]
+    pub fn insertions(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Added(_)))
+            .count()
+    }
+
+    /// Number of removed lines
+    #[must_use]
+    pub fn deletions(&self) -> usize {
+        self.lines
+            .iter()
+            .filter(|line| matches!(line, DiffLine::Removed(_)))
+            .count()
+    }
+}
+
+impl FileDiff<'_> {
+    /// Whether git reported the file as binary instead of printing its lines
+    #[must_use]
+    pub fn is_binary(&self) -> bool {
+        self.header
+            .iter()
+            .any(|line| line.starts_with("Binary files ") || *line == "GIT binary patch")
+    }
+
+    /// Number of added lines
+    #[must_use]
+    pub fn insertions(&self) -> usize {
+        self.hunks.iter().map(Hunk::insertions).sum()
+    }
+
+    /// Number of removed lines
+    #[must_use]
+    pub fn deletions(&self) -> usize {
+        self.hunks.iter().map(Hunk::deletions).sum()
+    }
+
+    /// Number of lines the file takes up in the diff, including its header
+    #[must_use]
+    pub fn line_count(&self) -> usize {
+        self.header.len()
+            + self
+                .hunks
+                .iter()
+                .map(|hunk| 1 + hunk.lines.len())
+                .sum::<usize>()
+    }
+
+    /// The file's changes as a patch that `git apply` accepts
+    #[must_use]
+    pub fn to_patch(&self) -> String {
+        let mut out = String::new();
+        for line in &self.header {
+            out.push_str(line);
+            out.push('\n');
+        }
+        for hunk in &self.hunks {
+            out.push_str(hunk.header);
+            out.push('\n');
+            for line in &hunk.lines {
+                line.write_to(&mut out);
+            }
+        }
+        out
+    }
+}
+
+/// Path of the file after the change, from a `diff --git a/old b/new` line
+#[must_use]
+pub fn section_path(line: &str) -> Option<&str> {
+    line.strip_prefix("diff --git ")?
+        .rsplit_once(" b/")
+        .map(|(_, path)| path)
+}
+
+impl<'a> Diff<'a> {
+    /// Parse the output of `git diff`
+    ///
+    /// Lines that are not part of a diff are kept in the preamble or the header of
+    /// the current file, so any text can be parsed.
+    #[must_use]
+    pub fn parse(text: &'a str) -> Self {
+        let mut diff = Diff::default();
+        for line in text.lines() {
+            if let Some(path) = section_path(line) {
+                diff.files.push(FileDiff {
+                    path,
+                    header: vec![line],
+                    hunks: Vec::new(),
+                });
+                continue;
+            }
+            // A hunk without a file header, e.g. a diff cut down to one hunk
+            if diff.files.is_empty() && line.starts_with("@@") {
+                diff.files.push(FileDiff {
+                    path: "",
+                    header: Vec::new(),
+                    hunks: Vec::new(),
+                });
+            }
+            let Some(file) = diff.files.last_mut() else {
+                diff.preamble.push(line);
+                continue;
+            };
+            if line.starts_with("@@") {
+                file.hunks.push(Hunk::new(line));
+                continue;
+            }
+            match (file.hunks.last_mut(), DiffLine::parse(line)) {
+                (Some(hunk), Some(parsed)) => hunk.lines.push(parsed),
+                // git never prints other lines after the hunks of a file
+                (None, _) => file.header.push(line),
+                _ => diff.preamble.push(line),
+            }
+        }
+        diff
+    }
+
+    /// Whether the diff has no changed files
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.files.is_empty()
+    }
+
+    /// Added and removed lines as `path:+line`, sorted and without duplicates
+    ///
+    /// Blank lines and trailing whitespace are ignored.
+    #[must_use]
+    pub fn changed_lines(&self) -> Vec<String> {
+        let mut lines: Vec<String> =
+            self.files
+                .iter()
+                .flat_map(|file| {
+                    file.hunks.iter().flat_map(|hunk| &hunk.lines).filter_map(
+                        move |line| match line {
+                            DiffLine::Added(text) if !text.trim_end().is_empty() => {
+                                Some(format!("{}:+{}", file.path, text.trim_end()))
+                            }
+                            DiffLine::Removed(text) if !text.trim_end().is_empty() => {
+                                Some(format!("{}:-{}", file.path, text.trim_end()))
+                            }
+                            _ => None,
+                        },
+                    )
+                })
+                .collect();
+        lines.sort();
+        lines.dedup();
+        lines
+    }
+
+    /// Hash of the changes, equal for diffs that make the same changes
+    ///
+    /// Blob ids, hunk line numbers, trailing whitespace, and blank lines are ignored.
+    /// Returns `None` for an empty diff.
+    #[must_use]
+    pub fn normalized_hash(&self) -> Option<String> {
+        if self.is_empty() {
+            return None;
+        }
+        let mut normalized = String::new();
+        for file in &self.files {
+            for line in file
+                .header
+                .iter()
+                .filter(|line| !line.starts_with("index "))
+            {
+                normalized.push_str(line.trim_end());
+                normalized.push('\n');
+            }
+            for hunk in &file.hunks {
+                normalized.push_str("@@\n");
+                for line in &hunk.lines {
+                    let mut text = String::new();
+                    line.write_to(&mut text);
+                    let text = text.trim_end();
+                    if text.len() > 1 {
+                        normalized.push_str(text);
+                        normalized.push('\n');
+                    }
+                }
+            }
+        }
+        Some(hash_text(&normalized))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DIFF: &str = "\
+diff --git a/src/lib.rs b/src/lib.rs
+index 1111111..2222222 100644
+--- a/src/lib.rs
++++ b/src/lib.rs
+@@ -1,3 +1,3 @@ fn main
+ fn main() {
+-    old();
++    new();
+ }
+diff --git a/logo.png b/logo.png
+new file mode 100644
+index 0000000..3333333
+Binary files /dev/null and b/logo.png differ
+diff --git a/notes.txt b/notes.txt
+new file mode 100644
+--- /dev/null
++++ b/notes.txt
+@@ -0,0 +1 @@
++hello
+\\ No newline at end of file
+";
+
+    #[test]
+    fn test_parse() {
+        let diff = Diff::parse(DIFF);
+        assert!(diff.preamble.is_empty());
+        let paths: Vec<&str> = diff.files.iter().map(|file| file.path).collect();
+        assert_eq!(paths, ["src/lib.rs", "logo.png", "notes.txt"]);
+
+        let lib = &diff.files[0];
+        assert_eq!(lib.header.len(), 4);
+        assert_eq!(lib.hunks[0].old_start, 1);
+        assert_eq!(lib.hunks[0].new_start, 1);
+        assert_eq!(
+            lib.hunks[0].lines,
+            [
+                DiffLine::Context("fn main() {"),
+                DiffLine::Removed("    old();"),
+                DiffLine::Added("    new();"),
+                DiffLine::Context("}"),
+            ]
+        );
+        assert_eq!((lib.insertions(), lib.deletions()), (1, 1));
+        assert!(!lib.is_binary());
+        assert!(diff.files[1].is_binary());
+        assert_eq!(diff.files[2].hunks[0].new_start, 1);
+    }
+
+    #[test]
+    fn test_round_trip() {
+        let diff = Diff::parse(DIFF);
+        let patch: String = diff.files.iter().map(FileDiff::to_patch).collect();
+        assert_eq!(patch, DIFF);
+        let lines: usize = diff.files.iter().map(FileDiff::line_count).sum();
+        assert_eq!(lines, DIFF.lines().count());
+    }
+
+    #[test]
+    fn test_parse_text_without_diff() {
+        let diff = Diff::parse("No changes detected.\n");
+        assert!(diff.is_empty());
+        assert_eq!(diff.preamble, ["No changes detected."]);
+        assert_eq!(diff.normalized_hash(), None);
+    }
+
+    #[test]
+    fn test_parse_hunk_without_file_header() {
+        let diff = Diff::parse("@@ -1 +1 @@\n-old\n+new\n");
+        assert!(diff.preamble.is_empty());
+        assert_eq!(diff.files[0].path, "");
+        assert_eq!(
+            diff.files[0].hunks[0].lines,
+            [DiffLine::Removed("old"), DiffLine::Added("new")]
+        );
+    }
+
+    #[test]
+    fn test_changed_lines() {
+        let diff = Diff::parse(
+            "diff --git a/a.rs b/a.rs\n--- a/a.rs\n+++ b/a.rs\n@@ -1 +1 @@\n-old\n+new\n+extra\n+\n",
+        );
+        assert_eq!(
+            diff.changed_lines(),
+            ["a.rs:+extra", "a.rs:+new", "a.rs:-old"]
+        );
+    }
+
+    #[test]
+    fn test_normalized_hash() {
+        let moved = DIFF
+            .replace("index 1111111..2222222", "index 4444444..5555555")
+            .replace("@@ -1,3 +1,3 @@ fn main", "@@ -10,3 +10,3 @@")
+            .replace("new();", "new();   ");
+        assert_eq!(
+            Diff::parse(DIFF).normalized_hash(),
+            Diff::parse(&moved).normalized_hash()
+        );
+        let different = DIFF.replace("new();", "other();");
+        assert_ne!(
+            Diff::parse(DIFF).normalized_hash(),
+            Diff::parse(&different).normalized_hash()
+        );
+    }
+}
@@ -0,0 +1,167 @@
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use std::sync::Mutex;
+
+use chrono::{DateTime, Local};
+use serde::Serialize;
+
+use crate::error::Result;
+use crate::executor::OutputLine;
+
+/// Something that happened during a run, emitted as it happens by `--json-stream`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+#[serde(tag = "event", rename_all = "snake_case")]
+pub enum RunEvent {
+    /// An agent started working in its worktree
+    AgentStarted { agent: String },
+    /// An agent printed a line
+    Output {
+        agent: String,
+        stream: Stream,
+        line: String,
+    },
+    /// An agent finished, successfully or not
+    AgentFinished {
+        agent: String,
+        success: bool,
+        exit_code: Option<i32>,
+    },
+    /// A result was placed in the list offered for review (rank 1 is shown first)
+    ResultRanked {
+        rank: usize,
+        agent: String,
+        success: bool,
+        files_changed: usize,
+        insertions: usize,
+        deletions: usize,
+    },
+    /// A result was applied to the target directory
+    Applied {
+        agent: String,
+        created: usize,
+        updated: usize,
+        deleted: usize,
+    },
+}
+
+/// Output stream a line was printed to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Stream {
+    Stdout,
+    Stderr,
+}
+
+impl RunEvent {
+    /// Event for a line of an agent's output
+    #[must_use]
+    pub fn output(agent: &str, line: &OutputLine) -> Self {
+        let (stream, line) = match line {
+            OutputLine::Stdout(line) => (Stream::Stdout, line),
+            OutputLine::Stderr(line) => (Stream::Stderr, line),
+        };
+        Self::Output {
+            agent: agent.to_string(),
+            stream,
+            line: line.clone(),
+        }
+    }
+}
+
+/// Receives the events of a run as they happen
+pub trait EventSink: Send + Sync {
+    /// Handle one event
+    fn emit(&self, event: &RunEvent);
+}
+
+/// An event with the time it happened, as written to the stream
+#[derive(Serialize)]
+struct Record<'a> {
+    time: DateTime<Local>,
+    #[serde(flatten)]
+    event: &'a RunEvent,
+}
+
+/// Writes events as newline-delimited JSON
+pub struct JsonStream {
+    writer: Mutex<Box<dyn Write + Send>>,
+}
+
+impl JsonStream {
+    /// Stream events to a new file at `path`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be created.
+    pub fn create(path: &Path) -> Result<Self> {
+        Ok(Self::new(Box::new(File::create(path)?)))
+    }
+
+    /// Stream events to any writer
+    #[must_use]
+    pub fn new(writer: Box<dyn Write + Send>) -> Self {
+        Self {
+            writer: Mutex::new(writer),
+        }
+    }
+}
+
+impl EventSink for JsonStream {
+    fn emit(&self, event: &RunEvent) {
+        let record = Record {
+            time: Local::now(),
+            event,
+        };
+        let Ok(json) = serde_json::to_string(&record) else {
+            return;
+        };
+        // A consumer that went away must not stop the run
+        if let Ok(mut writer) = self.writer.lock() {
+            let _ = writeln!(writer, "{json}");
+            let _ = writer.flush();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_event_json() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let event = RunEvent::output("claude", &OutputLine::Stderr("oops".to_string()));
+        let json = serde_json::to_value(&event)?;
+        assert_eq!(json["event"], "output");
+        assert_eq!(json["agent"], "claude");
+        assert_eq!(json["stream"], "stderr");
+        assert_eq!(json["line"], "oops");
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_stream_writes_lines() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("events.jsonl");
+        let stream = JsonStream::create(&path)?;
+        stream.emit(&RunEvent::AgentStarted {
+            agent: "gemini".to_string(),
+        });
+        stream.emit(&RunEvent::AgentFinished {
+            agent: "gemini".to_string(),
+            success: true,
+            exit_code: Some(0),
+        });
+
+        let content = std::fs::read_to_string(&path)?;
+        let lines: Vec<serde_json::Value> = content
+            .lines()
+            .map(serde_json::from_str)
+            .collect::<std::result::Result<_, _>>()?;
+        assert_eq!(lines.len(), 2);
+        assert_eq!(lines[0]["event"], "agent_started");
+        assert_eq!(lines[1]["success"], true);
+        assert!(lines[1]["time"].is_string());
+        Ok(())
+    }
+}
@@ -0,0 +1,630 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::result::ResultInfo;
+use super::run::{RunInfo, RunOptions};
+use super::snapshot::RunSnapshot;
+use super::store::{AgentStats, RUN_DATABASE, RunStore};
+use crate::config::history_dir;
+use crate::error::{Error, Result};
+use crate::executor::{ErrorKind, OutputLine};
+
+/// The user's impressions of a candidate, collected while reviewing results
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Review {
+    /// Whether the candidate was marked as rejected
+    pub rejected: bool,
+    /// Free-form note, e.g. "nice tests but broke API"
+    pub note: Option<String>,
+}
+
+/// A run as stored in the history
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct RunRecord {
+    /// Identifier of the run
+    pub id: String,
+    /// The prompt given to every agent
+    pub prompt: String,
+    /// Directory the agents were run against
+    pub working_dir: PathBuf,
+    /// Commit the worktrees were created from
+    pub base_commit: Option<String>,
+    /// When the run started
+    pub started_at: DateTime<Local>,
+    /// One entry per agent that produced a result
+    pub candidates: Vec<CandidateRecord>,
+    /// Hash of the prompt, to find runs of the same prompt
+    #[serde(default)]
+    pub prompt_hash: String,
+    /// The result the user applied, if any
+    #[serde(default)]
+    pub decision: Option<Decision>,
+    /// URL of the issue the prompt was taken from
+    #[serde(default)]
+    pub issue: Option<String>,
+    /// Uncommitted files and agent versions the run started with
+    #[serde(default)]
+    pub snapshot: RunSnapshot,
+    /// Options the agents were run with
+    #[serde(default)]
+    pub options: RunOptions,
+    /// Identifier of the run this one repeats with `parari rerun`
+    #[serde(default)]
+    pub rerun_of: Option<String>,
+}
+
+/// Which candidate of a run was applied
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct Decision {
+    /// Name of the applied agent
+    pub agent: String,
+    /// Whether `--auto-select` chose it instead of the user
+    pub auto_selected: bool,
+    /// When the result was applied
+    pub decided_at: DateTime<Local>,
+}
+
+/// Stable hash of a prompt, ignoring surrounding whitespace
+#[must_use]
+pub fn prompt_hash(prompt: &str) -> String {
+    hash_text(prompt.trim())
+}
+
+/// Stable hash of a text that does not change between versions (64-bit FNV-1a, in hex)
+pub(crate) fn hash_text(text: &str) -> String {
+    hash_bytes(text.as_bytes())
+}
+
+/// Stable hash of bytes, such as a file's content; see [`hash_text`]
+pub(crate) fn hash_bytes(bytes: &[u8]) -> String {
+    let mut hasher = Fnv::default();
+    hasher.update(bytes);
+    hasher.finish()
+}
+
+/// 64-bit FNV-1a state of [`hash_bytes`], fed a piece at a time
+///
+/// As a writer, it hashes a file copied into it without reading the file into memory.
+pub(crate) struct Fnv(u64);
+
+impl Default for Fnv {
+    fn default() -> Self {
+        Self(0xcbf2_9ce4_8422_2325)
+    }
+}
+
+impl Fnv {
+    /// Hash the next bytes
+    pub(crate) fn update(&mut self, bytes: &[u8]) {
+        self.0 = bytes.iter().fold(self.0, |hash, &byte| {
+            (hash ^ u64::from(byte)).wrapping_mul(0x0100_0000_01b3)
+        });
+    }
+
+    /// The hash of every byte so far, in hex
+    pub(crate) fn finish(&self) -> String {
+        format!("{:016x}", self.0)
+    }
+}
+
+impl std::io::Write for Fnv {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.update(buf);
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+/// A candidate of a run as stored in the history
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct CandidateRecord {
+    /// Name of the agent
+    pub agent: String,
+    /// Whether the agent finished successfully
+    pub success: bool,
+    /// Number of files changed
+    pub files_changed: usize,
+    /// Number of inserted lines
+    pub insertions: usize,
+    /// Number of deleted lines
+    pub deletions: usize,
+    /// Whether the candidate was rejected while reviewing
+    #[serde(default)]
+    pub rejected: bool,
+    /// The user's note about the candidate
+    #[serde(default)]
+    pub note: Option<String>,
+    /// `#tags` found in the note
+    #[serde(default)]
+    pub tags: Vec<String>,
+    /// Last line of the error output of a failed agent
+    #[serde(default)]
+    pub error: Option<String>,
+    /// Why the agent failed, if recognized
+    #[serde(default)]
+    pub error_kind: Option<ErrorKind>,
+    /// Violations of the repository's policy found when the agent finished
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub policy: Vec<String>,
+}
+
+/// Words of a note starting with `#`, without the `#` and trailing punctuation
+pub fn note_tags(note: &str) -> Vec<String> {
+    note.split_whitespace()
+        .filter_map(|word| word.strip_prefix('#'))
+        .map(|tag| tag.trim_end_matches(|c: char| c.is_ascii_punctuation() && c != '-' && c != '_'))
+        .filter(|tag| !tag.is_empty())
+        .map(str::to_string)
+        .collect()
+}
+
+impl RunRecord {
+    /// Build the history record of a run from its results and the user's reviews
+    ///
+    /// `reviews` is indexed like `results`; missing entries count as not reviewed.
+    #[must_use]
+    pub fn new(run: &RunInfo, results: &[ResultInfo], reviews: &[Review]) -> Self {
+        let candidates = results
+            .iter()
+            .enumerate()
+            .map(|(i, info)| {
+                let review = reviews.get(i).cloned().unwrap_or_default();
+                let (insertions, deletions) = info
+                    .change_summary
+                    .as_ref()
+                    .map_or((0, 0), |s| (s.insertions, s.deletions));
+                CandidateRecord {
+                    agent: info.executor_name.clone(),
+                    success: info.success,
+                    files_changed: info.files_changed,
+                    insertions,
+                    deletions,
+                    rejected: review.rejected,
+                    tags: review.note.as_deref().map(note_tags).unwrap_or_default(),
+                    note: review.note,
+                    error: info.failure_reason(),
+                    error_kind: info.error_kind,
+                    policy: info.policy.clone(),
+                }
+            })
+            .collect();
+
+        Self {
+            id: run.id.clone(),
+            prompt: run.prompt.clone(),
+            working_dir: run.working_dir.clone(),
+            base_commit: run.base_commit.clone(),
+            started_at: run.started_at,
+            candidates,
+            prompt_hash: prompt_hash(&run.prompt),
+            decision: None,
+            issue: run.issue.clone(),
+            snapshot: run.snapshot.clone(),
+            options: run.options.clone(),
+            rerun_of: run.rerun_of.clone(),
+        }
+    }
+
+    /// Record that the result of `agent` was applied
+    pub fn record_decision(&mut self, agent: &str, auto_selected: bool) {
+        self.decision = Some(Decision {
+            agent: agent.to_string(),
+            auto_selected,
+            decided_at: Local::now(),
+        });
+    }
+
+    /// The run as it was started, e.g. to review it again
+    #[must_use]
+    pub fn run_info(&self) -> RunInfo {
+        RunInfo {
+            id: self.id.clone(),
+            prompt: self.prompt.clone(),
+            working_dir: self.working_dir.clone(),
+            base_commit: self.base_commit.clone(),
+            started_at: self.started_at,
+            issue: self.issue.clone(),
+            snapshot: self.snapshot.clone(),
+            options: self.options.clone(),
+            rerun_of: self.rerun_of.clone(),
+        }
+    }
+
+    /// The reviews of the candidates, indexed like `candidates`
+    #[must_use]
+    pub fn reviews(&self) -> Vec<Review> {
+        self.candidates
+            .iter()
+            .map(|candidate| Review {
+                rejected: candidate.rejected,
+                note: candidate.note.clone(),
+            })
+            .collect()
+    }
+
+    /// Replace the reviews of the candidates, indexed like `candidates`
+    pub fn set_reviews(&mut self, reviews: &[Review]) {
+        for (candidate, review) in self.candidates.iter_mut().zip(reviews) {
+            candidate.rejected = review.rejected;
+            candidate.note.clone_from(&review.note);
+            candidate.tags = review.note.as_deref().map(note_tags).unwrap_or_default();
+        }
+    }
+
+    /// Save the record to the user's history directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record cannot be written.
+    pub fn save(&self) -> Result<PathBuf> {
+        self.save_to(&history_dir())
+    }
+
+    /// Save the record to the run database in the given directory, returning its path
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the record cannot be serialized or written.
+    pub fn save_to(&self, dir: &Path) -> Result<PathBuf> {
+        RunStore::open(dir)?.save(self)?;
+        Ok(dir.join(RUN_DATABASE))
+    }
+}
+
+/// Save the output of an agent as `<run id>/<agent>.log` in the user's history
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be written.
+pub fn save_agent_log(run_id: &str, agent: &str, lines: &[OutputLine]) -> Result<PathBuf> {
+    save_agent_log_to(&history_dir(), run_id, agent, lines)
+}
+
+/// Save the output of an agent as `<run id>/<agent>.log` in the given directory
+///
+/// # Errors
+///
+/// Returns an error if the log cannot be written.
+pub fn save_agent_log_to(
+    dir: &Path,
+    run_id: &str,
+    agent: &str,
+    lines: &[OutputLine],
+) -> Result<PathBuf> {
+    let dir = dir.join(run_id);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{agent}.log"));
+    let mut content = String::new();
+    for line in lines {
+        match line {
+            OutputLine::Stdout(text) | OutputLine::Stderr(text) => content.push_str(text),
+        }
+        content.push('\n');
+    }
+    std::fs::write(&path, content)?;
+    Ok(path)
+}
+
+/// Save what an agent changed as `<run id>/<agent>.patch` in the user's history
+///
+/// # Errors
+///
+/// Returns an error if the patch cannot be written.
+pub fn save_agent_patch(run_id: &str, agent: &str, diff: &str) -> Result<PathBuf> {
+    save_agent_patch_to(&history_dir(), run_id, agent, diff)
+}
+
+/// Save what an agent changed as `<run id>/<agent>.patch` in the given directory
+///
+/// # Errors
+///
+/// Returns an error if the patch cannot be written.
+pub fn save_agent_patch_to(dir: &Path, run_id: &str, agent: &str, diff: &str) -> Result<PathBuf> {
+    let dir = dir.join(run_id);
+    std::fs::create_dir_all(&dir)?;
+    let path = dir.join(format!("{agent}.patch"));
+    std::fs::write(&path, diff)?;
+    Ok(path)
+}
+
+/// Remove the outputs and diffs saved with the runs of the user's history that started
+/// more than `max_age` ago
+///
+/// See [`remove_old_agent_files_in`].
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be read or a directory cannot be removed.
+pub fn remove_old_agent_files(max_age: Duration) -> Result<usize> {
+    remove_old_agent_files_in(&history_dir(), max_age, Local::now())
+}
+
+/// Remove the `<run id>` directories of the runs in the given history directory that
+/// started more than `max_age` before `now`, returning how many were removed
+///
+/// The runs themselves stay in the history, without their logs and diffs.
+///
+/// # Errors
+///
+/// Returns an error if the history cannot be read or a directory cannot be removed.
+pub fn remove_old_agent_files_in(
+    dir: &Path,
+    max_age: Duration,
+    now: DateTime<Local>,
+) -> Result<usize> {
+    let mut removed = 0;
+    for run in list_runs_in(dir)? {
+        let expired = (now - run.started_at)
+            .to_std()
+            .is_ok_and(|age| age > max_age);
+        let path = dir.join(&run.id);
+        if expired && path.is_dir() {
+            std::fs::remove_dir_all(path)?;
+            removed += 1;
+        }
+    }
+    Ok(removed)
+}
+
+/// Load a run from the user's history
+///
+/// # Errors
+///
+/// Returns an error if the run does not exist or cannot be read.
+pub fn load_run(id: &str) -> Result<RunRecord> {
+    load_run_from(&history_dir(), id)
+}
+
+/// Load a run from the given history directory
+///
+/// # Errors
+///
+/// Returns an error if the run does not exist or cannot be read.
+pub fn load_run_from(dir: &Path, id: &str) -> Result<RunRecord> {
+    if !dir.exists() {
+        return Err(Error::RunNotFound { id: id.to_string() });
+    }
+    RunStore::open(dir)?.load(id)
+}
+
+/// All runs in the user's history, newest first
+///
+/// # Errors
+///
+/// Returns an error if the history directory cannot be read.
+pub fn list_runs() -> Result<Vec<RunRecord>> {
+    list_runs_in(&history_dir())
+}
+
+/// All runs in the given history directory, newest first
+///
+/// Records that cannot be parsed are skipped.
+///
+/// # Errors
+///
+/// Returns an error if the directory exists but its database cannot be read.
+pub fn list_runs_in(dir: &Path) -> Result<Vec<RunRecord>> {
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    RunStore::open(dir)?.list()
+}
+
+/// How each agent fared over the runs of the user's history
+///
+/// # Errors
+///
+/// Returns an error if the history directory exists but its database cannot be read.
+pub fn agent_stats() -> Result<Vec<AgentStats>> {
+    let dir = history_dir();
+    if !dir.exists() {
+        return Ok(Vec::new());
+    }
+    RunStore::open(&dir)?.agent_stats()
+}
+
+/// Identifiers of the runs of the user's history that repeat the given run, oldest first
+///
+/// # Errors
+///
+/// Returns an error if the history directory cannot be read.
+pub fn list_reruns(id: &str) -> Result<Vec<String>> {
+    list_reruns_in(&history_dir(), id)
+}
+
+/// Identifiers of the runs of the given history directory that repeat the given run,
+/// oldest first
+///
+/// # Errors
+///
+/// Returns an error if the directory exists but its database cannot be read.
+pub fn list_reruns_in(dir: &Path, id: &str) -> Result<Vec<String>> {
+    Ok(list_runs_in(dir)?
+        .into_iter()
+        .rev()
+        .filter(|run| run.rerun_of.as_deref() == Some(id))
+        .map(|run| run.id)
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn result_info(name: &str) -> ResultInfo {
+        ResultInfo {
+            executor_name: name.to_string(),
+            success: true,
+            files_changed: 2,
+            change_summary: None,
+            summary: None,
+            worktree_path: PathBuf::from("/tmp/worktree"),
+            stdout: String::new(),
+            stderr: String::new(),
+            conflicts: vec![],
+            score: None,
+            check: None,
+            duration: None,
+            breakdown: None,
+            diff_hash: None,
+            duplicate_of: None,
+            changed_lines: vec![],
+            similarity: vec![],
+            retries: vec![],
+            error_kind: None,
+            secrets: vec![],
+            policy: vec![],
+            output_lines: vec![],
+        }
+    }
+
+    #[test]
+    fn test_note_tags() {
+        assert_eq!(
+            note_tags("nice tests but #broke-api, see #review #"),
+            vec!["broke-api", "review"]
+        );
+        assert!(note_tags("no tags here").is_empty());
+    }
+
+    #[test]
+    fn test_record_keeps_reviews() {
+        let run = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        let reviews = vec![
+            Review::default(),
+            Review {
+                rejected: true,
+                note: Some("broke the API #api".to_string()),
+            },
+        ];
+        let record = RunRecord::new(
+            &run,
+            &[result_info("claude"), result_info("codex")],
+            &reviews,
+        );
+
+        assert_eq!(record.id, run.id);
+        assert_eq!(record.candidates.len(), 2);
+        assert!(!record.candidates[0].rejected);
+        assert!(record.candidates[1].rejected);
+        assert_eq!(record.candidates[1].tags, vec!["api"]);
+    }
+
+    #[test]
+    fn test_record_keeps_failures() {
+        let run = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        let mut failed = result_info("codex");
+        failed.success = false;
+        failed.stdout = "Reading files...\n".to_string();
+        failed.stderr = "warning: slow\nError: 529 overloaded\n\n".to_string();
+        let record = RunRecord::new(&run, &[result_info("claude"), failed.clone()], &[]);
+
+        assert_eq!(record.candidates[0].error, None);
+        assert_eq!(
+            record.candidates[1].error.as_deref(),
+            Some("Error: 529 overloaded")
+        );
+
+        failed.stderr.clear();
+        assert_eq!(failed.failure_reason().as_deref(), Some("Reading files..."));
+    }
+
+    #[test]
+    fn test_prompt_hash() {
+        assert_eq!(prompt_hash("Add a flag\n"), prompt_hash("Add a flag"));
+        assert_ne!(prompt_hash("Add a flag"), prompt_hash("Add a flags"));
+        assert_eq!(prompt_hash(""), "cbf29ce484222325");
+    }
+
+    #[test]
+    fn test_save_and_load() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let run = RunInfo::new("Add a flag", "/tmp/repo");
+        let mut record = RunRecord::new(&run, &[result_info("gemini")], &[]);
+        record.record_decision("gemini", false);
+
+        record.save_to(dir.path())?;
+        assert_eq!(load_run_from(dir.path(), &run.id)?, record);
+        assert_eq!(list_runs_in(dir.path())?, vec![record]);
+        assert!(matches!(
+            load_run_from(dir.path(), "missing"),
+            Err(Error::RunNotFound { .. })
+        ));
+
+        let lines = [
+            OutputLine::Stdout("Reading files...".to_string()),
+            OutputLine::Stderr("Error: 529 overloaded".to_string()),
+        ];
+        let log = save_agent_log_to(dir.path(), &run.id, "codex", &lines)?;
+        assert_eq!(log, dir.path().join(&run.id).join("codex.log"));
+        assert_eq!(
+            std::fs::read_to_string(&log)?,
+            "Reading files...\nError: 529 overloaded\n"
+        );
+        // Logs do not show up as runs
+        assert_eq!(list_runs_in(dir.path())?.len(), 1);
+        Ok(())
+    }
+
+    #[test]
+    fn test_save_agent_patch() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let diff = "--- a/README.md\n+++ b/README.md\n";
+        let patch = save_agent_patch_to(dir.path(), "run", "claude", diff)?;
+        assert!(patch.ends_with("run/claude.patch"));
+        assert_eq!(std::fs::read_to_string(&patch)?, diff);
+        Ok(())
+    }
+
+    #[test]
+    fn test_remove_old_agent_files() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut run = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        run.id = "20250101-120000".to_string();
+        RunRecord::new(&run, &[], &[]).save_to(dir.path())?;
+        save_agent_patch_to(dir.path(), &run.id, "claude", "diff")?;
+
+        let day = Duration::from_hours(24);
+        let later = run.started_at + chrono::Duration::days(2);
+        assert_eq!(remove_old_agent_files_in(dir.path(), 3 * day, later)?, 0);
+        assert!(dir.path().join(&run.id).exists());
+        assert_eq!(remove_old_agent_files_in(dir.path(), day, later)?, 1);
+        assert!(!dir.path().join(&run.id).exists());
+        assert_eq!(load_run_from(dir.path(), &run.id)?.prompt, run.prompt);
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_missing_directory() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        assert!(list_runs_in(&dir.path().join("history"))?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_list_reruns() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut original = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        original.id = "20250101-120000".to_string();
+        RunRecord::new(&original, &[], &[]).save_to(dir.path())?;
+        for (days, id) in [(1, "20250102-120000"), (2, "20250103-120000")] {
+            let mut rerun = original.clone().with_rerun_of(Some(original.id.clone()));
+            rerun.id = id.to_string();
+            rerun.started_at += chrono::Duration::days(days);
+            RunRecord::new(&rerun, &[], &[]).save_to(dir.path())?;
+        }
+
+        assert_eq!(
+            list_reruns_in(dir.path(), &original.id)?,
+            vec!["20250102-120000", "20250103-120000"]
+        );
+        assert!(list_reruns_in(dir.path(), "20250102-120000")?.is_empty());
+        Ok(())
+    }
+}
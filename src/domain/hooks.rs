@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+
+/// Outcome of the repository's score command in a worktree
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
+pub struct Score {
+    /// Whether the command exited with status 0
+    pub passed: bool,
+    /// Number printed on the last line of the output, if any
+    pub value: Option<f64>,
+    /// Standard output and standard error of the command
+    pub output: String,
+}
+
+impl Score {
+    /// Short description for the result list, e.g. `0.82 (passed)`
+    #[must_use]
+    pub fn label(&self) -> String {
+        let status = if self.passed { "passed" } else { "failed" };
+        match self.value {
+            Some(value) => format!("{value} ({status})"),
+            None => status.to_string(),
+        }
+    }
+}
+
+/// Outcome of the quick check command in a worktree, e.g. `cargo check`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CheckResult {
+    /// Whether the command exited with status 0
+    pub passed: bool,
+    /// Standard output and standard error of the command
+    pub output: String,
+}
+
+/// Run a shell command in `dir`, returning whether it succeeded and its combined output
+async fn run_shell(command: &str, dir: &Path) -> Result<(bool, String)> {
+    let output = Command::new("sh")
+        .args(["-c", command])
+        .current_dir(dir)
+        .output()
+        .await?;
+    let mut text = String::from_utf8_lossy(&output.stdout).to_string();
+    text.push_str(&String::from_utf8_lossy(&output.stderr));
+    Ok((output.status.success(), text))
+}
+
+/// Run the repository's setup command in a worktree before the agent starts
+///
+/// # Errors
+///
+/// Returns an error with the command's output if it cannot be started or fails.
+pub async fn run_setup(command: &str, dir: &Path) -> Result<()> {
+    let (success, output) = run_shell(command, dir).await?;
+    if success {
+        Ok(())
+    } else {
+        Err(Error::SetupFailed {
+            command: command.to_string(),
+            output,
+        })
+    }
+}
+
+/// Run the quick check command in a worktree after the agent finished
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be started.
+pub async fn run_check(command: &str, dir: &Path) -> Result<CheckResult> {
+    let (passed, output) = run_shell(command, dir).await?;
+    Ok(CheckResult { passed, output })
+}
+
+/// Run the repository's score command in a worktree after the agent finished
+///
+/// # Errors
+///
+/// Returns an error if the command cannot be started.
+pub async fn run_score(command: &str, dir: &Path) -> Result<Score> {
+    let (passed, output) = run_shell(command, dir).await?;
+    let value = output
+        .lines()
+        .rev()
+        .find(|line| !line.trim().is_empty())
+        .and_then(|line| line.trim().parse().ok());
+    Ok(Score {
+        passed,
+        value,
+        output,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_run_score() -> Result<()> {
+        let dir = std::env::temp_dir();
+        let score = run_score("echo running; echo 0.75", &dir).await?;
+        assert!(score.passed);
+        assert_eq!(score.value, Some(0.75));
+        assert_eq!(score.label(), "0.75 (passed)");
+
+        let score = run_score("echo broken; exit 1", &dir).await?;
+        assert!(!score.passed);
+        assert_eq!(score.value, None);
+        assert_eq!(score.label(), "failed");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_check() -> Result<()> {
+        let dir = std::env::temp_dir();
+        assert!(run_check("true", &dir).await?.passed);
+        let check = run_check("echo 'error[E0308]'; exit 101", &dir).await?;
+        assert!(!check.passed);
+        assert_eq!(check.output, "error[E0308]\n");
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_setup() -> Result<()> {
+        let dir = std::env::temp_dir();
+        run_setup("true", &dir).await?;
+        let result = run_setup("echo missing; false", &dir).await;
+        assert!(matches!(result, Err(Error::SetupFailed { output, .. }) if output == "missing\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,152 @@
+use std::path::Path;
+
+use super::diff::{Diff, DiffLine};
+use super::hooks::run_check;
+use crate::config::PolicySettings;
+use crate::git;
+
+/// Marker of an SPDX license header
+const SPDX_MARKER: &str = "SPDX-License-Identifier:";
+
+/// Lines at the top of a new file searched for the header, to allow for shebangs
+/// and copyright lines before it
+const HEADER_LINES: usize = 10;
+
+/// Extensions of source files, which can carry a header in a comment
+const SOURCE_EXTENSIONS: [&str; 24] = [
+    "c", "cc", "cpp", "cs", "css", "go", "h", "hpp", "java", "js", "jsx", "kt", "mjs", "php", "py",
+    "rb", "rs", "scala", "scss", "sh", "swift", "ts", "tsx", "vue",
+];
+
+/// Check the repository's policy in a worktree after the agent finished
+///
+/// Returns a description of each violation; the diff failing to be read or the policy
+/// command failing to start counts as one.
+pub async fn check_policy(policy: &PolicySettings, worktree: &Path) -> Vec<String> {
+    let mut violations = Vec::new();
+    if policy.spdx_header {
+        match git::get_diff(worktree).await {
+            Ok(diff) => violations.extend(missing_spdx_headers(&Diff::parse(&diff))),
+            Err(e) => violations.push(format!("SPDX headers could not be checked: {e}")),
+        }
+    }
+    if let Some(command) = &policy.command {
+        match run_check(command, worktree).await {
+            Ok(result) if result.passed => {}
+            Ok(result) => {
+                let last = result
+                    .output
+                    .lines()
+                    .map(str::trim)
+                    .rfind(|line| !line.is_empty())
+                    .unwrap_or("no output");
+                violations.push(format!("`{command}` failed: {last}"));
+            }
+            Err(e) => violations.push(format!("`{command}` could not run: {e}")),
+        }
+    }
+    violations
+}
+
+/// The new source files of a diff without an SPDX header near their top
+#[must_use]
+pub fn missing_spdx_headers(diff: &Diff<'_>) -> Vec<String> {
+    diff.files
+        .iter()
+        .filter(|file| file.header.iter().any(|line| line.starts_with("new file")))
+        .filter(|file| {
+            Path::new(file.path)
+                .extension()
+                .and_then(|ext| ext.to_str())
+                .is_some_and(|ext| SOURCE_EXTENSIONS.contains(&ext))
+        })
+        .filter(|file| {
+            !file
+                .hunks
+                .iter()
+                .flat_map(|hunk| &hunk.lines)
+                .filter_map(|line| match line {
+                    DiffLine::Added(text) => Some(text),
+                    _ => None,
+                })
+                .take(HEADER_LINES)
+                .any(|text| text.contains(SPDX_MARKER))
+        })
+        .map(|file| format!("{} has no SPDX-License-Identifier header", file.path))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_missing_spdx_headers() {
+        let diff = "\
+diff --git a/src/lexer.rs b/src/lexer.rs
+new file mode 100644
+--- /dev/null
++++ b/src/lexer.rs
+@@ -0,0 +1,2 @@
++// SPDX-License-Identifier: MIT
++pub fn lex() {}
+diff --git a/src/parser.rs b/src/parser.rs
+new file mode 100644
+--- /dev/null
++++ b/src/parser.rs
+@@ -0,0 +1 @@
++pub fn parse() {}
+diff --git a/docs/parser.md b/docs/parser.md
+new file mode 100644
+--- /dev/null
++++ b/docs/parser.md
+@@ -0,0 +1 @@
++# Parser
+diff --git a/src/main.rs b/src/main.rs
+--- a/src/main.rs
++++ b/src/main.rs
+@@ -1 +1,2 @@
+ fn main() {}
++mod parser;
+";
+        assert_eq!(
+            missing_spdx_headers(&Diff::parse(diff)),
+            vec!["src/parser.rs has no SPDX-License-Identifier header"]
+        );
+    }
+
+    #[tokio::test]
+    async fn test_policy_command() {
+        let dir = std::env::temp_dir();
+        let policy = PolicySettings {
+            command: Some("echo 'checking'; echo 'LICENSES/MIT.txt missing'; exit 1".to_string()),
+            spdx_header: false,
+        };
+        assert_eq!(
+            check_policy(&policy, &dir).await,
+            vec![format!(
+                "`{}` failed: LICENSES/MIT.txt missing",
+                policy.command.as_deref().unwrap_or_default()
+            )]
+        );
+        assert!(
+            check_policy(&PolicySettings::default(), &dir)
+                .await
+                .is_empty()
+        );
+    }
+
+    #[tokio::test]
+    async fn test_policy_without_diff() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        // Not a repository, so the diff cannot be read and nothing is known to pass
+        let dir = tempfile::tempdir()?;
+        let policy = PolicySettings {
+            command: None,
+            spdx_header: true,
+        };
+        let violations = check_policy(&policy, dir.path()).await;
+        assert_eq!(violations.len(), 1);
+        assert!(violations[0].starts_with("SPDX headers could not be checked"));
+        Ok(())
+    }
+}
@@ -0,0 +1,250 @@
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+use crate::error::{Error, Result};
+use crate::executor::traits::Executor;
+
+/// Extensions of the image files that can be attached to a prompt
+const IMAGE_EXTENSIONS: [&str; 5] = ["png", "jpg", "jpeg", "gif", "webp"];
+
+/// Characters per token assumed when estimating the size of a prompt
+const CHARS_PER_TOKEN: usize = 4;
+
+/// Part of an agent's context window a prompt may fill, as a divisor; the rest is
+/// left for the files the agent reads while working
+const PROMPT_SHARE: usize = 2;
+
+/// An agent whose context window the prompt would fill too much
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PromptWarning {
+    /// Name of the agent
+    pub agent: String,
+    /// Estimated tokens of the prompt
+    pub tokens: usize,
+    /// Tokens the agent's model holds in its context
+    pub context_window: usize,
+}
+
+/// Rough number of tokens in a text
+///
+/// Tokenizers differ between agents, so this only tells short prompts from long ones.
+#[must_use]
+pub fn estimate_tokens(text: &str) -> usize {
+    text.chars().count().div_ceil(CHARS_PER_TOKEN)
+}
+
+/// The agents for which the prompt takes more than half of the context window
+#[must_use]
+pub fn check_prompt_length(prompt: &str, executors: &[Arc<dyn Executor>]) -> Vec<PromptWarning> {
+    let tokens = estimate_tokens(prompt);
+    executors
+        .iter()
+        .filter_map(|executor| {
+            let context_window = executor.context_window()?;
+            (tokens > context_window / PROMPT_SHARE).then(|| PromptWarning {
+                agent: executor.name().to_string(),
+                tokens,
+                context_window,
+            })
+        })
+        .collect()
+}
+
+/// The prompt cut down to fit every agent of `warnings`
+///
+/// The task is usually stated first and the pasted context, such as logs or an
+/// issue thread, follows, so the first and last lines are kept and lines in between
+/// are replaced by a note saying how many were left out.
+#[must_use]
+pub fn truncate_prompt(prompt: &str, warnings: &[PromptWarning]) -> String {
+    let Some(window) = warnings.iter().map(|w| w.context_window).min() else {
+        return prompt.to_string();
+    };
+    let budget = window / PROMPT_SHARE * CHARS_PER_TOKEN;
+    if prompt.chars().count() <= budget {
+        return prompt.to_string();
+    }
+
+    let lines: Vec<&str> = prompt.lines().collect();
+    let width = |line: &str| line.chars().count() + 1;
+    let mut used = 0;
+    let head = lines
+        .iter()
+        .take_while(|line| {
+            used += width(line);
+            used <= budget * 3 / 4
+        })
+        .count();
+    let mut used = lines[..head].iter().map(|line| width(line)).sum::<usize>();
+    let tail = lines[head..]
+        .iter()
+        .rev()
+        .take_while(|line| {
+            used += width(line);
+            used <= budget
+        })
+        .count();
+    if head == 0 {
+        // A single line too long to keep, e.g. minified code
+        return prompt.chars().take(budget).collect();
+    }
+
+    let omitted = lines.len() - head - tail;
+    format!(
+        "{}\n\n[{omitted} lines were left out to fit the context window]\n\n{}",
+        lines[..head].join("\n"),
+        lines[lines.len() - tail..].join("\n")
+    )
+}
+
+/// Check the images to attach and make their paths absolute, so agents find them
+/// from their worktrees
+///
+/// # Errors
+///
+/// Returns an error if an image does not exist or is not a supported image format.
+pub fn resolve_images(paths: &[PathBuf]) -> Result<Vec<PathBuf>> {
+    paths
+        .iter()
+        .map(|path| {
+            let invalid = |reason: &str| Error::InvalidImage {
+                path: path.clone(),
+                reason: reason.to_string(),
+            };
+            if !is_image(path) {
+                return Err(invalid("expected a .png, .jpg, .gif, or .webp file"));
+            }
+            path.canonicalize().map_err(|_| invalid("file not found"))
+        })
+        .collect()
+}
+
+/// The prompt for an agent with the images attached
+///
+/// Agents that cannot see images get a note naming the images instead, so they know
+/// the prompt refers to something they are missing.
+#[must_use]
+pub fn prompt_with_images(executor: &dyn Executor, prompt: &str, images: &[PathBuf]) -> String {
+    if images.is_empty() {
+        return prompt.to_string();
+    }
+    if executor.capabilities().images
+        && let Some(prompt) = executor.attach_images(prompt, images)
+    {
+        return prompt;
+    }
+    let names: Vec<String> = images
+        .iter()
+        .map(|image| {
+            image.file_name().map_or_else(
+                || image.display().to_string(),
+                |name| name.to_string_lossy().to_string(),
+            )
+        })
+        .collect();
+    format!(
+        "{prompt}\n\nNote: the user attached images you cannot see ({}). Work from the text of the task.",
+        names.join(", ")
+    )
+}
+
+/// Whether a path looks like an image that can be attached
+#[must_use]
+pub fn is_image(path: &Path) -> bool {
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .is_some_and(|ext| IMAGE_EXTENSIONS.contains(&ext.to_lowercase().as_str()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::{Capabilities, ClaudeExecutor, GeminiExecutor, MockExecutor};
+
+    #[test]
+    fn test_estimate_tokens() {
+        assert_eq!(estimate_tokens(""), 0);
+        assert_eq!(estimate_tokens("abcd"), 1);
+        assert_eq!(estimate_tokens("abcde"), 2);
+    }
+
+    #[test]
+    fn test_resolve_images() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let image = dir.path().join("mockup.PNG");
+        std::fs::write(&image, b"")?;
+        assert_eq!(
+            resolve_images(std::slice::from_ref(&image))?,
+            [image.canonicalize()?]
+        );
+        assert!(matches!(
+            resolve_images(&[dir.path().join("missing.png")]),
+            Err(Error::InvalidImage { .. })
+        ));
+        assert!(matches!(
+            resolve_images(&[dir.path().join("notes.txt")]),
+            Err(Error::InvalidImage { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_prompt_with_images() {
+        let images = [PathBuf::from("/tmp/my mockup.png")];
+        let claude = ClaudeExecutor::new();
+        assert_eq!(prompt_with_images(&claude, "Fix it", &[]), "Fix it");
+        assert!(prompt_with_images(&claude, "Fix it", &images).ends_with("- /tmp/my mockup.png"));
+        let gemini = GeminiExecutor::new();
+        assert!(prompt_with_images(&gemini, "Fix it", &images).ends_with("@/tmp/my\\ mockup.png"));
+        let mock = MockExecutor::new("mock");
+        assert!(
+            prompt_with_images(&mock, "Fix it", &images)
+                .contains("images you cannot see (my mockup.png)")
+        );
+        // Tools that read images but do not say how are told about them all the same
+        let mock = mock.with_capabilities(Capabilities { images: true });
+        assert!(
+            prompt_with_images(&mock, "Fix it", &images)
+                .contains("images you cannot see (my mockup.png)")
+        );
+    }
+
+    #[test]
+    fn test_check_prompt_length() {
+        let executors: Vec<Arc<dyn Executor>> = vec![
+            Arc::new(MockExecutor::new("small").with_context_window(100)),
+            Arc::new(MockExecutor::new("large").with_context_window(1000)),
+            Arc::new(MockExecutor::new("unknown")),
+        ];
+        let prompt = "x".repeat(400);
+        assert_eq!(
+            check_prompt_length(&prompt, &executors),
+            [PromptWarning {
+                agent: "small".to_string(),
+                tokens: 100,
+                context_window: 100,
+            }]
+        );
+        assert!(check_prompt_length("Fix the bug", &executors).is_empty());
+    }
+
+    #[test]
+    fn test_truncate_prompt() {
+        let warnings = [PromptWarning {
+            agent: "small".to_string(),
+            tokens: 100,
+            context_window: 20,
+        }];
+        // 40 characters fit: the task and the end of the log are kept
+        let log: Vec<String> = (1..=20).map(|i| format!("line {i:02}")).collect();
+        let prompt = format!("Fix it\n{}", log.join("\n"));
+        assert_eq!(
+            truncate_prompt(&prompt, &warnings),
+            "Fix it\nline 01\nline 02\n\n\
+             [16 lines were left out to fit the context window]\n\nline 19\nline 20"
+        );
+        assert_eq!(truncate_prompt("Fix it", &warnings), "Fix it");
+        assert_eq!(truncate_prompt(&"x".repeat(100), &warnings), "x".repeat(40));
+        assert_eq!(truncate_prompt(&prompt, &[]), prompt);
+    }
+}
@@ -0,0 +1,256 @@
+use std::collections::HashMap;
+
+use serde::Serialize;
+
+use super::result::ResultInfo;
+use super::store::AgentStats;
+use crate::config::ScoringWeights;
+
+/// Weighted score of a candidate and the contribution of each signal
+///
+/// Every signal is normalized to `0.0..=1.0` before being multiplied by its weight.
+#[derive(Debug, Clone, PartialEq, Serialize)]
+pub struct ScoreBreakdown {
+    /// Name of the agent
+    pub agent: String,
+    /// The score command passed
+    pub tests: f64,
+    /// The quick check command passed
+    pub check: f64,
+    /// Number printed by the score command, relative to the best candidate
+    pub score: f64,
+    /// Fewer changed lines than the other candidates
+    pub diff_size: f64,
+    /// Finished faster than the other candidates
+    pub duration: f64,
+    /// The agent's past candidates succeeded and were not rejected
+    pub history: f64,
+    /// Sum of the contributions
+    pub total: f64,
+}
+
+impl ScoreBreakdown {
+    /// One-line description, e.g. `4.20 (tests 3.00, diff 0.50, ...)`
+    #[must_use]
+    pub fn label(&self) -> String {
+        format!(
+            "{:.2} (tests {:.2}, check {:.2}, score {:.2}, diff {:.2}, time {:.2}, history {:.2})",
+            self.total,
+            self.tests,
+            self.check,
+            self.score,
+            self.diff_size,
+            self.duration,
+            self.history
+        )
+    }
+}
+
+/// How often each agent's past candidates succeeded and were kept
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct AgentPriors {
+    rates: HashMap<String, f64>,
+}
+
+impl AgentPriors {
+    /// Priors from the stats of past runs, smoothed so a few runs do not dominate
+    #[must_use]
+    pub fn from_stats(stats: &[AgentStats]) -> Self {
+        let rates = stats
+            .iter()
+            .map(|stats| {
+                (
+                    stats.agent.clone(),
+                    (f64::from(stats.kept) + 1.0) / (f64::from(stats.runs) + 2.0),
+                )
+            })
+            .collect();
+        Self { rates }
+    }
+
+    /// Prior of an agent, 0.5 if it has no history
+    #[must_use]
+    pub fn rate(&self, agent: &str) -> f64 {
+        self.rates.get(agent).copied().unwrap_or(0.5)
+    }
+}
+
+/// Score every candidate with the weighted formula
+///
+/// The breakdowns are in the order of `results`; failed candidates score 0.
+#[must_use]
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "line counts are only compared relative to each other"
+)]
+pub fn score_candidates(
+    results: &[ResultInfo],
+    weights: &ScoringWeights,
+    priors: &AgentPriors,
+) -> Vec<ScoreBreakdown> {
+    let lines = ResultInfo::changed_line_count;
+    let best_value = results
+        .iter()
+        .filter_map(|info| info.score.as_ref()?.value)
+        .fold(0.0_f64, f64::max);
+    let fewest_lines = results
+        .iter()
+        .map(lines)
+        .filter(|&n| n > 0)
+        .min()
+        .unwrap_or(0);
+    let fastest = results
+        .iter()
+        .filter_map(|info| info.duration)
+        .filter(|d| !d.is_zero())
+        .min();
+
+    results
+        .iter()
+        .map(|info| {
+            let mut breakdown = ScoreBreakdown {
+                agent: info.executor_name.clone(),
+                tests: 0.0,
+                check: 0.0,
+                score: 0.0,
+                diff_size: 0.0,
+                duration: 0.0,
+                history: 0.0,
+                total: 0.0,
+            };
+            if !info.success {
+                return breakdown;
+            }
+
+            let passed = info.score.as_ref().is_some_and(|s| s.passed);
+            let value = info.score.as_ref().and_then(|s| s.value).unwrap_or(0.0);
+            let changed = lines(info);
+            breakdown.tests = f64::from(weights.tests) * if passed { 1.0 } else { 0.0 };
+            if info.check.as_ref().is_some_and(|c| c.passed) {
+                breakdown.check = f64::from(weights.check);
+            }
+            if best_value > 0.0 {
+                breakdown.score = f64::from(weights.score) * (value / best_value).max(0.0);
+            }
+            // A candidate that changed nothing gets no credit for a small diff
+            if changed > 0 {
+                breakdown.diff_size =
+                    f64::from(weights.diff_size) * fewest_lines as f64 / changed as f64;
+            }
+            if let (Some(fastest), Some(duration)) = (fastest, info.duration)
+                && !duration.is_zero()
+            {
+                breakdown.duration =
+                    f64::from(weights.duration) * fastest.as_secs_f64() / duration.as_secs_f64();
+            }
+            breakdown.history = f64::from(weights.history) * priors.rate(&info.executor_name);
+            breakdown.total = breakdown.tests
+                + breakdown.check
+                + breakdown.score
+                + breakdown.diff_size
+                + breakdown.duration
+                + breakdown.history;
+            breakdown
+        })
+        .collect()
+}
+
+/// Index of the successful candidate with the highest weighted score
+#[must_use]
+pub fn best_candidate(results: &[ResultInfo], scores: &[ScoreBreakdown]) -> Option<usize> {
+    results
+        .iter()
+        .zip(scores)
+        .enumerate()
+        .filter(|(_, (info, _))| info.success)
+        .max_by(|(_, (_, a)), (_, (_, b))| a.total.total_cmp(&b.total))
+        .map(|(i, _)| i)
+}
+
+#[cfg(test)]
+mod tests {
+    use std::time::Duration;
+
+    use super::*;
+    use crate::domain::Score;
+    use crate::git::ChangeSummary;
+
+    fn info(name: &str, lines: usize, seconds: u64, passed: bool) -> ResultInfo {
+        ResultInfo {
+            executor_name: name.to_string(),
+            success: true,
+            files_changed: 1,
+            change_summary: Some(ChangeSummary {
+                insertions: lines,
+                ..ChangeSummary::default()
+            }),
+            summary: None,
+            worktree_path: std::path::PathBuf::from("/tmp/test"),
+            stdout: String::new(),
+            stderr: String::new(),
+            output_lines: vec![],
+            conflicts: vec![],
+            score: Some(Score {
+                passed,
+                value: None,
+                output: String::new(),
+            }),
+            check: None,
+            duration: Some(Duration::from_secs(seconds)),
+            breakdown: None,
+            diff_hash: None,
+            duplicate_of: None,
+            changed_lines: vec![],
+            similarity: vec![],
+            retries: vec![],
+            error_kind: None,
+            secrets: vec![],
+            policy: vec![],
+        }
+    }
+
+    #[test]
+    fn test_score_candidates() {
+        let results = vec![info("claude", 100, 60, true), info("gemini", 20, 30, false)];
+        let weights = ScoringWeights::default();
+        let scores = score_candidates(&results, &weights, &AgentPriors::default());
+
+        assert!((scores[0].tests - f64::from(weights.tests)).abs() < f64::EPSILON);
+        assert!((scores[1].diff_size - f64::from(weights.diff_size)).abs() < f64::EPSILON);
+        assert!(scores[0].duration < scores[1].duration);
+        assert_eq!(best_candidate(&results, &scores), Some(0));
+
+        let no_tests = ScoringWeights {
+            tests: 0,
+            ..ScoringWeights::default()
+        };
+        let scores = score_candidates(&results, &no_tests, &AgentPriors::default());
+        assert_eq!(best_candidate(&results, &scores), Some(1));
+    }
+
+    #[test]
+    fn test_failed_candidates_are_not_chosen() {
+        let mut failed = info("claude", 10, 10, true);
+        failed.success = false;
+        let results = vec![failed];
+        let scores = score_candidates(
+            &results,
+            &ScoringWeights::default(),
+            &AgentPriors::default(),
+        );
+        assert!(scores[0].total.abs() < f64::EPSILON);
+        assert_eq!(best_candidate(&results, &scores), None);
+    }
+
+    #[test]
+    fn test_agent_priors() {
+        let stats = |agent: &str, kept: u32| AgentStats {
+            agent: agent.to_string(),
+            runs: 1,
+            kept,
+        };
+        let priors = AgentPriors::from_stats(&[stats("claude", 1), stats("codex", 0)]);
+        assert!(priors.rate("claude") > priors.rate("gemini"));
+        assert!(priors.rate("codex") < priors.rate("gemini"));
+    }
+}
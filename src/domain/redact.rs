@@ -0,0 +1,149 @@
+use regex::Regex;
+
+use super::history::RunRecord;
+use crate::error::{Error, Result};
+use crate::executor::OutputLine;
+
+/// What redacted text is replaced with
+pub const REDACTED: &str = "[redacted]";
+
+/// Strips the secrets configured in `[redact]` from text
+#[derive(Debug, Clone, Default)]
+pub struct Redactor {
+    patterns: Vec<Regex>,
+}
+
+impl Redactor {
+    /// Compile the regular expressions of `[redact] patterns`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error naming the first pattern that is not a valid regular expression.
+    pub fn new(patterns: &[String]) -> Result<Self> {
+        let patterns = patterns
+            .iter()
+            .map(|pattern| {
+                Regex::new(pattern).map_err(|e| Error::Config {
+                    message: format!("invalid redact pattern `{pattern}`: {e}"),
+                })
+            })
+            .collect::<Result<_>>()?;
+        Ok(Self { patterns })
+    }
+
+    /// Whether no pattern is configured
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.patterns.is_empty()
+    }
+
+    /// `text` with every match of a pattern replaced, and how many matches there were
+    #[must_use]
+    pub fn redact(&self, text: &str) -> (String, usize) {
+        let mut text = text.to_string();
+        let mut count = 0;
+        for pattern in &self.patterns {
+            let found = pattern.find_iter(&text).count();
+            if found > 0 {
+                text = pattern.replace_all(&text, REDACTED).into_owned();
+                count += found;
+            }
+        }
+        (text, count)
+    }
+
+    /// `text` with every match of a pattern replaced
+    #[must_use]
+    pub fn redact_text(&self, text: &str) -> String {
+        self.redact(text).0
+    }
+
+    /// Output lines with every match of a pattern replaced
+    #[must_use]
+    pub fn redact_lines(&self, lines: &[OutputLine]) -> Vec<OutputLine> {
+        lines
+            .iter()
+            .map(|line| match line {
+                OutputLine::Stdout(text) => OutputLine::Stdout(self.redact_text(text)),
+                OutputLine::Stderr(text) => OutputLine::Stderr(self.redact_text(text)),
+            })
+            .collect()
+    }
+
+    /// A patch with every match of a pattern replaced in the lines it adds
+    ///
+    /// Context and removed lines come from the base commit, so they are kept and the
+    /// patch still applies to it.
+    #[must_use]
+    pub fn redact_patch(&self, patch: &str) -> String {
+        if self.is_empty() {
+            return patch.to_string();
+        }
+        patch
+            .split_inclusive('\n')
+            .map(|line| match line.strip_prefix('+') {
+                Some(added) if !line.starts_with("+++ ") => format!("+{}", self.redact_text(added)),
+                _ => line.to_string(),
+            })
+            .collect()
+    }
+
+    /// Strip the prompt and the agents' errors of a run before it is saved
+    pub fn redact_record(&self, record: &mut RunRecord) {
+        if self.is_empty() {
+            return;
+        }
+        record.prompt = self.redact_text(&record.prompt);
+        for candidate in &mut record.candidates {
+            if let Some(error) = &candidate.error {
+                candidate.error = Some(self.redact_text(error));
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_redact() -> Result<()> {
+        let redactor = Redactor::new(&[
+            "sk-[A-Za-z0-9]{8,}".to_string(),
+            "/home/[a-z]+/".to_string(),
+        ])?;
+        assert_eq!(
+            redactor.redact("Use sk-abcdef123456 from /home/alice/keys and sk-zyxwvu987654"),
+            (
+                "Use [redacted] from [redacted]keys and [redacted]".to_string(),
+                3
+            )
+        );
+        assert_eq!(redactor.redact("sk-short"), ("sk-short".to_string(), 0));
+        assert!(Redactor::new(&[]).is_ok_and(|r| r.is_empty()));
+        assert!(matches!(
+            Redactor::new(&["(unclosed".to_string()]),
+            Err(Error::Config { .. })
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_redact_patch() -> Result<()> {
+        let redactor = Redactor::new(&["sk-[A-Za-z0-9]{8,}".to_string()])?;
+        let patch = "\
+diff --git a/.env b/.env
+--- a/.env
++++ b/.env
+@@ -1,2 +1,2 @@
+ OLD=sk-committed1234
+-KEY=sk-previous1234
++KEY=sk-abcdef123456
+";
+        assert_eq!(
+            redactor.redact_patch(patch),
+            patch.replace("+KEY=sk-abcdef123456", "+KEY=[redacted]")
+        );
+        Ok(())
+    }
+}
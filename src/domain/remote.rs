@@ -0,0 +1,132 @@
+use std::path::{Path, PathBuf};
+
+use tempfile::TempDir;
+
+use crate::error::Result;
+use crate::git;
+
+/// A shallow clone of a remote repository to run the agents in, removed when dropped
+///
+/// Lets parari be tried on a project that is not checked out locally; the applied
+/// result is pushed back as a branch or written out as a patch.
+#[derive(Debug)]
+pub struct RemoteCheckout {
+    url: String,
+    path: PathBuf,
+    _dir: TempDir,
+}
+
+impl RemoteCheckout {
+    /// Clone the default branch of `url` into a temporary directory
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or the clone fails.
+    pub async fn fetch(url: &str) -> Result<Self> {
+        let dir = tempfile::Builder::new().prefix("parari-repo-").tempdir()?;
+        let path = dir.path().join("repo");
+        git::clone_shallow(url, &path).await?;
+        Ok(Self {
+            url: url.to_string(),
+            // Worktree paths are compared with canonical paths, e.g. /private/var on macOS
+            path: path.canonicalize()?,
+            _dir: dir,
+        })
+    }
+
+    /// URL the repository was cloned from
+    #[must_use]
+    pub fn url(&self) -> &str {
+        &self.url
+    }
+
+    /// Root of the clone
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Commit the applied result on `branch` and push it to the remote
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if committing or pushing fails, e.g. without push access.
+    pub async fn push_result(&self, branch: &str, message: &str) -> Result<()> {
+        git::push_branch(&self.path, branch, message).await
+    }
+
+    /// Write the applied result as a patch that `git apply` accepts
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the diff cannot be computed or the file cannot be written.
+    pub async fn write_patch(&self, path: &Path) -> Result<()> {
+        let diff = git::get_diff(&self.path).await?;
+        tokio::fs::write(path, diff).await?;
+        Ok(())
+    }
+}
+
+/// Branch a result is pushed to, for `--repo` and `--pr`
+#[must_use]
+pub fn result_branch(agent: &str, run_id: &str) -> String {
+    format!("parari/{agent}-{run_id}")
+}
+
+/// File a result of a run on a remote repository is written to as a patch
+#[must_use]
+pub fn result_patch(agent: &str, run_id: &str) -> PathBuf {
+    PathBuf::from(format!("parari-{agent}-{run_id}.patch"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    #[tokio::test]
+    async fn test_remote_checkout() -> Result<()> {
+        let origin = TestRepo::builder()
+            .with_file("src/lib.rs", "pub fn a() {}\n")
+            .build()?;
+        let url = format!("file://{}", origin.path().display());
+        let checkout = RemoteCheckout::fetch(&url).await?;
+        assert_eq!(checkout.url(), url);
+        assert!(checkout.path().join("src/lib.rs").exists());
+
+        std::fs::write(checkout.path().join("notes.txt"), "hello\n")?;
+        let patch = checkout.path().with_file_name("result.patch");
+        checkout.write_patch(&patch).await?;
+        assert!(std::fs::read_to_string(&patch)?.contains("+hello"));
+
+        let identity = [
+            ["config", "user.name", "Test User"],
+            ["config", "user.email", "test@test.com"],
+            ["config", "commit.gpgsign", "false"],
+        ];
+        for args in identity {
+            std::process::Command::new("git")
+                .args(args)
+                .current_dir(checkout.path())
+                .status()?;
+        }
+        let branch = result_branch("claude", "20250101-120000");
+        checkout.push_result(&branch, "Add notes").await?;
+        assert!(
+            origin
+                .git(&["branch", "--list", &branch])?
+                .contains(&branch)
+        );
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fetch_unreachable_repository() {
+        let dir = tempfile::tempdir().map(|dir| dir.path().join("missing"));
+        let url = format!("file://{}", dir.unwrap_or_default().display());
+        assert!(matches!(
+            RemoteCheckout::fetch(&url).await,
+            Err(crate::error::Error::RemoteRepository { .. })
+        ));
+    }
+}
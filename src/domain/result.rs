@@ -1,9 +1,19 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
 
-use crate::error::Result;
-use crate::executor::OutputLine;
+use serde::Serialize;
+
+use crate::config::RepoSettings;
+use crate::error::{Error, Result};
+use crate::executor::{ErrorKind, OutputLine};
 use crate::git;
+use crate::integrations::CiStatus;
 
+use super::diff::Diff;
+use super::hooks::{CheckResult, Score};
+use super::ranking::ScoreBreakdown;
+use super::retry::is_transient;
+use super::secrets::{SecretFinding, find_secrets};
 use super::task::TaskResult;
 
 /// Options for displaying results
@@ -30,6 +40,8 @@ pub struct ResultInfo {
     pub files_changed: usize,
     /// The change summary
     pub change_summary: Option<git::ChangeSummary>,
+    /// Short agent-written summary of the changes
+    pub summary: Option<String>,
     /// Path to the worktree
     pub worktree_path: std::path::PathBuf,
     /// Standard output from the executor
@@ -38,6 +50,148 @@ pub struct ResultInfo {
     pub stderr: String,
     /// Output lines in order of arrival (stdout and stderr interleaved)
     pub output_lines: Vec<OutputLine>,
+    /// Uncommitted local files that applying this result would overwrite
+    pub conflicts: Vec<String>,
+    /// Outcome of the repository's score command
+    pub score: Option<Score>,
+    /// Outcome of the quick check command
+    pub check: Option<CheckResult>,
+    /// How long the agent took
+    pub duration: Option<Duration>,
+    /// Weighted score used by `--auto-select weighted`
+    pub breakdown: Option<ScoreBreakdown>,
+    /// Hash of the normalized diff, equal for candidates with the same changes
+    pub diff_hash: Option<String>,
+    /// Name of an earlier candidate with the same changes
+    pub duplicate_of: Option<String>,
+    /// Added and removed lines of the diff, prefixed with their file, sorted
+    pub changed_lines: Vec<String>,
+    /// Similarity to each other candidate by name, from 0.0 to 1.0
+    pub similarity: Vec<(String, f64)>,
+    /// Output line explaining each failed attempt that was retried
+    pub retries: Vec<String>,
+    /// Why the agent failed, if recognized
+    pub error_kind: Option<ErrorKind>,
+    /// Added lines that look like API keys, tokens, or private keys
+    pub secrets: Vec<SecretFinding>,
+    /// Violations of the repository's policy, e.g. a new file without a license header
+    pub policy: Vec<String>,
+}
+
+/// Last non-empty line of `stderr`, or of `stdout` if `stderr` is empty
+fn last_output_line(stderr: &str, stdout: &str) -> Option<String> {
+    let last_line = |text: &str| {
+        text.lines()
+            .map(str::trim)
+            .rfind(|line| !line.is_empty())
+            .map(str::to_string)
+    };
+    last_line(stderr).or_else(|| last_line(stdout))
+}
+
+/// Similarity from which two candidates count as agreeing
+pub const AGREEMENT_THRESHOLD: f64 = 0.5;
+
+impl ResultInfo {
+    /// Last line of the error output of a failed candidate, or of its output if
+    /// it printed no errors
+    #[must_use]
+    pub fn failure_reason(&self) -> Option<String> {
+        if self.success {
+            return None;
+        }
+        last_output_line(&self.stderr, &self.stdout)
+    }
+
+    /// Number of inserted and deleted lines
+    #[must_use]
+    pub fn changed_line_count(&self) -> usize {
+        self.change_summary
+            .as_ref()
+            .map_or(0, |s| s.insertions + s.deletions)
+    }
+
+    /// Whether the candidate changed more files or lines than the limits, where 0 means no limit
+    ///
+    /// Catches agents that reformatted or regenerated much more than they were asked to.
+    #[must_use]
+    pub fn is_large_diff(&self, max_files: usize, max_lines: usize) -> bool {
+        (max_files > 0 && self.files_changed > max_files)
+            || (max_lines > 0 && self.changed_line_count() > max_lines)
+    }
+
+    /// Number of other candidates whose changes are similar to this one's
+    #[must_use]
+    pub fn agreements(&self) -> usize {
+        self.similarity
+            .iter()
+            .filter(|(_, similarity)| *similarity >= AGREEMENT_THRESHOLD)
+            .count()
+    }
+}
+
+/// Jaccard similarity of two sorted sets of changed lines
+#[must_use]
+#[expect(
+    clippy::cast_precision_loss,
+    reason = "line counts are far below the precision of f64"
+)]
+pub fn jaccard(a: &[String], b: &[String]) -> f64 {
+    let (mut i, mut j, mut shared) = (0, 0, 0);
+    while i < a.len() && j < b.len() {
+        match a[i].cmp(&b[j]) {
+            std::cmp::Ordering::Less => i += 1,
+            std::cmp::Ordering::Greater => j += 1,
+            std::cmp::Ordering::Equal => {
+                shared += 1;
+                i += 1;
+                j += 1;
+            }
+        }
+    }
+    let union = a.len() + b.len() - shared;
+    if union == 0 {
+        0.0
+    } else {
+        shared as f64 / union as f64
+    }
+}
+
+/// Fill in the pairwise similarity of the successful candidates
+pub fn compute_similarity(infos: &mut [ResultInfo]) {
+    let matrix: Vec<Vec<(String, f64)>> = infos
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            infos
+                .iter()
+                .enumerate()
+                .filter(|&(j, b)| j != i && a.success && b.success)
+                .map(|(_, b)| {
+                    (
+                        b.executor_name.clone(),
+                        jaccard(&a.changed_lines, &b.changed_lines),
+                    )
+                })
+                .collect()
+        })
+        .collect();
+    for (info, row) in infos.iter_mut().zip(matrix) {
+        info.similarity = row;
+    }
+}
+
+/// Point every candidate whose changes equal an earlier candidate's to that candidate
+pub fn mark_duplicates(infos: &mut [ResultInfo]) {
+    for i in 1..infos.len() {
+        let Some(hash) = infos[i].diff_hash.clone() else {
+            continue;
+        };
+        infos[i].duplicate_of = infos[..i]
+            .iter()
+            .find(|earlier| earlier.diff_hash.as_ref() == Some(&hash))
+            .map(|earlier| earlier.executor_name.clone());
+    }
 }
 
 /// Prepare result information for display
@@ -45,7 +199,7 @@ pub struct ResultInfo {
 /// # Errors
 ///
 /// Currently this function does not return errors, but the signature is kept for future use.
-pub fn prepare_result_info(
+pub async fn prepare_result_info(
     result: &TaskResult,
     _original_path: &Path,
     _options: &DisplayOptions,
@@ -54,35 +208,514 @@ pub fn prepare_result_info(
         .change_summary
         .as_ref()
         .map_or(0, |s| s.files_added + s.files_modified + s.files_deleted);
+    let diff_text = git::get_diff(&result.worktree_path)
+        .await
+        .unwrap_or_default();
+    let diff = Diff::parse(&diff_text);
 
     Ok(ResultInfo {
         executor_name: result.execution.executor_name.clone(),
         success: result.execution.success,
         files_changed,
         change_summary: result.change_summary.clone(),
+        summary: result.summary.clone(),
         worktree_path: result.worktree_path.clone(),
         stdout: result.execution.stdout.clone(),
         stderr: result.execution.stderr.clone(),
         output_lines: result.execution.output_lines.clone(),
+        conflicts: Vec::new(),
+        score: result.score.clone(),
+        check: result.check.clone(),
+        duration: Some(result.duration),
+        breakdown: None,
+        diff_hash: diff.normalized_hash(),
+        duplicate_of: None,
+        changed_lines: diff.changed_lines(),
+        similarity: Vec::new(),
+        retries: result.retries.clone(),
+        error_kind: result.execution.error_kind,
+        secrets: find_secrets(&diff),
+        policy: result.policy.clone(),
+    })
+}
+
+/// Machine-readable outcome of a run, printed by `--json`
+#[derive(Debug, Clone, Default, PartialEq, Serialize)]
+pub struct RunOutcome {
+    /// Identifier of the run in the history
+    pub run_id: Option<String>,
+    /// Agent whose result was applied
+    pub applied: Option<String>,
+    /// Files created by applying the result
+    pub created: Vec<String>,
+    /// Files updated by applying the result
+    pub updated: Vec<String>,
+    /// Files deleted by applying the result
+    pub deleted: Vec<String>,
+    /// URL of the pull request opened for the applied result with `--pr`
+    pub pull_request: Option<String>,
+    /// State of the pull request's CI checks, waited for with `--watch-ci`
+    pub ci: Option<CiStatus>,
+    /// Weighted score of every candidate, with the contribution of each signal
+    pub scores: Vec<ScoreBreakdown>,
+    /// Agents that failed, with the reason
+    pub failed: Vec<FailedAgent>,
+    /// Message of the error that ended the run
+    pub error: Option<String>,
+    /// Exit code of the process
+    pub exit_code: i32,
+}
+
+/// An agent that failed, as listed in the `--json` output
+#[derive(Debug, Clone, PartialEq, Eq, Serialize)]
+pub struct FailedAgent {
+    /// Name of the agent
+    pub agent: String,
+    /// Last line of the agent's error output
+    pub error: Option<String>,
+    /// Exit code of the agent, if it ran and exited
+    pub exit_code: Option<i32>,
+    /// Why the agent failed, if recognized
+    pub kind: Option<ErrorKind>,
+    /// File with the agent's full output, if it was saved
+    pub log: Option<PathBuf>,
+    /// What to check to fix the failure, if its cause is recognized
+    pub hint: Option<String>,
+}
+
+impl FailedAgent {
+    /// Describe the failure of an agent from its result
+    #[must_use]
+    pub fn new(result: &TaskResult) -> Self {
+        let execution = &result.execution;
+        Self {
+            agent: execution.executor_name.clone(),
+            error: last_output_line(&execution.stderr, &execution.stdout),
+            exit_code: execution.exit_code,
+            kind: execution.error_kind,
+            log: None,
+            hint: failure_hint(&execution.executor_name, &execution.stderr),
+        }
+    }
+}
+
+impl From<&ResultInfo> for FailedAgent {
+    fn from(info: &ResultInfo) -> Self {
+        Self {
+            agent: info.executor_name.clone(),
+            error: info.failure_reason(),
+            exit_code: None,
+            kind: info.error_kind,
+            log: None,
+            hint: failure_hint(&info.executor_name, &info.stderr),
+        }
+    }
+}
+
+/// Suggest what to check for a failed agent, from its error output
+#[must_use]
+pub fn failure_hint(agent: &str, stderr: &str) -> Option<String> {
+    let lower = stderr.to_lowercase();
+    let mentions = |patterns: &[&str]| patterns.iter().any(|pattern| lower.contains(pattern));
+    if stderr.starts_with("Setup command") {
+        Some(
+            "Fix the `setup` command in .parari.toml; it failed before the agent started"
+                .to_string(),
+        )
+    } else if mentions(&[
+        "unauthorized",
+        "not logged in",
+        "api key",
+        "authenticat",
+        "forbidden",
+    ]) {
+        Some(format!(
+            "Check that {agent} is logged in, e.g. by running `{agent}` in a terminal"
+        ))
+    } else if mentions(&[
+        "no such file or directory",
+        "command not found",
+        "not found in path",
+    ]) {
+        Some(format!(
+            "Check that the {agent} CLI is installed and on PATH (`{agent} --version`)"
+        ))
+    } else if stderr.lines().any(is_transient) {
+        Some("The error looks temporary; try again, or with `--retries 2`".to_string())
+    } else if stderr.starts_with("Stopped after printing nothing") {
+        Some("The agent stalled; raise `--stall-timeout` or try a smaller task".to_string())
+    } else {
+        None
+    }
+}
+
+impl RunOutcome {
+    /// Outcome of a run that ended with an error
+    #[must_use]
+    pub fn failed(error: &Error) -> Self {
+        Self {
+            error: Some(error.to_string()),
+            exit_code: error.exit_code(),
+            ..Self::default()
+        }
+    }
+}
+
+/// A checkout to apply the selected result to instead of the working directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ApplyTarget {
+    /// Root of the checkout
+    pub root: PathBuf,
+    /// Whether HEAD is at another commit than the worktrees were created from, so the
+    /// result is merged in with [`apply_result_as_patch`] instead of copied
+    pub moved: bool,
+}
+
+/// Resolve and validate a checkout to apply a result to instead of the working directory
+///
+/// The target must be a checkout of the same repository, so it has to contain the
+/// commit the worktrees were created from.
+///
+/// # Errors
+///
+/// Returns an error if the target does not exist, is not a git repository, or does not
+/// contain `base_commit`.
+pub async fn resolve_apply_target(path: &Path, base_commit: Option<&str>) -> Result<ApplyTarget> {
+    let invalid = |reason: String| Error::InvalidApplyTarget {
+        path: path.to_path_buf(),
+        reason,
+    };
+
+    let path = path
+        .canonicalize()
+        .map_err(|_| invalid("directory does not exist".to_string()))?;
+    if !git::is_git_repository(&path).await {
+        return Err(invalid("not a git repository".to_string()));
+    }
+    if let Some(commit) = base_commit
+        && !git::has_commit(&path, commit).await
+    {
+        let short: String = commit.chars().take(7).collect();
+        return Err(invalid(format!(
+            "commit {short} is missing, so it is not a checkout of the same repository"
+        )));
+    }
+
+    let moved = match base_commit {
+        Some(commit) => git::get_head_commit(&path).await.ok().as_deref() != Some(commit),
+        None => false,
+    };
+    Ok(ApplyTarget {
+        root: git::get_repo_root(&path).await?,
+        moved,
     })
 }
 
+/// Merge the selected result into a target checkout at another commit than its worktree
+///
+/// Copying the files would undo the commits the target has on top of the worktree's,
+/// so the changes are applied as a patch with a three-way merge instead. Files matching
+/// the repository's ignore or secret file patterns are left out. Files with conflicts
+/// are listed in the summary, with the conflict markers left in them.
+///
+/// # Errors
+///
+/// Returns an error if the patch cannot be created or does not apply.
+pub async fn apply_result_as_patch(
+    result: &TaskResult,
+    target: &Path,
+    repo_settings: &RepoSettings,
+) -> Result<git::ApplySummary> {
+    let changed: Vec<&git::FileChange> = result
+        .change_summary
+        .iter()
+        .flat_map(|s| &s.changed_files)
+        .filter(|file| {
+            !repo_settings.is_ignored(&file.path) && !repo_settings.is_secret(&file.path)
+        })
+        .collect();
+    let paths: Vec<String> = changed.iter().map(|file| file.path.clone()).collect();
+
+    let mut summary = git::ApplySummary {
+        conflicts: git::apply_changes_as_patch(&result.worktree_path, target, &paths).await?,
+        ..git::ApplySummary::default()
+    };
+    for file in changed {
+        let files = match file.status {
+            git::FileStatus::Added => &mut summary.created,
+            git::FileStatus::Modified => &mut summary.updated,
+            git::FileStatus::Deleted => &mut summary.deleted,
+        };
+        files.push(file.path.clone());
+        if file.binary && file.status != git::FileStatus::Deleted {
+            summary.binary.push(file.path.clone());
+        }
+    }
+    Ok(summary)
+}
+
+/// A result's worktree after running the repository's formatter in it
+#[derive(Debug, Clone)]
+pub struct FormattedResult {
+    /// Outcome of the formatter
+    pub check: CheckResult,
+    /// Files of the formatted result that overwrite local edits in the target,
+    /// except generated ones
+    pub conflicts: Vec<String>,
+}
+
+/// Run the repository's formatter in a result's worktree, then check the formatted
+/// files for conflicts with local edits in `target`
+///
+/// The formatter may touch files the agent did not, so conflicts found before
+/// formatting can miss some.
+///
+/// # Errors
+///
+/// Returns an error if the formatter cannot be started or the conflicts cannot be
+/// checked.
+pub async fn format_result(
+    command: &str,
+    worktree: &Path,
+    target: &Path,
+    repo_settings: &RepoSettings,
+) -> Result<FormattedResult> {
+    let check = super::hooks::run_check(command, worktree).await?;
+    let mut conflicts = git::check_conflicts(worktree, target).await?;
+    conflicts.retain(|file| !repo_settings.is_generated(file));
+    Ok(FormattedResult { check, conflicts })
+}
+
 /// Apply the selected result to the target directory
 ///
 /// # Errors
 ///
 /// Returns an error if file operations fail during the copy.
-pub async fn apply_result(result: &TaskResult, target: &Path) -> Result<()> {
-    git::apply_changes(&result.worktree_path, target).await
+pub async fn apply_result(result: &TaskResult, target: &Path) -> Result<git::ApplySummary> {
+    apply_result_with_progress(result, target, &RepoSettings::default(), |_, _| {}).await
+}
+
+/// Apply the selected result to the target directory, reporting copied files
+///
+/// Files the agent deleted are removed from the target as well. Files matching the
+/// repository's ignore or secret file patterns are neither copied nor removed, so the
+/// local secrets kept away from the agents are never touched either.
+///
+/// # Errors
+///
+/// Returns an error if file operations fail during the copy.
+pub async fn apply_result_with_progress(
+    result: &TaskResult,
+    target: &Path,
+    repo_settings: &RepoSettings,
+    on_progress: impl Fn(usize, usize),
+) -> Result<git::ApplySummary> {
+    let mut summary = git::apply_changes_with_progress(
+        &result.worktree_path,
+        target,
+        |file| repo_settings.is_ignored(file) || repo_settings.is_secret(file),
+        on_progress,
+    )
+    .await?;
+
+    let deleted: Vec<String> = result
+        .change_summary
+        .iter()
+        .flat_map(|s| &s.changed_files)
+        .filter(|file| file.status == git::FileStatus::Deleted)
+        .filter(|file| {
+            !repo_settings.is_ignored(&file.path) && !repo_settings.is_secret(&file.path)
+        })
+        .map(|file| file.path.clone())
+        .collect();
+    summary.deleted = git::remove_files(target, &deleted).await?;
+
+    Ok(summary)
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::TestRepo;
 
     #[test]
     fn test_display_options_default() {
         let opts = DisplayOptions::default();
         assert!(opts.show_summary);
     }
+
+    #[test]
+    fn test_failure_hint() {
+        let hint = |stderr: &str| failure_hint("codex", stderr);
+        assert!(
+            hint("Setup command 'npm ci' failed:\nnpm ERR!").is_some_and(|h| h.contains("setup"))
+        );
+        assert!(
+            hint("Error: Not logged in. Run codex login").is_some_and(|h| h.contains("logged in"))
+        );
+        assert!(
+            hint("No such file or directory (os error 2)").is_some_and(|h| h.contains("installed"))
+        );
+        assert!(hint("API Error: 529 overloaded").is_some_and(|h| h.contains("--retries")));
+        assert_eq!(hint("Could not complete the task"), None);
+    }
+
+    #[test]
+    fn test_mark_duplicates() {
+        let info = |name: &str, hash: Option<&str>| ResultInfo {
+            executor_name: name.to_string(),
+            success: true,
+            files_changed: 1,
+            change_summary: None,
+            summary: None,
+            worktree_path: PathBuf::from("/tmp/test"),
+            stdout: String::new(),
+            stderr: String::new(),
+            output_lines: vec![],
+            conflicts: vec![],
+            score: None,
+            check: None,
+            duration: None,
+            breakdown: None,
+            diff_hash: hash.map(str::to_string),
+            duplicate_of: None,
+            changed_lines: vec![],
+            similarity: vec![],
+            retries: vec![],
+            error_kind: None,
+            secrets: vec![],
+            policy: vec![],
+        };
+        let mut infos = vec![
+            info("claude", Some("a")),
+            info("gemini", Some("b")),
+            info("codex", Some("a")),
+            info("claude-2", None),
+        ];
+        mark_duplicates(&mut infos);
+        let duplicates: Vec<Option<&str>> =
+            infos.iter().map(|i| i.duplicate_of.as_deref()).collect();
+        assert_eq!(duplicates, [None, None, Some("claude"), None]);
+    }
+
+    #[test]
+    fn test_similarity() {
+        let changed_lines = |path: &str, lines: &str| {
+            Diff::parse(&format!(
+                "diff --git a/{path} b/{path}\n@@ -1 +1 @@\n{lines}"
+            ))
+            .changed_lines()
+        };
+        let claude = changed_lines("a.rs", "-old\n+new\n+extra\n");
+        let codex = changed_lines("a.rs", "-old\n+new\n");
+        let gemini = changed_lines("b.rs", "-old\n+new\n");
+
+        assert!((jaccard(&claude, &codex) - 2.0 / 3.0).abs() < f64::EPSILON);
+        assert!(jaccard(&claude, &gemini).abs() < f64::EPSILON);
+        assert!(jaccard(&[], &[]).abs() < f64::EPSILON);
+
+        let info = |name: &str, changed_lines: &[String]| ResultInfo {
+            executor_name: name.to_string(),
+            success: true,
+            files_changed: 1,
+            change_summary: None,
+            summary: None,
+            worktree_path: PathBuf::from("/tmp/test"),
+            stdout: String::new(),
+            stderr: String::new(),
+            output_lines: vec![],
+            conflicts: vec![],
+            score: None,
+            check: None,
+            duration: None,
+            breakdown: None,
+            diff_hash: None,
+            duplicate_of: None,
+            changed_lines: changed_lines.to_vec(),
+            similarity: vec![],
+            retries: vec![],
+            error_kind: None,
+            secrets: vec![],
+            policy: vec![],
+        };
+        let mut infos = vec![
+            info("claude", &claude),
+            info("codex", &codex),
+            info("gemini", &gemini),
+        ];
+        compute_similarity(&mut infos);
+        let names: Vec<&str> = infos[0]
+            .similarity
+            .iter()
+            .map(|(n, _)| n.as_str())
+            .collect();
+        assert_eq!(names, ["codex", "gemini"]);
+        assert_eq!(infos[0].agreements(), 1);
+        assert_eq!(infos[2].agreements(), 0);
+    }
+
+    #[tokio::test]
+    async fn test_format_result_checks_formatted_files()
+    -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let target = TestRepo::builder()
+            .with_file("a.txt", "one\n")
+            .with_file("b.txt", "two\n")
+            .build()?;
+        let dir = tempfile::tempdir()?;
+        let worktree = dir.path().join("worktree");
+        target.git(&[
+            "worktree",
+            "add",
+            "-q",
+            "--detach",
+            &worktree.to_string_lossy(),
+        ])?;
+        std::fs::write(worktree.join("a.txt"), "agent\n")?;
+        target.write("b.txt", "local\n")?;
+        assert!(
+            git::check_conflicts(&worktree, target.path())
+                .await?
+                .is_empty()
+        );
+
+        // The formatter touches a file with local edits the agent left alone
+        let formatted = format_result(
+            "printf 'formatted\\n' > b.txt",
+            &worktree,
+            target.path(),
+            &RepoSettings::default(),
+        )
+        .await?;
+        assert!(formatted.check.passed);
+        assert_eq!(formatted.conflicts, ["b.txt"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_resolve_apply_target() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cwd = std::env::current_dir()?;
+        let root = git::get_repo_root(&cwd).await?;
+        let head = git::get_head_commit(&cwd).await?;
+        assert_eq!(
+            resolve_apply_target(&cwd, Some(&head)).await?,
+            ApplyTarget { root, moved: false }
+        );
+
+        // A checkout that moved on from the base commit gets the result as a patch
+        let repo = TestRepo::new()?;
+        let base = git::get_head_commit(repo.path()).await?;
+        repo.commit("next")?;
+        assert!(resolve_apply_target(repo.path(), Some(&base)).await?.moved);
+
+        let missing = cwd.join("does-not-exist");
+        assert!(matches!(
+            resolve_apply_target(&missing, None).await,
+            Err(Error::InvalidApplyTarget { .. })
+        ));
+        assert!(matches!(
+            resolve_apply_target(&cwd, Some(&"0".repeat(40))).await,
+            Err(Error::InvalidApplyTarget { .. })
+        ));
+        Ok(())
+    }
 }
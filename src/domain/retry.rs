@@ -0,0 +1,112 @@
+use std::time::Duration;
+
+use crate::executor::traits::{ExecutionResult, OutputLine};
+
+/// Output of failures that are likely to go away when the agent is run again
+///
+/// Matched case-insensitively against each line of output.
+const TRANSIENT_PATTERNS: [&str; 16] = [
+    "rate limit",
+    "rate_limit",
+    "too many requests",
+    "overloaded",
+    "internal server error",
+    "bad gateway",
+    "service unavailable",
+    "gateway timeout",
+    "econnreset",
+    "econnrefused",
+    "etimedout",
+    "connection reset",
+    "connection refused",
+    "network error",
+    "socket hang up",
+    "temporarily unavailable",
+];
+
+/// HTTP status codes of transient failures, matched as whole words
+const TRANSIENT_STATUS_CODES: [&str; 6] = ["429", "500", "502", "503", "504", "529"];
+
+/// Delay before the first retry; doubled for each further attempt
+const BASE_DELAY: Duration = Duration::from_secs(2);
+
+/// Longest delay between two attempts
+const MAX_DELAY: Duration = Duration::from_mins(1);
+
+/// The output line explaining why a failed execution is worth retrying
+///
+/// Returns `None` for successful executions and for failures that do not look
+/// transient, such as the agent giving up on the task.
+#[must_use]
+pub fn transient_failure(execution: &ExecutionResult) -> Option<String> {
+    if execution.success {
+        return None;
+    }
+    execution
+        .output_lines
+        .iter()
+        .map(|line| match line {
+            OutputLine::Stdout(text) | OutputLine::Stderr(text) => text.trim(),
+        })
+        .find(|text| is_transient(text))
+        .map(str::to_string)
+}
+
+/// Whether a line of output reports a transient failure
+#[must_use]
+pub fn is_transient(line: &str) -> bool {
+    let lower = line.to_lowercase();
+    TRANSIENT_PATTERNS
+        .iter()
+        .any(|pattern| lower.contains(pattern))
+        || lower
+            .split(|c: char| !c.is_ascii_alphanumeric())
+            .any(|word| TRANSIENT_STATUS_CODES.contains(&word))
+}
+
+/// How long to wait before retrying after `attempt` failed attempts
+#[must_use]
+pub fn retry_delay(attempt: usize) -> Duration {
+    let factor = 1_u32 << attempt.min(16);
+    BASE_DELAY.saturating_mul(factor).min(MAX_DELAY)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_transient_failure() {
+        let overloaded = ExecutionResult::failure(
+            "claude",
+            "Starting...\nAPI Error: 529 {\"type\":\"overloaded_error\"}".to_string(),
+            Some(1),
+        );
+        assert_eq!(
+            transient_failure(&overloaded).as_deref(),
+            Some("API Error: 529 {\"type\":\"overloaded_error\"}")
+        );
+
+        let network = ExecutionResult::failure("codex", "Error: ECONNRESET".to_string(), Some(1));
+        assert!(transient_failure(&network).is_some());
+
+        let gave_up = ExecutionResult::failure(
+            "gemini",
+            "Could not find the file src/5000.rs".to_string(),
+            Some(1),
+        );
+        assert_eq!(transient_failure(&gave_up), None);
+
+        let success = ExecutionResult::success("claude", "retrying after 429".to_string());
+        assert_eq!(transient_failure(&success), None);
+    }
+
+    #[test]
+    fn test_retry_delay() {
+        assert_eq!(retry_delay(0), Duration::from_secs(2));
+        assert_eq!(retry_delay(1), Duration::from_secs(4));
+        assert_eq!(retry_delay(2), Duration::from_secs(8));
+        assert_eq!(retry_delay(10), MAX_DELAY);
+        assert_eq!(retry_delay(100), MAX_DELAY);
+    }
+}
@@ -0,0 +1,119 @@
+use std::path::PathBuf;
+
+use chrono::{DateTime, Local};
+use serde::{Deserialize, Serialize};
+
+use super::snapshot::RunSnapshot;
+
+/// What was asked in a run and where, shown alongside its results
+#[derive(Debug, Clone)]
+pub struct RunInfo {
+    /// Identifier of the run in the history, derived from its start time
+    pub id: String,
+    /// The prompt given to every agent
+    pub prompt: String,
+    /// Directory the agents were run against
+    pub working_dir: PathBuf,
+    /// Commit the worktrees were created from, if the repository has one
+    pub base_commit: Option<String>,
+    /// When the run started
+    pub started_at: DateTime<Local>,
+    /// URL of the issue the prompt was taken from with `--issue`
+    pub issue: Option<String>,
+    /// Uncommitted files and agent versions the run started with
+    pub snapshot: RunSnapshot,
+    /// Options that shaped the agents' work, to run the prompt the same way again
+    pub options: RunOptions,
+    /// Identifier of the run this one repeats with `parari rerun`
+    pub rerun_of: Option<String>,
+}
+
+/// Command-line options of a run that `parari rerun` uses again
+#[derive(Debug, Clone, Default, PartialEq, Deserialize, Serialize)]
+pub struct RunOptions {
+    /// Directories checked out in the worktrees, with `--scope`
+    #[serde(default)]
+    pub scope: Option<Vec<String>>,
+    /// Quick check run in each worktree, with `--check-cmd` (empty for the default)
+    #[serde(default)]
+    pub check_cmd: Option<String>,
+    /// Whether agents were not asked to summarize their changes
+    #[serde(default)]
+    pub no_summary: bool,
+    /// Retries of agents failing with a transient error
+    #[serde(default)]
+    pub retries: u8,
+    /// Spending limit of each agent in US dollars
+    #[serde(default)]
+    pub max_cost: Option<f64>,
+    /// Token limit of each agent
+    #[serde(default)]
+    pub max_tokens: Option<u64>,
+    /// Whether stalled agents were stopped
+    #[serde(default)]
+    pub kill_stalled: bool,
+}
+
+impl RunInfo {
+    /// Create run information for a run starting now
+    #[must_use]
+    pub fn new(prompt: impl Into<String>, working_dir: impl Into<PathBuf>) -> Self {
+        let started_at = Local::now();
+        Self {
+            id: started_at.format("%Y%m%d-%H%M%S").to_string(),
+            prompt: prompt.into(),
+            working_dir: working_dir.into(),
+            base_commit: None,
+            started_at,
+            issue: None,
+            snapshot: RunSnapshot::default(),
+            options: RunOptions::default(),
+            rerun_of: None,
+        }
+    }
+
+    /// Set the commit the run is based on
+    #[must_use]
+    pub fn with_base_commit(mut self, commit: Option<String>) -> Self {
+        self.base_commit = commit;
+        self
+    }
+
+    /// Set the issue the prompt was taken from
+    #[must_use]
+    pub fn with_issue(mut self, issue: Option<String>) -> Self {
+        self.issue = issue;
+        self
+    }
+
+    /// Set the uncommitted files and agent versions the run started with
+    #[must_use]
+    pub fn with_snapshot(mut self, snapshot: RunSnapshot) -> Self {
+        self.snapshot = snapshot;
+        self
+    }
+
+    /// Set the options the agents were run with
+    #[must_use]
+    pub fn with_options(mut self, options: RunOptions) -> Self {
+        self.options = options;
+        self
+    }
+
+    /// Set the run this one repeats
+    #[must_use]
+    pub fn with_rerun_of(mut self, id: Option<String>) -> Self {
+        self.rerun_of = id;
+        self
+    }
+
+    /// The first non-empty line of the prompt, used as a title
+    #[must_use]
+    pub fn title(&self) -> &str {
+        self.prompt
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty())
+            .unwrap_or_default()
+    }
+}
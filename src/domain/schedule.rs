@@ -0,0 +1,92 @@
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
+use std::time::Duration;
+
+use tokio::sync::Semaphore;
+
+use crate::config::ProviderSettings;
+
+/// When agents start and how many of them run at once, per provider
+#[derive(Debug, Default)]
+pub struct Schedule {
+    providers: BTreeMap<String, ProviderSettings>,
+    /// Free slots of the providers limiting their running agents
+    slots: HashMap<String, Arc<Semaphore>>,
+}
+
+impl Schedule {
+    /// Create a schedule following the settings of each provider
+    #[must_use]
+    pub fn new(providers: BTreeMap<String, ProviderSettings>) -> Self {
+        let slots = providers
+            .iter()
+            .filter_map(|(provider, settings)| {
+                let limit = settings.max_concurrent?;
+                Some((provider.clone(), Arc::new(Semaphore::new(limit.max(1)))))
+            })
+            .collect();
+        Self { providers, slots }
+    }
+
+    /// How long each agent waits before starting, given its provider
+    ///
+    /// The first agent of a provider starts at once, and each further one waits one
+    /// more stagger of that provider.
+    #[must_use]
+    pub fn start_delays(&self, providers: &[&str]) -> Vec<Duration> {
+        let mut started: HashMap<&str, u32> = HashMap::new();
+        providers
+            .iter()
+            .map(|provider| {
+                let index = started.entry(*provider).or_insert(0);
+                let stagger = self
+                    .providers
+                    .get(*provider)
+                    .map_or(Duration::ZERO, ProviderSettings::stagger);
+                let delay = stagger * *index;
+                *index += 1;
+                delay
+            })
+            .collect()
+    }
+
+    /// The slots an agent of `provider` needs one of to run, if the provider has a limit
+    #[must_use]
+    pub fn slots(&self, provider: &str) -> Option<Arc<Semaphore>> {
+        self.slots.get(provider).map(Arc::clone)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn schedule() -> Schedule {
+        Schedule::new(BTreeMap::from([(
+            "claude".to_string(),
+            ProviderSettings {
+                max_concurrent: Some(2),
+                stagger_secs: 10,
+            },
+        )]))
+    }
+
+    #[test]
+    fn test_start_delays_stagger_each_provider() {
+        let delays = schedule().start_delays(&["claude", "codex", "claude", "codex", "claude"]);
+        let secs: Vec<u64> = delays.iter().map(Duration::as_secs).collect();
+        assert_eq!(secs, [0, 0, 10, 0, 20]);
+    }
+
+    #[test]
+    fn test_slots_of_limited_providers() {
+        let schedule = schedule();
+        assert_eq!(
+            schedule
+                .slots("claude")
+                .map(|slots| slots.available_permits()),
+            Some(2)
+        );
+        assert!(schedule.slots("codex").is_none());
+    }
+}
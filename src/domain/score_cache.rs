@@ -0,0 +1,155 @@
+use std::path::{Path, PathBuf};
+
+use super::history::hash_text;
+use super::hooks::Score;
+use crate::config::score_cache_dir;
+use crate::git;
+
+/// Number of results kept; the least recently stored are removed beyond it
+const MAX_ENTRIES: usize = 1_000;
+
+/// Results of the score command, by the content of the worktree they were taken in
+///
+/// Running the tests of a large repository takes a while, and `parari watch` or a
+/// rerun often ends with a worktree that is the same as in an earlier run, so the
+/// earlier score is used again instead. Each result is a JSON file named after its key.
+/// Only passing scores are kept, as a failure may come from a flaky test or the
+/// machine rather than the content.
+#[derive(Debug, Clone)]
+pub struct ScoreCache {
+    dir: PathBuf,
+}
+
+impl ScoreCache {
+    /// The cache in the user's parari directory
+    #[must_use]
+    pub fn open() -> Self {
+        Self::in_dir(score_cache_dir())
+    }
+
+    /// A cache in the given directory, created when the first score is stored
+    #[must_use]
+    pub fn in_dir(dir: impl Into<PathBuf>) -> Self {
+        Self { dir: dir.into() }
+    }
+
+    /// The score stored under `key`, if any
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<Score> {
+        let content = std::fs::read_to_string(self.path(key)).ok()?;
+        serde_json::from_str(&content).ok()
+    }
+
+    /// Store a passing score under `key`; a cache that cannot be written is only slower
+    pub fn insert(&self, key: &str, score: &Score) {
+        if !score.passed {
+            return;
+        }
+        let Ok(json) = serde_json::to_string(score) else {
+            return;
+        };
+        if std::fs::create_dir_all(&self.dir).is_ok() {
+            let _ = std::fs::write(self.path(key), json);
+            self.evict(MAX_ENTRIES);
+        }
+    }
+
+    /// Remove the least recently stored results beyond `max`
+    fn evict(&self, max: usize) {
+        let Ok(entries) = std::fs::read_dir(&self.dir) else {
+            return;
+        };
+        let mut files: Vec<_> = entries
+            .filter_map(|entry| {
+                let entry = entry.ok()?;
+                let modified = entry.metadata().ok()?.modified().ok()?;
+                Some((modified, entry.path()))
+            })
+            .collect();
+        if files.len() <= max {
+            return;
+        }
+        files.sort();
+        for (_, path) in &files[..files.len() - max] {
+            let _ = std::fs::remove_file(path);
+        }
+    }
+
+    fn path(&self, key: &str) -> PathBuf {
+        self.dir.join(format!("{key}.json"))
+    }
+}
+
+/// Key of the score of `command` in a worktree: a hash of the command, the commit the
+/// worktree is based on, and its diff against that commit, binary files included
+///
+/// Returns `None` if the commit or diff cannot be read.
+pub async fn score_key(command: &str, worktree: &Path) -> Option<String> {
+    let commit = git::get_head_commit(worktree).await.ok()?;
+    let diff = git::get_binary_diff(worktree).await.ok()?;
+    Some(content_key(command, &commit, &diff))
+}
+
+/// Key of the score of `command` on `commit` with `diff` applied
+#[must_use]
+pub fn content_key(command: &str, commit: &str, diff: &str) -> String {
+    hash_text(&format!("{command}\0{commit}\0{diff}"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_score_cache() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let cache = ScoreCache::in_dir(dir.path().join("scores"));
+        let key = content_key("cargo test", "abc123", "+fn lex() {}\n");
+        assert_eq!(cache.get(&key), None);
+
+        let score = Score {
+            passed: true,
+            value: Some(0.75),
+            output: "test result: ok\n0.75\n".to_string(),
+        };
+        cache.insert(&key, &score);
+        assert_eq!(cache.get(&key), Some(score));
+
+        // Another command, commit, or diff is another result
+        assert_ne!(
+            key,
+            content_key("cargo test --all", "abc123", "+fn lex() {}\n")
+        );
+        assert_ne!(key, content_key("cargo test", "def456", "+fn lex() {}\n"));
+        assert_ne!(key, content_key("cargo test", "abc123", "+fn parse() {}\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_score_cache_keeps_passing_scores() -> Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let cache = ScoreCache::in_dir(dir.path().join("scores"));
+        let failed = Score {
+            passed: false,
+            value: None,
+            output: "test result: FAILED\n".to_string(),
+        };
+        cache.insert("failed", &failed);
+        assert_eq!(cache.get("failed"), None);
+
+        let passed = Score {
+            passed: true,
+            ..failed
+        };
+        for key in ["a", "b", "c"] {
+            cache.insert(key, &passed);
+        }
+        cache.evict(2);
+        let kept = ["a", "b", "c"]
+            .iter()
+            .filter(|key| cache.get(key).is_some())
+            .count();
+        assert_eq!(kept, 2);
+        Ok(())
+    }
+}
@@ -0,0 +1,180 @@
+use std::path::Path;
+
+use super::history::{RunRecord, list_runs_in};
+use crate::config::history_dir;
+use crate::error::Result;
+
+/// Most matching lines shown for one run
+const MAX_MATCHES: usize = 3;
+
+/// Longest matching line shown, in characters
+const MAX_LINE_WIDTH: usize = 100;
+
+/// A run of the history matching a search, with where it matched
+#[derive(Debug, Clone)]
+pub struct SearchHit {
+    /// The matching run
+    pub run: RunRecord,
+    /// The first few lines matching a term
+    pub matches: Vec<SearchMatch>,
+}
+
+/// A line of a run containing a search term
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SearchMatch {
+    /// Where the line is from, e.g. `prompt` or `codex diff`
+    pub source: String,
+    /// The line, trimmed and shortened
+    pub line: String,
+}
+
+/// Search the runs of the user's history, newest first
+///
+/// See [`search_runs_in`].
+///
+/// # Errors
+///
+/// Returns an error if the history directory cannot be read.
+pub fn search_runs(query: &str) -> Result<Vec<SearchHit>> {
+    search_runs_in(&history_dir(), query)
+}
+
+/// Search the runs of the given history directory, newest first
+///
+/// A run matches when each word of the query appears, ignoring case, in its prompt,
+/// the notes and errors of its candidates, or the logs and diffs saved with it.
+///
+/// # Errors
+///
+/// Returns an error if the directory exists but cannot be read.
+pub fn search_runs_in(dir: &Path, query: &str) -> Result<Vec<SearchHit>> {
+    let terms: Vec<String> = query.split_whitespace().map(str::to_lowercase).collect();
+    if terms.is_empty() {
+        return Ok(Vec::new());
+    }
+    Ok(list_runs_in(dir)?
+        .into_iter()
+        .filter_map(|run| {
+            let texts = searchable_texts(dir, &run);
+            let all_found = terms.iter().all(|term| {
+                texts
+                    .iter()
+                    .any(|(_, text)| text.to_lowercase().contains(term.as_str()))
+            });
+            all_found.then(|| SearchHit {
+                matches: matching_lines(&texts, &terms),
+                run,
+            })
+        })
+        .collect())
+}
+
+/// Everything of a run that is searched, with where it is from
+fn searchable_texts(dir: &Path, run: &RunRecord) -> Vec<(String, String)> {
+    let mut texts = vec![("prompt".to_string(), run.prompt.clone())];
+    for candidate in &run.candidates {
+        if let Some(note) = &candidate.note {
+            texts.push((format!("{} note", candidate.agent), note.clone()));
+        }
+        if let Some(error) = &candidate.error {
+            texts.push((format!("{} error", candidate.agent), error.clone()));
+        }
+    }
+    // Logs and diffs saved as `<run id>/<agent>.log` and `<run id>/<agent>.patch`
+    let Ok(entries) = std::fs::read_dir(dir.join(&run.id)) else {
+        return texts;
+    };
+    let mut files: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .collect();
+    files.sort();
+    for path in files {
+        let (Some(agent), Some(extension)) = (path.file_stem(), path.extension()) else {
+            continue;
+        };
+        let kind = match extension.to_str() {
+            Some("log") => "log",
+            Some("patch") => "diff",
+            _ => continue,
+        };
+        if let Ok(content) = std::fs::read_to_string(&path) {
+            texts.push((format!("{} {kind}", agent.to_string_lossy()), content));
+        }
+    }
+    texts
+}
+
+/// The first lines containing any of the terms
+fn matching_lines(texts: &[(String, String)], terms: &[String]) -> Vec<SearchMatch> {
+    texts
+        .iter()
+        .flat_map(|(source, text)| text.lines().map(move |line| (source, line)))
+        .filter(|(_, line)| {
+            let line = line.to_lowercase();
+            terms.iter().any(|term| line.contains(term.as_str()))
+        })
+        .take(MAX_MATCHES)
+        .map(|(source, line)| SearchMatch {
+            source: source.clone(),
+            line: shorten(line.trim()),
+        })
+        .collect()
+}
+
+/// Shorten a line to [`MAX_LINE_WIDTH`] characters
+fn shorten(line: &str) -> String {
+    if line.chars().count() <= MAX_LINE_WIDTH {
+        return line.to_string();
+    }
+    let short: String = line.chars().take(MAX_LINE_WIDTH - 3).collect();
+    format!("{short}...")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{RunInfo, save_agent_patch_to};
+
+    fn save_run(dir: &Path, id: &str, prompt: &str) -> Result<RunRecord> {
+        let mut info = RunInfo::new(prompt, "/tmp/repo");
+        info.id = id.to_string();
+        let record = RunRecord::new(&info, &[], &[]);
+        record.save_to(dir)?;
+        Ok(record)
+    }
+
+    #[test]
+    fn test_search_prompts_and_diffs() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let tokenizer = save_run(
+            dir.path(),
+            "20250101-120000",
+            "Fix the tokenizer on empty input",
+        )?;
+        save_agent_patch_to(
+            dir.path(),
+            &tokenizer.id,
+            "codex",
+            "+++ b/src/lexer.rs\n+    if input.is_empty() { return Vec::new(); }\n",
+        )?;
+        save_run(dir.path(), "20250102-120000", "Add a README")?;
+
+        let hits = search_runs_in(dir.path(), "Tokenizer")?;
+        assert_eq!(hits.len(), 1);
+        assert_eq!(
+            hits[0].matches[0],
+            SearchMatch {
+                source: "prompt".to_string(),
+                line: "Fix the tokenizer on empty input".to_string(),
+            }
+        );
+
+        // Every word must be found, but not on the same line
+        let hits = search_runs_in(dir.path(), "tokenizer lexer.rs")?;
+        assert_eq!(hits.len(), 1);
+        assert!(hits[0].matches.iter().any(|m| m.source == "codex diff"));
+        assert!(search_runs_in(dir.path(), "tokenizer parser")?.is_empty());
+        assert!(search_runs_in(dir.path(), "  ")?.is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,168 @@
+use std::collections::BTreeMap;
+use std::path::Path;
+use std::sync::Arc;
+
+use futures::future::join_all;
+use serde::{Deserialize, Serialize};
+
+use super::history::Fnv;
+use crate::executor::Executor;
+use crate::git;
+
+/// Hash recorded for an uncommitted file that was deleted
+pub const DELETED: &str = "deleted";
+
+/// What a run started from besides its base commit, to reproduce it later
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize, Serialize)]
+pub struct RunSnapshot {
+    /// Hash of each uncommitted file of the checkout, by repository-relative path;
+    /// deleted files are recorded as [`DELETED`]
+    #[serde(default)]
+    pub dirty_files: BTreeMap<String, String>,
+    /// Version printed by each agent's CLI, by agent name
+    #[serde(default)]
+    pub agent_versions: BTreeMap<String, String>,
+}
+
+impl RunSnapshot {
+    /// Record the uncommitted files of `checkout` and the versions of the agents' CLIs
+    pub async fn take(checkout: &Path, executors: &[Arc<dyn Executor>]) -> Self {
+        let versions = join_all(executors.iter().map(|executor| async move {
+            (executor.name().to_string(), executor.version().await)
+        }))
+        .await;
+        Self {
+            dirty_files: dirty_file_hashes(checkout).await,
+            agent_versions: versions
+                .into_iter()
+                .filter_map(|(agent, version)| Some((agent, version?)))
+                .collect(),
+        }
+    }
+
+    /// The uncommitted files that differ between the snapshot and `current`, sorted
+    #[must_use]
+    pub fn changed_files(&self, current: &BTreeMap<String, String>) -> Vec<String> {
+        let mut changed: Vec<String> = self
+            .dirty_files
+            .iter()
+            .filter(|(path, hash)| current.get(*path) != Some(hash))
+            .map(|(path, _)| path.clone())
+            .chain(
+                current
+                    .keys()
+                    .filter(|path| !self.dirty_files.contains_key(*path))
+                    .cloned(),
+            )
+            .collect();
+        changed.sort();
+        changed
+    }
+}
+
+/// Hash of each uncommitted file of a checkout, by repository-relative path
+///
+/// Untracked directories are hashed as a whole, with the names of their files that
+/// are not ignored.
+pub async fn dirty_file_hashes(checkout: &Path) -> BTreeMap<String, String> {
+    let mut hashes = BTreeMap::new();
+    for entry in git::get_uncommitted_files(checkout)
+        .await
+        .unwrap_or_default()
+    {
+        let hash = if entry.ends_with('/') {
+            let files = git::get_untracked_files(checkout, &entry)
+                .await
+                .unwrap_or_default();
+            hash_files(&checkout.join(&entry), &entry, &files)
+        } else {
+            hash_file(&checkout.join(&entry))
+        };
+        hashes.insert(entry.trim_end_matches('/').to_string(), hash);
+    }
+    hashes
+}
+
+/// Hash of a file, read a piece at a time; [`DELETED`] if it does not exist
+fn hash_file(path: &Path) -> String {
+    let mut hasher = Fnv::default();
+    match std::fs::File::open(path).and_then(|mut file| std::io::copy(&mut file, &mut hasher)) {
+        Ok(_) => hasher.finish(),
+        Err(_) => DELETED.to_string(),
+    }
+}
+
+/// Hash of the content of `files` in the directory `dir`, each with its path relative
+/// to the directory, sorted by path
+///
+/// `files` are relative to the checkout, so they start with `prefix`, the directory's path.
+fn hash_files(dir: &Path, prefix: &str, files: &[String]) -> String {
+    let mut files: Vec<&str> = files
+        .iter()
+        .filter_map(|file| file.strip_prefix(prefix))
+        .collect();
+    files.sort_by(|a, b| Path::new(a).cmp(Path::new(b)));
+    let mut hasher = Fnv::default();
+    for file in files {
+        let Ok(mut content) = std::fs::File::open(dir.join(file)) else {
+            continue;
+        };
+        hasher.update(file.as_bytes());
+        hasher.update(&[0]);
+        if std::io::copy(&mut content, &mut hasher).is_ok() {
+            hasher.update(&[0]);
+        }
+    }
+    hasher.finish()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::MockExecutor;
+    use crate::testing::TestRepo;
+
+    #[tokio::test]
+    async fn test_snapshot_of_dirty_files() -> Result<(), Box<dyn std::error::Error>> {
+        let test_repo = TestRepo::builder()
+            .with_file("tracked.txt", "original\n")
+            .with_file("gone.txt", "soon deleted\n")
+            .with_file(".gitignore", "*.log\n")
+            .build()?;
+        let repo = test_repo.path();
+        test_repo.write("tracked.txt", "edited\n")?;
+        std::fs::remove_file(repo.join("gone.txt"))?;
+        test_repo.write("notes/todo.md", "- fix lexer\n")?;
+        test_repo.write("notes/build.log", "compiling\n")?;
+
+        let executors: Vec<Arc<dyn Executor>> = vec![Arc::new(MockExecutor::new("mock"))];
+        let snapshot = RunSnapshot::take(repo, &executors).await;
+        assert_eq!(
+            snapshot.dirty_files.keys().collect::<Vec<_>>(),
+            ["gone.txt", "notes", "tracked.txt"]
+        );
+        assert_eq!(snapshot.dirty_files["gone.txt"], DELETED);
+        assert!(snapshot.agent_versions.is_empty());
+        assert!(
+            snapshot
+                .changed_files(&dirty_file_hashes(repo).await)
+                .is_empty()
+        );
+
+        // Ignored files in an untracked directory are not part of its hash
+        test_repo.write("notes/build.log", "compiled\n")?;
+        assert!(
+            snapshot
+                .changed_files(&dirty_file_hashes(repo).await)
+                .is_empty()
+        );
+
+        test_repo.write("notes/todo.md", "- fix parser\n")?;
+        test_repo.write("scratch.txt", "new\n")?;
+        assert_eq!(
+            snapshot.changed_files(&dirty_file_hashes(repo).await),
+            ["notes", "scratch.txt"]
+        );
+        Ok(())
+    }
+}
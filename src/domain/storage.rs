@@ -0,0 +1,136 @@
+use std::cmp::Reverse;
+use std::path::{Path, PathBuf};
+
+use crate::config::{history_dir, worktrees_dir};
+use crate::error::Result;
+use crate::git;
+
+/// Disk space used by one worktree of the parari data directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WorktreeUsage {
+    /// Path to the worktree
+    pub path: PathBuf,
+    /// Bytes used by the worktree's files
+    pub bytes: u64,
+    /// Whether a running parari process still uses the worktree
+    pub in_use: bool,
+}
+
+/// Disk space used by the parari data directory
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DiskUsage {
+    /// Worktrees kept from past runs, largest first
+    pub worktrees: Vec<WorktreeUsage>,
+    /// Bytes used by the run history
+    pub history: u64,
+}
+
+impl DiskUsage {
+    /// Bytes used in total
+    #[must_use]
+    pub fn total(&self) -> u64 {
+        self.worktrees.iter().map(|w| w.bytes).sum::<u64>() + self.history
+    }
+
+    /// Worktrees that can be removed because no running process uses them
+    pub fn idle_worktrees(&self) -> impl Iterator<Item = &WorktreeUsage> {
+        self.worktrees.iter().filter(|w| !w.in_use)
+    }
+
+    /// Bytes that removing the idle worktrees would free
+    #[must_use]
+    pub fn reclaimable(&self) -> u64 {
+        self.idle_worktrees().map(|w| w.bytes).sum()
+    }
+}
+
+/// Measure the disk space used by the user's parari data directory
+///
+/// # Errors
+///
+/// Returns an error if the worktrees directory cannot be read.
+pub async fn disk_usage() -> Result<DiskUsage> {
+    disk_usage_in(&worktrees_dir(), &history_dir()).await
+}
+
+/// Measure the disk space used by the given worktrees and history directories
+///
+/// # Errors
+///
+/// Returns an error if the worktrees directory exists but cannot be read.
+pub async fn disk_usage_in(worktrees: &Path, history: &Path) -> Result<DiskUsage> {
+    let mut usage = DiskUsage {
+        worktrees: Vec::new(),
+        history: dir_size(history),
+    };
+
+    if worktrees.exists() {
+        for entry in std::fs::read_dir(worktrees)? {
+            let entry = entry?;
+            if !entry.file_type()?.is_dir() {
+                continue;
+            }
+            let path = entry.path();
+            usage.worktrees.push(WorktreeUsage {
+                bytes: dir_size(&path),
+                in_use: git::is_worktree_in_use(&path).await,
+                path,
+            });
+        }
+    }
+    usage
+        .worktrees
+        .sort_by_key(|worktree| Reverse(worktree.bytes));
+
+    Ok(usage)
+}
+
+/// Total size of the files under a directory, without following symlinks
+///
+/// Unreadable entries are skipped; a missing directory has size zero.
+#[must_use]
+pub fn dir_size(path: &Path) -> u64 {
+    let Ok(entries) = std::fs::read_dir(path) else {
+        return 0;
+    };
+    entries
+        .filter_map(std::result::Result::ok)
+        .map(|entry| match entry.file_type() {
+            Ok(kind) if kind.is_dir() => dir_size(&entry.path()),
+            Ok(kind) if kind.is_file() => entry.metadata().map_or(0, |meta| meta.len()),
+            _ => 0,
+        })
+        .sum()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_disk_usage() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let worktrees = dir.path().join("worktrees");
+        let history = dir.path().join("history");
+        std::fs::create_dir_all(worktrees.join("small/src"))?;
+        std::fs::create_dir_all(worktrees.join("large"))?;
+        std::fs::create_dir_all(&history)?;
+        std::fs::write(worktrees.join("small/src/lib.rs"), "fn main() {}")?;
+        std::fs::write(worktrees.join("large/data.bin"), vec![0u8; 4096])?;
+        std::fs::write(worktrees.join("large.pid"), "1")?;
+        std::fs::write(history.join("run.json"), "{}")?;
+
+        let usage = disk_usage_in(&worktrees, &history).await?;
+        assert_eq!(usage.worktrees.len(), 2);
+        assert!(usage.worktrees[0].path.ends_with("large"));
+        assert_eq!(usage.worktrees[0].bytes, 4096);
+        assert_eq!(usage.history, 2);
+        assert_eq!(usage.total(), 4096 + 12 + 2);
+        Ok(())
+    }
+
+    #[test]
+    fn test_missing_directory_is_empty() {
+        assert_eq!(dir_size(Path::new("/nonexistent/parari")), 0);
+    }
+}
@@ -0,0 +1,439 @@
+use std::path::Path;
+use std::time::Duration;
+
+use chrono::{DateTime, Local, Utc};
+use rusqlite::{Connection, OptionalExtension, Transaction, params};
+
+use super::history::RunRecord;
+use crate::error::{Error, Result};
+use crate::executor::ErrorKind;
+
+/// File of the run database in the history directory
+pub const RUN_DATABASE: &str = "runs.db";
+
+/// How long a write waits while another parari process writes to the database
+const BUSY_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// Tables of the database; the whole record is kept as JSON next to the columns
+/// queried on their own, so new fields need no migration
+const SCHEMA: &str = "
+CREATE TABLE IF NOT EXISTS runs (
+    id TEXT PRIMARY KEY,
+    started_at TEXT NOT NULL,
+    prompt TEXT NOT NULL,
+    prompt_hash TEXT NOT NULL,
+    working_dir TEXT NOT NULL,
+    record TEXT NOT NULL
+);
+CREATE INDEX IF NOT EXISTS runs_started_at ON runs (started_at);
+CREATE INDEX IF NOT EXISTS runs_prompt_hash ON runs (prompt_hash);
+
+CREATE TABLE IF NOT EXISTS candidates (
+    run_id TEXT NOT NULL REFERENCES runs (id) ON DELETE CASCADE,
+    agent TEXT NOT NULL,
+    success INTEGER NOT NULL,
+    files_changed INTEGER NOT NULL,
+    insertions INTEGER NOT NULL,
+    deletions INTEGER NOT NULL,
+    rejected INTEGER NOT NULL,
+    note TEXT,
+    error_kind TEXT,
+    PRIMARY KEY (run_id, agent)
+);
+
+CREATE TABLE IF NOT EXISTS decisions (
+    run_id TEXT PRIMARY KEY REFERENCES runs (id) ON DELETE CASCADE,
+    agent TEXT NOT NULL,
+    auto_selected INTEGER NOT NULL,
+    decided_at TEXT NOT NULL
+);
+";
+
+/// Rewrite the times saved with a local offset by earlier versions in the format of
+/// [`database_time`]
+const UTC_TIMES: &str = "
+UPDATE runs SET started_at = strftime('%Y-%m-%dT%H:%M:%fZ', started_at)
+    WHERE started_at NOT LIKE '%Z';
+UPDATE decisions SET decided_at = strftime('%Y-%m-%dT%H:%M:%fZ', decided_at)
+    WHERE decided_at NOT LIKE '%Z';
+";
+
+/// The runs of the history, in a `SQLite` database shared by every parari process
+///
+/// Runs saved as `<id>.json` files by earlier versions are moved into the database
+/// when it is opened.
+#[derive(Debug)]
+pub struct RunStore {
+    connection: Connection,
+}
+
+/// How often an agent took part in runs and how often its result was kept
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AgentStats {
+    /// Name of the agent
+    pub agent: String,
+    /// Runs the agent produced a candidate in
+    pub runs: u32,
+    /// Runs the agent's result was applied in or, in runs where nothing was applied,
+    /// finished successfully without being rejected in
+    pub kept: u32,
+}
+
+impl RunStore {
+    /// Open the database in `dir`, creating it if needed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the database cannot be opened or a JSON record cannot be
+    /// moved into it.
+    pub fn open(dir: &Path) -> Result<Self> {
+        std::fs::create_dir_all(dir)?;
+        let connection = Connection::open(dir.join(RUN_DATABASE)).map_err(database_error)?;
+        connection
+            .busy_timeout(BUSY_TIMEOUT)
+            .map_err(database_error)?;
+        // Readers do not block a writing process, nor the other way around
+        connection
+            .pragma_update(None, "journal_mode", "WAL")
+            .map_err(database_error)?;
+        connection
+            .pragma_update(None, "foreign_keys", true)
+            .map_err(database_error)?;
+        connection.execute_batch(SCHEMA).map_err(database_error)?;
+        connection
+            .execute_batch(UTC_TIMES)
+            .map_err(database_error)?;
+        let mut store = Self { connection };
+        store.import_json_records(dir)?;
+        Ok(store)
+    }
+
+    /// Save a run, replacing an earlier version of it
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run cannot be written.
+    pub fn save(&mut self, record: &RunRecord) -> Result<()> {
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        insert_record(&transaction, record)?;
+        transaction.commit().map_err(database_error)
+    }
+
+    /// Load a run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if there is no run with that id or it cannot be read.
+    pub fn load(&self, id: &str) -> Result<RunRecord> {
+        let json: Option<String> = self
+            .connection
+            .query_row("SELECT record FROM runs WHERE id = ?1", [id], |row| {
+                row.get(0)
+            })
+            .optional()
+            .map_err(database_error)?;
+        let json = json.ok_or_else(|| Error::RunNotFound { id: id.to_string() })?;
+        parse_record(id, &json)
+    }
+
+    /// All runs, newest first
+    ///
+    /// Records that cannot be parsed are skipped.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runs cannot be read.
+    pub fn list(&self) -> Result<Vec<RunRecord>> {
+        let mut statement = self
+            .connection
+            .prepare("SELECT id, record FROM runs ORDER BY started_at DESC")
+            .map_err(database_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?))
+            })
+            .map_err(database_error)?;
+        let mut runs = Vec::new();
+        for row in rows {
+            let (id, json) = row.map_err(database_error)?;
+            if let Ok(record) = parse_record(&id, &json) {
+                runs.push(record);
+            }
+        }
+        Ok(runs)
+    }
+
+    /// How each agent fared over all runs, by name
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the runs cannot be read.
+    pub fn agent_stats(&self) -> Result<Vec<AgentStats>> {
+        let mut statement = self
+            .connection
+            .prepare(
+                "SELECT c.agent, COUNT(*), SUM(CASE
+                     WHEN d.run_id IS NULL THEN c.success AND NOT c.rejected
+                     ELSE d.agent = c.agent
+                 END)
+                 FROM candidates c
+                 LEFT JOIN decisions d ON d.run_id = c.run_id
+                 GROUP BY c.agent
+                 ORDER BY c.agent",
+            )
+            .map_err(database_error)?;
+        let rows = statement
+            .query_map([], |row| {
+                Ok(AgentStats {
+                    agent: row.get(0)?,
+                    runs: row.get(1)?,
+                    kept: row.get(2)?,
+                })
+            })
+            .map_err(database_error)?;
+        rows.collect::<rusqlite::Result<_>>()
+            .map_err(database_error)
+    }
+
+    /// Move the runs saved as `<id>.json` into the database
+    fn import_json_records(&mut self, dir: &Path) -> Result<()> {
+        let mut imported = Vec::new();
+        let transaction = self.connection.transaction().map_err(database_error)?;
+        for entry in std::fs::read_dir(dir)? {
+            let path = entry?.path();
+            if path.extension().is_none_or(|ext| ext != "json") {
+                continue;
+            }
+            // Files that cannot be parsed are left alone, as before
+            let Ok(content) = std::fs::read_to_string(&path) else {
+                continue;
+            };
+            let Ok(record) = serde_json::from_str::<RunRecord>(&content) else {
+                continue;
+            };
+            insert_record(&transaction, &record)?;
+            imported.push(path);
+        }
+        transaction.commit().map_err(database_error)?;
+        for path in imported {
+            // Another process may have imported the same file meanwhile
+            match std::fs::remove_file(path) {
+                Err(e) if e.kind() != std::io::ErrorKind::NotFound => return Err(e.into()),
+                _ => {}
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Write a run and its candidates and decision
+fn insert_record(transaction: &Transaction<'_>, record: &RunRecord) -> Result<()> {
+    let json = serde_json::to_string(record).map_err(|e| Error::History {
+        message: e.to_string(),
+    })?;
+    transaction
+        .execute(
+            "INSERT INTO runs (id, started_at, prompt, prompt_hash, working_dir, record)
+             VALUES (?1, ?2, ?3, ?4, ?5, ?6)
+             ON CONFLICT (id) DO UPDATE SET
+                 started_at = excluded.started_at,
+                 prompt = excluded.prompt,
+                 prompt_hash = excluded.prompt_hash,
+                 working_dir = excluded.working_dir,
+                 record = excluded.record",
+            params![
+                record.id,
+                database_time(&record.started_at),
+                record.prompt,
+                record.prompt_hash,
+                record.working_dir.to_string_lossy(),
+                json,
+            ],
+        )
+        .map_err(database_error)?;
+
+    transaction
+        .execute("DELETE FROM candidates WHERE run_id = ?1", [&record.id])
+        .map_err(database_error)?;
+    for candidate in &record.candidates {
+        transaction
+            .execute(
+                "INSERT OR REPLACE INTO candidates
+                 (run_id, agent, success, files_changed, insertions, deletions, rejected, note, error_kind)
+                 VALUES (?1, ?2, ?3, ?4, ?5, ?6, ?7, ?8, ?9)",
+                params![
+                    record.id,
+                    candidate.agent,
+                    candidate.success,
+                    candidate.files_changed,
+                    candidate.insertions,
+                    candidate.deletions,
+                    candidate.rejected,
+                    candidate.note,
+                    candidate.error_kind.map(error_kind_name),
+                ],
+            )
+            .map_err(database_error)?;
+    }
+
+    transaction
+        .execute("DELETE FROM decisions WHERE run_id = ?1", [&record.id])
+        .map_err(database_error)?;
+    if let Some(decision) = &record.decision {
+        transaction
+            .execute(
+                "INSERT INTO decisions (run_id, agent, auto_selected, decided_at)
+                 VALUES (?1, ?2, ?3, ?4)",
+                params![
+                    record.id,
+                    decision.agent,
+                    decision.auto_selected,
+                    database_time(&decision.decided_at),
+                ],
+            )
+            .map_err(database_error)?;
+    }
+    Ok(())
+}
+
+/// A time as written to the database, in UTC with milliseconds such as
+/// `2025-01-01T12:00:00.000Z`, so the text sorts in time order
+fn database_time(time: &DateTime<Local>) -> String {
+    time.with_timezone(&Utc)
+        .format("%Y-%m-%dT%H:%M:%S%.3fZ")
+        .to_string()
+}
+
+/// Name of an error kind as written in JSON, e.g. `rate_limit`
+fn error_kind_name(kind: ErrorKind) -> String {
+    serde_json::to_value(kind)
+        .ok()
+        .and_then(|value| value.as_str().map(str::to_string))
+        .unwrap_or_default()
+}
+
+fn parse_record(id: &str, json: &str) -> Result<RunRecord> {
+    serde_json::from_str(json).map_err(|e| Error::History {
+        message: format!("run {id}: {e}"),
+    })
+}
+
+fn database_error(error: impl std::fmt::Display) -> Error {
+    Error::History {
+        message: error.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::domain::{CandidateRecord, RunInfo};
+
+    fn record(id: &str, agents: &[&str]) -> RunRecord {
+        let mut run = RunInfo::new("Fix the tokenizer", "/tmp/repo");
+        run.id = id.to_string();
+        let mut record = RunRecord::new(&run, &[], &[]);
+        record.candidates = agents
+            .iter()
+            .map(|agent| CandidateRecord {
+                agent: (*agent).to_string(),
+                success: true,
+                files_changed: 1,
+                insertions: 3,
+                deletions: 0,
+                rejected: false,
+                note: None,
+                tags: Vec::new(),
+                error: None,
+                error_kind: None,
+                policy: Vec::new(),
+            })
+            .collect();
+        record
+    }
+
+    #[test]
+    fn test_agent_stats() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut store = RunStore::open(dir.path())?;
+        let mut first = record("20250101-120000", &["claude", "codex"]);
+        first.record_decision("codex", false);
+        store.save(&first)?;
+        store.save(&record("20250102-120000", &["claude"]))?;
+        // Saving again replaces the run instead of counting it twice
+        store.save(&first)?;
+
+        let stats = store.agent_stats()?;
+        assert_eq!(
+            stats,
+            [
+                AgentStats {
+                    agent: "claude".to_string(),
+                    runs: 2,
+                    kept: 1,
+                },
+                AgentStats {
+                    agent: "codex".to_string(),
+                    runs: 1,
+                    kept: 1,
+                },
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_runs_are_listed_in_utc_order() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let mut store = RunStore::open(dir.path())?;
+        store.save(&record("tokyo", &["claude"]))?;
+        store.save(&record("london", &["claude"]))?;
+        // Times saved with their local offset, as by earlier versions: the run in
+        // Tokyo started first although its text sorts last
+        store
+            .connection
+            .execute_batch(
+                "UPDATE runs SET started_at = '2025-01-01T12:00:00+09:00' WHERE id = 'tokyo';
+                 UPDATE runs SET started_at = '2025-01-01T05:00:00+00:00' WHERE id = 'london';",
+            )
+            .map_err(database_error)?;
+
+        let store = RunStore::open(dir.path())?;
+        let ids: Vec<String> = store.list()?.into_iter().map(|run| run.id).collect();
+        assert_eq!(ids, ["london", "tokyo"]);
+        let started_at: String = store
+            .connection
+            .query_row(
+                "SELECT started_at FROM runs WHERE id = 'tokyo'",
+                [],
+                |row| row.get(0),
+            )
+            .map_err(database_error)?;
+        assert_eq!(started_at, "2025-01-01T03:00:00.000Z");
+        Ok(())
+    }
+
+    #[test]
+    fn test_json_records_are_imported() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let old = record("20250101-120000", &["gemini"]);
+        let path = dir.path().join(format!("{}.json", old.id));
+        std::fs::write(
+            &path,
+            serde_json::to_string(&old).map_err(|e| Error::History {
+                message: e.to_string(),
+            })?,
+        )?;
+        std::fs::write(dir.path().join("broken.json"), "{")?;
+
+        let store = RunStore::open(dir.path())?;
+        assert_eq!(store.load(&old.id)?, old);
+        assert!(!path.exists());
+        assert!(dir.path().join("broken.json").exists());
+
+        // Two processes can use the database at the same time
+        let other = RunStore::open(dir.path())?;
+        assert_eq!(other.list()?, vec![old]);
+        Ok(())
+    }
+}
@@ -0,0 +1,163 @@
+use std::path::Path;
+
+use crate::executor::Executor;
+use crate::git;
+
+/// Maximum number of lines kept from an agent-written summary
+pub const MAX_SUMMARY_LINES: usize = 3;
+
+/// Maximum number of diff characters sent to the agent when summarizing
+const MAX_SUMMARY_DIFF_CHARS: usize = 20_000;
+
+/// Maximum number of lines kept from an agent-written comparison of two candidates
+pub const MAX_COMPARISON_LINES: usize = 30;
+
+/// Build the prompt asking an agent to summarize its own diff
+#[must_use]
+pub fn build_summary_prompt(diff: &str) -> String {
+    let truncated = truncate_diff(diff, MAX_SUMMARY_DIFF_CHARS);
+
+    format!(
+        "Summarize what the following changes do in at most {MAX_SUMMARY_LINES} short lines. \
+         Reply with the summary only, without any preamble.\n\n{truncated}"
+    )
+}
+
+/// Build the prompt asking an agent to compare the diffs of two candidates
+///
+/// Each diff gets half of the characters a summarized diff may have.
+#[must_use]
+pub fn build_comparison_prompt(a: (&str, &str), b: (&str, &str)) -> String {
+    let (name_a, diff_a) = a;
+    let (name_b, diff_b) = b;
+    let diff_a = truncate_diff(diff_a, MAX_SUMMARY_DIFF_CHARS / 2);
+    let diff_b = truncate_diff(diff_b, MAX_SUMMARY_DIFF_CHARS / 2);
+
+    format!(
+        "Two candidate solutions to the same task follow, by {name_a} and by {name_b}. \
+         Compare them as short bullet points under the headings Approach, Risk and \
+         Test coverage, naming which candidate each point is about. Reply with the \
+         comparison only, without any preamble.\n\n\
+         Changes by {name_a}:\n{diff_a}\n\nChanges by {name_b}:\n{diff_b}"
+    )
+}
+
+/// The first `max_chars` characters of a diff, noting when it was cut
+fn truncate_diff(diff: &str, max_chars: usize) -> String {
+    let mut truncated: String = diff.chars().take(max_chars).collect();
+    if truncated.len() < diff.len() {
+        truncated.push_str("\n(diff truncated)");
+    }
+    truncated
+}
+
+/// Normalize an agent-written summary to at most `MAX_SUMMARY_LINES` non-empty lines
+#[must_use]
+pub fn normalize_summary(summary: &str) -> String {
+    summary
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .take(MAX_SUMMARY_LINES)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ask the executor to summarize the changes it made in its worktree
+///
+/// Returns `None` if there are no changes, the executor does not support
+/// summarizing, or the summary could not be produced.
+pub async fn summarize_changes(executor: &dyn Executor, worktree: &Path) -> Option<String> {
+    let diff = git::get_diff(worktree).await.ok()?;
+    if diff.trim().is_empty() {
+        return None;
+    }
+
+    let prompt = build_summary_prompt(&diff);
+    let summary = executor.summarize(&prompt, worktree).await.ok()??;
+    let summary = normalize_summary(&summary);
+
+    (!summary.is_empty()).then_some(summary)
+}
+
+/// Normalize an agent-written comparison to at most `MAX_COMPARISON_LINES` non-empty lines
+///
+/// Unlike summaries, lines keep their indentation so nested bullet points stay nested.
+#[must_use]
+pub fn normalize_comparison(comparison: &str) -> String {
+    comparison
+        .lines()
+        .map(str::trim_end)
+        .filter(|line| !line.is_empty())
+        .take(MAX_COMPARISON_LINES)
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Ask the executor to compare the changes of two candidates, given by agent name and worktree
+///
+/// Returns `None` if neither candidate changed anything, the executor does not
+/// support summarizing, or the comparison could not be produced.
+pub async fn compare_changes(
+    executor: &dyn Executor,
+    a: (&str, &Path),
+    b: (&str, &Path),
+) -> Option<String> {
+    let diff_a = git::get_diff(a.1).await.unwrap_or_default();
+    let diff_b = git::get_diff(b.1).await.unwrap_or_default();
+    if diff_a.trim().is_empty() && diff_b.trim().is_empty() {
+        return None;
+    }
+
+    let prompt = build_comparison_prompt((a.0, &diff_a), (b.0, &diff_b));
+    let comparison = executor.summarize(&prompt, a.1).await.ok()??;
+    let comparison = normalize_comparison(&comparison);
+
+    (!comparison.is_empty()).then_some(comparison)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_build_summary_prompt_contains_diff() {
+        let prompt = build_summary_prompt("+fn main() {}");
+        assert!(prompt.contains("+fn main() {}"));
+        assert!(!prompt.contains("(diff truncated)"));
+    }
+
+    #[test]
+    fn test_build_summary_prompt_truncates_large_diff() {
+        let diff = "+".repeat(MAX_SUMMARY_DIFF_CHARS + 10);
+        let prompt = build_summary_prompt(&diff);
+        assert!(prompt.contains("(diff truncated)"));
+    }
+
+    #[test]
+    fn test_build_comparison_prompt_names_both_candidates() {
+        let prompt =
+            build_comparison_prompt(("claude", "+fn lex() {}"), ("codex", "+fn parse() {}"));
+        assert!(prompt.contains("Changes by claude:\n+fn lex() {}"));
+        assert!(prompt.contains("Changes by codex:\n+fn parse() {}"));
+        assert!(prompt.contains("Test coverage"));
+    }
+
+    #[test]
+    fn test_normalize_comparison_keeps_nesting() {
+        let comparison = "Approach\n\n- claude adds a lexer\n  - with tests   \n";
+        assert_eq!(
+            normalize_comparison(comparison),
+            "Approach\n- claude adds a lexer\n  - with tests"
+        );
+    }
+
+    #[test]
+    fn test_normalize_summary() {
+        let summary = "\n  Added a parser\n\nUpdated tests\nFixed docs\nExtra line\n";
+        assert_eq!(
+            normalize_summary(summary),
+            "Added a parser\nUpdated tests\nFixed docs"
+        );
+    }
+}
@@ -1,13 +1,28 @@
-use std::path::Path;
-use std::sync::Arc;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
 
 use futures::future::join_all;
+use tokio::sync::{OwnedSemaphorePermit, Semaphore, watch};
 
+use crate::cli::glyphs::glyph;
 use crate::cli::progress::{AgentStatus, ProgressTracker};
+use crate::config::{PolicySettings, ProviderSettings, WorktreeSettings};
 use crate::error::{Error, Result};
-use crate::executor::traits::{ExecutionResult, Executor};
+use crate::executor::traits::{ErrorKind, ExecutionResult, Executor, OutputLine};
 use crate::git;
 
+use super::budget::{Budget, Meter};
+use super::events::{EventSink, RunEvent};
+use super::hooks::{CheckResult, Score, run_check, run_score, run_setup};
+use super::policy::check_policy;
+use super::prompt::prompt_with_images;
+use super::retry::{retry_delay, transient_failure};
+use super::schedule::Schedule;
+use super::score_cache::{ScoreCache, score_key};
+use super::summary::summarize_changes;
+use super::transcript::describe_activity;
 use super::worktree::WorktreeManager;
 
 /// Result of a task execution including the worktree path
@@ -19,12 +34,97 @@ pub struct TaskResult {
     pub worktree_path: std::path::PathBuf,
     /// Summary of changes made
     pub change_summary: Option<git::ChangeSummary>,
+    /// Short agent-written summary of the changes
+    pub summary: Option<String>,
+    /// Outcome of the repository's score command
+    pub score: Option<Score>,
+    /// Outcome of the quick check command
+    pub check: Option<CheckResult>,
+    /// How long the agent took, without the setup and score commands
+    pub duration: Duration,
+    /// How long the agent took to print its first line, if it printed anything
+    pub first_output: Option<Duration>,
+    /// Output line explaining each failed attempt that was retried
+    pub retries: Vec<String>,
+    /// Violations of the repository's policy, e.g. a new file without a license header
+    pub policy: Vec<String>,
+}
+
+/// Output of an agent that was stopped with [`Abort::trigger`]
+pub const ABORTED: &str = "Aborted by the user";
+
+/// Stops every agent of a run, e.g. from a key press while they work
+///
+/// Clones share the same state, so one can be handed to the runner and another to
+/// whatever triggers it.
+#[derive(Debug, Clone)]
+pub struct Abort(Arc<watch::Sender<bool>>);
+
+impl Abort {
+    /// Create an abort handle that has not been triggered
+    #[must_use]
+    pub fn new() -> Self {
+        Self(Arc::new(watch::Sender::new(false)))
+    }
+
+    /// Stop all agents; their worktrees keep what they changed so far
+    pub fn trigger(&self) {
+        self.0.send_replace(true);
+    }
+
+    /// Whether the run was aborted
+    #[must_use]
+    pub fn is_triggered(&self) -> bool {
+        *self.0.borrow()
+    }
+
+    /// Wait until the run is aborted
+    async fn triggered(&self) {
+        // Only fails if the sender is dropped, which `self` prevents
+        let _ = self.0.subscribe().wait_for(|aborted| *aborted).await;
+    }
+}
+
+impl Default for Abort {
+    fn default() -> Self {
+        Self::new()
+    }
 }
 
 /// Orchestrates task execution across multiple executors
 pub struct TaskRunner {
     /// The worktree manager
     worktree_manager: WorktreeManager,
+    /// Whether to ask each executor to summarize its changes
+    summarize: bool,
+    /// Receives the events of the run as they happen
+    events: Option<Arc<dyn EventSink>>,
+    /// Shell command run in each worktree before the agent starts
+    setup: Option<String>,
+    /// Shell command run in each worktree after the agent finishes
+    score: Option<String>,
+    /// Maximum number of score commands running at the same time
+    score_jobs: usize,
+    /// Earlier scores of the same worktree content, used instead of running the command
+    score_cache: Option<ScoreCache>,
+    /// Quick check command run in each worktree after the agent finishes
+    check: Option<String>,
+    /// License and header rules checked in each worktree after the agent finishes
+    policy: PolicySettings,
+    /// Agents printing nothing for this long are shown as stalled
+    stall_timeout: Option<Duration>,
+    /// Whether stalled agents are stopped
+    kill_stalled: bool,
+    /// How often an agent is run again after a transient failure
+    retries: usize,
+    /// Images attached to the prompt, as absolute paths
+    images: Vec<PathBuf>,
+    /// Stops all agents when triggered
+    abort: Abort,
+    /// Agents using more than this are stopped
+    budget: Budget,
+    /// When agents start and how many of each provider run at once
+    schedule: Schedule,
 }
 
 impl TaskRunner {
@@ -35,7 +135,157 @@ impl TaskRunner {
     /// Returns an error if the path is not a git repository.
     pub async fn new(repo_path: impl AsRef<Path>) -> Result<Self> {
         let worktree_manager = WorktreeManager::new(repo_path).await?;
-        Ok(Self { worktree_manager })
+        Ok(Self {
+            worktree_manager,
+            summarize: true,
+            events: None,
+            setup: None,
+            score: None,
+            score_jobs: 1,
+            score_cache: None,
+            check: None,
+            policy: PolicySettings::default(),
+            stall_timeout: None,
+            kill_stalled: false,
+            retries: 0,
+            images: Vec::new(),
+            abort: Abort::new(),
+            budget: Budget::default(),
+            schedule: Schedule::default(),
+        })
+    }
+
+    /// Set whether each executor is asked to summarize its changes after execution
+    #[must_use]
+    pub fn with_summaries(mut self, enabled: bool) -> Self {
+        self.summarize = enabled;
+        self
+    }
+
+    /// Send the events of the run, including every line of output, to `events`
+    #[must_use]
+    pub fn with_events(mut self, events: Arc<dyn EventSink>) -> Self {
+        self.events = Some(events);
+        self
+    }
+
+    /// Limit the agents' worktrees to a sparse checkout of the given directories
+    #[must_use]
+    pub fn with_scope(mut self, scope: Vec<String>) -> Self {
+        self.worktree_manager = self.worktree_manager.with_scope(scope);
+        self
+    }
+
+    /// Keep uncommitted files matching the given patterns out of the agents' worktrees
+    #[must_use]
+    pub fn with_secrets(mut self, secrets: Vec<String>) -> Self {
+        self.worktree_manager = self.worktree_manager.with_secrets(secrets);
+        self
+    }
+
+    /// Create the worktrees at `base` instead of the working directory's HEAD
+    #[must_use]
+    pub fn with_base(mut self, base: Option<String>) -> Self {
+        self.worktree_manager = self.worktree_manager.with_base(base);
+        self
+    }
+
+    /// Set the retention policy applied to old worktrees before creating new ones
+    #[must_use]
+    pub fn with_retention(mut self, retention: WorktreeSettings) -> Self {
+        self.worktree_manager = self.worktree_manager.with_retention(retention);
+        self
+    }
+
+    /// Run `command` in each worktree before its agent starts
+    #[must_use]
+    pub fn with_setup(mut self, command: Option<String>) -> Self {
+        self.setup = command;
+        self
+    }
+
+    /// Score each result by running `command` in its worktree after the agent finishes
+    #[must_use]
+    pub fn with_score(mut self, command: Option<String>) -> Self {
+        self.score = command;
+        self
+    }
+
+    /// Run at most `jobs` score commands at the same time; the others wait for a free slot
+    #[must_use]
+    pub fn with_score_jobs(mut self, jobs: usize) -> Self {
+        self.score_jobs = jobs.max(1);
+        self
+    }
+
+    /// Reuse the score of a worktree whose content was scored before instead of running
+    /// the score command again
+    #[must_use]
+    pub fn with_score_cache(mut self, cache: Option<ScoreCache>) -> Self {
+        self.score_cache = cache;
+        self
+    }
+
+    /// Run the quick `command` in each worktree after the agent finishes, e.g. `cargo check`
+    #[must_use]
+    pub fn with_check(mut self, command: Option<String>) -> Self {
+        self.check = command;
+        self
+    }
+
+    /// Check the repository's policy in each worktree after the agent finishes
+    #[must_use]
+    pub fn with_policy(mut self, policy: PolicySettings) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// Flag agents that print nothing for `timeout` as stalled, and stop them if `kill` is set
+    #[must_use]
+    pub fn with_stall_timeout(mut self, timeout: Option<Duration>, kill: bool) -> Self {
+        self.stall_timeout = timeout;
+        self.kill_stalled = kill;
+        self
+    }
+
+    /// Run an agent up to `retries` more times in a reset worktree when it fails with a
+    /// transient error such as a rate limit, waiting longer before each attempt
+    #[must_use]
+    pub fn with_retries(mut self, retries: usize) -> Self {
+        self.retries = retries;
+        self
+    }
+
+    /// Attach images to the prompt of every agent; agents that cannot see images get a
+    /// note about them instead
+    #[must_use]
+    pub fn with_images(mut self, images: Vec<PathBuf>) -> Self {
+        self.images = images;
+        self
+    }
+
+    /// Stop all agents when `abort` is triggered; they finish as failed results with
+    /// whatever they changed so far
+    #[must_use]
+    pub fn with_abort(mut self, abort: Abort) -> Self {
+        self.abort = abort;
+        self
+    }
+
+    /// Stop agents whose reported usage goes over `budget`; they finish as failed
+    /// results with what they changed so far
+    #[must_use]
+    pub fn with_budget(mut self, budget: Budget) -> Self {
+        self.budget = budget;
+        self
+    }
+
+    /// Stagger the starts of agents of the same provider and limit how many of them run
+    /// at once, as set for each provider
+    #[must_use]
+    pub fn with_providers(mut self, providers: BTreeMap<String, ProviderSettings>) -> Self {
+        self.schedule = Schedule::new(providers);
+        self
     }
 
     /// Get reference to worktree manager
@@ -51,7 +301,7 @@ impl TaskRunner {
 
     /// Run the task with the given executors in parallel
     ///
-    /// Returns results from all executors that completed successfully
+    /// Returns a result for every available executor, including those that failed
     ///
     /// # Errors
     ///
@@ -66,7 +316,7 @@ impl TaskRunner {
 
     /// Run the task with the given executors in parallel with progress tracking
     ///
-    /// Returns results from all executors that completed successfully
+    /// Returns a result for every available executor, including those that failed
     ///
     /// # Errors
     ///
@@ -96,58 +346,26 @@ impl TaskRunner {
             .await?;
 
         // Execute in parallel
-        let repo_path = self.worktree_manager.repo_path().to_path_buf();
+        let score_slots = Semaphore::new(self.score_jobs);
+        let providers: Vec<&str> = available_executors.iter().map(|e| e.provider()).collect();
+        let start_delays = self.schedule.start_delays(&providers);
         let futures: Vec<_> = available_executors
             .iter()
-            .filter_map(|executor| {
-                let executor = Arc::clone(executor);
-                let worktree = self.worktree_manager.get_worktree(executor.name())?.clone();
-                let prompt = prompt.to_string();
-                let repo_path = repo_path.clone();
-                let progress = progress.clone();
-
-                Some(async move {
-                    let executor_name = executor.name().to_string();
-
-                    // Update progress: Running
-                    if let Some(ref p) = progress {
-                        p.update_status(&executor_name, &AgentStatus::Running);
-                    }
-
-                    let result = executor.execute(&prompt, &worktree.path).await;
-
-                    if let Ok(execution) = result {
-                        // Get change summary
-                        let change_summary = git::get_change_summary(&repo_path, &worktree.path)
-                            .await
-                            .ok();
-
-                        // Update progress based on execution success
-                        if let Some(ref p) = progress {
-                            if execution.success {
-                                p.update_status(&executor_name, &AgentStatus::Completed);
-                            } else {
-                                p.update_status(&executor_name, &AgentStatus::Failed);
-                            }
-                        }
-
-                        Some(TaskResult {
-                            execution,
-                            worktree_path: worktree.path,
-                            change_summary,
-                        })
-                    } else {
-                        // Update progress: Failed
-                        if let Some(ref p) = progress {
-                            p.update_status(&executor_name, &AgentStatus::Failed);
-                        }
-                        None
-                    }
-                })
+            .zip(start_delays)
+            .filter_map(|(executor, start_delay)| {
+                let worktree = self.worktree_manager.get_worktree(executor.name())?;
+                Some(self.run_agent(
+                    executor.as_ref(),
+                    prompt,
+                    worktree,
+                    start_delay,
+                    &score_slots,
+                    progress.as_deref(),
+                ))
             })
             .collect();
 
-        let results: Vec<_> = join_all(futures).await.into_iter().flatten().collect();
+        let results = join_all(futures).await;
 
         // Finish all progress bars
         if let Some(ref p) = progress {
@@ -157,6 +375,299 @@ impl TaskRunner {
         Ok(results)
     }
 
+    /// Run one agent in its worktree, then summarize, check and score its changes
+    async fn run_agent(
+        &self,
+        executor: &dyn Executor,
+        prompt: &str,
+        worktree: &git::WorktreeInfo,
+        start_delay: Duration,
+        score_slots: &Semaphore,
+        progress: Option<&ProgressTracker>,
+    ) -> TaskResult {
+        let executor_name = executor.name();
+        let prompt = prompt_with_images(executor, prompt, &self.images);
+        let provider_slot = self.wait_to_start(executor, start_delay, progress).await;
+
+        // Update progress: Running
+        if let Some(p) = progress {
+            p.update_status(executor_name, &AgentStatus::Running);
+        }
+
+        // Prepare the worktree; an agent whose setup fails is not started
+        let setup_error = match &self.setup {
+            Some(command) => {
+                if let Some(p) = progress {
+                    p.update_message(executor_name, "Setting up...");
+                }
+                run_setup(command, &worktree.path).await.err()
+            }
+            None => None,
+        };
+
+        let started = Instant::now();
+        let (result, first_output, retries) = if self.abort.is_triggered() {
+            (Ok(aborted(executor_name)), None, Vec::new())
+        } else if let Some(error) = setup_error {
+            let failure = ExecutionResult::failure(executor_name, error.to_string(), None);
+            (Ok(failure), None, Vec::new())
+        } else {
+            self.execute_with_retries(executor, &prompt, worktree, progress)
+                .await
+        };
+
+        let duration = started.elapsed();
+        drop(provider_slot);
+        // An agent that could not be run is kept as a failed result, so its
+        // error can be reviewed next to the others
+        let execution = result.unwrap_or_else(|error| {
+            ExecutionResult::failure(executor_name, error.to_string(), None)
+        });
+
+        // Get change summary
+        let change_summary =
+            git::get_change_summary(self.worktree_manager.repo_path(), &worktree.path)
+                .await
+                .ok();
+
+        // Ask the executor for a short summary of its changes
+        let summary = if self.summarize && execution.success {
+            if let Some(p) = progress {
+                p.update_message(executor_name, "Summarizing...");
+            }
+            summarize_changes(executor, &worktree.path).await
+        } else {
+            None
+        };
+
+        let check = match &self.check {
+            Some(command) if execution.success => {
+                if let Some(p) = progress {
+                    p.update_message(executor_name, "Checking...");
+                }
+                run_check(command, &worktree.path).await.ok()
+            }
+            _ => None,
+        };
+
+        let policy = if execution.success && self.policy.is_enabled() {
+            if let Some(p) = progress {
+                p.update_message(executor_name, "Checking policy...");
+            }
+            check_policy(&self.policy, &worktree.path).await
+        } else {
+            Vec::new()
+        };
+
+        let score = match &self.score {
+            Some(command) if execution.success => {
+                self.score_worktree(command, worktree, score_slots, progress)
+                    .await
+            }
+            _ => None,
+        };
+
+        // Update progress based on execution success
+        if let Some(p) = progress {
+            if execution.success {
+                p.update_status(executor_name, &AgentStatus::Completed);
+            } else {
+                p.update_status(executor_name, &AgentStatus::Failed);
+            }
+        }
+
+        TaskResult {
+            execution,
+            worktree_path: worktree.path.clone(),
+            change_summary,
+            summary,
+            score,
+            check,
+            duration,
+            first_output,
+            retries,
+            policy,
+        }
+    }
+
+    /// Wait for the start delay and a free slot of the agent's provider
+    ///
+    /// Returns the slot, held until the agent finishes; none if the provider has no
+    /// limit or the run was aborted.
+    async fn wait_to_start(
+        &self,
+        executor: &dyn Executor,
+        start_delay: Duration,
+        progress: Option<&ProgressTracker>,
+    ) -> Option<OwnedSemaphorePermit> {
+        let executor_name = executor.name();
+
+        // Agents of the same provider start one after the other, to stay
+        // clear of its rate limits
+        if !start_delay.is_zero() {
+            if let Some(p) = progress {
+                p.update_queued(
+                    executor_name,
+                    &format!("Queued (starts in {}s)", start_delay.as_secs()),
+                );
+            }
+            tokio::select! {
+                () = tokio::time::sleep(start_delay) => {}
+                () = self.abort.triggered() => {}
+            }
+        }
+        let slots = self.schedule.slots(executor.provider())?;
+        if slots.available_permits() == 0
+            && let Some(p) = progress
+        {
+            p.update_queued(
+                executor_name,
+                &format!("Queued (waiting for a free {} slot)", executor.provider()),
+            );
+        }
+        tokio::select! {
+            slot = slots.acquire_owned() => slot.ok(),
+            () = self.abort.triggered() => None,
+        }
+    }
+
+    /// Run an agent, again from a clean worktree after each transient failure
+    ///
+    /// Returns the last execution, when the agent first printed output, and why it
+    /// was retried.
+    async fn execute_with_retries(
+        &self,
+        executor: &dyn Executor,
+        prompt: &str,
+        worktree: &git::WorktreeInfo,
+        progress: Option<&ProgressTracker>,
+    ) -> (Result<ExecutionResult>, Option<Duration>, Vec<String>) {
+        let executor_name = executor.name();
+        let events = self.events.as_deref();
+        let abort = &self.abort;
+        let max_retries = self.retries;
+        let mut first_output;
+        let mut retries = Vec::new();
+        let result = loop {
+            let activity = Activity::new();
+            let meter = Meter::new(self.budget);
+            if let Some(events) = events {
+                events.emit(&RunEvent::AgentStarted {
+                    agent: executor_name.to_string(),
+                });
+            }
+            let on_line = |line: &OutputLine| {
+                meter.record(line);
+                if let (Some(message), Some(p)) = (activity.record(line), progress) {
+                    p.update_message(executor_name, &message);
+                }
+                if let Some(events) = events {
+                    events.emit(&RunEvent::output(executor_name, line));
+                }
+            };
+            let streaming = executor.execute_streaming(prompt, &worktree.path, &on_line);
+            // Dropping the execution kills the agent
+            let execution = async {
+                tokio::select! {
+                    result = streaming => result,
+                    () = abort.triggered() => Ok(aborted(executor_name)),
+                    reason = meter.exceeded() => {
+                        Ok(over_budget(executor_name, reason))
+                    }
+                }
+            };
+            let result = watch_for_stall(
+                execution,
+                &activity,
+                self.stall_timeout,
+                self.kill_stalled,
+                progress,
+                executor_name,
+            )
+            .await;
+            let result = result.map(|mut execution| {
+                if execution.error_kind.is_none() {
+                    execution.error_kind = executor.classify_error(&execution);
+                }
+                execution
+            });
+            if let Some(events) = events {
+                events.emit(&RunEvent::AgentFinished {
+                    agent: executor_name.to_string(),
+                    success: result.as_ref().is_ok_and(|r| r.success),
+                    exit_code: result.as_ref().ok().and_then(|r| r.exit_code),
+                });
+            }
+            first_output = activity.first_output();
+
+            // Run the agent again from a clean worktree after a transient failure
+            let reason = match &result {
+                Ok(execution) if retries.len() < max_retries && !abort.is_triggered() => {
+                    transient_failure(execution)
+                }
+                _ => None,
+            };
+            let Some(reason) = reason else {
+                break result;
+            };
+            let delay = retry_delay(retries.len());
+            if let Some(p) = progress {
+                p.update_message(
+                    executor_name,
+                    &format!(
+                        "Retrying in {}s ({}/{max_retries}): {reason}",
+                        delay.as_secs(),
+                        retries.len() + 1
+                    ),
+                );
+            }
+            retries.push(reason);
+            tokio::select! {
+                () = tokio::time::sleep(delay) => {}
+                () = abort.triggered() => {}
+            }
+            if let Err(error) = self.worktree_manager.reset_worktree(worktree).await {
+                break Err(error);
+            }
+        };
+        (result, first_output, retries)
+    }
+
+    /// Score a worktree with `command`, or reuse the cached score of the same content
+    async fn score_worktree(
+        &self,
+        command: &str,
+        worktree: &git::WorktreeInfo,
+        score_slots: &Semaphore,
+        progress: Option<&ProgressTracker>,
+    ) -> Option<Score> {
+        let executor_name = &worktree.executor_name;
+        let cache = self.score_cache.as_ref();
+        let key = match cache {
+            Some(_) => score_key(command, &worktree.path).await,
+            None => None,
+        };
+        if let Some(score) = cache
+            .zip(key.as_deref())
+            .and_then(|(cache, key)| cache.get(key))
+        {
+            return Some(score);
+        }
+        if let Some(p) = progress {
+            p.update_message(executor_name, "Waiting to test...");
+        }
+        // Held until the command finishes; only fails if closed
+        let _slot = score_slots.acquire().await.ok();
+        if let Some(p) = progress {
+            p.update_message(executor_name, "Testing...");
+        }
+        let score = run_score(command, &worktree.path).await.ok();
+        if let (Some(cache), Some(key), Some(score)) = (cache, &key, &score) {
+            cache.insert(key, score);
+        }
+        score
+    }
+
     /// Cleanup worktrees
     ///
     /// # Errors
@@ -167,6 +678,154 @@ impl TaskRunner {
     }
 }
 
+/// Number of recent actions shown in the progress line of a running agent
+const ACTIVITY_FEED_LENGTH: usize = 3;
+
+/// How often a running agent is checked for stalling
+const STALL_CHECK_INTERVAL: Duration = Duration::from_secs(5);
+
+/// When an agent printed its lines, shared by its output callback and the stall watchdog
+struct Activity {
+    /// When the agent was started
+    started: Instant,
+    state: Mutex<ActivityState>,
+}
+
+#[derive(Debug, Clone)]
+struct ActivityState {
+    first_output: Option<Duration>,
+    last_output: Instant,
+    stalled: bool,
+    /// What the agent did last, newest first
+    feed: Vec<String>,
+}
+
+impl Activity {
+    fn new() -> Self {
+        let started = Instant::now();
+        Self {
+            started,
+            state: Mutex::new(ActivityState {
+                first_output: None,
+                last_output: started,
+                stalled: false,
+                feed: Vec::new(),
+            }),
+        }
+    }
+
+    /// Record a line of output, returning a new progress message if the agent
+    /// printed its first line, came back from a stall, or started something new
+    ///
+    /// The message lists the last few things the agent did, newest first.
+    fn record(&self, line: &OutputLine) -> Option<String> {
+        let mut state = self.state.lock().ok()?;
+        let now = Instant::now();
+        state.last_output = now;
+        let action = describe_activity(line).filter(|action| state.feed.first() != Some(action));
+        if let Some(action) = &action {
+            state.feed.insert(0, action.clone());
+            state.feed.truncate(ACTIVITY_FEED_LENGTH);
+        }
+        if state.first_output.is_none() {
+            let first = now - self.started;
+            state.first_output = Some(first);
+            return Some(format!("Working (first output after {}s)", first.as_secs()));
+        }
+        let stalled = std::mem::take(&mut state.stalled);
+        if action.is_none() && !stalled {
+            return None;
+        }
+        if state.feed.is_empty() {
+            return Some("Working...".to_string());
+        }
+        Some(state.feed.join(glyph(" · ", " | ")))
+    }
+
+    /// How long the agent has printed nothing
+    fn quiet_for(&self) -> Duration {
+        self.state
+            .lock()
+            .map_or(Duration::ZERO, |state| state.last_output.elapsed())
+    }
+
+    fn mark_stalled(&self) {
+        if let Ok(mut state) = self.state.lock() {
+            state.stalled = true;
+        }
+    }
+
+    fn first_output(&self) -> Option<Duration> {
+        self.state.lock().ok().and_then(|state| state.first_output)
+    }
+}
+
+/// Failed result of an agent stopped by [`Abort::trigger`]
+fn aborted(executor_name: &str) -> ExecutionResult {
+    ExecutionResult::failure(executor_name, ABORTED.to_string(), None)
+}
+
+/// Failed result of an agent stopped for going over its budget
+fn over_budget(executor_name: &str, reason: String) -> ExecutionResult {
+    let mut stopped = ExecutionResult::failure(executor_name, reason, None);
+    stopped.error_kind = Some(ErrorKind::OverBudget);
+    stopped
+}
+
+/// Await an agent, flagging it as stalled once it prints nothing for `timeout`
+///
+/// With `kill`, a stalled agent is stopped by dropping its execution, and a failed
+/// result is returned in its place.
+async fn watch_for_stall(
+    execution: impl Future<Output = Result<ExecutionResult>>,
+    activity: &Activity,
+    timeout: Option<Duration>,
+    kill: bool,
+    progress: Option<&ProgressTracker>,
+    executor_name: &str,
+) -> Result<ExecutionResult> {
+    let Some(timeout) = timeout else {
+        return execution.await;
+    };
+    tokio::pin!(execution);
+    let mut checks = tokio::time::interval(STALL_CHECK_INTERVAL.min(timeout));
+    loop {
+        tokio::select! {
+            result = &mut execution => return result,
+            _ = checks.tick() => {
+                let quiet = activity.quiet_for();
+                if quiet < timeout {
+                    continue;
+                }
+                let quiet = quiet_time(quiet);
+                if kill {
+                    let mut stopped = ExecutionResult::failure(
+                        executor_name,
+                        format!("Stopped after printing nothing for {quiet}"),
+                        None,
+                    );
+                    stopped.error_kind = Some(ErrorKind::Timeout);
+                    return Ok(stopped);
+                }
+                activity.mark_stalled();
+                if let Some(p) = progress {
+                    p.update_message(executor_name, &format!("Stalled (no output for {quiet})"));
+                }
+            }
+        }
+    }
+}
+
+/// How long an agent printed nothing, e.g. `45s`, `2m`, or `2m 30s`
+fn quiet_time(quiet: Duration) -> String {
+    let (minutes, seconds) = (quiet.as_secs() / 60, quiet.as_secs() % 60);
+    match (minutes, seconds) {
+        (0, seconds) => format!("{seconds}s"),
+        (minutes, 0) => format!("{minutes}m"),
+        (minutes, seconds) => format!("{minutes}m {seconds}s"),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -193,4 +852,59 @@ mod tests {
         assert!(matches!(result, Err(Error::NoExecutorsAvailable)));
         Ok(())
     }
+
+    #[test]
+    fn test_activity_reports_first_output_once() {
+        let line = |text: &str| OutputLine::Stdout(text.to_string());
+        let activity = Activity::new();
+        assert_eq!(activity.first_output(), None);
+        assert!(activity.record(&line("Thinking")).is_some());
+        assert!(activity.first_output().is_some());
+        assert_eq!(activity.record(&line("Thinking")), None);
+
+        activity.mark_stalled();
+        assert_eq!(
+            activity.record(&line("Thinking")).as_deref(),
+            Some("Working...")
+        );
+    }
+
+    #[test]
+    fn test_activity_feed() {
+        let line = |text: &str| OutputLine::Stdout(text.to_string());
+        let activity = Activity::new();
+        activity.record(&line("Starting"));
+        assert_eq!(
+            activity.record(&line("Editing src/lib.rs")).as_deref(),
+            Some("editing src/lib.rs")
+        );
+        assert_eq!(activity.record(&line("Editing src/lib.rs")), None);
+        activity.record(&line("$ pytest"));
+        activity.record(&line("Reading README.md"));
+        let feed = activity.record(&line("Running cargo fmt"));
+        let feed = feed.unwrap_or_default();
+        assert!(feed.starts_with("running cargo fmt"));
+        assert!(feed.contains("running pytest"));
+        assert!(!feed.contains("editing"));
+    }
+
+    #[tokio::test]
+    async fn test_watch_for_stall_kills_silent_agent() -> Result<()> {
+        let activity = Activity::new();
+        let execution = std::future::pending::<Result<ExecutionResult>>();
+        let timeout = Some(Duration::from_millis(20));
+        let result = watch_for_stall(execution, &activity, timeout, true, None, "test").await?;
+        assert!(!result.success);
+        assert!(result.stderr.starts_with("Stopped after printing nothing"));
+        assert_eq!(result.error_kind, Some(ErrorKind::Timeout));
+        Ok(())
+    }
+
+    #[test]
+    fn test_quiet_time() {
+        assert_eq!(quiet_time(Duration::from_millis(20)), "0s");
+        assert_eq!(quiet_time(Duration::from_secs(45)), "45s");
+        assert_eq!(quiet_time(Duration::from_mins(2)), "2m");
+        assert_eq!(quiet_time(Duration::from_secs(150)), "2m 30s");
+    }
 }
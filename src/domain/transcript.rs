@@ -0,0 +1,362 @@
+use serde_json::Value;
+
+use crate::executor::OutputLine;
+
+/// Input fields that name what a tool call works on, most telling first
+const TOOL_TARGET_FIELDS: [&str; 7] = [
+    "command",
+    "file_path",
+    "path",
+    "pattern",
+    "url",
+    "query",
+    "description",
+];
+
+/// Longest description of an action in the activity feed, in characters
+const ACTIVITY_WIDTH: usize = 48;
+
+/// Words that start plain-text lines describing what an agent does, e.g. `Editing src/lib.rs`
+const ACTIVITY_WORDS: [&str; 8] = [
+    "editing",
+    "writing",
+    "creating",
+    "updating",
+    "reading",
+    "running",
+    "searching",
+    "testing",
+];
+
+/// One step of an agent's conversation, reconstructed from its structured output
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum TranscriptStep {
+    /// A message given to the agent
+    User(String),
+    /// Text written by the agent
+    Assistant(String),
+    /// A tool the agent called, and what it called it on (a command, a file, ...)
+    ToolCall { tool: String, target: String },
+    /// What a tool call returned
+    ToolResult { output: String, failed: bool },
+    /// The agent's final answer
+    Result(String),
+}
+
+/// The conversation of an agent, or `None` if it printed no structured output
+///
+/// Understands Claude's `--output-format stream-json` and Codex's `exec --json` events;
+/// other lines are skipped.
+#[must_use]
+pub fn transcript(lines: &[OutputLine]) -> Option<Vec<TranscriptStep>> {
+    let mut structured = false;
+    let mut steps = Vec::new();
+    for line in lines {
+        let OutputLine::Stdout(line) = line else {
+            continue;
+        };
+        if let Some(line_steps) = parse_transcript_line(line) {
+            structured = true;
+            steps.extend(line_steps);
+        }
+    }
+    structured.then_some(steps)
+}
+
+/// The steps in one line of structured output, or `None` if it is not an event
+///
+/// Events without readable content, such as session starts, give no steps.
+#[must_use]
+pub fn parse_transcript_line(line: &str) -> Option<Vec<TranscriptStep>> {
+    if !line.trim_start().starts_with('{') {
+        return None;
+    }
+    let event: Value = serde_json::from_str(line).ok()?;
+    let kind = event.get("type")?.as_str()?;
+    Some(match kind {
+        // Claude
+        "assistant" | "user" => message_steps(kind, &event["message"]["content"]),
+        "result" => text(&event["result"])
+            .map(TranscriptStep::Result)
+            .into_iter()
+            .collect(),
+        "system" => Vec::new(),
+        // Codex
+        "item.started" | "item.updated" | "item.completed" => item_steps(kind, &event["item"]),
+        _ if kind.starts_with("thread.") || kind.starts_with("turn.") => Vec::new(),
+        _ => return None,
+    })
+}
+
+/// A short description of what an agent is doing, from one line of its output
+///
+/// Tool calls in structured output are described by their tool ("editing src/lib.rs",
+/// "running pytest"); plain-text lines are recognized when they start with a word such
+/// as "Editing" or "Running", or with a `$ ` prompt.
+#[must_use]
+pub fn describe_activity(line: &OutputLine) -> Option<String> {
+    let (OutputLine::Stdout(line) | OutputLine::Stderr(line)) = line;
+    let description = if let Some(steps) = parse_transcript_line(line) {
+        steps.into_iter().find_map(|step| match step {
+            TranscriptStep::ToolCall { tool, target } => Some(describe_tool(&tool, &target)),
+            _ => None,
+        })?
+    } else {
+        let line = line.trim();
+        if let Some(command) = line.strip_prefix("$ ") {
+            format!("running {command}")
+        } else {
+            let (word, rest) = line.split_once(' ')?;
+            let word = word.to_lowercase();
+            if !ACTIVITY_WORDS.contains(&word.as_str()) || rest.trim().is_empty() {
+                return None;
+            }
+            format!("{word} {}", rest.trim().trim_end_matches(['.', ':']))
+        }
+    };
+    Some(truncate_activity(&description))
+}
+
+/// Describe a tool call by what it does
+fn describe_tool(tool: &str, target: &str) -> String {
+    let target = target.lines().next().unwrap_or_default().trim();
+    let verb = match tool.to_lowercase().as_str() {
+        "edit" | "multiedit" | "write" | "notebookedit" | "apply_patch" => "editing",
+        "bash" | "shell" => {
+            let command = target.strip_prefix("bash -lc ").unwrap_or(target);
+            return format!("running {}", command.trim_matches(['\'', '"']));
+        }
+        "read" => "reading",
+        "grep" | "glob" => "searching",
+        "webfetch" | "websearch" | "web_search" => "browsing",
+        "todowrite" => return "planning".to_string(),
+        _ => return format!("{tool} {target}").trim().to_string(),
+    };
+    format!("{verb} {target}").trim().to_string()
+}
+
+/// Shorten a description to [`ACTIVITY_WIDTH`] characters
+fn truncate_activity(description: &str) -> String {
+    if description.chars().count() <= ACTIVITY_WIDTH {
+        return description.to_string();
+    }
+    let shortened: String = description.chars().take(ACTIVITY_WIDTH - 3).collect();
+    format!("{shortened}...")
+}
+
+/// Steps of the content blocks of a Claude message
+fn message_steps(role: &str, content: &Value) -> Vec<TranscriptStep> {
+    if let Some(message) = content.as_str() {
+        return vec![role_text(role, message)];
+    }
+    let Some(blocks) = content.as_array() else {
+        return Vec::new();
+    };
+    blocks
+        .iter()
+        .filter_map(|block| match block["type"].as_str()? {
+            "text" => Some(role_text(role, block["text"].as_str()?)),
+            "tool_use" => Some(TranscriptStep::ToolCall {
+                tool: block["name"].as_str()?.to_string(),
+                target: tool_target(&block["input"]),
+            }),
+            "tool_result" => Some(TranscriptStep::ToolResult {
+                output: text(&block["content"]).unwrap_or_default(),
+                failed: block["is_error"].as_bool().unwrap_or(false),
+            }),
+            _ => None,
+        })
+        .collect()
+}
+
+/// Steps of a Codex item; commands show as a call when started and a result when done
+fn item_steps(kind: &str, item: &Value) -> Vec<TranscriptStep> {
+    let completed = kind == "item.completed";
+    let item_type = item["type"]
+        .as_str()
+        .or_else(|| item["item_type"].as_str())
+        .unwrap_or_default();
+    match item_type {
+        "agent_message" | "assistant_message" if completed => text(&item["text"])
+            .map(TranscriptStep::Assistant)
+            .into_iter()
+            .collect(),
+        "command_execution" if kind == "item.started" => vec![TranscriptStep::ToolCall {
+            tool: "shell".to_string(),
+            target: text(&item["command"]).unwrap_or_default(),
+        }],
+        "command_execution" if completed => vec![TranscriptStep::ToolResult {
+            output: text(&item["aggregated_output"]).unwrap_or_default(),
+            failed: item["exit_code"].as_i64().is_some_and(|code| code != 0),
+        }],
+        "file_change" if completed => vec![TranscriptStep::ToolCall {
+            tool: "edit".to_string(),
+            target: item["changes"]
+                .as_array()
+                .map(|changes| {
+                    changes
+                        .iter()
+                        .filter_map(|change| change["path"].as_str())
+                        .collect::<Vec<_>>()
+                        .join(", ")
+                })
+                .unwrap_or_default(),
+        }],
+        "mcp_tool_call" if kind == "item.started" => vec![TranscriptStep::ToolCall {
+            tool: item["tool"].as_str().unwrap_or("mcp").to_string(),
+            target: item["server"].as_str().unwrap_or_default().to_string(),
+        }],
+        "web_search" if completed => vec![TranscriptStep::ToolCall {
+            tool: "web_search".to_string(),
+            target: text(&item["query"]).unwrap_or_default(),
+        }],
+        _ => Vec::new(),
+    }
+}
+
+fn role_text(role: &str, text: &str) -> TranscriptStep {
+    if role == "user" {
+        TranscriptStep::User(text.to_string())
+    } else {
+        TranscriptStep::Assistant(text.to_string())
+    }
+}
+
+/// What a tool call works on, from its input
+fn tool_target(input: &Value) -> String {
+    TOOL_TARGET_FIELDS
+        .iter()
+        .find_map(|field| text(&input[*field]))
+        .unwrap_or_else(|| match input {
+            Value::Null => String::new(),
+            input => input.to_string(),
+        })
+}
+
+/// Text of a string, an array of strings or text blocks, or `None` if there is none
+fn text(value: &Value) -> Option<String> {
+    let text = match value {
+        Value::String(text) => text.clone(),
+        Value::Array(items) => items
+            .iter()
+            .filter_map(|item| item.as_str().or_else(|| item["text"].as_str()))
+            .collect::<Vec<_>>()
+            .join(if items.iter().all(Value::is_string) {
+                " "
+            } else {
+                "\n"
+            }),
+        _ => return None,
+    };
+    (!text.trim().is_empty()).then_some(text)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stdout(lines: &[&str]) -> Vec<OutputLine> {
+        lines
+            .iter()
+            .map(|line| OutputLine::Stdout((*line).to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn test_claude_transcript() {
+        let lines = stdout(&[
+            r#"{"type":"system","subtype":"init","session_id":"1"}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"text","text":"Running the tests."},{"type":"tool_use","id":"t1","name":"Bash","input":{"command":"cargo test","description":"Run tests"}}]}}"#,
+            r#"{"type":"user","message":{"content":[{"type":"tool_result","tool_use_id":"t1","content":"test result: FAILED","is_error":true}]}}"#,
+            r#"{"type":"assistant","message":{"content":[{"type":"tool_use","id":"t2","name":"Edit","input":{"file_path":"src/lib.rs","old_string":"a","new_string":"b"}}]}}"#,
+            r#"{"type":"result","subtype":"success","result":"Fixed the test."}"#,
+        ]);
+        assert_eq!(
+            transcript(&lines),
+            Some(vec![
+                TranscriptStep::Assistant("Running the tests.".to_string()),
+                TranscriptStep::ToolCall {
+                    tool: "Bash".to_string(),
+                    target: "cargo test".to_string()
+                },
+                TranscriptStep::ToolResult {
+                    output: "test result: FAILED".to_string(),
+                    failed: true
+                },
+                TranscriptStep::ToolCall {
+                    tool: "Edit".to_string(),
+                    target: "src/lib.rs".to_string()
+                },
+                TranscriptStep::Result("Fixed the test.".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_codex_transcript() {
+        let lines = stdout(&[
+            r#"{"type":"thread.started","thread_id":"1"}"#,
+            r#"{"type":"item.started","item":{"id":"0","type":"command_execution","command":"bash -lc ls","status":"in_progress"}}"#,
+            r#"{"type":"item.completed","item":{"id":"0","type":"command_execution","command":"bash -lc ls","aggregated_output":"src\n","exit_code":0}}"#,
+            r#"{"type":"item.completed","item":{"id":"1","type":"file_change","changes":[{"path":"src/main.rs","kind":"update"}]}}"#,
+            r#"{"type":"item.completed","item":{"id":"2","type":"agent_message","text":"Done."}}"#,
+        ]);
+        assert_eq!(
+            transcript(&lines),
+            Some(vec![
+                TranscriptStep::ToolCall {
+                    tool: "shell".to_string(),
+                    target: "bash -lc ls".to_string()
+                },
+                TranscriptStep::ToolResult {
+                    output: "src\n".to_string(),
+                    failed: false
+                },
+                TranscriptStep::ToolCall {
+                    tool: "edit".to_string(),
+                    target: "src/main.rs".to_string()
+                },
+                TranscriptStep::Assistant("Done.".to_string()),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_describe_activity() {
+        let describe = |line: &str| describe_activity(&OutputLine::Stdout(line.to_string()));
+        assert_eq!(
+            describe(
+                r#"{"type":"assistant","message":{"content":[{"type":"tool_use","name":"Edit","input":{"file_path":"src/lib.rs"}}]}}"#
+            )
+            .as_deref(),
+            Some("editing src/lib.rs")
+        );
+        assert_eq!(
+            describe(
+                r#"{"type":"item.started","item":{"type":"command_execution","command":"bash -lc 'pytest -q'"}}"#
+            )
+            .as_deref(),
+            Some("running pytest -q")
+        );
+        assert_eq!(
+            describe("Editing src/main.rs...").as_deref(),
+            Some("editing src/main.rs")
+        );
+        assert_eq!(
+            describe("$ cargo test").as_deref(),
+            Some("running cargo test")
+        );
+        assert_eq!(describe("I will look at the parser"), None);
+        assert_eq!(
+            describe(&format!("Reading {}", "a".repeat(100)))
+                .map(|description| description.chars().count()),
+            Some(ACTIVITY_WIDTH)
+        );
+    }
+
+    #[test]
+    fn test_plain_output_has_no_transcript() {
+        let lines = stdout(&["Editing src/lib.rs", "{not json", r#"{"no_type":1}"#]);
+        assert_eq!(transcript(&lines), None);
+    }
+}
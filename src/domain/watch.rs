@@ -0,0 +1,78 @@
+use std::path::Path;
+use std::time::{Duration, SystemTime};
+
+use crate::error::Result;
+
+/// How often a watched file is checked for changes
+const POLL_INTERVAL: Duration = Duration::from_millis(250);
+
+/// How long a file must stay unchanged before it counts as changed, so a burst of
+/// saves starts a single run
+const DEBOUNCE: Duration = Duration::from_millis(500);
+
+/// Modification time of a file, or `None` while it does not exist
+///
+/// Editors that save by replacing the file remove it for a moment.
+async fn modified(path: &Path) -> Result<Option<SystemTime>> {
+    match tokio::fs::metadata(path).await {
+        Ok(metadata) => Ok(Some(metadata.modified()?)),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Wait until the file at `path` was modified after `since` and stayed unchanged
+/// for a moment, returning its new modification time
+///
+/// With `since` of `None`, returns as soon as the file exists.
+///
+/// # Errors
+///
+/// Returns an error if the file's metadata cannot be read.
+pub async fn wait_for_change(path: &Path, since: Option<SystemTime>) -> Result<SystemTime> {
+    loop {
+        if let Some(changed) = modified(path).await?
+            && Some(changed) != since
+        {
+            tokio::time::sleep(DEBOUNCE).await;
+            if modified(path).await? == Some(changed) {
+                return Ok(changed);
+            }
+            continue;
+        }
+        tokio::time::sleep(POLL_INTERVAL).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_wait_for_change() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("TASK.md");
+        std::fs::write(&path, "Add a --verbose flag")?;
+
+        let first = wait_for_change(&path, None).await?;
+
+        let file = std::fs::File::options().write(true).open(&path)?;
+        file.set_modified(first + Duration::from_secs(10))?;
+        let second =
+            tokio::time::timeout(Duration::from_secs(5), wait_for_change(&path, Some(first)))
+                .await
+                .map_err(|e| std::io::Error::other(e.to_string()))??;
+        assert_eq!(second, first + Duration::from_secs(10));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_wait_for_missing_file() -> Result<()> {
+        let dir = tempfile::tempdir()?;
+        let path = dir.path().join("missing.md");
+        let waited =
+            tokio::time::timeout(Duration::from_millis(800), wait_for_change(&path, None)).await;
+        assert!(waited.is_err());
+        Ok(())
+    }
+}
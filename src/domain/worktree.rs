@@ -1,6 +1,7 @@
 use std::path::{Path, PathBuf};
 use std::sync::{Mutex, OnceLock};
 
+use crate::config::WorktreeSettings;
 use crate::error::Result;
 use crate::git;
 
@@ -47,8 +48,9 @@ fn unregister_worktree(worktree_path: &Path) {
     }
 }
 
-/// Cleanup all registered worktrees (called on signal/shutdown).
-/// This is a synchronous function that creates its own runtime.
+/// Cleanup all registered worktrees (called on signal/shutdown/panic).
+/// This is a synchronous function that creates its own runtime on a separate thread,
+/// so it can also be called from within a tokio runtime.
 pub fn cleanup_all_registered_worktrees() {
     let entries = {
         match get_registry().lock() {
@@ -62,17 +64,20 @@ pub fn cleanup_all_registered_worktrees() {
     }
 
     // Create a new runtime for cleanup since we may be called from a signal handler
-    let rt = tokio::runtime::Builder::new_current_thread()
-        .enable_all()
-        .build();
-
-    if let Ok(rt) = rt {
-        rt.block_on(async {
-            for (worktree_path, repo_path) in entries {
-                let _ = git::remove_worktree(&repo_path, &worktree_path).await;
-            }
-        });
-    }
+    let handle = std::thread::spawn(move || {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build();
+
+        if let Ok(rt) = rt {
+            rt.block_on(async {
+                for (worktree_path, repo_path) in entries {
+                    let _ = git::remove_worktree(&repo_path, &worktree_path).await;
+                }
+            });
+        }
+    });
+    let _ = handle.join();
 }
 
 /// Manages worktrees for parallel execution
@@ -80,6 +85,18 @@ pub fn cleanup_all_registered_worktrees() {
 pub struct WorktreeManager {
     /// Path to the original repository
     repo_path: PathBuf,
+    /// Checkout the worktrees are based on, a linked worktree of `repo_path` or the same path
+    source_path: PathBuf,
+    /// VCS managing the source checkout
+    vcs: git::Vcs,
+    /// Directories to check out; empty for the whole repository
+    scope: Vec<String>,
+    /// Patterns of uncommitted files that are not copied into worktrees
+    secrets: Vec<String>,
+    /// Commit new worktrees check out instead of the source checkout's HEAD
+    base: Option<String>,
+    /// Retention policy applied to old worktrees before creating new ones
+    retention: WorktreeSettings,
     /// Active worktrees
     worktrees: Vec<git::WorktreeInfo>,
 }
@@ -87,24 +104,69 @@ pub struct WorktreeManager {
 impl WorktreeManager {
     /// Create a new worktree manager for the given repository
     ///
+    /// When `repo_path` is inside a linked worktree, new worktrees are added to the main
+    /// repository but based on the linked worktree's checkout.
+    ///
     /// # Errors
     ///
     /// Returns an error if the path is not a git repository.
     pub async fn new(repo_path: impl AsRef<Path>) -> Result<Self> {
-        let repo_path = git::get_repo_root(repo_path.as_ref()).await?;
+        let source_path = git::get_repo_root(repo_path.as_ref()).await?;
+        let repo_path = git::get_main_repo_root(&source_path).await?;
+        let vcs = git::Vcs::detect(&source_path).await;
 
         Ok(Self {
             repo_path,
+            source_path,
+            vcs,
+            scope: Vec::new(),
+            secrets: Vec::new(),
+            base: None,
+            retention: WorktreeSettings::default(),
             worktrees: Vec::new(),
         })
     }
 
+    /// Limit new worktrees to a sparse checkout of the given directories
+    #[must_use]
+    pub fn with_scope(mut self, scope: Vec<String>) -> Self {
+        self.scope = scope;
+        self
+    }
+
+    /// Keep uncommitted files matching the given patterns out of new worktrees
+    #[must_use]
+    pub fn with_secrets(mut self, secrets: Vec<String>) -> Self {
+        self.secrets = secrets;
+        self
+    }
+
+    /// Create new worktrees at `base` instead of the source checkout's HEAD
+    #[must_use]
+    pub fn with_base(mut self, base: Option<String>) -> Self {
+        self.base = base;
+        self
+    }
+
     /// Get the repository path
     #[must_use]
     pub fn repo_path(&self) -> &Path {
         &self.repo_path
     }
 
+    /// Set the retention policy for old worktrees
+    #[must_use]
+    pub fn with_retention(mut self, retention: WorktreeSettings) -> Self {
+        self.retention = retention;
+        self
+    }
+
+    /// Get the VCS managing the source checkout
+    #[must_use]
+    pub fn vcs(&self) -> git::Vcs {
+        self.vcs
+    }
+
     /// Create worktrees for the given executor names
     ///
     /// # Errors
@@ -112,10 +174,21 @@ impl WorktreeManager {
     /// Returns an error if worktree creation fails.
     pub async fn create_worktrees(&mut self, executor_names: &[&str]) -> Result<()> {
         // First cleanup old worktrees to stay under limit
-        git::cleanup_old_worktrees(&self.repo_path).await?;
+        git::cleanup_old_worktrees(&self.repo_path, &self.retention).await?;
+
+        // Let jj record the working copy, so the worktrees see the same files it does
+        self.vcs.snapshot(&self.source_path).await?;
 
         for name in executor_names {
-            let info = git::create_worktree(&self.repo_path, name).await?;
+            let info = git::create_worktree(
+                &self.repo_path,
+                &self.source_path,
+                name,
+                &self.scope,
+                &self.secrets,
+                self.base.as_deref(),
+            )
+            .await?;
             // Register worktree for cleanup on process termination
             register_worktree(&self.repo_path, &info.path);
             self.worktrees.push(info);
@@ -124,6 +197,21 @@ impl WorktreeManager {
         Ok(())
     }
 
+    /// Return the worktree of an executor to the state it was created in, e.g. to retry
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the reset fails.
+    pub async fn reset_worktree(&self, worktree: &git::WorktreeInfo) -> Result<()> {
+        git::reset_worktree(
+            &self.source_path,
+            &worktree.path,
+            &self.scope,
+            &self.secrets,
+        )
+        .await
+    }
+
     /// Get the worktree for a specific executor
     #[must_use]
     pub fn get_worktree(&self, executor_name: &str) -> Option<&git::WorktreeInfo> {
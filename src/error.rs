@@ -22,9 +22,18 @@ pub enum Error {
     #[error("Git command failed: {message}")]
     GitCommand { message: String },
 
+    #[error("Cannot clone {url}: {message}")]
+    RemoteRepository { url: String, message: String },
+
+    #[error("{forge} failed: {message}")]
+    Forge { forge: String, message: String },
+
     #[error("Not a git repository: {path}")]
     NotGitRepository { path: PathBuf },
 
+    #[error("Cannot apply to {path}: {reason}")]
+    InvalidApplyTarget { path: PathBuf, reason: String },
+
     #[error("Worktree already exists: {path}")]
     WorktreeAlreadyExists { path: PathBuf },
 
@@ -37,11 +46,103 @@ pub enum Error {
     #[error("No executors available")]
     NoExecutorsAvailable,
 
+    #[error("Some agents failed: {}", agents.join(", "))]
+    AgentsFailed { agents: Vec<String> },
+
+    #[error("Every agent failed")]
+    AllAgentsFailed,
+
+    #[error("Nothing was applied: {reason}")]
+    NothingApplied { reason: String },
+
     #[error("User cancelled the operation")]
     UserCancelled,
 
+    #[error("Setup command '{command}' failed:\n{output}")]
+    SetupFailed { command: String, output: String },
+
     #[error("Editor failed: {message}")]
     EditorFailed { message: String },
+
+    #[error("Could not read the prompt from the clipboard: {message}")]
+    Clipboard { message: String },
+
+    #[error("Cannot attach image {path}: {reason}")]
+    InvalidImage { path: PathBuf, reason: String },
+
+    #[error("Invalid configuration: {message}")]
+    Config { message: String },
+
+    #[error("Run history error: {message}")]
+    History { message: String },
+
+    #[error("Run not found in history: {id}")]
+    RunNotFound { id: String },
+
+    #[error("Invalid run archive {path}: {reason}")]
+    InvalidArchive { path: PathBuf, reason: String },
+
+    #[error("Cannot review run {id}: {reason}")]
+    CannotReview { id: String, reason: String },
+
+    #[error("Cannot rerun run {id}: {reason}")]
+    CannotRerun { id: String, reason: String },
+}
+
+impl Error {
+    /// Exit code of the process when the run ends with this error
+    #[must_use]
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Self::AgentsFailed { .. } => exit_code::SOME_AGENTS_FAILED,
+            Self::AllAgentsFailed => exit_code::ALL_AGENTS_FAILED,
+            Self::NothingApplied { .. } => exit_code::NOTHING_APPLIED,
+            Self::UserCancelled => exit_code::CANCELLED,
+            _ => exit_code::ERROR,
+        }
+    }
+}
+
+/// Exit codes of the `parari` command, for wrapper scripts to branch on
+pub mod exit_code {
+    /// A result was applied
+    pub const SUCCESS: i32 = 0;
+    /// The environment or a command failed (not a git repository, invalid config, ...)
+    pub const ERROR: i32 = 1;
+    /// Some agents failed and `--expect success-all` was given
+    pub const SOME_AGENTS_FAILED: i32 = 3;
+    /// No agent succeeded
+    pub const ALL_AGENTS_FAILED: i32 = 4;
+    /// The run finished without changing any file
+    pub const NOTHING_APPLIED: i32 = 5;
+    /// The user cancelled
+    pub const CANCELLED: i32 = 6;
+    /// The CI checks of the pull request opened with `--pr` failed (`--watch-ci`)
+    pub const CI_FAILED: i32 = 7;
+    /// The process was interrupted with Ctrl-C
+    pub const INTERRUPTED: i32 = 130;
 }
 
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_exit_codes() {
+        assert_eq!(
+            Error::AllAgentsFailed.exit_code(),
+            exit_code::ALL_AGENTS_FAILED
+        );
+        assert_eq!(Error::UserCancelled.exit_code(), exit_code::CANCELLED);
+        assert_eq!(Error::NoExecutorsAvailable.exit_code(), exit_code::ERROR);
+        assert_eq!(
+            Error::AgentsFailed {
+                agents: vec!["codex".to_string()]
+            }
+            .to_string(),
+            "Some agents failed: codex"
+        );
+    }
+}
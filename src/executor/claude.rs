@@ -1,28 +1,120 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use tokio::process::Command;
 
-use super::traits::{ExecutionResult, Executor, execute_with_ordered_output};
+use super::traits::{
+    Capabilities, ErrorKind, ExecutionResult, Executor, OnLine, classify_error, cli_version,
+    execute_with_line_callback, execute_with_ordered_output,
+};
 use crate::error::{Error, Result};
 
 /// Executor for Claude Code CLI
-#[derive(Debug, Default)]
-pub struct ClaudeExecutor;
+#[derive(Debug)]
+pub struct ClaudeExecutor {
+    /// Name shown for this executor and used for its worktree
+    name: String,
+    /// Extra flags passed to the CLI, e.g. `--model`
+    args: Vec<String>,
+    /// Environment variables set for the CLI, e.g. the API key of an account
+    env: BTreeMap<String, String>,
+    /// Most US dollars a run may spend, enforced by the CLI itself
+    max_cost: Option<f64>,
+}
+
+impl Default for ClaudeExecutor {
+    fn default() -> Self {
+        Self {
+            name: "claude".to_string(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+            max_cost: None,
+        }
+    }
+}
 
 impl ClaudeExecutor {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Set the name shown for this executor, to tell apart several of the same CLI
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Pass extra flags to the CLI, e.g. `["--model", "opus"]`
+    #[must_use]
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Set environment variables for the CLI, e.g. `ANTHROPIC_API_KEY` for another account
+    #[must_use]
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
+    }
+
+    /// Have the CLI stop once it spent `max_cost` US dollars, with `--max-budget-usd`
+    #[must_use]
+    pub fn with_max_cost(mut self, max_cost: Option<f64>) -> Self {
+        self.max_cost = max_cost;
+        self
     }
 }
 
 #[async_trait]
 impl Executor for ClaudeExecutor {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn provider(&self) -> &'static str {
         "claude"
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { images: true }
+    }
+
+    fn classify_error(&self, result: &ExecutionResult) -> Option<ErrorKind> {
+        classify_error(
+            result,
+            &[
+                ("oauth token has expired", ErrorKind::Auth),
+                ("credit balance is too low", ErrorKind::RateLimit),
+                ("usage limit reached", ErrorKind::RateLimit),
+            ],
+        )
+    }
+
+    fn context_window(&self) -> Option<usize> {
+        Some(200_000)
+    }
+
+    fn reports_usage(&self) -> bool {
+        // `--output-format stream-json` or `--output-format=stream-json`
+        self.args.iter().any(|arg| arg.ends_with("stream-json"))
+    }
+
+    fn attach_images(&self, prompt: &str, images: &[PathBuf]) -> Option<String> {
+        // Claude Code reads image files given by path with its file tools
+        let list: Vec<String> = images
+            .iter()
+            .map(|image| format!("- {}", image.display()))
+            .collect();
+        Some(format!(
+            "{prompt}\n\nAttached images (read them before starting):\n{}",
+            list.join("\n")
+        ))
+    }
+
     async fn is_available(&self) -> bool {
         Command::new("which")
             .arg("claude")
@@ -31,7 +123,20 @@ impl Executor for ClaudeExecutor {
             .is_ok_and(|output| output.status.success())
     }
 
+    async fn version(&self) -> Option<String> {
+        cli_version("claude").await
+    }
+
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult> {
+        self.execute_streaming(prompt, working_dir, &|_| {}).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        on_line: &OnLine<'_>,
+    ) -> Result<ExecutionResult> {
         if !working_dir.exists() {
             return Err(Error::WorkingDirectoryNotFound {
                 path: working_dir.to_path_buf(),
@@ -41,10 +146,26 @@ impl Executor for ClaudeExecutor {
         let mut cmd = Command::new("claude");
         cmd.arg("--print")
             .arg("--dangerously-skip-permissions")
+            .args(&self.args)
+            .envs(&self.env);
+        if let Some(max_cost) = self.max_cost {
+            cmd.arg("--max-budget-usd").arg(max_cost.to_string());
+        }
+        cmd.arg(prompt).current_dir(working_dir);
+
+        let result = execute_with_line_callback(cmd, self.name(), on_line).await?;
+        Ok(result)
+    }
+
+    async fn summarize(&self, prompt: &str, working_dir: &Path) -> Result<Option<String>> {
+        let mut cmd = Command::new("claude");
+        cmd.arg("--print")
+            .args(&self.args)
+            .envs(&self.env)
             .arg(prompt)
             .current_dir(working_dir);
 
         let result = execute_with_ordered_output(cmd, self.name()).await?;
-        Ok(result)
+        Ok(result.success.then_some(result.stdout))
     }
 }
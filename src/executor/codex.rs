@@ -1,28 +1,93 @@
+use std::collections::BTreeMap;
 use std::path::Path;
 
 use async_trait::async_trait;
 use tokio::process::Command;
 
-use super::traits::{ExecutionResult, Executor, execute_with_ordered_output};
+use super::traits::{
+    ErrorKind, ExecutionResult, Executor, OnLine, classify_error, cli_version,
+    execute_with_line_callback, execute_with_ordered_output,
+};
 use crate::error::{Error, Result};
 
 /// Executor for `OpenAI` Codex CLI
-#[derive(Debug, Default)]
-pub struct CodexExecutor;
+#[derive(Debug)]
+pub struct CodexExecutor {
+    /// Name shown for this executor and used for its worktree
+    name: String,
+    /// Extra flags passed to the CLI, e.g. `--model`
+    args: Vec<String>,
+    /// Environment variables set for the CLI, e.g. the API key of an account
+    env: BTreeMap<String, String>,
+}
+
+impl Default for CodexExecutor {
+    fn default() -> Self {
+        Self {
+            name: "codex".to_string(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+        }
+    }
+}
 
 impl CodexExecutor {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Set the name shown for this executor, to tell apart several of the same CLI
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Pass extra flags to the CLI, e.g. `["--model", "opus"]`
+    #[must_use]
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Set environment variables for the CLI, e.g. `OPENAI_API_KEY` for another account
+    #[must_use]
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
     }
 }
 
 #[async_trait]
 impl Executor for CodexExecutor {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn provider(&self) -> &'static str {
         "codex"
     }
 
+    fn classify_error(&self, result: &ExecutionResult) -> Option<ErrorKind> {
+        classify_error(
+            result,
+            &[
+                ("codex login", ErrorKind::Auth),
+                ("usage limit", ErrorKind::RateLimit),
+                ("exceeds the context window", ErrorKind::ContextTooLong),
+            ],
+        )
+    }
+
+    fn context_window(&self) -> Option<usize> {
+        Some(272_000)
+    }
+
+    fn reports_usage(&self) -> bool {
+        self.args.iter().any(|arg| arg == "--json")
+    }
+
     async fn is_available(&self) -> bool {
         Command::new("which")
             .arg("codex")
@@ -31,7 +96,20 @@ impl Executor for CodexExecutor {
             .is_ok_and(|output| output.status.success())
     }
 
+    async fn version(&self) -> Option<String> {
+        cli_version("codex").await
+    }
+
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult> {
+        self.execute_streaming(prompt, working_dir, &|_| {}).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        on_line: &OnLine<'_>,
+    ) -> Result<ExecutionResult> {
         if !working_dir.exists() {
             return Err(Error::WorkingDirectoryNotFound {
                 path: working_dir.to_path_buf(),
@@ -41,10 +119,26 @@ impl Executor for CodexExecutor {
         let mut cmd = Command::new("codex");
         cmd.arg("--full-auto")
             .arg("exec")
+            .args(&self.args)
+            .envs(&self.env)
             .arg(prompt)
             .current_dir(working_dir);
 
-        let result = execute_with_ordered_output(cmd, self.name()).await?;
+        let result = execute_with_line_callback(cmd, self.name(), on_line).await?;
         Ok(result)
     }
+
+    async fn summarize(&self, prompt: &str, working_dir: &Path) -> Result<Option<String>> {
+        let mut cmd = Command::new("codex");
+        cmd.arg("exec")
+            .arg("--sandbox")
+            .arg("read-only")
+            .args(&self.args)
+            .envs(&self.env)
+            .arg(prompt)
+            .current_dir(working_dir);
+
+        let result = execute_with_ordered_output(cmd, self.name()).await?;
+        Ok(result.success.then_some(result.stdout))
+    }
 }
@@ -1,28 +1,108 @@
-use std::path::Path;
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 
 use async_trait::async_trait;
 use tokio::process::Command;
 
-use super::traits::{ExecutionResult, Executor, execute_with_ordered_output};
+use super::traits::{
+    Capabilities, ErrorKind, ExecutionResult, Executor, OnLine, classify_error, cli_version,
+    execute_with_line_callback, execute_with_ordered_output,
+};
 use crate::error::{Error, Result};
 
 /// Executor for Gemini CLI
-#[derive(Debug, Default)]
-pub struct GeminiExecutor;
+#[derive(Debug)]
+pub struct GeminiExecutor {
+    /// Name shown for this executor and used for its worktree
+    name: String,
+    /// Extra flags passed to the CLI, e.g. `--model`
+    args: Vec<String>,
+    /// Environment variables set for the CLI, e.g. the API key of an account
+    env: BTreeMap<String, String>,
+}
+
+impl Default for GeminiExecutor {
+    fn default() -> Self {
+        Self {
+            name: "gemini".to_string(),
+            args: Vec::new(),
+            env: BTreeMap::new(),
+        }
+    }
+}
 
 impl GeminiExecutor {
     #[must_use]
     pub fn new() -> Self {
-        Self
+        Self::default()
+    }
+
+    /// Set the name shown for this executor, to tell apart several of the same CLI
+    #[must_use]
+    pub fn with_name(mut self, name: impl Into<String>) -> Self {
+        self.name = name.into();
+        self
+    }
+
+    /// Pass extra flags to the CLI, e.g. `["--model", "opus"]`
+    #[must_use]
+    pub fn with_args(mut self, args: Vec<String>) -> Self {
+        self.args = args;
+        self
+    }
+
+    /// Set environment variables for the CLI, e.g. `GEMINI_API_KEY` for another account
+    #[must_use]
+    pub fn with_env(mut self, env: BTreeMap<String, String>) -> Self {
+        self.env = env;
+        self
     }
 }
 
 #[async_trait]
 impl Executor for GeminiExecutor {
-    fn name(&self) -> &'static str {
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn provider(&self) -> &'static str {
         "gemini"
     }
 
+    fn capabilities(&self) -> Capabilities {
+        Capabilities { images: true }
+    }
+
+    fn classify_error(&self, result: &ExecutionResult) -> Option<ErrorKind> {
+        classify_error(
+            result,
+            &[
+                ("gemini_api_key", ErrorKind::Auth),
+                ("google_cloud_project", ErrorKind::Auth),
+                (
+                    "exceeds the maximum number of tokens",
+                    ErrorKind::ContextTooLong,
+                ),
+            ],
+        )
+    }
+
+    fn context_window(&self) -> Option<usize> {
+        Some(1_048_576)
+    }
+
+    fn attach_images(&self, prompt: &str, images: &[PathBuf]) -> Option<String> {
+        // `@path` includes a file in the prompt; spaces in the path are escaped
+        let references: Vec<String> = images
+            .iter()
+            .map(|image| format!("@{}", image.display().to_string().replace(' ', "\\ ")))
+            .collect();
+        Some(format!(
+            "{prompt}\n\nAttached images: {}",
+            references.join(" ")
+        ))
+    }
+
     async fn is_available(&self) -> bool {
         Command::new("which")
             .arg("gemini")
@@ -31,7 +111,20 @@ impl Executor for GeminiExecutor {
             .is_ok_and(|output| output.status.success())
     }
 
+    async fn version(&self) -> Option<String> {
+        cli_version("gemini").await
+    }
+
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult> {
+        self.execute_streaming(prompt, working_dir, &|_| {}).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        on_line: &OnLine<'_>,
+    ) -> Result<ExecutionResult> {
         if !working_dir.exists() {
             return Err(Error::WorkingDirectoryNotFound {
                 path: working_dir.to_path_buf(),
@@ -39,9 +132,24 @@ impl Executor for GeminiExecutor {
         }
 
         let mut cmd = Command::new("gemini");
-        cmd.arg("--yolo").arg(prompt).current_dir(working_dir);
+        cmd.arg("--yolo")
+            .args(&self.args)
+            .envs(&self.env)
+            .arg(prompt)
+            .current_dir(working_dir);
 
-        let result = execute_with_ordered_output(cmd, self.name()).await?;
+        let result = execute_with_line_callback(cmd, self.name(), on_line).await?;
         Ok(result)
     }
+
+    async fn summarize(&self, prompt: &str, working_dir: &Path) -> Result<Option<String>> {
+        let mut cmd = Command::new("gemini");
+        cmd.args(&self.args)
+            .envs(&self.env)
+            .arg(prompt)
+            .current_dir(working_dir);
+
+        let result = execute_with_ordered_output(cmd, self.name()).await?;
+        Ok(result.success.then_some(result.stdout))
+    }
 }
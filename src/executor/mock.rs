@@ -1,14 +1,21 @@
+pub mod fixture;
+
 use std::path::Path;
 use std::sync::Arc;
 use std::sync::Mutex;
+use std::time::Duration;
 
 use async_trait::async_trait;
+use serde::Deserialize;
 
-use super::traits::{ExecutionResult, Executor};
+use super::traits::{Capabilities, ExecutionResult, Executor, OnLine, OutputLine};
 use crate::error::Result;
 
+pub use fixture::{FIXTURE_ENV, Fixture, SEED_ENV};
+
 /// Action to perform on a file during mock execution
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(tag = "action", rename_all = "snake_case")]
 pub enum FileAction {
     /// Create or overwrite a file with the given content
     Write {
@@ -44,6 +51,24 @@ pub struct MockExecutor {
     responses: Arc<Mutex<Vec<ExecutionResult>>>,
     /// File actions to perform during execution
     file_actions: Arc<Mutex<Vec<FileAction>>>,
+    /// Summary to return from `summarize`
+    summary: Option<String>,
+    /// Context window to report
+    context_window: Option<usize>,
+    /// Capabilities to report
+    capabilities: Capabilities,
+    /// Time before the first line is printed
+    delay: Duration,
+    /// Lines printed one by one while running
+    streamed: Vec<OutputLine>,
+    /// Time between two streamed lines
+    line_interval: Duration,
+    /// Largest random time added to each wait
+    jitter: Duration,
+    /// Seed of the jitter, so runs with the same seed take the same time
+    seed: u64,
+    /// Fail after printing this many streamed lines, with the given result
+    fail_after: Option<(usize, ExecutionResult)>,
 }
 
 /// A recorded call to the mock executor
@@ -62,6 +87,15 @@ impl MockExecutor {
             calls: Arc::new(Mutex::new(Vec::new())),
             responses: Arc::new(Mutex::new(Vec::new())),
             file_actions: Arc::new(Mutex::new(Vec::new())),
+            summary: None,
+            context_window: None,
+            capabilities: Capabilities::default(),
+            delay: Duration::ZERO,
+            streamed: Vec::new(),
+            line_interval: Duration::ZERO,
+            jitter: Duration::ZERO,
+            seed: 0,
+            fail_after: None,
         }
     }
 
@@ -133,6 +167,65 @@ impl MockExecutor {
         self.with_file_action(FileAction::CreateDir { path: path.into() })
     }
 
+    /// Set the summary returned when asked to summarize changes
+    #[must_use]
+    pub fn with_summary(mut self, summary: impl Into<String>) -> Self {
+        self.summary = Some(summary.into());
+        self
+    }
+
+    /// Set the capabilities reported to the caller
+    #[must_use]
+    pub fn with_capabilities(mut self, capabilities: Capabilities) -> Self {
+        self.capabilities = capabilities;
+        self
+    }
+
+    /// Wait this long before printing the first line
+    #[must_use]
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Print these lines one by one while running, `interval` apart, before the
+    /// lines of the response
+    #[must_use]
+    pub fn with_streamed_output(mut self, lines: Vec<OutputLine>, interval: Duration) -> Self {
+        self.streamed = lines;
+        self.line_interval = interval;
+        self
+    }
+
+    /// Add up to `jitter` to every wait, drawn from `seed` so the timing is the
+    /// same each time
+    #[must_use]
+    pub fn with_jitter(mut self, jitter: Duration, seed: u64) -> Self {
+        self.jitter = jitter;
+        self.seed = seed;
+        self
+    }
+
+    /// Fail after printing `lines` of the streamed output, without changing any files
+    #[must_use]
+    pub fn with_failure_after(
+        mut self,
+        lines: usize,
+        stderr: impl Into<String>,
+        exit_code: Option<i32>,
+    ) -> Self {
+        let failure = ExecutionResult::failure(self.name.clone(), stderr.into(), exit_code);
+        self.fail_after = Some((lines, failure));
+        self
+    }
+
+    /// Set the context window reported to the caller, in tokens
+    #[must_use]
+    pub fn with_context_window(mut self, tokens: usize) -> Self {
+        self.context_window = Some(tokens);
+        self
+    }
+
     /// Get all recorded calls
     #[must_use]
     pub fn calls(&self) -> Vec<MockCall> {
@@ -160,6 +253,23 @@ impl MockExecutor {
         }
     }
 
+    /// Wait for `duration` plus up to the configured jitter
+    async fn wait(&self, duration: Duration, rng: &mut fastrand::Rng) {
+        let jitter = u64::try_from(self.jitter.as_millis()).unwrap_or(u64::MAX);
+        let total = duration + Duration::from_millis(rng.u64(..=jitter));
+        if !total.is_zero() {
+            tokio::time::sleep(total).await;
+        }
+    }
+
+    /// Random numbers for the jitter, different for each agent but fixed by the seed
+    fn jitter_rng(&self) -> fastrand::Rng {
+        let seed = self.name.bytes().fold(self.seed, |hash, byte| {
+            hash.wrapping_mul(31).wrapping_add(u64::from(byte))
+        });
+        fastrand::Rng::with_seed(seed)
+    }
+
     /// Perform the configured file actions in the given directory
     async fn perform_file_actions(&self, working_dir: &Path) -> Result<()> {
         let actions = self
@@ -213,7 +323,24 @@ impl Executor for MockExecutor {
         self.available
     }
 
+    fn capabilities(&self) -> Capabilities {
+        self.capabilities
+    }
+
+    fn context_window(&self) -> Option<usize> {
+        self.context_window
+    }
+
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult> {
+        self.execute_streaming(prompt, working_dir, &|_| {}).await
+    }
+
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        on_line: &OnLine<'_>,
+    ) -> Result<ExecutionResult> {
         // Record the call
         if let Ok(mut calls) = self.calls.lock() {
             calls.push(MockCall {
@@ -222,12 +349,52 @@ impl Executor for MockExecutor {
             });
         }
 
+        // Print the streamed lines, failing midway if configured
+        let mut rng = self.jitter_rng();
+        self.wait(self.delay, &mut rng).await;
+        let mut printed = Vec::new();
+        for (i, line) in self.streamed.iter().enumerate() {
+            if self
+                .fail_after
+                .as_ref()
+                .is_some_and(|(after, _)| *after == i)
+            {
+                break;
+            }
+            if i > 0 {
+                self.wait(self.line_interval, &mut rng).await;
+            }
+            on_line(line);
+            printed.push(line.clone());
+        }
+        if let Some((_, failure)) = &self.fail_after {
+            let mut failure = failure.clone();
+            failure.executor_name.clone_from(&self.name);
+            for line in &failure.output_lines {
+                on_line(line);
+            }
+            printed.append(&mut failure.output_lines);
+            failure.output_lines = printed;
+            return Ok(failure);
+        }
+
         // Perform file actions
         self.perform_file_actions(working_dir).await?;
 
         // Return the next configured response, or a default success
         let response = self.responses.lock().map_or(None, |mut r| r.pop());
-        Ok(response.unwrap_or_else(|| ExecutionResult::success(self.name.clone(), String::new())))
+        let mut response =
+            response.unwrap_or_else(|| ExecutionResult::success(self.name.clone(), String::new()));
+        for line in &response.output_lines {
+            on_line(line);
+        }
+        printed.append(&mut response.output_lines);
+        response.output_lines = printed;
+        Ok(response)
+    }
+
+    async fn summarize(&self, _prompt: &str, _working_dir: &Path) -> Result<Option<String>> {
+        Ok(self.summary.clone())
     }
 }
 
@@ -286,6 +453,21 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_mock_executor_summary() -> Result<()> {
+        let working_dir = PathBuf::from("/tmp");
+
+        let mock = MockExecutor::new("test");
+        assert_eq!(mock.summarize("summarize", &working_dir).await?, None);
+
+        let mock = MockExecutor::new("test").with_summary("Added a parser");
+        assert_eq!(
+            mock.summarize("summarize", &working_dir).await?,
+            Some("Added a parser".to_string())
+        );
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_mock_executor_creates_file() -> Result<()> {
         let temp_dir = std::env::temp_dir().join("parari_test_mock_file");
@@ -325,4 +507,78 @@ mod tests {
         tokio::fs::remove_dir_all(&temp_dir).await?;
         Ok(())
     }
+
+    #[tokio::test]
+    async fn test_mock_executor_delay() -> Result<()> {
+        let mock = MockExecutor::new("test").with_delay(Duration::from_millis(50));
+
+        let started = std::time::Instant::now();
+        mock.execute("test prompt", &PathBuf::from("/tmp")).await?;
+        assert!(started.elapsed() >= Duration::from_millis(50));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_executor_streams_output() -> Result<()> {
+        let mock = MockExecutor::new("test")
+            .with_streamed_output(
+                vec![
+                    OutputLine::Stdout("Reading".to_string()),
+                    OutputLine::Stderr("warning".to_string()),
+                ],
+                Duration::from_millis(20),
+            )
+            .with_success("Done");
+
+        let seen = Mutex::new(Vec::new());
+        let on_line = |line: &OutputLine| {
+            if let Ok(mut seen) = seen.lock() {
+                seen.push((line.clone(), std::time::Instant::now()));
+            }
+        };
+        let result = mock
+            .execute_streaming("test prompt", &PathBuf::from("/tmp"), &on_line)
+            .await?;
+
+        let seen = seen.into_inner().unwrap_or_default();
+        let lines: Vec<OutputLine> = seen.iter().map(|(line, _)| line.clone()).collect();
+        let expected = [
+            OutputLine::Stdout("Reading".to_string()),
+            OutputLine::Stderr("warning".to_string()),
+            OutputLine::Stdout("Done".to_string()),
+        ];
+        assert_eq!(lines, expected);
+        assert_eq!(result.output_lines, expected);
+        assert!(seen[1].1 - seen[0].1 >= Duration::from_millis(20));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_mock_executor_fails_midway() -> Result<()> {
+        let temp_dir = tempfile::tempdir()?;
+        let mock = MockExecutor::new("test")
+            .with_streamed_output(
+                vec![
+                    OutputLine::Stdout("one".to_string()),
+                    OutputLine::Stdout("two".to_string()),
+                ],
+                Duration::ZERO,
+            )
+            .with_failure_after(1, "Error: connection reset", None)
+            .with_file("test.txt", "never written");
+
+        let result = mock.execute("test prompt", temp_dir.path()).await?;
+
+        assert!(!result.success);
+        assert_eq!(result.exit_code, None);
+        assert_eq!(
+            result.output_lines,
+            [
+                OutputLine::Stdout("one".to_string()),
+                OutputLine::Stderr("Error: connection reset".to_string()),
+            ]
+        );
+        assert!(!temp_dir.path().join("test.txt").exists());
+        Ok(())
+    }
 }
@@ -0,0 +1,254 @@
+//! Mock agents described in a file, for reproducible demos and end-to-end tests
+//!
+//! With the `mock` feature, setting `PARARI_MOCK_FIXTURE` to a TOML file replaces
+//! the built-in mock agents with the ones in the file:
+//!
+//! ```toml
+//! seed = 7
+//!
+//! [[agents]]
+//! name = "mock-claude"
+//! delay_ms = 800
+//! line_interval_ms = 300
+//! jitter_ms = 100
+//! lines = ["Reading src/lib.rs", { stderr = "warning: unused import" }]
+//! files = [{ action = "write", path = "src/lib.rs", content = "pub fn parse() {}\n" }]
+//!
+//! [[agents]]
+//! name = "mock-codex"
+//! lines = ["Planning the change"]
+//! fail = { after_lines = 1, stderr = "Error: 429 rate limit exceeded", exit_code = 1 }
+//! ```
+
+use std::path::Path;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use super::{FileAction, MockExecutor};
+use crate::error::{Error, Result};
+use crate::executor::traits::OutputLine;
+
+/// Environment variable naming the fixture file
+pub const FIXTURE_ENV: &str = "PARARI_MOCK_FIXTURE";
+
+/// Environment variable overriding the seed of the fixture
+pub const SEED_ENV: &str = "PARARI_MOCK_SEED";
+
+/// Mock agents and the seed of their timing
+#[derive(Debug, Clone, Default, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Fixture {
+    /// Seed of the random jitter; the same seed gives the same timing
+    #[serde(default)]
+    pub seed: u64,
+    /// The agents, in the order they are run
+    #[serde(default)]
+    pub agents: Vec<AgentFixture>,
+}
+
+/// How one mock agent behaves
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AgentFixture {
+    /// Name shown for the agent
+    pub name: String,
+    /// Whether the agent is installed
+    #[serde(default = "default_available")]
+    pub available: bool,
+    /// Milliseconds before the first line is printed
+    #[serde(default)]
+    pub delay_ms: u64,
+    /// Milliseconds between two lines
+    #[serde(default)]
+    pub line_interval_ms: u64,
+    /// Largest random number of milliseconds added to each wait
+    #[serde(default)]
+    pub jitter_ms: u64,
+    /// Lines printed one by one
+    #[serde(default)]
+    pub lines: Vec<FixtureLine>,
+    /// Changes made in the worktree when the agent finishes, as
+    /// `{ action = "write", path = "...", content = "..." }`,
+    /// `{ action = "delete", path = "..." }` or `{ action = "create_dir", path = "..." }`
+    #[serde(default)]
+    pub files: Vec<FileAction>,
+    /// Fail instead of finishing
+    pub fail: Option<FailureFixture>,
+    /// Answer when asked to summarize the changes
+    pub summary: Option<String>,
+}
+
+fn default_available() -> bool {
+    true
+}
+
+/// A printed line: a plain string goes to stdout, `{ stderr = "..." }` to stderr
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(untagged)]
+pub enum FixtureLine {
+    /// A line printed to stdout
+    Stdout(String),
+    /// A line printed to stderr
+    Stderr { stderr: String },
+}
+
+/// A failure after some of the lines were printed
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct FailureFixture {
+    /// Lines printed before failing; all of them when not given
+    pub after_lines: Option<usize>,
+    /// Error output of the failure
+    pub stderr: String,
+    /// Exit code, or none for an agent killed by a signal
+    pub exit_code: Option<i32>,
+}
+
+impl Fixture {
+    /// Parse a fixture from TOML
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the TOML is invalid or has unknown keys.
+    pub fn parse(content: &str) -> Result<Self> {
+        toml::from_str(content).map_err(|e| Error::Config {
+            message: format!("invalid mock fixture: {e}"),
+        })
+    }
+
+    /// Read a fixture file
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed.
+    pub fn load(path: &Path) -> Result<Self> {
+        let content = std::fs::read_to_string(path).map_err(|e| Error::Config {
+            message: format!("cannot read mock fixture {}: {e}", path.display()),
+        })?;
+        Self::parse(&content)
+    }
+
+    /// The fixture named by `PARARI_MOCK_FIXTURE`, with the seed from
+    /// `PARARI_MOCK_SEED` if set
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read or parsed, or the seed is not a number.
+    pub fn from_env() -> Result<Option<Self>> {
+        let Some(path) = std::env::var_os(FIXTURE_ENV) else {
+            return Ok(None);
+        };
+        let mut fixture = Self::load(Path::new(&path))?;
+        if let Ok(seed) = std::env::var(SEED_ENV) {
+            fixture.seed = seed.trim().parse().map_err(|_| Error::Config {
+                message: format!("{SEED_ENV} must be a number, got \"{seed}\""),
+            })?;
+        }
+        Ok(Some(fixture))
+    }
+
+    /// Mock executors behaving as described
+    #[must_use]
+    pub fn executors(&self) -> Vec<MockExecutor> {
+        self.agents
+            .iter()
+            .map(|agent| agent.executor(self.seed))
+            .collect()
+    }
+}
+
+impl AgentFixture {
+    /// A mock executor behaving as described, with its jitter drawn from `seed`
+    #[must_use]
+    pub fn executor(&self, seed: u64) -> MockExecutor {
+        let lines = self
+            .lines
+            .iter()
+            .map(|line| match line {
+                FixtureLine::Stdout(text) => OutputLine::Stdout(text.clone()),
+                FixtureLine::Stderr { stderr } => OutputLine::Stderr(stderr.clone()),
+            })
+            .collect();
+        let mut mock = MockExecutor::new(&self.name)
+            .with_available(self.available)
+            .with_delay(Duration::from_millis(self.delay_ms))
+            .with_streamed_output(lines, Duration::from_millis(self.line_interval_ms))
+            .with_jitter(Duration::from_millis(self.jitter_ms), seed);
+        if let Some(fail) = &self.fail {
+            mock = mock.with_failure_after(
+                fail.after_lines.unwrap_or(self.lines.len()),
+                fail.stderr.clone(),
+                fail.exit_code,
+            );
+        }
+        if let Some(summary) = &self.summary {
+            mock = mock.with_summary(summary);
+        }
+        self.files
+            .iter()
+            .fold(mock, |mock, file| mock.with_file_action(file.clone()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::executor::traits::Executor;
+
+    #[test]
+    fn test_demo_fixture_parses() -> Result<()> {
+        let fixture = Fixture::parse(include_str!("../../../tests/fixtures/demo.toml"))?;
+        let names: Vec<&str> = fixture.agents.iter().map(|a| a.name.as_str()).collect();
+        assert_eq!(names, ["mock-claude", "mock-gemini", "mock-codex"]);
+        assert!(Fixture::parse("[[agents]]\nname = \"a\"\ncolour = \"red\"\n").is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_fixture_agent_fails_midway() -> Result<()> {
+        let fixture = Fixture::parse(
+            r#"
+            [[agents]]
+            name = "mock-codex"
+            lines = ["Planning", { stderr = "retrying" }, "Editing"]
+            fail = { after_lines = 2, stderr = "Error: 429 rate limit exceeded", exit_code = 1 }
+            "#,
+        )?;
+        let mock = fixture.agents[0].executor(fixture.seed);
+        let result = mock.execute("prompt", Path::new("/tmp")).await?;
+
+        assert!(!result.success);
+        assert_eq!(result.exit_code, Some(1));
+        assert_eq!(
+            result.output_lines,
+            [
+                OutputLine::Stdout("Planning".to_string()),
+                OutputLine::Stderr("retrying".to_string()),
+                OutputLine::Stderr("Error: 429 rate limit exceeded".to_string()),
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_same_seed_same_timing() {
+        let agent = AgentFixture {
+            name: "mock-claude".to_string(),
+            available: true,
+            delay_ms: 0,
+            line_interval_ms: 0,
+            jitter_ms: 40,
+            lines: vec![],
+            files: vec![],
+            fail: None,
+            summary: None,
+        };
+        let jitter = |seed: u64| {
+            let mut rng = agent.executor(seed).jitter_rng();
+            (0..5).map(|_| rng.u64(..=40)).collect::<Vec<u64>>()
+        };
+        assert_eq!(jitter(3), jitter(3));
+        assert_ne!(jitter(3), jitter(4));
+    }
+}
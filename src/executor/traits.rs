@@ -1,7 +1,9 @@
-use std::path::Path;
+use std::path::{Path, PathBuf};
 use std::process::Stdio;
+use std::sync::Mutex;
 
 use async_trait::async_trait;
+use serde::{Deserialize, Serialize};
 use tokio::io::{AsyncBufReadExt, BufReader};
 use tokio::process::Command;
 
@@ -31,6 +33,97 @@ pub struct ExecutionResult {
     pub output_lines: Vec<OutputLine>,
     /// Exit code if available
     pub exit_code: Option<i32>,
+    /// Why the execution failed, if recognized
+    pub error_kind: Option<ErrorKind>,
+}
+
+/// Cause of a failed execution, recognized from the tool's output
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ErrorKind {
+    /// The tool is not logged in or its credentials were rejected
+    Auth,
+    /// The provider rejected the request because of a rate limit, quota, or overload
+    RateLimit,
+    /// The prompt and files did not fit in the model's context
+    ContextTooLong,
+    /// The tool exited abnormally, e.g. killed by a signal or after a panic
+    Crash,
+    /// The tool timed out or stalled
+    Timeout,
+    /// The tool was stopped for using more tokens or money than its budget
+    OverBudget,
+}
+
+impl ErrorKind {
+    /// Short label for badges, e.g. `rate limit`
+    #[must_use]
+    pub fn label(self) -> &'static str {
+        match self {
+            ErrorKind::Auth => "auth",
+            ErrorKind::RateLimit => "rate limit",
+            ErrorKind::ContextTooLong => "context too long",
+            ErrorKind::Crash => "crash",
+            ErrorKind::Timeout => "timeout",
+            ErrorKind::OverBudget => "over budget",
+        }
+    }
+}
+
+/// Lines at the end of stdout checked for the reason of a failure, besides stderr;
+/// earlier lines are the agent's work, which may well mention a timeout or a quota
+const STDOUT_TAIL_LINES: usize = 10;
+
+/// Output of failures common to all tools, checked in order
+const ERROR_PATTERNS: [(&str, ErrorKind); 24] = [
+    ("context length", ErrorKind::ContextTooLong),
+    ("context_length_exceeded", ErrorKind::ContextTooLong),
+    ("prompt is too long", ErrorKind::ContextTooLong),
+    ("maximum context", ErrorKind::ContextTooLong),
+    ("too many tokens", ErrorKind::ContextTooLong),
+    ("unauthorized", ErrorKind::Auth),
+    ("not logged in", ErrorKind::Auth),
+    ("invalid api key", ErrorKind::Auth),
+    ("authentication", ErrorKind::Auth),
+    ("/login", ErrorKind::Auth),
+    ("forbidden", ErrorKind::Auth),
+    ("rate limit", ErrorKind::RateLimit),
+    ("rate_limit", ErrorKind::RateLimit),
+    ("too many requests", ErrorKind::RateLimit),
+    ("quota", ErrorKind::RateLimit),
+    ("resource_exhausted", ErrorKind::RateLimit),
+    ("overloaded", ErrorKind::RateLimit),
+    ("timed out", ErrorKind::Timeout),
+    ("timeout", ErrorKind::Timeout),
+    ("stopped after printing nothing", ErrorKind::Timeout),
+    ("panicked at", ErrorKind::Crash),
+    ("segmentation fault", ErrorKind::Crash),
+    ("traceback (most recent call last)", ErrorKind::Crash),
+    ("fatal error", ErrorKind::Crash),
+];
+
+/// Recognize why an execution failed from its output
+///
+/// `patterns` specific to a tool are checked before the common ones, in stderr and
+/// the last lines of stdout. A tool killed by a signal counts as crashed. Returns
+/// `None` for successful executions and for failures that match nothing.
+#[must_use]
+pub fn classify_error(
+    result: &ExecutionResult,
+    patterns: &[(&str, ErrorKind)],
+) -> Option<ErrorKind> {
+    if result.success {
+        return None;
+    }
+    let lines: Vec<&str> = result.stdout.lines().collect();
+    let tail = lines[lines.len().saturating_sub(STDOUT_TAIL_LINES)..].join("\n");
+    let output = format!("{}\n{tail}", result.stderr).to_lowercase();
+    patterns
+        .iter()
+        .chain(&ERROR_PATTERNS)
+        .find(|(pattern, _)| output.contains(pattern))
+        .map(|(_, kind)| *kind)
+        .or_else(|| result.exit_code.is_none().then_some(ErrorKind::Crash))
 }
 
 impl ExecutionResult {
@@ -47,6 +140,7 @@ impl ExecutionResult {
             stderr: String::new(),
             output_lines,
             exit_code: Some(0),
+            error_kind: None,
         }
     }
 
@@ -67,10 +161,23 @@ impl ExecutionResult {
             stderr,
             output_lines,
             exit_code,
+            error_kind: None,
         }
     }
 }
 
+/// Features an AI CLI tool supports besides running a prompt
+///
+/// Lets orchestration adapt to each agent without matching on its name.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Capabilities {
+    /// Reads image files attached to the prompt
+    pub images: bool,
+}
+
+/// Callback receiving each output line of a tool as it arrives
+pub type OnLine<'a> = dyn Fn(&OutputLine) + Send + Sync + 'a;
+
 /// Trait for executing AI CLI tools
 ///
 /// This trait abstracts the execution of AI CLI tools (claude, gemini, codex)
@@ -80,9 +187,54 @@ pub trait Executor: Send + Sync {
     /// Returns the name of the executor (e.g., "claude", "gemini", "codex")
     fn name(&self) -> &str;
 
+    /// The CLI running the agent, whose provider's rate limits it shares with other
+    /// agents of the same CLI; the name by default
+    fn provider(&self) -> &str {
+        self.name()
+    }
+
     /// Check if the executor is available in PATH
     async fn is_available(&self) -> bool;
 
+    /// Version of the CLI tool, recorded with each run to reproduce it later
+    ///
+    /// `None` by default, and when the tool does not report one.
+    async fn version(&self) -> Option<String> {
+        None
+    }
+
+    /// Number of tokens the tool's model holds in its context, if known
+    fn context_window(&self) -> Option<usize> {
+        None
+    }
+
+    /// Whether the tool, with the flags it is run with, prints structured output
+    /// reporting the tokens it used, so `--max-tokens` can stop it; false by default
+    fn reports_usage(&self) -> bool {
+        false
+    }
+
+    /// Features the tool supports; none by default
+    fn capabilities(&self) -> Capabilities {
+        Capabilities::default()
+    }
+
+    /// Recognize why an execution of the tool failed
+    ///
+    /// The default implementation only knows the failures common to all tools.
+    fn classify_error(&self, result: &ExecutionResult) -> Option<ErrorKind> {
+        classify_error(result, &[])
+    }
+
+    /// Refer to image files in the prompt in a form the tool reads them from
+    ///
+    /// Only called for tools whose [`Capabilities`] include images. The default returns
+    /// `None`, so the tool is told about images it cannot see instead of losing them
+    /// without a word.
+    fn attach_images(&self, _prompt: &str, _images: &[PathBuf]) -> Option<String> {
+        None
+    }
+
     /// Execute the CLI tool with the given prompt in the specified working directory
     ///
     /// # Arguments
@@ -93,6 +245,101 @@ pub trait Executor: Send + Sync {
     /// * `Ok(ExecutionResult)` - The result of the execution
     /// * `Err(Error)` - If the execution could not be started
     async fn execute(&self, prompt: &str, working_dir: &Path) -> Result<ExecutionResult>;
+
+    /// Execute like [`Executor::execute`], passing each output line to `on_line` as it arrives
+    ///
+    /// The default implementation reports all lines once the tool has finished.
+    async fn execute_streaming(
+        &self,
+        prompt: &str,
+        working_dir: &Path,
+        on_line: &OnLine<'_>,
+    ) -> Result<ExecutionResult> {
+        let result = self.execute(prompt, working_dir).await?;
+        for line in &result.output_lines {
+            on_line(line);
+        }
+        Ok(result)
+    }
+
+    /// Ask the CLI tool for a short, read-only answer to the given prompt
+    ///
+    /// Used to summarize the changes an executor made in its worktree. Unlike
+    /// `execute`, implementations must not allow the tool to modify files.
+    ///
+    /// # Returns
+    /// * `Ok(Some(String))` - The tool's answer
+    /// * `Ok(None)` - If the executor does not support summarizing or the tool failed
+    /// * `Err(Error)` - If the tool could not be started
+    async fn summarize(&self, _prompt: &str, _working_dir: &Path) -> Result<Option<String>> {
+        Ok(None)
+    }
+}
+
+/// Process ids of the tools currently running, killed when parari panics
+static RUNNING_AGENTS: Mutex<Vec<u32>> = Mutex::new(Vec::new());
+
+/// Keeps a running tool's process id registered until dropped
+struct RunningAgent(u32);
+
+impl RunningAgent {
+    fn register(pid: u32) -> Self {
+        if let Ok(mut running) = RUNNING_AGENTS.lock() {
+            running.push(pid);
+        }
+        Self(pid)
+    }
+}
+
+impl Drop for RunningAgent {
+    fn drop(&mut self) {
+        if let Ok(mut running) = RUNNING_AGENTS.lock() {
+            running.retain(|pid| *pid != self.0);
+        }
+    }
+}
+
+/// Kill every tool still running
+///
+/// Dropping an execution already stops its tool, but a panic does not drop the
+/// executions of other tasks before the process exits.
+pub fn kill_running_agents() {
+    let pids = match RUNNING_AGENTS.lock() {
+        Ok(mut running) => std::mem::take(&mut *running),
+        // A panic while the registry was locked leaves it poisoned, but the ids are intact
+        Err(poisoned) => std::mem::take(&mut *poisoned.into_inner()),
+    };
+    for pid in pids {
+        let pid = pid.to_string();
+        let mut kill = if cfg!(windows) {
+            let mut kill = std::process::Command::new("taskkill");
+            kill.args(["/F", "/T", "/PID", &pid]);
+            kill
+        } else {
+            let mut kill = std::process::Command::new("kill");
+            kill.args(["-KILL", &pid]);
+            kill
+        };
+        let _ = kill.stdout(Stdio::null()).stderr(Stdio::null()).status();
+    }
+}
+
+/// First line `program --version` prints, e.g. `1.0.58 (Claude Code)`
+pub async fn cli_version(program: &str) -> Option<String> {
+    let output = Command::new(program)
+        .arg("--version")
+        .stdin(Stdio::null())
+        .output()
+        .await
+        .ok()?;
+    if !output.status.success() {
+        return None;
+    }
+    String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .find(|line| !line.is_empty())
+        .map(str::to_string)
 }
 
 /// Helper function to execute a command and capture stdout/stderr in order of arrival
@@ -108,12 +355,29 @@ pub trait Executor: Send + Sync {
 ///
 /// Panics if stdout or stderr are not available after spawning with `Stdio::piped()`.
 pub async fn execute_with_ordered_output(
+    cmd: Command,
+    executor_name: &str,
+) -> std::io::Result<ExecutionResult> {
+    execute_with_line_callback(cmd, executor_name, &|_| {}).await
+}
+
+/// Like [`execute_with_ordered_output`], passing each line to `on_line` as it arrives
+///
+/// # Errors
+///
+/// Returns an error if the process cannot be spawned or if reading from stdout/stderr fails.
+pub async fn execute_with_line_callback(
     mut cmd: Command,
     executor_name: &str,
+    on_line: &OnLine<'_>,
 ) -> std::io::Result<ExecutionResult> {
-    cmd.stdout(Stdio::piped()).stderr(Stdio::piped());
+    // A dropped execution, e.g. of a stalled agent, stops the tool
+    cmd.stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .kill_on_drop(true);
 
     let mut child = cmd.spawn()?;
+    let _registered = child.id().map(RunningAgent::register);
 
     let stdout = child
         .stdout
@@ -131,25 +395,29 @@ pub async fn execute_with_ordered_output(
     let mut stdout_content = String::new();
     let mut stderr_content = String::new();
 
+    // Keep each line in its stream's content and in the interleaved output
+    let mut record = |line: OutputLine| {
+        let (content, text) = match &line {
+            OutputLine::Stdout(text) => (&mut stdout_content, text),
+            OutputLine::Stderr(text) => (&mut stderr_content, text),
+        };
+        if !content.is_empty() {
+            content.push('\n');
+        }
+        content.push_str(text);
+        on_line(&line);
+        output_lines.push(line);
+    };
+
     loop {
         tokio::select! {
             result = stdout_reader.next_line() => {
                 match result {
-                    Ok(Some(line)) => {
-                        if !stdout_content.is_empty() {
-                            stdout_content.push('\n');
-                        }
-                        stdout_content.push_str(&line);
-                        output_lines.push(OutputLine::Stdout(line));
-                    }
+                    Ok(Some(line)) => record(OutputLine::Stdout(line)),
                     Ok(None) => {
                         // stdout closed, drain stderr
                         while let Ok(Some(line)) = stderr_reader.next_line().await {
-                            if !stderr_content.is_empty() {
-                                stderr_content.push('\n');
-                            }
-                            stderr_content.push_str(&line);
-                            output_lines.push(OutputLine::Stderr(line));
+                            record(OutputLine::Stderr(line));
                         }
                         break;
                     }
@@ -158,21 +426,11 @@ pub async fn execute_with_ordered_output(
             }
             result = stderr_reader.next_line() => {
                 match result {
-                    Ok(Some(line)) => {
-                        if !stderr_content.is_empty() {
-                            stderr_content.push('\n');
-                        }
-                        stderr_content.push_str(&line);
-                        output_lines.push(OutputLine::Stderr(line));
-                    }
+                    Ok(Some(line)) => record(OutputLine::Stderr(line)),
                     Ok(None) => {
                         // stderr closed, drain stdout
                         while let Ok(Some(line)) = stdout_reader.next_line().await {
-                            if !stdout_content.is_empty() {
-                                stdout_content.push('\n');
-                            }
-                            stdout_content.push_str(&line);
-                            output_lines.push(OutputLine::Stdout(line));
+                            record(OutputLine::Stdout(line));
                         }
                         break;
                     }
@@ -193,5 +451,67 @@ pub async fn execute_with_ordered_output(
         stderr: stderr_content,
         output_lines,
         exit_code,
+        error_kind: None,
     })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_classify_error() {
+        let failed = |stderr: &str, exit_code| {
+            ExecutionResult::failure("claude", stderr.to_string(), exit_code)
+        };
+        let kind = |stderr: &str| classify_error(&failed(stderr, Some(1)), &[]);
+        assert_eq!(
+            kind("API Error: 429 rate_limit_error"),
+            Some(ErrorKind::RateLimit)
+        );
+        assert_eq!(
+            kind("Invalid API key · Please run /login"),
+            Some(ErrorKind::Auth)
+        );
+        assert_eq!(kind("Prompt is too long"), Some(ErrorKind::ContextTooLong));
+        assert_eq!(kind("Request timed out"), Some(ErrorKind::Timeout));
+        assert_eq!(kind("Could not finish the task"), None);
+        assert_eq!(
+            classify_error(&failed("", None), &[]),
+            Some(ErrorKind::Crash)
+        );
+        assert_eq!(
+            classify_error(
+                &failed("Credit balance is too low", Some(1)),
+                &[("credit balance", ErrorKind::RateLimit)]
+            ),
+            Some(ErrorKind::RateLimit)
+        );
+        let success = ExecutionResult::success("claude", "rate limit handled".to_string());
+        assert_eq!(classify_error(&success, &[]), None);
+
+        // Only the end of stdout tells why the agent stopped
+        let work = format!(
+            "Raised the request timeout to 30s\n{}",
+            "Ran the tests\n".repeat(STDOUT_TAIL_LINES)
+        );
+        let mut result = failed("", Some(1));
+        result.stdout = format!("{work}Could not finish the task\n");
+        assert_eq!(classify_error(&result, &[]), None);
+        result.stdout = format!("{work}Error: 429 Too Many Requests\n");
+        assert_eq!(classify_error(&result, &[]), Some(ErrorKind::RateLimit));
+    }
+
+    #[test]
+    fn test_running_agent_registration() {
+        let registered = |pid| {
+            RUNNING_AGENTS
+                .lock()
+                .is_ok_and(|running| running.contains(&pid))
+        };
+        let agent = RunningAgent::register(u32::MAX);
+        assert!(registered(u32::MAX));
+        drop(agent);
+        assert!(!registered(u32::MAX));
+    }
+}
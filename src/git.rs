@@ -1,5 +1,15 @@
 pub mod merge;
+pub mod remote;
+pub mod retention;
+pub mod status;
+pub mod trace;
+pub mod vcs;
 pub mod worktree;
 
 pub use merge::*;
+pub use remote::*;
+pub use retention::*;
+pub use status::*;
+pub use trace::*;
+pub use vcs::*;
 pub use worktree::*;
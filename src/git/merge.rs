@@ -1,8 +1,12 @@
-use std::path::Path;
+use std::collections::HashMap;
+use std::io::Write;
+use std::path::{Path, PathBuf};
 
 use tokio::process::Command;
 
-use crate::error::Result;
+use super::status::{get_status, get_unmerged_files};
+use super::trace::{traced, traced_blocking};
+use crate::error::{Error, Result};
 
 /// Check if target directory has uncommitted changes
 ///
@@ -10,41 +14,29 @@ use crate::error::Result;
 ///
 /// Returns an error if the git command fails.
 pub async fn has_uncommitted_changes(target: &Path) -> Result<bool> {
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(target)
-        .output()
-        .await?;
-
-    let status = String::from_utf8_lossy(&output.stdout);
-    Ok(!status.trim().is_empty())
+    Ok(!get_status(target).await?.is_empty())
 }
 
 /// Get list of uncommitted files in target directory
 ///
+/// Renamed files are listed by their new path; untracked directories end with `/`.
+///
 /// # Errors
 ///
 /// Returns an error if the git command fails.
 pub async fn get_uncommitted_files(target: &Path) -> Result<Vec<String>> {
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(target)
-        .output()
-        .await?;
-
-    let status = String::from_utf8_lossy(&output.stdout);
-    let files: Vec<String> = status
-        .lines()
-        .filter(|line| line.len() >= 3)
-        .map(|line| line[3..].to_string())
-        .collect();
-
-    Ok(files)
+    Ok(get_status(target)
+        .await?
+        .into_iter()
+        .map(|entry| entry.path)
+        .collect())
 }
 
 /// Check for conflicts between worktree changes and target uncommitted changes
 ///
-/// Returns a list of files that would conflict (exist in both worktree changes and target uncommitted changes)
+/// Returns a list of files that would conflict (exist in both worktree changes and target uncommitted changes).
+/// Files whose content is the same on both sides, such as local edits copied into the
+/// worktree and left alone by the agent, are not conflicts.
 ///
 /// # Errors
 ///
@@ -53,15 +45,158 @@ pub async fn check_conflicts(worktree: &Path, target: &Path) -> Result<Vec<Strin
     let worktree_changes = get_uncommitted_files(worktree).await?;
     let target_changes = get_uncommitted_files(target).await?;
 
-    let conflicts: Vec<String> = worktree_changes
-        .iter()
-        .filter(|f| target_changes.contains(f))
-        .cloned()
-        .collect();
+    let mut conflicts = Vec::new();
+    for file in worktree_changes {
+        if target_changes.contains(&file)
+            && !same_content(&worktree.join(&file), &target.join(&file))
+                .await
+                .unwrap_or(false)
+        {
+            conflicts.push(file);
+        }
+    }
 
     Ok(conflicts)
 }
 
+/// Stash the uncommitted changes to `paths` in the target directory
+///
+/// Untracked files among `paths` are stashed too, so that applying a result cannot
+/// overwrite them; other local changes and the index are left alone. Returns whether
+/// anything was stashed.
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn stash_changes(target: &Path, paths: &[String]) -> Result<bool> {
+    if paths.is_empty() {
+        return Ok(false);
+    }
+    let before = stash_count(target).await?;
+    let output = traced(
+        Command::new("git")
+            .args([
+                "stash",
+                "push",
+                "--include-untracked",
+                "--message",
+                "parari: local changes before apply",
+                "--",
+            ])
+            .args(paths)
+            .current_dir(target),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+
+    Ok(stash_count(target).await? > before)
+}
+
+/// Restore changes to `paths` stashed by [`stash_changes`] on top of an applied result
+///
+/// git only merges a stash into files whose changes are staged, so the applied versions
+/// of `paths` are staged first. They stay staged, which leaves the restored local edits
+/// as the unstaged changes on top of them. Returns the files with merge conflicts,
+/// including untracked files the result created as well; in that case the conflict
+/// markers are left in the files and the stash is kept.
+///
+/// # Errors
+///
+/// Returns an error if the git commands fail for a reason other than merge conflicts.
+pub async fn restore_stash(target: &Path, paths: &[String]) -> Result<Vec<String>> {
+    // Untracked files the result removed are not known to git and cannot be staged
+    let mut ls_files = vec!["ls-files", "-z", "--"];
+    ls_files.extend(paths.iter().map(String::as_str));
+    let tracked = git_checked(target, &ls_files).await?;
+    let tracked: Vec<&str> = tracked.split('\0').collect();
+    let mut add = vec!["add", "--"];
+    add.extend(
+        paths
+            .iter()
+            .map(String::as_str)
+            .filter(|path| target.join(path).exists() || tracked.contains(path)),
+    );
+    if add.len() > 2 {
+        git_checked(target, &add).await?;
+    }
+
+    let output = traced(
+        Command::new("git")
+            .args(["stash", "pop", "--index"])
+            .current_dir(target),
+    )
+    .await?;
+
+    let mut conflicts = get_unmerged_files(target).await?;
+    if !output.status.success() && conflicts.is_empty() {
+        // Untracked files of the stash are not restored over files the result created
+        let untracked = git_checked(
+            target,
+            &["ls-tree", "-r", "-z", "--name-only", "stash@{0}^3"],
+        )
+        .await
+        .unwrap_or_default();
+        conflicts = untracked
+            .split('\0')
+            .filter(|path| !path.is_empty() && target.join(path).exists())
+            .map(str::to_string)
+            .collect();
+    }
+    if !output.status.success() && conflicts.is_empty() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(conflicts)
+}
+
+async fn stash_count(target: &Path) -> Result<usize> {
+    Ok(git_checked(target, &["stash", "list"])
+        .await?
+        .lines()
+        .count())
+}
+
+/// Run a git command and return its stdout, failing on a non-zero exit
+pub(super) async fn git_checked(dir: &Path, args: &[&str]) -> Result<String> {
+    let output = traced(Command::new("git").args(args).current_dir(dir)).await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Files touched in the target directory by applying a worktree
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ApplySummary {
+    /// Files that did not exist in the target
+    pub created: Vec<String>,
+    /// Files whose content was replaced
+    pub updated: Vec<String>,
+    /// Files removed from the target
+    pub deleted: Vec<String>,
+    /// Created or updated files with binary content, also listed in `created` or `updated`
+    pub binary: Vec<String>,
+    /// Files left with conflict markers by a three-way merge
+    pub conflicts: Vec<String>,
+}
+
+impl ApplySummary {
+    /// Whether applying changed nothing in the target
+    #[must_use]
+    pub fn is_empty(&self) -> bool {
+        self.created.is_empty() && self.updated.is_empty() && self.deleted.is_empty()
+    }
+}
+
 /// Apply changes from a worktree to the target directory
 ///
 /// This copies all files from the worktree to the target, excluding .git
@@ -69,38 +204,150 @@ pub async fn check_conflicts(worktree: &Path, target: &Path) -> Result<Vec<Strin
 /// # Errors
 ///
 /// Returns an error if file operations fail.
-pub async fn apply_changes(worktree: &Path, target: &Path) -> Result<()> {
-    copy_dir_recursive(worktree, target).await
+pub async fn apply_changes(worktree: &Path, target: &Path) -> Result<ApplySummary> {
+    apply_changes_with_progress(worktree, target, |_| false, |_, _| {}).await
+}
+
+/// Apply changes from a worktree to the target directory, reporting progress
+///
+/// `on_progress` is called with the number of files processed so far and the
+/// total. Files whose content is already identical in the target are left alone,
+/// and so are the files for which `skip` returns true.
+///
+/// # Errors
+///
+/// Returns an error if file operations fail.
+pub async fn apply_changes_with_progress(
+    worktree: &Path,
+    target: &Path,
+    skip: impl Fn(&str) -> bool,
+    on_progress: impl Fn(usize, usize),
+) -> Result<ApplySummary> {
+    let mut files = Vec::new();
+    collect_files(worktree, Path::new(""), &mut files).await?;
+    files.retain(|relative| !skip(&relative.to_string_lossy()));
+
+    let total = files.len();
+    let mut summary = ApplySummary::default();
+    for (i, relative) in files.iter().enumerate() {
+        let src_path = worktree.join(relative);
+        let dst_path = target.join(relative);
+        let name = relative.to_string_lossy().to_string();
+
+        if dst_path.exists() {
+            if same_content(&src_path, &dst_path).await? {
+                on_progress(i + 1, total);
+                continue;
+            }
+            // Remove target file first to avoid "Text file busy" error
+            // when overwriting a running executable (ETXTBSY)
+            tokio::fs::remove_file(&dst_path).await?;
+            summary.updated.push(name.clone());
+        } else {
+            if let Some(parent) = dst_path.parent() {
+                tokio::fs::create_dir_all(parent).await?;
+            }
+            summary.created.push(name.clone());
+        }
+        if is_binary_file(&src_path).await? {
+            summary.binary.push(name);
+        }
+        tokio::fs::copy(&src_path, &dst_path).await?;
+        on_progress(i + 1, total);
+    }
+
+    Ok(summary)
 }
 
+/// Merge the changes of a worktree to `paths` into a target checkout at another commit
+///
+/// The diff of the worktree against its HEAD is applied with `git apply --3way`, so it is
+/// merged with the commits the target has on top of that HEAD instead of undoing them.
+/// Returns the files left with conflict markers.
+///
+/// # Errors
+///
+/// Returns an error if the patch does not apply for a reason other than conflicts.
+pub async fn apply_changes_as_patch(
+    worktree: &Path,
+    target: &Path,
+    paths: &[String],
+) -> Result<Vec<String>> {
+    if paths.is_empty() {
+        return Ok(Vec::new());
+    }
+    add_intent_to_add(worktree).await?;
+    let diff = traced(
+        Command::new("git")
+            .args(["diff", "HEAD", "--binary", "--no-renames", "--"])
+            .args(paths)
+            .current_dir(worktree),
+    )
+    .await?;
+    if !diff.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&diff.stderr).to_string(),
+        });
+    }
+    let mut patch = tempfile::NamedTempFile::new()?;
+    patch.write_all(&diff.stdout)?;
+
+    let output = traced(
+        Command::new("git")
+            .args(["apply", "--3way"])
+            .arg(patch.path())
+            .current_dir(target),
+    )
+    .await?;
+
+    let conflicts = get_unmerged_files(target).await?;
+    if !output.status.success() && conflicts.is_empty() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(conflicts)
+}
+
+/// Remove the given files (relative to `target`) if they exist
+///
+/// Returns the files that were actually removed.
+///
+/// # Errors
+///
+/// Returns an error if a file cannot be removed.
+pub async fn remove_files(target: &Path, files: &[String]) -> Result<Vec<String>> {
+    let mut removed = Vec::new();
+    for file in files {
+        let path = target.join(file);
+        if path.is_file() {
+            tokio::fs::remove_file(&path).await?;
+            removed.push(file.clone());
+        }
+    }
+    Ok(removed)
+}
+
+/// Collect the regular files under `root/dir` as paths relative to `root`, skipping .git
 #[async_recursion::async_recursion]
-async fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
-    let mut entries = tokio::fs::read_dir(src).await?;
+async fn collect_files(root: &Path, dir: &Path, files: &mut Vec<PathBuf>) -> Result<()> {
+    let mut entries = tokio::fs::read_dir(root.join(dir)).await?;
 
     while let Some(entry) = entries.next_entry().await? {
         let file_name = entry.file_name();
-        let file_name_str = file_name.to_str().unwrap_or("");
 
         // Skip .git directory
-        if file_name_str == ".git" {
+        if file_name == ".git" {
             continue;
         }
 
-        let src_path = entry.path();
-        let dst_path = dst.join(&file_name);
-
+        let relative = dir.join(&file_name);
         let file_type = entry.file_type().await?;
 
         if file_type.is_dir() {
-            tokio::fs::create_dir_all(&dst_path).await?;
-            copy_dir_recursive(&src_path, &dst_path).await?;
+            collect_files(root, &relative, files).await?;
         } else if file_type.is_file() {
-            // Remove target file first to avoid "Text file busy" error
-            // when overwriting a running executable (ETXTBSY)
-            if dst_path.exists() {
-                tokio::fs::remove_file(&dst_path).await?;
-            }
-            tokio::fs::copy(&src_path, &dst_path).await?;
+            files.push(relative);
         }
         // Skip symlinks for now
     }
@@ -108,8 +355,146 @@ async fn copy_dir_recursive(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Get a summary of changes between original and worktree
+/// Whether a file looks binary, using git's heuristic of a NUL byte in its first 8000 bytes
+async fn is_binary_file(path: &Path) -> Result<bool> {
+    use tokio::io::AsyncReadExt;
+
+    let mut head = Vec::new();
+    tokio::fs::File::open(path)
+        .await?
+        .take(8000)
+        .read_to_end(&mut head)
+        .await?;
+    Ok(head.contains(&0))
+}
+
+/// Whether two files have the same content
+async fn same_content(a: &Path, b: &Path) -> Result<bool> {
+    let (meta_a, meta_b) = (tokio::fs::metadata(a).await?, tokio::fs::metadata(b).await?);
+    if !meta_b.is_file() || meta_a.len() != meta_b.len() {
+        return Ok(false);
+    }
+    Ok(tokio::fs::read(a).await? == tokio::fs::read(b).await?)
+}
+
+/// Get the diff of a worktree against HEAD
+///
+/// Untracked files are marked as intent-to-add first so new files are part of the diff.
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn get_diff(worktree: &Path) -> Result<String> {
+    add_intent_to_add(worktree).await?;
+    let output = traced(
+        Command::new("git")
+            .args(["diff", "HEAD"])
+            .current_dir(worktree),
+    )
+    .await?;
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Get the diff of a worktree against HEAD including the content of binary files
+///
+/// Like [`get_diff`], but two diffs only compare equal when every file does.
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn get_binary_diff(worktree: &Path) -> Result<String> {
+    add_intent_to_add(worktree).await?;
+    let output = traced(
+        Command::new("git")
+            .args(["diff", "HEAD", "--binary"])
+            .current_dir(worktree),
+    )
+    .await?;
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Apply a patch written by [`get_diff`] to a worktree
+///
+/// # Errors
+///
+/// Returns an error if the patch does not apply.
+pub async fn apply_patch(worktree: &Path, patch: &Path) -> Result<()> {
+    let output = traced(
+        Command::new("git")
+            .arg("apply")
+            .arg(patch)
+            .current_dir(worktree),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// Status of a changed file
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FileStatus {
+    Added,
+    Modified,
+    Deleted,
+}
+
+impl FileStatus {
+    /// Single-character marker used when listing files
+    #[must_use]
+    pub fn symbol(self) -> char {
+        match self {
+            FileStatus::Added => '+',
+            FileStatus::Modified => '~',
+            FileStatus::Deleted => '-',
+        }
+    }
+
+    /// Parse a two-character `git status --porcelain` status code
+    ///
+    /// First char = staged status, second char = unstaged status.
+    #[must_use]
+    pub fn from_porcelain(status_code: &str) -> Self {
+        match status_code {
+            "??" | "A " | " A" => FileStatus::Added,
+            "D " | " D" => FileStatus::Deleted,
+            _ => FileStatus::Modified,
+        }
+    }
+}
+
+/// A single changed file with its line counts
 #[derive(Debug, Clone)]
+pub struct FileChange {
+    /// Path relative to the repository root
+    pub path: String,
+    /// How the file was changed
+    pub status: FileStatus,
+    /// Number of inserted lines
+    pub insertions: usize,
+    /// Number of deleted lines
+    pub deletions: usize,
+    /// Whether git treats the file as binary, so it has no line counts
+    pub binary: bool,
+}
+
+/// Get a summary of changes between original and worktree
+#[derive(Debug, Clone, Default)]
 pub struct ChangeSummary {
     /// Number of files added
     pub files_added: usize,
@@ -117,56 +502,170 @@ pub struct ChangeSummary {
     pub files_modified: usize,
     /// Number of files deleted
     pub files_deleted: usize,
-    /// List of changed file paths
-    pub changed_files: Vec<String>,
+    /// Number of inserted lines
+    pub insertions: usize,
+    /// Number of deleted lines
+    pub deletions: usize,
+    /// Changed files with their status and line counts
+    pub changed_files: Vec<FileChange>,
+}
+
+/// Mark untracked files in a worktree as intent-to-add
+///
+/// This makes new files show up in `git diff HEAD` without staging their content.
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn add_intent_to_add(worktree: &Path) -> Result<()> {
+    traced(
+        Command::new("git")
+            .args(["add", "--intent-to-add", "."])
+            .current_dir(worktree),
+    )
+    .await?;
+
+    Ok(())
+}
+
+/// Mark untracked files in a worktree as intent-to-add, for callers outside the runtime
+///
+/// # Errors
+///
+/// Returns an error if the git command cannot be started.
+pub fn add_intent_to_add_blocking(worktree: &Path) -> Result<()> {
+    traced_blocking(
+        std::process::Command::new("git")
+            .args(["add", "--intent-to-add", "."])
+            .current_dir(worktree),
+    )?;
+
+    Ok(())
+}
+
+/// Parse the output of `git diff --numstat -z` into (path, insertions, deletions)
+///
+/// Entries are `<insertions>\t<deletions>\t<path>` separated by NUL bytes, with
+/// paths neither quoted nor escaped; a renamed file has an empty path followed by
+/// its original and new paths as fields of their own. Binary files are reported
+/// as `-` and parsed as `None`.
+fn parse_numstat_entries(numstat: &[u8]) -> Vec<(String, Option<(usize, usize)>)> {
+    let mut fields = numstat
+        .split(|&byte| byte == 0)
+        .map(String::from_utf8_lossy);
+    let mut entries = Vec::new();
+    while let Some(field) = fields.next() {
+        let mut parts = field.splitn(3, '\t');
+        let (Some(insertions), Some(deletions), Some(path)) =
+            (parts.next(), parts.next(), parts.next())
+        else {
+            continue;
+        };
+        let path = if path.is_empty() {
+            // Renamed: the original path, then the new one
+            let _original = fields.next();
+            match fields.next() {
+                Some(path) => path.into_owned(),
+                None => continue,
+            }
+        } else {
+            path.to_string()
+        };
+        let counts = insertions.parse().ok().zip(deletions.parse().ok());
+        entries.push((path, counts));
+    }
+    entries
+}
+
+/// Parse `git diff --numstat -z` output into per-file (insertions, deletions)
+///
+/// Binary files (reported as `-`) are counted as zero lines.
+#[must_use]
+pub fn parse_numstat_by_file(numstat: &[u8]) -> HashMap<String, (usize, usize)> {
+    parse_numstat_entries(numstat)
+        .into_iter()
+        .map(|(path, counts)| (path, counts.unwrap_or((0, 0))))
+        .collect()
+}
+
+/// Paths of the binary files in `git diff --numstat -z` output, reported as `-\t-`
+#[must_use]
+pub fn binary_files_in_numstat(numstat: &[u8]) -> Vec<String> {
+    parse_numstat_entries(numstat)
+        .into_iter()
+        .filter(|(_, counts)| counts.is_none())
+        .map(|(path, _)| path)
+        .collect()
+}
+
+/// Parse `git diff --numstat -z` output into total (insertions, deletions)
+///
+/// Binary files (reported as `-`) are not counted.
+#[must_use]
+pub fn parse_numstat(numstat: &[u8]) -> (usize, usize) {
+    parse_numstat_by_file(numstat)
+        .values()
+        .fold((0, 0), |(ins, del), (i, d)| (ins + i, del + d))
 }
 
 /// Get a summary of changes in a worktree compared to HEAD
 ///
+/// Untracked files are marked as intent-to-add so their lines are included in the counts.
+///
 /// # Errors
 ///
 /// Returns an error if the git command fails.
 pub async fn get_change_summary(_original: &Path, worktree: &Path) -> Result<ChangeSummary> {
-    // Use git status --porcelain to get all changes including untracked files
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(worktree)
-        .output()
-        .await?;
+    add_intent_to_add(worktree).await?;
+
+    // git status lists all changes, including untracked files
+    let entries = get_status(worktree).await?;
 
-    let status = String::from_utf8_lossy(&output.stdout);
+    let numstat = traced(
+        Command::new("git")
+            .args(["diff", "HEAD", "--numstat", "-z"])
+            .current_dir(worktree),
+    )
+    .await?;
+    let line_counts = parse_numstat_by_file(&numstat.stdout);
+    let binary_files = binary_files_in_numstat(&numstat.stdout);
 
     let mut files_added = 0;
     let mut files_modified = 0;
     let mut files_deleted = 0;
+    let mut insertions = 0;
+    let mut deletions = 0;
     let mut changed_files = Vec::new();
 
-    for line in status.lines() {
-        if line.len() < 3 {
-            continue;
+    for entry in entries {
+        let status = FileStatus::from_porcelain(&entry.code);
+        let path = entry.path;
+
+        match status {
+            FileStatus::Added => files_added += 1,
+            FileStatus::Deleted => files_deleted += 1,
+            FileStatus::Modified => files_modified += 1,
         }
 
-        let status_code = &line[0..2];
-        let file_name = line[3..].to_string();
-        changed_files.push(file_name);
+        let (file_insertions, file_deletions) = line_counts.get(&path).copied().unwrap_or((0, 0));
+        insertions += file_insertions;
+        deletions += file_deletions;
 
-        // Parse status codes:
-        // ?? = untracked (new file)
-        // A  = added (staged)
-        // M  = modified
-        // D  = deleted
-        // First char = staged status, second char = unstaged status
-        match status_code {
-            "??" | "A " | " A" => files_added += 1,
-            "D " | " D" => files_deleted += 1,
-            _ => files_modified += 1,
-        }
+        changed_files.push(FileChange {
+            binary: binary_files.contains(&path),
+            path,
+            status,
+            insertions: file_insertions,
+            deletions: file_deletions,
+        });
     }
 
     Ok(ChangeSummary {
         files_added,
         files_modified,
         files_deleted,
+        insertions,
+        deletions,
         changed_files,
     })
 }
@@ -174,6 +673,7 @@ pub async fn get_change_summary(_original: &Path, worktree: &Path) -> Result<Cha
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::TestRepo;
 
     #[tokio::test]
     async fn test_change_summary_default() {
@@ -181,10 +681,239 @@ mod tests {
             files_added: 0,
             files_modified: 0,
             files_deleted: 0,
+            insertions: 0,
+            deletions: 0,
             changed_files: vec![],
         };
         assert_eq!(summary.files_added, 0);
         assert_eq!(summary.files_modified, 0);
         assert_eq!(summary.files_deleted, 0);
     }
+
+    #[tokio::test]
+    async fn test_apply_changes_summary() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let worktree = tempfile::tempdir()?;
+        let target = tempfile::tempdir()?;
+        std::fs::create_dir_all(worktree.path().join("src"))?;
+        std::fs::create_dir_all(worktree.path().join(".git"))?;
+        std::fs::write(worktree.path().join(".git/HEAD"), "ref")?;
+        std::fs::write(worktree.path().join("src/new.rs"), "new")?;
+        std::fs::write(worktree.path().join("same.txt"), "same")?;
+        std::fs::write(worktree.path().join("changed.txt"), "after")?;
+        std::fs::write(worktree.path().join("build.log"), "noise")?;
+        std::fs::write(target.path().join("same.txt"), "same")?;
+        std::fs::write(target.path().join("changed.txt"), "before")?;
+        std::fs::write(target.path().join("gone.txt"), "old")?;
+
+        let progress = std::sync::Mutex::new(Vec::new());
+        let summary = apply_changes_with_progress(
+            worktree.path(),
+            target.path(),
+            |file| {
+                std::path::Path::new(file)
+                    .extension()
+                    .is_some_and(|ext| ext.eq_ignore_ascii_case("log"))
+            },
+            |done, total| {
+                if let Ok(mut progress) = progress.lock() {
+                    progress.push((done, total));
+                }
+            },
+        )
+        .await?;
+
+        assert_eq!(summary.created, vec!["src/new.rs"]);
+        assert_eq!(summary.updated, vec!["changed.txt"]);
+        assert_eq!(
+            std::fs::read_to_string(target.path().join("changed.txt"))?,
+            "after"
+        );
+        assert!(!target.path().join(".git").exists());
+        assert!(!target.path().join("build.log").exists());
+        let progress = progress.into_inner().unwrap_or_default();
+        assert_eq!(progress.last(), Some(&(3, 3)));
+
+        let removed = remove_files(
+            target.path(),
+            &["gone.txt".to_string(), "missing.txt".to_string()],
+        )
+        .await?;
+        assert_eq!(removed, vec!["gone.txt"]);
+        assert!(!target.path().join("gone.txt").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_check_conflicts_ignores_identical_files()
+    -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let worktree = TestRepo::new()?;
+        let target = TestRepo::new()?;
+        for repo in [&worktree, &target] {
+            repo.write("copied.txt", "local edit")?;
+        }
+        worktree.write("edited.txt", "agent")?;
+        target.write("edited.txt", "local")?;
+
+        let conflicts = check_conflicts(worktree.path(), target.path()).await?;
+        assert_eq!(conflicts, vec!["edited.txt"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_get_diff_includes_new_files()
+    -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let repo = TestRepo::builder().with_file("a.txt", "one\n").build()?;
+        let dir = repo.path();
+        repo.write("new.txt", "hello\n")?;
+
+        let diff = get_diff(dir).await?;
+        assert!(diff.contains("diff --git a/new.txt b/new.txt"));
+        assert!(diff.contains("+hello"));
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_stash_and_restore() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let repo = TestRepo::builder()
+            .with_file("a.txt", "one\ntwo\nthree\n")
+            .build()?;
+        let dir = repo.path();
+
+        let paths = vec!["a.txt".to_string(), "notes.txt".to_string()];
+
+        // Nothing to stash in a clean checkout
+        assert!(!stash_changes(dir, &paths).await?);
+
+        // A local edit at the end is merged with an applied edit at the start, while
+        // local changes to other files stay in place
+        std::fs::write(dir.join("a.txt"), "one\ntwo\nthree!\n")?;
+        std::fs::write(dir.join("notes.txt"), "local\n")?;
+        std::fs::write(dir.join("other.txt"), "untouched\n")?;
+        assert!(stash_changes(dir, &paths).await?);
+        assert_eq!(
+            std::fs::read_to_string(dir.join("a.txt"))?,
+            "one\ntwo\nthree\n"
+        );
+        assert!(!dir.join("notes.txt").exists());
+        assert!(dir.join("other.txt").exists());
+        std::fs::write(dir.join("a.txt"), "one!\ntwo\nthree\n")?;
+        assert!(restore_stash(dir, &paths).await?.is_empty());
+        assert_eq!(
+            std::fs::read_to_string(dir.join("a.txt"))?,
+            "one!\ntwo\nthree!\n"
+        );
+        assert_eq!(std::fs::read_to_string(dir.join("notes.txt"))?, "local\n");
+
+        // Edits of the same line conflict and keep the stash
+        repo.git(&["reset", "--hard", "--quiet"])?;
+        std::fs::write(dir.join("a.txt"), "one\nlocal\nthree\n")?;
+        assert!(stash_changes(dir, &paths).await?);
+        std::fs::write(dir.join("a.txt"), "one\napplied\nthree\n")?;
+        assert_eq!(restore_stash(dir, &paths).await?, vec!["a.txt"]);
+        assert_eq!(stash_count(dir).await?, 1);
+
+        // An untracked file the result created as well is kept in the stash
+        repo.git(&["reset", "--hard", "--quiet"])?;
+        repo.git(&["clean", "-fdq"])?;
+        std::fs::write(dir.join("notes.txt"), "local\n")?;
+        assert!(stash_changes(dir, &paths).await?);
+        std::fs::write(dir.join("notes.txt"), "applied\n")?;
+        assert_eq!(restore_stash(dir, &paths).await?, vec!["notes.txt"]);
+        assert_eq!(stash_count(dir).await?, 2);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_apply_changes_as_patch() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let repo = TestRepo::builder()
+            .with_file("a.txt", "one\ntwo\nthree\n")
+            .with_file("old.txt", "old\n")
+            .build()?;
+        let target = repo.path().to_path_buf();
+        let worktrees = tempfile::tempdir()?;
+        let worktree = worktrees.path().join("worktree");
+        repo.git(&[
+            "worktree",
+            "add",
+            "-q",
+            "--detach",
+            &worktree.to_string_lossy(),
+        ])?;
+
+        // The target moves on while the agent edits the worktree
+        std::fs::write(target.join("a.txt"), "one\ntwo\nthree!\n")?;
+        repo.git(&["commit", "-q", "-am", "next"])?;
+        std::fs::write(worktree.join("a.txt"), "one!\ntwo\nthree\n")?;
+        std::fs::write(worktree.join("new.txt"), "new\n")?;
+        std::fs::remove_file(worktree.join("old.txt"))?;
+
+        let paths = ["a.txt", "new.txt", "old.txt"].map(String::from);
+        assert!(
+            apply_changes_as_patch(&worktree, &target, &paths)
+                .await?
+                .is_empty()
+        );
+        assert_eq!(
+            std::fs::read_to_string(target.join("a.txt"))?,
+            "one!\ntwo\nthree!\n"
+        );
+        assert_eq!(std::fs::read_to_string(target.join("new.txt"))?, "new\n");
+        assert!(!target.join("old.txt").exists());
+
+        // Edits of the same line are left with conflict markers
+        repo.git(&["commit", "-q", "-am", "merged"])?;
+        std::fs::write(target.join("a.txt"), "one?\ntwo\nthree!\n")?;
+        repo.git(&["commit", "-q", "-am", "again"])?;
+        assert_eq!(
+            apply_changes_as_patch(&worktree, &target, &paths[..1]).await?,
+            vec!["a.txt"]
+        );
+        assert!(std::fs::read_to_string(target.join("a.txt"))?.contains("<<<<<<<"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_numstat() {
+        let numstat = b"10\t2\tsrc/main.rs\x003\t0\tsrc/lib.rs\0-\t-\tassets/logo.png\0";
+        assert_eq!(parse_numstat(numstat), (13, 2));
+        assert_eq!(parse_numstat(b""), (0, 0));
+    }
+
+    #[test]
+    fn test_parse_numstat_by_file() {
+        let numstat = b"10\t2\tsrc/main.rs\0-\t-\tassets/logo.png\0\
+            1\t0\tdocs/my \"notes\".md\x004\t1\t\0old name.txt\0caf\xc3\xa9.txt\0";
+        let counts = parse_numstat_by_file(numstat);
+        assert_eq!(counts.get("src/main.rs"), Some(&(10, 2)));
+        assert_eq!(counts.get("assets/logo.png"), Some(&(0, 0)));
+        assert_eq!(counts.get("docs/my \"notes\".md"), Some(&(1, 0)));
+        assert_eq!(counts.get("café.txt"), Some(&(4, 1)));
+        assert!(!counts.contains_key("old name.txt"));
+        assert_eq!(binary_files_in_numstat(numstat), ["assets/logo.png"]);
+    }
+
+    #[tokio::test]
+    async fn test_apply_changes_lists_binary_files()
+    -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let worktree = tempfile::tempdir()?;
+        let target = tempfile::tempdir()?;
+        std::fs::write(worktree.path().join("logo.png"), b"\x89PNG\0\x01")?;
+        std::fs::write(worktree.path().join("notes.txt"), "text")?;
+        std::fs::write(target.path().join("notes.txt"), "old text")?;
+
+        let summary = apply_changes(worktree.path(), target.path()).await?;
+        assert_eq!(summary.created, vec!["logo.png"]);
+        assert_eq!(summary.binary, vec!["logo.png"]);
+        assert_eq!(summary.updated, vec!["notes.txt"]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_file_status_from_porcelain() {
+        assert_eq!(FileStatus::from_porcelain("??"), FileStatus::Added);
+        assert_eq!(FileStatus::from_porcelain(" A"), FileStatus::Added);
+        assert_eq!(FileStatus::from_porcelain(" D"), FileStatus::Deleted);
+        assert_eq!(FileStatus::from_porcelain(" M"), FileStatus::Modified);
+        assert_eq!(FileStatus::from_porcelain("MM"), FileStatus::Modified);
+    }
 }
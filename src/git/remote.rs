@@ -0,0 +1,135 @@
+use std::path::Path;
+
+use tokio::process::Command;
+
+use super::merge::git_checked;
+use super::trace::traced;
+use crate::error::{Error, Result};
+
+/// Clone the latest commit of the default branch of `url` into `dest`
+///
+/// # Errors
+///
+/// Returns an error if git cannot reach or clone the repository.
+pub async fn clone_shallow(url: &str, dest: &Path) -> Result<()> {
+    let output = traced(
+        Command::new("git")
+            .args(["clone", "--quiet", "--depth", "1", "--no-tags", url])
+            .arg(dest),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::RemoteRepository {
+            url: url.to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(())
+}
+
+/// URL of the `origin` remote of `repo`
+///
+/// # Errors
+///
+/// Returns an error if the repository has no `origin` remote.
+pub async fn remote_url(repo: &Path) -> Result<String> {
+    Ok(git_checked(repo, &["remote", "get-url", "origin"])
+        .await?
+        .trim()
+        .to_string())
+}
+
+/// Commit every change in `repo` on a new branch and push it to `origin`
+///
+/// The commit is built in a temporary index, so the checkout stays on its branch with
+/// the changes uncommitted, also when the push fails.
+///
+/// # Errors
+///
+/// Returns an error if the branch exists, there is nothing to commit, or the push is
+/// rejected.
+pub async fn push_branch(repo: &Path, branch: &str, message: &str) -> Result<()> {
+    let head = git_checked(repo, &["rev-parse", "--verify", "HEAD"]).await?;
+    let head = head.trim();
+
+    let index_dir = tempfile::tempdir()?;
+    let index = index_dir.path().join("index");
+    git_with_index(repo, &index, &["read-tree", head]).await?;
+    git_with_index(repo, &index, &["add", "--all"]).await?;
+    let tree = git_with_index(repo, &index, &["write-tree"]).await?;
+    let tree = tree.trim();
+    let parent_tree = git_checked(repo, &["rev-parse", "HEAD^{tree}"]).await?;
+    if tree == parent_tree.trim() {
+        return Err(Error::GitCommand {
+            message: "nothing to commit".to_string(),
+        });
+    }
+
+    let commit = git_checked(repo, &["commit-tree", tree, "-p", head, "-m", message]).await?;
+    git_checked(repo, &["branch", branch, commit.trim()]).await?;
+    git_checked(repo, &["push", "--quiet", "origin", branch]).await?;
+    Ok(())
+}
+
+/// Run a git command on the index file `index` instead of the checkout's index
+async fn git_with_index(repo: &Path, index: &Path, args: &[&str]) -> Result<String> {
+    let output = traced(
+        Command::new("git")
+            .args(args)
+            .env("GIT_INDEX_FILE", index)
+            .current_dir(repo),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    #[tokio::test]
+    async fn test_push_branch_keeps_the_checkout() -> Result<()> {
+        let origin = tempfile::tempdir()?;
+        let repo = TestRepo::new()?;
+        let init = std::process::Command::new("git")
+            .args(["init", "-q", "--bare"])
+            .arg(origin.path())
+            .status()?;
+        assert!(init.success());
+        repo.git(&["remote", "add", "origin", &origin.path().to_string_lossy()])?;
+        repo.git(&["config", "user.name", "Test User"])?;
+        repo.git(&["config", "user.email", "test@test.com"])?;
+        let current = repo.git(&["branch", "--show-current"])?;
+        repo.write("README.md", "# Changed\n")?;
+        repo.write("new.txt", "new\n")?;
+
+        push_branch(repo.path(), "parari/claude", "Change the README").await?;
+        assert_eq!(repo.git(&["branch", "--show-current"])?, current);
+        assert_eq!(repo.read("README.md")?, "# Changed\n");
+        assert_eq!(
+            repo.git(&["status", "--porcelain"])?,
+            " M README.md\n?? new.txt\n"
+        );
+        let pushed = repo.git(&["ls-remote", "origin", "parari/claude"])?;
+        let files = repo.git(&["show", "--name-only", "--format=", "parari/claude"])?;
+        assert!(!pushed.is_empty());
+        assert_eq!(files, "README.md\nnew.txt\n");
+
+        // A second push of the same branch fails without touching the checkout
+        assert!(
+            push_branch(repo.path(), "parari/claude", "Again")
+                .await
+                .is_err()
+        );
+        assert_eq!(repo.git(&["branch", "--show-current"])?, current);
+        Ok(())
+    }
+}
@@ -0,0 +1,243 @@
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use chrono::{DateTime, NaiveDateTime, Utc};
+use tokio::process::Command;
+
+use super::worktree::{get_main_repo_root, remove_worktree};
+use crate::config::{self, WorktreeSettings};
+use crate::error::Result;
+
+/// A worktree in the parari worktrees directory, as seen by the retention policy
+#[derive(Debug, Clone)]
+struct WorktreeEntry {
+    /// Path to the worktree
+    path: PathBuf,
+    /// Repository the worktree was added to, if it can still be determined
+    repo: Option<PathBuf>,
+    /// When the worktree was created, from the timestamp in its name
+    created_at: Option<DateTime<Utc>>,
+    /// Whether the parari process that created it is still running
+    running: bool,
+}
+
+/// Path of the file recording which process owns a worktree
+///
+/// The extension is appended, so worktrees of agents named like `gpt-4.1` and
+/// `gpt-4.5` do not share a file.
+pub(crate) fn owner_file(worktree_path: &Path) -> PathBuf {
+    let name = worktree_path
+        .file_name()
+        .map(|name| name.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let dir = worktree_path.parent().unwrap_or(worktree_path);
+    dir.join(format!("{name}.pid"))
+}
+
+/// Record the current process as the owner of a worktree
+///
+/// # Errors
+///
+/// Returns an error if the file cannot be written.
+pub(crate) async fn mark_owned(worktree_path: &Path) -> Result<()> {
+    tokio::fs::write(owner_file(worktree_path), std::process::id().to_string()).await?;
+    Ok(())
+}
+
+/// Creation time encoded at the start of a worktree name (`%Y%m%d%H%M%S%3f`, UTC)
+fn created_at(name: &str) -> Option<DateTime<Utc>> {
+    let timestamp = name.get(..17)?;
+    NaiveDateTime::parse_from_str(timestamp, "%Y%m%d%H%M%S%3f")
+        .ok()
+        .map(|time| time.and_utc())
+}
+
+/// Whether a worktree belongs to a parari process that is still running
+pub async fn is_worktree_in_use(worktree_path: &Path) -> bool {
+    let Ok(pid) = tokio::fs::read_to_string(owner_file(worktree_path)).await else {
+        return false;
+    };
+    let pid = pid.trim();
+    if pid == std::process::id().to_string() {
+        return true;
+    }
+    Command::new("kill")
+        .args(["-0", pid])
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+/// Worktrees to remove under the retention policy
+///
+/// Worktrees older than `max_age` are removed whichever repository they belong to;
+/// of the remaining worktrees of `repo`, only the newest `max_count` are kept.
+/// Worktrees of running processes are never selected.
+fn expired(
+    entries: &[WorktreeEntry],
+    repo: &Path,
+    max_count: usize,
+    max_age: Option<Duration>,
+    now: DateTime<Utc>,
+) -> Vec<usize> {
+    let too_old = |entry: &WorktreeEntry| {
+        max_age.is_some_and(|max_age| {
+            entry.created_at.is_some_and(|created| {
+                now.signed_duration_since(created)
+                    .to_std()
+                    .is_ok_and(|age| age > max_age)
+            })
+        })
+    };
+
+    let mut selected = Vec::new();
+    let mut kept_in_repo = Vec::new();
+    for (i, entry) in entries.iter().enumerate() {
+        if entry.running {
+            continue;
+        }
+        if too_old(entry) {
+            selected.push(i);
+        } else if entry.repo.as_deref() == Some(repo) {
+            kept_in_repo.push(i);
+        }
+    }
+
+    // Entries are sorted oldest first
+    let excess = kept_in_repo.len().saturating_sub(max_count);
+    selected.extend(kept_in_repo.into_iter().take(excess));
+    selected.sort_unstable();
+    selected
+}
+
+/// Remove old worktrees according to the retention policy
+///
+/// # Errors
+///
+/// Returns an error if file system operations fail.
+pub async fn cleanup_old_worktrees(repo_path: &Path, settings: &WorktreeSettings) -> Result<()> {
+    let worktrees_dir = config::worktrees_dir();
+
+    if !worktrees_dir.exists() {
+        return Ok(());
+    }
+
+    let mut paths = Vec::new();
+    let mut read_dir = tokio::fs::read_dir(&worktrees_dir).await?;
+    while let Some(entry) = read_dir.next_entry().await? {
+        if entry.file_type().await?.is_dir() {
+            paths.push(entry.path());
+        }
+    }
+
+    // Sort by name (which includes timestamp) - oldest first
+    paths.sort();
+
+    let mut entries = Vec::with_capacity(paths.len());
+    for path in paths {
+        let name = path
+            .file_name()
+            .map(|name| name.to_string_lossy().to_string())
+            .unwrap_or_default();
+        entries.push(WorktreeEntry {
+            repo: get_main_repo_root(&path).await.ok(),
+            created_at: created_at(&name),
+            running: is_worktree_in_use(&path).await,
+            path,
+        });
+    }
+
+    let repo = get_main_repo_root(repo_path)
+        .await
+        .unwrap_or_else(|_| repo_path.to_path_buf());
+    let max_age = settings.max_age.map(config::Age::duration);
+    for i in expired(&entries, &repo, settings.max_count, max_age, Utc::now()) {
+        let entry = &entries[i];
+        let owner = entry.repo.as_deref().unwrap_or(repo_path);
+        let _ = remove_worktree(owner, &entry.path).await;
+    }
+
+    Ok(())
+}
+
+/// Remove a worktree of the parari worktrees directory, whichever repository it belongs to
+///
+/// # Errors
+///
+/// Returns an error if file system operations fail.
+pub async fn remove_parari_worktree(worktree_path: &Path) -> Result<()> {
+    let owner = get_main_repo_root(worktree_path)
+        .await
+        .unwrap_or_else(|_| worktree_path.to_path_buf());
+    remove_worktree(&owner, worktree_path).await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(name: &str, repo: &str, running: bool) -> WorktreeEntry {
+        WorktreeEntry {
+            path: PathBuf::from(format!("/worktrees/{name}")),
+            repo: Some(PathBuf::from(repo)),
+            created_at: created_at(name),
+            running,
+        }
+    }
+
+    #[test]
+    fn test_created_at() {
+        let time = created_at("20261016141502123-claude").map(|t| t.to_rfc3339());
+        assert_eq!(time.as_deref(), Some("2026-10-16T14:15:02.123+00:00"));
+        assert_eq!(created_at("claude"), None);
+    }
+
+    #[test]
+    fn test_owner_file_keeps_dotted_names() {
+        assert_eq!(
+            owner_file(Path::new("/worktrees/20261016141502123-gpt-4.1")),
+            Path::new("/worktrees/20261016141502123-gpt-4.1.pid")
+        );
+        assert_ne!(
+            owner_file(Path::new("/worktrees/20261016141502123-gpt-4.1")),
+            owner_file(Path::new("/worktrees/20261016141502123-gpt-4.5"))
+        );
+    }
+
+    #[test]
+    fn test_count_limit_is_per_repository() {
+        let entries = vec![
+            entry("20261001000000000-claude", "/repo/a", false),
+            entry("20261002000000000-claude", "/repo/b", false),
+            entry("20261003000000000-gemini", "/repo/a", true),
+            entry("20261004000000000-codex", "/repo/a", false),
+            entry("20261005000000000-claude", "/repo/a", false),
+        ];
+        let now = created_at("20261006000000000").unwrap_or_default();
+
+        // The running worktree is kept and does not count
+        assert_eq!(
+            expired(&entries, Path::new("/repo/a"), 1, None, now),
+            vec![0, 3]
+        );
+        assert!(expired(&entries, Path::new("/repo/b"), 1, None, now).is_empty());
+    }
+
+    #[test]
+    fn test_age_limit_applies_to_all_repositories() {
+        let entries = vec![
+            entry("20261001000000000-claude", "/repo/b", false),
+            entry("20261001000000000-gemini", "/repo/b", true),
+            entry("20261005000000000-claude", "/repo/a", false),
+        ];
+        let now = created_at("20261006000000000").unwrap_or_default();
+        let week = Some(Duration::from_hours(7 * 24));
+        let two_days = Some(Duration::from_hours(2 * 24));
+
+        assert!(expired(&entries, Path::new("/repo/a"), 20, week, now).is_empty());
+        assert_eq!(
+            expired(&entries, Path::new("/repo/a"), 20, two_days, now),
+            vec![0]
+        );
+    }
+}
@@ -0,0 +1,217 @@
+use std::path::Path;
+
+use tokio::process::Command;
+
+use super::trace::traced;
+use crate::error::{Error, Result};
+
+/// A changed file of a checkout, as listed by `git status`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct StatusEntry {
+    /// Two-character status code: the index, then the work tree, e.g. `" M"`, `"R "`
+    /// or `"??"`
+    pub code: String,
+    /// Path relative to the repository root; untracked directories end with `/`
+    pub path: String,
+    /// Path the file had before it was renamed or copied
+    pub original: Option<String>,
+}
+
+impl StatusEntry {
+    /// Whether the file was deleted from the index or the work tree
+    #[must_use]
+    pub fn is_deleted(&self) -> bool {
+        matches!(self.code.as_str(), "D " | " D" | "DD")
+    }
+}
+
+/// The changed files of a checkout, including untracked ones
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn get_status(dir: &Path) -> Result<Vec<StatusEntry>> {
+    let output = traced(
+        Command::new("git")
+            .args(["status", "--porcelain=v1", "-z"])
+            .current_dir(dir),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(parse_status(&output.stdout))
+}
+
+/// The untracked, not ignored files under `path` of a checkout, relative to `dir`
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn get_untracked_files(dir: &Path, path: &str) -> Result<Vec<String>> {
+    let output = traced(
+        Command::new("git")
+            .args([
+                "ls-files",
+                "--others",
+                "--exclude-standard",
+                "-z",
+                "--",
+                path,
+            ])
+            .current_dir(dir),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(split_names(&output.stdout))
+}
+
+/// The files of a checkout left with merge conflicts, relative to its root
+///
+/// # Errors
+///
+/// Returns an error if the git command fails.
+pub async fn get_unmerged_files(dir: &Path) -> Result<Vec<String>> {
+    let output = traced(
+        Command::new("git")
+            .args(["diff", "--name-only", "--diff-filter=U", "-z"])
+            .current_dir(dir),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+    Ok(split_names(&output.stdout))
+}
+
+/// File names printed by git with `-z`, neither quoted nor escaped
+fn split_names(output: &[u8]) -> Vec<String> {
+    output
+        .split(|&byte| byte == 0)
+        .filter(|name| !name.is_empty())
+        .map(|name| String::from_utf8_lossy(name).into_owned())
+        .collect()
+}
+
+/// Parse the output of `git status --porcelain=v1 -z`
+///
+/// Entries are `XY <path>` separated by NUL bytes, with paths neither quoted nor
+/// escaped; a renamed or copied file is followed by its original path as a field
+/// of its own.
+#[must_use]
+pub fn parse_status(output: &[u8]) -> Vec<StatusEntry> {
+    let mut fields = output.split(|&byte| byte == 0).map(String::from_utf8_lossy);
+    let mut entries = Vec::new();
+    while let Some(field) = fields.next() {
+        let (Some(code), Some(path)) = (field.get(..2), field.get(3..)) else {
+            continue;
+        };
+        let original = if code.contains(['R', 'C']) {
+            fields.next().map(std::borrow::Cow::into_owned)
+        } else {
+            None
+        };
+        entries.push(StatusEntry {
+            code: code.to_string(),
+            path: path.to_string(),
+            original,
+        });
+    }
+    entries
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::testing::TestRepo;
+
+    #[test]
+    fn test_parse_status() {
+        let output = b" M src/lexer.rs\0\
+            R  docs/new name.md\0docs/old \"name\".md\0\
+            ?? notes/\0 D caf\xc3\xa9.txt\0";
+        let entries = parse_status(output);
+        assert_eq!(
+            entries,
+            vec![
+                StatusEntry {
+                    code: " M".to_string(),
+                    path: "src/lexer.rs".to_string(),
+                    original: None,
+                },
+                StatusEntry {
+                    code: "R ".to_string(),
+                    path: "docs/new name.md".to_string(),
+                    original: Some("docs/old \"name\".md".to_string()),
+                },
+                StatusEntry {
+                    code: "??".to_string(),
+                    path: "notes/".to_string(),
+                    original: None,
+                },
+                StatusEntry {
+                    code: " D".to_string(),
+                    path: "café.txt".to_string(),
+                    original: None,
+                },
+            ]
+        );
+        assert!(entries[3].is_deleted());
+        assert!(parse_status(b"").is_empty());
+    }
+
+    #[tokio::test]
+    async fn test_status_of_unusual_names() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        let repo = dir.path();
+        std::process::Command::new("git")
+            .args(["init", "-q"])
+            .current_dir(repo)
+            .status()?;
+        std::fs::write(repo.join("with space.txt"), "a\n")?;
+        std::fs::write(repo.join("naïve \"quoted\".txt"), "b\n")?;
+
+        let mut paths: Vec<String> = get_status(repo)
+            .await?
+            .into_iter()
+            .map(|e| e.path)
+            .collect();
+        paths.sort();
+        assert_eq!(paths, ["naïve \"quoted\".txt", "with space.txt"]);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_unmerged_files() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let repo = TestRepo::builder()
+            .with_file("naïve \"quoted\".txt", "base\n")
+            .build()?;
+        assert!(get_unmerged_files(repo.path()).await?.is_empty());
+
+        repo.git(&["checkout", "-q", "-b", "other"])?;
+        repo.write("naïve \"quoted\".txt", "other\n")?;
+        repo.commit("Change on other")?;
+        repo.git(&["checkout", "-q", "-"])?;
+        repo.write("naïve \"quoted\".txt", "main\n")?;
+        repo.commit("Change on main")?;
+        // The merge stops with the conflict, so its failure is expected
+        let _ = repo.git(&["merge", "-q", "other"]);
+
+        assert_eq!(
+            get_unmerged_files(repo.path()).await?,
+            ["naïve \"quoted\".txt"]
+        );
+        Ok(())
+    }
+}
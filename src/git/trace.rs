@@ -0,0 +1,148 @@
+use std::fmt::Display;
+use std::io;
+use std::process::{ExitStatus, Output};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::time::{Duration, Instant};
+
+use console::style;
+
+use crate::cli::output::hold;
+
+/// Whether every git command is logged, with `--trace-git`
+static TRACE: AtomicBool = AtomicBool::new(false);
+
+/// Whether commands removing worktrees are only logged, with `--git-dry-run`
+static DRY_RUN: AtomicBool = AtomicBool::new(false);
+
+/// Log every git command parari runs, with its directory, duration and exit code
+///
+/// Like `verbosity::set_quiet`, this is a process-wide switch. The log goes to
+/// stderr, or to the split view's status area while it is shown.
+pub fn set_trace(enabled: bool) {
+    TRACE.store(enabled, Ordering::Relaxed);
+}
+
+/// Only log the git commands that remove worktrees or prune their metadata,
+/// leaving the worktrees in place
+pub fn set_dry_run(enabled: bool) {
+    DRY_RUN.store(enabled, Ordering::Relaxed);
+}
+
+/// Run a git command and collect its output, logging it with `--trace-git`
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started.
+pub async fn traced(command: &mut tokio::process::Command) -> io::Result<Output> {
+    let start = Instant::now();
+    let output = command.output().await;
+    trace(
+        command.as_std(),
+        start.elapsed(),
+        output.as_ref().map(|o| o.status),
+    );
+    output
+}
+
+/// Run a git command and collect its output on the current thread, logging it with
+/// `--trace-git`
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started.
+pub fn traced_blocking(command: &mut std::process::Command) -> io::Result<Output> {
+    let start = Instant::now();
+    let output = command.output();
+    trace(command, start.elapsed(), output.as_ref().map(|o| o.status));
+    output
+}
+
+/// Run an interactive git command on the terminal and wait for it, logging it with
+/// `--trace-git`
+///
+/// # Errors
+///
+/// Returns an error if git cannot be started.
+pub fn traced_status(command: &mut std::process::Command) -> io::Result<ExitStatus> {
+    let start = Instant::now();
+    let status = command.status();
+    trace(command, start.elapsed(), status.as_ref().copied());
+    status
+}
+
+/// Whether commands removing worktrees are only logged, with `--git-dry-run`
+#[must_use]
+pub fn dry_run() -> bool {
+    DRY_RUN.load(Ordering::Relaxed)
+}
+
+/// Whether a command removing worktrees is skipped by `--git-dry-run`; it is logged
+/// instead
+#[must_use]
+pub fn skipped_by_dry_run(command: &std::process::Command) -> bool {
+    if !dry_run() {
+        return false;
+    }
+    print(&format!("git dry-run: would run {}", describe(command)));
+    true
+}
+
+/// Log a finished command with `--trace-git`
+fn trace(
+    command: &std::process::Command,
+    elapsed: Duration,
+    status: Result<ExitStatus, impl Display>,
+) {
+    if !TRACE.load(Ordering::Relaxed) {
+        return;
+    }
+    let outcome = match status {
+        Ok(status) => match status.code() {
+            Some(code) => format!("exit {code}"),
+            None => "killed by a signal".to_string(),
+        },
+        Err(e) => format!("failed to start: {e}"),
+    };
+    print(&format!(
+        "git: {}  {}ms, {outcome}",
+        describe(command),
+        elapsed.as_millis()
+    ));
+}
+
+/// The command line of a command as a shell would take it, and its directory
+fn describe(command: &std::process::Command) -> String {
+    let args: Vec<String> = std::iter::once(command.get_program())
+        .chain(command.get_args())
+        .map(|arg| arg.to_string_lossy().into_owned())
+        .collect();
+    let line = shlex::try_join(args.iter().map(String::as_str)).unwrap_or_else(|_| args.join(" "));
+    match command.get_current_dir() {
+        Some(dir) => format!("{line}  (in {})", dir.display()),
+        None => line,
+    }
+}
+
+/// Print a line of the log, or hold it for the split view's status area
+fn print(line: &str) {
+    if !hold(line) {
+        eprintln!("{}", style(line).dim());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_describe() {
+        let mut command = std::process::Command::new("git");
+        command
+            .args(["commit", "-m", "Fix the lexer"])
+            .current_dir("/tmp/repo");
+        assert_eq!(
+            describe(&command),
+            "git commit -m 'Fix the lexer'  (in /tmp/repo)"
+        );
+    }
+}
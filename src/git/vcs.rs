@@ -0,0 +1,105 @@
+use std::path::Path;
+
+use tokio::process::Command;
+
+use crate::error::{Error, Result};
+
+/// Version control system managing the working copy of a repository
+///
+/// Agents always work in git worktrees; this decides what else has to happen around
+/// them so that another VCS sharing the repository keeps track of the working copy.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Vcs {
+    /// Plain git
+    Git,
+    /// Jujutsu colocated with git (`.jj` next to `.git`)
+    Jujutsu,
+}
+
+impl Vcs {
+    /// Detect the VCS of the repository at `repo_root`
+    ///
+    /// A colocated jj repository is only treated as such when `jj` is installed;
+    /// otherwise it is handled like a plain git repository.
+    pub async fn detect(repo_root: &Path) -> Self {
+        if repo_root.join(".jj").is_dir() && is_jj_available().await {
+            Self::Jujutsu
+        } else {
+            Self::Git
+        }
+    }
+
+    /// Display name of the VCS
+    #[must_use]
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Git => "git",
+            Self::Jujutsu => "jj",
+        }
+    }
+
+    /// Whether local changes can be put aside with `git stash`
+    ///
+    /// jj does not track the git index or stash, so stashing would leave its
+    /// working-copy commit out of sync; its operation log is used instead.
+    #[must_use]
+    pub fn supports_stash(self) -> bool {
+        self == Self::Git
+    }
+
+    /// Record the current working copy in the VCS
+    ///
+    /// For jj this snapshots the working-copy commit, so that the state before and
+    /// after parari touches the files are separate operations that `jj op restore`
+    /// can return to. Git needs nothing.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the snapshot command fails.
+    pub async fn snapshot(self, repo_root: &Path) -> Result<()> {
+        match self {
+            Self::Git => Ok(()),
+            Self::Jujutsu => {
+                // Every jj command snapshots the working copy before it runs
+                let output = Command::new("jj")
+                    .args(["--no-pager", "status"])
+                    .current_dir(repo_root)
+                    .output()
+                    .await?;
+
+                if !output.status.success() {
+                    return Err(Error::GitCommand {
+                        message: format!(
+                            "jj snapshot failed: {}",
+                            String::from_utf8_lossy(&output.stderr).trim()
+                        ),
+                    });
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+async fn is_jj_available() -> bool {
+    Command::new("jj")
+        .arg("--version")
+        .output()
+        .await
+        .is_ok_and(|output| output.status.success())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn test_detect_plain_git() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let dir = tempfile::tempdir()?;
+        assert_eq!(Vcs::detect(dir.path()).await, Vcs::Git);
+        assert!(Vcs::Git.supports_stash());
+        assert!(!Vcs::Jujutsu.supports_stash());
+        Vcs::Git.snapshot(dir.path()).await?;
+        Ok(())
+    }
+}
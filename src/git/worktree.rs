@@ -2,6 +2,9 @@ use std::path::{Path, PathBuf};
 
 use tokio::process::Command;
 
+use super::retention::{mark_owned, owner_file};
+use super::status::get_status;
+use super::trace::{skipped_by_dry_run, traced};
 use crate::config;
 use crate::error::{Error, Result};
 
@@ -18,12 +21,13 @@ pub struct WorktreeInfo {
 
 /// Check if a directory is a git repository
 pub async fn is_git_repository(path: &Path) -> bool {
-    Command::new("git")
-        .args(["rev-parse", "--is-inside-work-tree"])
-        .current_dir(path)
-        .output()
-        .await
-        .is_ok_and(|output| output.status.success())
+    traced(
+        Command::new("git")
+            .args(["rev-parse", "--is-inside-work-tree"])
+            .current_dir(path),
+    )
+    .await
+    .is_ok_and(|output| output.status.success())
 }
 
 /// Get the root of the git repository
@@ -32,11 +36,12 @@ pub async fn is_git_repository(path: &Path) -> bool {
 ///
 /// Returns an error if the path is not inside a git repository or the git command fails.
 pub async fn get_repo_root(path: &Path) -> Result<PathBuf> {
-    let output = Command::new("git")
-        .args(["rev-parse", "--show-toplevel"])
-        .current_dir(path)
-        .output()
-        .await?;
+    let output = traced(
+        Command::new("git")
+            .args(["rev-parse", "--show-toplevel"])
+            .current_dir(path),
+    )
+    .await?;
 
     if !output.status.success() {
         return Err(Error::NotGitRepository {
@@ -48,15 +53,102 @@ pub async fn get_repo_root(path: &Path) -> Result<PathBuf> {
     Ok(PathBuf::from(root))
 }
 
+/// Get the directory of the main repository that `path` belongs to
+///
+/// For a linked worktree (created by `git worktree add`) this is the repository the
+/// worktree was added to; otherwise it is the same as [`get_repo_root`]. Worktree
+/// commands must run here so that new worktrees are not nested in a linked one.
+///
+/// # Errors
+///
+/// Returns an error if the path is not inside a git repository or the git command fails.
+pub async fn get_main_repo_root(path: &Path) -> Result<PathBuf> {
+    let output = traced(
+        Command::new("git")
+            .args(["rev-parse", "--path-format=absolute", "--git-common-dir"])
+            .current_dir(path),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::NotGitRepository {
+            path: path.to_path_buf(),
+        });
+    }
+
+    let common_dir = PathBuf::from(String::from_utf8_lossy(&output.stdout).trim());
+    Ok(repo_dir_from_common_dir(&common_dir))
+}
+
+/// The repository directory owning a git common directory
+///
+/// The common directory is `<repo>/.git` for normal repositories and the repository
+/// itself for bare ones.
+fn repo_dir_from_common_dir(common_dir: &Path) -> PathBuf {
+    match common_dir.parent() {
+        Some(parent) if common_dir.file_name().is_some_and(|name| name == ".git") => {
+            parent.to_path_buf()
+        }
+        _ => common_dir.to_path_buf(),
+    }
+}
+
+/// Get the commit hash of HEAD
+///
+/// # Errors
+///
+/// Returns an error if the git command fails (e.g. the repository has no commits yet).
+pub async fn get_head_commit(path: &Path) -> Result<String> {
+    let output = traced(
+        Command::new("git")
+            .args(["rev-parse", "HEAD"])
+            .current_dir(path),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+
+    Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+}
+
+/// Check whether a commit exists in the repository at `path`
+pub async fn has_commit(path: &Path, commit: &str) -> bool {
+    traced(
+        Command::new("git")
+            .args(["cat-file", "-e", &format!("{commit}^{{commit}}")])
+            .current_dir(path),
+    )
+    .await
+    .is_ok_and(|output| output.status.success())
+}
+
 /// Create a worktree for the given executor
 ///
+/// The worktree is added to the repository at `repo_path` and checks out `base`, or
+/// the HEAD of `source_path` without one; the two paths differ when parari runs
+/// inside a linked worktree.
 /// Returns the path to the created worktree.
-/// This also copies uncommitted changes from the source repository to the worktree.
+/// This also copies uncommitted changes from the source checkout to the worktree.
+///
+/// A non-empty `scope` limits the worktree to those directories (plus top-level files)
+/// with a cone-mode sparse checkout, which keeps setup fast in large repositories.
+/// Uncommitted files matching a pattern of `secrets` are not copied.
 ///
 /// # Errors
 ///
 /// Returns an error if the git command fails or file operations fail.
-pub async fn create_worktree(repo_path: &Path, executor_name: &str) -> Result<WorktreeInfo> {
+pub async fn create_worktree(
+    repo_path: &Path,
+    source_path: &Path,
+    executor_name: &str,
+    scope: &[String],
+    secrets: &[String],
+    base: Option<&str>,
+) -> Result<WorktreeInfo> {
     let timestamp = chrono::Utc::now().format("%Y%m%d%H%M%S%3f").to_string();
     let worktree_name = format!("{timestamp}-{executor_name}");
     let worktree_path = config::worktrees_dir().join(&worktree_name);
@@ -68,12 +160,24 @@ pub async fn create_worktree(repo_path: &Path, executor_name: &str) -> Result<Wo
         message: "worktree path contains invalid UTF-8".to_string(),
     })?;
 
-    // Create the worktree
-    let output = Command::new("git")
-        .args(["worktree", "add", "--detach", worktree_path_str])
-        .current_dir(repo_path)
-        .output()
-        .await?;
+    // Create the worktree at the base, or the source checkout's HEAD if it has one
+    let head = match base {
+        Some(base) => Some(base.to_string()),
+        None => get_head_commit(source_path).await.ok(),
+    };
+    let output = traced(
+        Command::new("git")
+            .args(["worktree", "add", "--detach"])
+            .args(if scope.is_empty() {
+                None
+            } else {
+                Some("--no-checkout")
+            })
+            .arg(worktree_path_str)
+            .args(head.as_deref())
+            .current_dir(repo_path),
+    )
+    .await?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -82,8 +186,15 @@ pub async fn create_worktree(repo_path: &Path, executor_name: &str) -> Result<Wo
         });
     }
 
-    // Copy uncommitted changes from source repository to worktree
-    copy_uncommitted_changes(repo_path, &worktree_path).await?;
+    // Keep the worktree from being pruned by other parari processes while this one runs
+    mark_owned(&worktree_path).await?;
+
+    if !scope.is_empty() {
+        sparse_checkout(&worktree_path, scope).await?;
+    }
+
+    // Copy uncommitted changes from source checkout to worktree
+    copy_uncommitted_changes(source_path, &worktree_path, scope, secrets).await?;
 
     Ok(WorktreeInfo {
         path: worktree_path,
@@ -92,46 +203,101 @@ pub async fn create_worktree(repo_path: &Path, executor_name: &str) -> Result<Wo
     })
 }
 
-/// Copy uncommitted changes from source repository to worktree
-async fn copy_uncommitted_changes(source: &Path, worktree: &Path) -> Result<()> {
-    // Get list of changed files (both staged and unstaged, including untracked)
-    let output = Command::new("git")
-        .args(["status", "--porcelain"])
-        .current_dir(source)
-        .output()
-        .await?;
+/// Check out only the `scope` directories in a worktree added with `--no-checkout`
+async fn sparse_checkout(worktree: &Path, scope: &[String]) -> Result<()> {
+    let output = traced(
+        Command::new("git")
+            .args(["sparse-checkout", "set", "--cone", "--"])
+            .args(scope)
+            .current_dir(worktree),
+    )
+    .await?;
+
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
 
-    let status = String::from_utf8_lossy(&output.stdout);
+    let output = traced(
+        Command::new("git")
+            .args(["checkout", "--quiet"])
+            .current_dir(worktree),
+    )
+    .await?;
 
-    for line in status.lines() {
-        if line.len() < 3 {
+    if !output.status.success() {
+        return Err(Error::GitCommand {
+            message: String::from_utf8_lossy(&output.stderr).to_string(),
+        });
+    }
+
+    Ok(())
+}
+
+/// Whether a repository-relative path is checked out with the `scope` directories
+///
+/// Like a cone-mode sparse checkout, this covers everything inside the directories and
+/// the files directly inside the root or a parent of one of them. Paths ending with `/`
+/// are directories. An empty scope contains everything.
+fn in_scope(path: &str, scope: &[String]) -> bool {
+    let file_parent =
+        (!path.ends_with('/')).then(|| path.rsplit_once('/').map_or("", |(parent, _)| parent));
+    scope.is_empty()
+        || scope.iter().any(|dir| {
+            let dir = dir.trim_end_matches('/');
+            path == dir
+                || path
+                    .strip_prefix(dir)
+                    .is_some_and(|rest| rest.starts_with('/'))
+                || file_parent.is_some_and(|parent| {
+                    parent.is_empty()
+                        || dir
+                            .strip_prefix(parent)
+                            .is_some_and(|rest| rest.starts_with('/'))
+                })
+        })
+}
+
+/// Copy uncommitted changes from source repository to worktree
+///
+/// Changes outside a non-empty `scope` and files matching a pattern of `secrets` are
+/// not copied.
+async fn copy_uncommitted_changes(
+    source: &Path,
+    worktree: &Path,
+    scope: &[String],
+    secrets: &[String],
+) -> Result<()> {
+    // Get list of changed files (both staged and unstaged, including untracked)
+    for entry in get_status(source).await? {
+        if !in_scope(&entry.path, scope) {
             continue;
         }
 
-        let status_code = &line[0..2];
-        let file_path = &line[3..];
-
-        // Skip deleted files
-        if status_code == "D " || status_code == " D" || status_code == "DD" {
-            // For deleted files, also delete in worktree
-            let dst_path = worktree.join(file_path);
+        // For deleted files, also delete in worktree; a renamed file is gone from its
+        // old path as well
+        let removed = if entry.is_deleted() {
+            Some(entry.path.as_str())
+        } else if entry.code.starts_with('R') {
+            entry.original.as_deref()
+        } else {
+            None
+        };
+        if let Some(removed) = removed {
+            let dst_path = worktree.join(removed);
             if dst_path.exists() {
                 let _ = tokio::fs::remove_file(&dst_path).await;
             }
+        }
+        if entry.is_deleted() {
             continue;
         }
 
-        // Handle renamed files (R status shows "old -> new")
-        let actual_path = if status_code.starts_with('R') {
-            // Format: "R  old_name -> new_name"
-            if let Some(arrow_pos) = file_path.find(" -> ") {
-                &file_path[arrow_pos + 4..]
-            } else {
-                file_path
-            }
-        } else {
-            file_path
-        };
+        let actual_path = entry.path.as_str();
+        if is_secret(actual_path, secrets) {
+            continue;
+        }
 
         let src_path = source.join(actual_path);
         let dst_path = worktree.join(actual_path);
@@ -148,17 +314,33 @@ async fn copy_uncommitted_changes(source: &Path, worktree: &Path) -> Result<()>
             }
             tokio::fs::copy(&src_path, &dst_path).await?;
         } else if src_path.is_dir() {
-            // Copy directory recursively
-            copy_dir_to_worktree(&src_path, &dst_path).await?;
+            // Copy directory recursively, ignored files included
+            let prefix = actual_path.trim_end_matches('/');
+            copy_dir_to_worktree(&src_path, &dst_path, prefix, secrets).await?;
         }
     }
 
     Ok(())
 }
 
+/// Whether a repository-relative path matches a pattern of `secrets`
+fn is_secret(path: &str, secrets: &[String]) -> bool {
+    secrets
+        .iter()
+        .any(|pattern| config::matches_pattern(pattern, path))
+}
+
 /// Copy a directory recursively (used for copying uncommitted directories)
+///
+/// `prefix` is the directory's path relative to the repository root; files matching
+/// a pattern of `secrets` below it are skipped.
 #[async_recursion::async_recursion]
-async fn copy_dir_to_worktree(src: &Path, dst: &Path) -> Result<()> {
+async fn copy_dir_to_worktree(
+    src: &Path,
+    dst: &Path,
+    prefix: &str,
+    secrets: &[String],
+) -> Result<()> {
     tokio::fs::create_dir_all(dst).await?;
 
     let mut entries = tokio::fs::read_dir(src).await?;
@@ -174,12 +356,16 @@ async fn copy_dir_to_worktree(src: &Path, dst: &Path) -> Result<()> {
 
         let src_path = entry.path();
         let dst_path = dst.join(&file_name);
+        let path = format!("{prefix}/{}", file_name.to_string_lossy());
 
         let file_type = entry.file_type().await?;
 
         if file_type.is_dir() {
-            copy_dir_to_worktree(&src_path, &dst_path).await?;
+            copy_dir_to_worktree(&src_path, &dst_path, &path, secrets).await?;
         } else if file_type.is_file() {
+            if is_secret(&path, secrets) {
+                continue;
+            }
             // Remove target file first to avoid "Text file busy" error (ETXTBSY)
             if dst_path.exists() {
                 tokio::fs::remove_file(&dst_path).await?;
@@ -191,7 +377,59 @@ async fn copy_dir_to_worktree(src: &Path, dst: &Path) -> Result<()> {
     Ok(())
 }
 
-/// Remove a worktree
+/// Throw away an agent's changes, returning a worktree to the state it was created in
+///
+/// Tracked files are reset and new files removed, but ignored files such as installed
+/// dependencies are kept. The source checkout's uncommitted changes are copied again,
+/// except files matching a pattern of `secrets`.
+///
+/// # Errors
+///
+/// Returns an error if a git command or file operation fails.
+pub async fn reset_worktree(
+    source_path: &Path,
+    worktree_path: &Path,
+    scope: &[String],
+    secrets: &[String],
+) -> Result<()> {
+    for args in [
+        &["reset", "--hard", "--quiet"][..],
+        &["clean", "-d", "--force", "--quiet"],
+    ] {
+        let output = traced(Command::new("git").args(args).current_dir(worktree_path)).await?;
+
+        if !output.status.success() {
+            return Err(Error::GitCommand {
+                message: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+    }
+
+    copy_uncommitted_changes(source_path, worktree_path, scope, secrets).await
+}
+
+/// Check out a commit in a worktree, throwing away its changes and new files
+///
+/// # Errors
+///
+/// Returns an error if a git command fails, e.g. when the commit does not exist.
+pub async fn checkout_commit(worktree_path: &Path, commit: &str) -> Result<()> {
+    for args in [
+        ["checkout", "--detach", "--force", "--quiet", commit].as_slice(),
+        ["clean", "-d", "--force", "--quiet"].as_slice(),
+    ] {
+        let output = traced(Command::new("git").args(args).current_dir(worktree_path)).await?;
+
+        if !output.status.success() {
+            return Err(Error::GitCommand {
+                message: String::from_utf8_lossy(&output.stderr).to_string(),
+            });
+        }
+    }
+    Ok(())
+}
+
+/// Remove a worktree; with `--git-dry-run` the removal is only logged
 ///
 /// # Errors
 ///
@@ -203,11 +441,14 @@ async fn copy_dir_to_worktree(src: &Path, dst: &Path) -> Result<()> {
 pub async fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<()> {
     // First, try to remove with --force
     let worktree_path_str = worktree_path.to_str().unwrap_or("");
-    let output = Command::new("git")
+    let mut remove = Command::new("git");
+    remove
         .args(["worktree", "remove", "--force", worktree_path_str])
-        .current_dir(repo_path)
-        .output()
-        .await?;
+        .current_dir(repo_path);
+    if skipped_by_dry_run(remove.as_std()) {
+        return Ok(());
+    }
+    let output = traced(&mut remove).await?;
 
     if !output.status.success() {
         // If git worktree remove fails, try to manually remove the directory
@@ -217,13 +458,16 @@ pub async fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<(
         }
 
         // Prune worktrees
-        let _ = Command::new("git")
-            .args(["worktree", "prune"])
-            .current_dir(repo_path)
-            .output()
-            .await;
+        let _ = traced(
+            Command::new("git")
+                .args(["worktree", "prune"])
+                .current_dir(repo_path),
+        )
+        .await;
     }
 
+    let _ = tokio::fs::remove_file(owner_file(worktree_path)).await;
+
     Ok(())
 }
 
@@ -233,11 +477,12 @@ pub async fn remove_worktree(repo_path: &Path, worktree_path: &Path) -> Result<(
 ///
 /// Returns an error if the git command fails.
 pub async fn list_worktrees(repo_path: &Path) -> Result<Vec<PathBuf>> {
-    let output = Command::new("git")
-        .args(["worktree", "list", "--porcelain"])
-        .current_dir(repo_path)
-        .output()
-        .await?;
+    let output = traced(
+        Command::new("git")
+            .args(["worktree", "list", "--porcelain"])
+            .current_dir(repo_path),
+    )
+    .await?;
 
     if !output.status.success() {
         let stderr = String::from_utf8_lossy(&output.stderr);
@@ -256,42 +501,6 @@ pub async fn list_worktrees(repo_path: &Path) -> Result<Vec<PathBuf>> {
     Ok(worktrees)
 }
 
-/// Remove old worktrees if there are more than `MAX_WORKTREES`
-///
-/// # Errors
-///
-/// Returns an error if file system operations fail.
-pub async fn cleanup_old_worktrees(repo_path: &Path) -> Result<()> {
-    let worktrees_dir = config::worktrees_dir();
-
-    if !worktrees_dir.exists() {
-        return Ok(());
-    }
-
-    let mut entries = Vec::new();
-    let mut read_dir = tokio::fs::read_dir(&worktrees_dir).await?;
-
-    while let Some(entry) = read_dir.next_entry().await? {
-        entries.push(entry);
-    }
-
-    // Sort by name (which includes timestamp) - oldest first
-    entries.sort_by_key(tokio::fs::DirEntry::file_name);
-
-    // Remove oldest if we exceed MAX_WORKTREES
-    while entries.len() > config::MAX_WORKTREES {
-        if let Some(entry) = entries.first() {
-            let path = entry.path();
-            let _ = remove_worktree(repo_path, &path).await;
-            entries.remove(0);
-        } else {
-            break;
-        }
-    }
-
-    Ok(())
-}
-
 /// Remove all worktrees in the parari worktrees directory
 ///
 /// # Errors
@@ -307,16 +516,19 @@ pub async fn cleanup_all_worktrees(repo_path: &Path) -> Result<()> {
     let mut entries = tokio::fs::read_dir(&worktrees_dir).await?;
 
     while let Some(entry) = entries.next_entry().await? {
+        if !entry.file_type().await?.is_dir() {
+            continue;
+        }
         let path = entry.path();
         let _ = remove_worktree(repo_path, &path).await;
     }
 
     // Also prune any orphaned worktrees
-    let _ = Command::new("git")
-        .args(["worktree", "prune"])
-        .current_dir(repo_path)
-        .output()
-        .await;
+    let mut prune = Command::new("git");
+    prune.args(["worktree", "prune"]).current_dir(repo_path);
+    if !skipped_by_dry_run(prune.as_std()) {
+        let _ = traced(&mut prune).await;
+    }
 
     Ok(())
 }
@@ -324,6 +536,7 @@ pub async fn cleanup_all_worktrees(repo_path: &Path) -> Result<()> {
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::testing::TestRepo;
     use std::env;
 
     #[tokio::test]
@@ -334,6 +547,122 @@ mod tests {
         Ok(())
     }
 
+    #[tokio::test]
+    async fn test_get_head_commit() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cwd = env::current_dir()?;
+        let commit = get_head_commit(&cwd).await?;
+        assert_eq!(commit.len(), 40);
+        assert!(commit.chars().all(|c| c.is_ascii_hexdigit()));
+        Ok(())
+    }
+
+    #[test]
+    fn test_in_scope() {
+        let scope = vec!["crates/core/".to_string(), "docs".to_string()];
+        assert!(in_scope("crates/core/src/lib.rs", &scope));
+        assert!(in_scope("docs", &scope));
+        assert!(!in_scope("crates/core-extra/lib.rs", &scope));
+        assert!(in_scope("README.md", &[]));
+
+        // Cone mode checks out the files of the root and of the parents of the scope
+        assert!(in_scope("README.md", &scope));
+        assert!(in_scope("crates/Cargo.toml", &scope));
+        assert!(!in_scope("crates/other/Cargo.toml", &scope));
+        assert!(!in_scope("vendor/", &scope));
+    }
+
+    #[test]
+    fn test_repo_dir_from_common_dir() {
+        assert_eq!(
+            repo_dir_from_common_dir(Path::new("/src/project/.git")),
+            PathBuf::from("/src/project")
+        );
+        assert_eq!(
+            repo_dir_from_common_dir(Path::new("/srv/project.git")),
+            PathBuf::from("/srv/project.git")
+        );
+    }
+
+    #[tokio::test]
+    async fn test_main_repo_root_of_linked_worktree()
+    -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_repo = TestRepo::new()?;
+        let repo = test_repo.path().to_path_buf();
+        let dir = tempfile::tempdir()?;
+        let linked = dir.path().canonicalize()?.join("linked");
+        test_repo.git(&[
+            "worktree",
+            "add",
+            "-q",
+            "--detach",
+            &linked.to_string_lossy(),
+        ])?;
+
+        assert_eq!(get_main_repo_root(&repo).await?, repo);
+        assert_eq!(get_main_repo_root(&linked).await?, repo);
+        assert_eq!(get_repo_root(&linked).await?, linked);
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_reset_worktree() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let test_repo = TestRepo::builder()
+            .with_file("tracked.txt", "original\n")
+            .with_file(".gitignore", "deps/\n")
+            .build()?;
+        let repo = test_repo.path().to_path_buf();
+        let dir = tempfile::tempdir()?;
+        let linked = dir.path().canonicalize()?.join("linked");
+        test_repo.git(&[
+            "worktree",
+            "add",
+            "-q",
+            "--detach",
+            &linked.to_string_lossy(),
+        ])?;
+        std::fs::write(repo.join("local.txt"), "uncommitted\n")?;
+        std::fs::create_dir_all(repo.join("keys"))?;
+        std::fs::write(repo.join("keys/deploy.pem"), "private\n")?;
+        std::fs::write(repo.join(".env"), "TOKEN=secret\n")?;
+        // An untracked directory with an ignored secret in it
+        std::fs::create_dir_all(repo.join("newdir"))?;
+        std::fs::write(repo.join("newdir/.gitignore"), ".env\n")?;
+        std::fs::write(repo.join("newdir/notes.txt"), "notes\n")?;
+        std::fs::write(repo.join("newdir/.env"), "TOKEN=secret\n")?;
+
+        std::fs::write(linked.join("tracked.txt"), "changed by agent\n")?;
+        std::fs::write(linked.join("new.txt"), "new\n")?;
+        std::fs::create_dir_all(linked.join("deps"))?;
+        std::fs::write(linked.join("deps/lib.txt"), "installed\n")?;
+
+        let secrets = [".env".to_string(), "*.pem".to_string()];
+        reset_worktree(&repo, &linked, &[], &secrets).await?;
+        assert_eq!(
+            std::fs::read_to_string(linked.join("tracked.txt"))?,
+            "original\n"
+        );
+        assert!(!linked.join("new.txt").exists());
+        assert!(linked.join("deps/lib.txt").exists());
+        assert_eq!(
+            std::fs::read_to_string(linked.join("local.txt"))?,
+            "uncommitted\n"
+        );
+        assert!(!linked.join(".env").exists());
+        assert!(!linked.join("keys/deploy.pem").exists());
+        assert!(linked.join("newdir/notes.txt").exists());
+        assert!(!linked.join("newdir/.env").exists());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_has_commit() -> std::result::Result<(), Box<dyn std::error::Error>> {
+        let cwd = env::current_dir()?;
+        let commit = get_head_commit(&cwd).await?;
+        assert!(has_commit(&cwd, &commit).await);
+        assert!(!has_commit(&cwd, &"0".repeat(40)).await);
+        Ok(())
+    }
+
     #[tokio::test]
     async fn test_is_not_git_repository() {
         let temp_dir = env::temp_dir();
@@ -0,0 +1,8 @@
+//! Integrations with the services a repository is hosted on
+//!
+//! The forges are driven through their command-line tools (`gh`, `glab`, `tea`),
+//! which keep the user's credentials, so parari stores no tokens.
+
+pub mod forge;
+
+pub use forge::*;
@@ -0,0 +1,656 @@
+use std::path::Path;
+use std::process::Output;
+use std::time::{Duration, Instant};
+
+use async_trait::async_trait;
+use clap::ValueEnum;
+use serde::{Deserialize, Serialize};
+use tokio::process::Command;
+
+use crate::domain::RunInfo;
+use crate::error::{Error, Result};
+use crate::git;
+
+/// A pull request (a merge request on GitLab) opened for an applied result
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PullRequest {
+    /// Branch with the result, already pushed to the remote
+    pub branch: String,
+    /// Title, the first line of the prompt
+    pub title: String,
+    /// Description naming the agent and quoting the prompt
+    pub body: String,
+}
+
+impl PullRequest {
+    /// The pull request for the result of `agent` in a run, pushed to `branch`
+    #[must_use]
+    pub fn for_result(run: &RunInfo, agent: &str, branch: impl Into<String>) -> Self {
+        let title = run.title();
+        let prompt: String = run
+            .prompt
+            .trim()
+            .lines()
+            .map(|line| {
+                if line.trim().is_empty() {
+                    ">\n".to_string()
+                } else {
+                    format!("> {line}\n")
+                }
+            })
+            .collect();
+        let closes = run
+            .issue
+            .as_ref()
+            .map(|issue| format!("\nCloses {issue}\n"))
+            .unwrap_or_default();
+        let body = format!(
+            "Applied the result of {agent}, selected with parari.\n\nPrompt:\n\n{prompt}{closes}"
+        );
+        Self {
+            branch: branch.into(),
+            title: title.to_string(),
+            body,
+        }
+    }
+
+    /// The commit message of the result
+    #[must_use]
+    pub fn commit_message(&self) -> String {
+        format!("{}\n\n{}", self.title, self.body)
+    }
+}
+
+/// How often the CI status of a pull request is checked by [`wait_for_checks`]
+const CHECKS_POLL_INTERVAL: Duration = Duration::from_secs(30);
+
+/// How long a pull request may show no checks before it counts as having none;
+/// checks are registered a little after the branch is pushed
+const CHECKS_GRACE: Duration = Duration::from_mins(2);
+
+/// Combined state of the CI checks of a pull request
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum CiStatus {
+    /// Some checks are still queued or running
+    Pending,
+    /// Every check passed or was skipped
+    Passed,
+    /// A check failed or was cancelled
+    Failed,
+    /// No checks are configured for the branch
+    NoChecks,
+}
+
+/// An issue whose text is used as the prompt with `--issue`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Issue {
+    /// Web address, recorded in the history and linked from the pull request
+    pub url: String,
+    /// Title of the issue
+    pub title: String,
+    /// Description of the issue, possibly empty
+    pub body: String,
+}
+
+impl Issue {
+    /// The prompt asking the agents to resolve the issue, followed by `extra` instructions
+    #[must_use]
+    pub fn prompt(&self, extra: Option<&str>) -> String {
+        let mut prompt = self.title.trim().to_string();
+        for part in [self.body.trim(), extra.unwrap_or_default().trim()] {
+            if !part.is_empty() {
+                prompt.push_str("\n\n");
+                prompt.push_str(part);
+            }
+        }
+        prompt
+    }
+}
+
+/// An issue as printed by `gh issue view --json url,title,body`
+#[derive(Deserialize)]
+struct GhIssue {
+    url: String,
+    title: String,
+    #[serde(default)]
+    body: String,
+}
+
+/// An issue as printed by `glab issue view --output json`
+#[derive(Deserialize)]
+struct GlabIssue {
+    web_url: String,
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+}
+
+/// A check as printed by `gh pr checks --json bucket`
+#[derive(Deserialize)]
+struct GhCheck {
+    bucket: String,
+}
+
+/// A pipeline as printed by `glab ci get --output json`
+#[derive(Deserialize)]
+struct GlabPipeline {
+    status: String,
+}
+
+/// A code forge whose command-line tool opens pull requests
+#[async_trait]
+pub trait Forge: Send + Sync {
+    /// Name shown to the user, e.g. "GitHub"
+    fn name(&self) -> &'static str;
+
+    /// The command-line tool used, e.g. "gh"
+    fn program(&self) -> &'static str;
+
+    /// Arguments to `program` that open `pr` from its pushed branch
+    fn create_args(&self, pr: &PullRequest) -> Vec<String>;
+
+    /// Open the pull request in `repo`, returning its URL
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the tool is not installed or fails, e.g. when not logged in.
+    async fn create_pull_request(&self, repo: &Path, pr: &PullRequest) -> Result<String> {
+        let output = run_forge_tool(self, repo, &self.create_args(pr)).await?;
+        Ok(url_in(&output).unwrap_or(output.trim()).to_string())
+    }
+
+    /// Title and description of the issue with the given number or URL
+    ///
+    /// Not every forge's tool prints issues in a readable form; the default
+    /// implementation fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if issues are not supported, or the issue cannot be fetched.
+    async fn fetch_issue(&self, _repo: &Path, _reference: &str) -> Result<Issue> {
+        Err(Error::Forge {
+            forge: self.name().to_string(),
+            message: format!("`{}` cannot fetch issues", self.program()),
+        })
+    }
+
+    /// Whether [`Forge::ci_status`] is supported, so `--watch-ci` can be used
+    fn reports_ci(&self) -> bool {
+        false
+    }
+
+    /// Current state of the CI checks of the pull request for `branch`
+    ///
+    /// Not every forge's tool reports CI status; the default implementation fails.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the status is not supported or cannot be read.
+    async fn ci_status(&self, _repo: &Path, _branch: &str) -> Result<CiStatus> {
+        Err(Error::Forge {
+            forge: self.name().to_string(),
+            message: format!("`{}` does not report CI status", self.program()),
+        })
+    }
+}
+
+/// GitHub, through `gh`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitHub;
+
+#[async_trait]
+impl Forge for GitHub {
+    fn name(&self) -> &'static str {
+        "GitHub"
+    }
+
+    fn program(&self) -> &'static str {
+        "gh"
+    }
+
+    fn create_args(&self, pr: &PullRequest) -> Vec<String> {
+        to_strings(&[
+            "pr", "create", "--head", &pr.branch, "--title", &pr.title, "--body", &pr.body,
+        ])
+    }
+
+    async fn fetch_issue(&self, repo: &Path, reference: &str) -> Result<Issue> {
+        let args = to_strings(&["issue", "view", reference, "--json", "url,title,body"]);
+        let output = run_forge_tool(self, repo, &args).await?;
+        let issue: GhIssue = parse_json(self, &output)?;
+        Ok(Issue {
+            url: issue.url,
+            title: issue.title,
+            body: issue.body,
+        })
+    }
+
+    fn reports_ci(&self) -> bool {
+        true
+    }
+
+    async fn ci_status(&self, repo: &Path, branch: &str) -> Result<CiStatus> {
+        // `gh pr checks` exits with 1 for failed and 8 for pending checks, still printing them
+        let args = to_strings(&["pr", "checks", branch, "--json", "bucket"]);
+        let output = forge_tool_output(self, repo, &args).await?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if stderr.contains("no checks reported") {
+            return Ok(CiStatus::NoChecks);
+        }
+        let checks: Vec<GhCheck> =
+            serde_json::from_slice(&output.stdout).map_err(|_| Error::Forge {
+                forge: self.name().to_string(),
+                message: stderr.trim().to_string(),
+            })?;
+        Ok(combine_checks(
+            checks.iter().map(|check| check.bucket.as_str()),
+        ))
+    }
+}
+
+/// GitLab, including self-hosted instances, through `glab`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct GitLab;
+
+#[async_trait]
+impl Forge for GitLab {
+    fn name(&self) -> &'static str {
+        "GitLab"
+    }
+
+    fn program(&self) -> &'static str {
+        "glab"
+    }
+
+    fn create_args(&self, pr: &PullRequest) -> Vec<String> {
+        to_strings(&[
+            "mr",
+            "create",
+            "--source-branch",
+            &pr.branch,
+            "--title",
+            &pr.title,
+            "--description",
+            &pr.body,
+            "--yes",
+        ])
+    }
+
+    async fn fetch_issue(&self, repo: &Path, reference: &str) -> Result<Issue> {
+        let args = to_strings(&["issue", "view", reference, "--output", "json"]);
+        let output = run_forge_tool(self, repo, &args).await?;
+        let issue: GlabIssue = parse_json(self, &output)?;
+        Ok(Issue {
+            url: issue.web_url,
+            title: issue.title,
+            body: issue.description.unwrap_or_default(),
+        })
+    }
+
+    fn reports_ci(&self) -> bool {
+        true
+    }
+
+    async fn ci_status(&self, repo: &Path, branch: &str) -> Result<CiStatus> {
+        let args = to_strings(&["ci", "get", "--branch", branch, "--output", "json"]);
+        let output = forge_tool_output(self, repo, &args).await?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        // The pipeline is created a little after the push
+        if stderr.to_lowercase().contains("no pipeline") {
+            return Ok(CiStatus::NoChecks);
+        }
+        let pipeline: GlabPipeline =
+            serde_json::from_slice(&output.stdout).map_err(|_| Error::Forge {
+                forge: self.name().to_string(),
+                message: stderr.trim().to_string(),
+            })?;
+        Ok(match pipeline.status.as_str() {
+            "success" | "skipped" => CiStatus::Passed,
+            "failed" | "canceled" => CiStatus::Failed,
+            _ => CiStatus::Pending,
+        })
+    }
+}
+
+/// Gitea and Forgejo, e.g. Codeberg, through `tea`
+#[derive(Debug, Clone, Copy, Default)]
+pub struct Gitea;
+
+#[async_trait]
+impl Forge for Gitea {
+    fn name(&self) -> &'static str {
+        "Gitea"
+    }
+
+    fn program(&self) -> &'static str {
+        "tea"
+    }
+
+    fn create_args(&self, pr: &PullRequest) -> Vec<String> {
+        to_strings(&[
+            "pulls",
+            "create",
+            "--head",
+            &pr.branch,
+            "--title",
+            &pr.title,
+            "--description",
+            &pr.body,
+        ])
+    }
+}
+
+/// The forges `--forge` selects
+#[derive(ValueEnum, Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ForgeKind {
+    /// GitHub, through `gh`
+    #[value(name = "github")]
+    GitHub,
+    /// GitLab, through `glab`
+    #[value(name = "gitlab")]
+    GitLab,
+    /// Gitea or Forgejo, through `tea`
+    #[value(name = "gitea")]
+    Gitea,
+}
+
+impl ForgeKind {
+    /// Guess the forge from the host of a remote URL, defaulting to GitHub
+    ///
+    /// Self-hosted instances are recognized when their host names the forge, e.g.
+    /// `gitlab.example.com`; others need `--forge`.
+    #[must_use]
+    pub fn from_remote_url(url: &str) -> Self {
+        let host = remote_host(url).to_lowercase();
+        if host.contains("gitlab") {
+            Self::GitLab
+        } else if host.contains("gitea") || host.contains("forgejo") || host == "codeberg.org" {
+            Self::Gitea
+        } else {
+            Self::GitHub
+        }
+    }
+
+    /// The forge of this kind
+    #[must_use]
+    pub fn forge(self) -> Box<dyn Forge> {
+        match self {
+            Self::GitHub => Box::new(GitHub),
+            Self::GitLab => Box::new(GitLab),
+            Self::Gitea => Box::new(Gitea),
+        }
+    }
+}
+
+/// The host of a remote URL, either `scheme://[user@]host[:port]/path` or scp-like
+/// `[user@]host:path`
+fn remote_host(url: &str) -> &str {
+    let (rest, scheme) = match url.split_once("://") {
+        Some((_, rest)) => (rest, true),
+        None => (url, false),
+    };
+    let authority = if scheme {
+        rest.split('/').next().unwrap_or_default()
+    } else {
+        rest.split(':').next().unwrap_or_default()
+    };
+    let host = authority.rsplit('@').next().unwrap_or_default();
+    host.split(':').next().unwrap_or_default()
+}
+
+/// The forge hosting the `origin` remote of `repo`
+///
+/// # Errors
+///
+/// Returns an error if the repository has no `origin` remote.
+pub async fn detect_forge(repo: &Path) -> Result<ForgeKind> {
+    Ok(ForgeKind::from_remote_url(&git::remote_url(repo).await?))
+}
+
+/// Fetch the issue with the given number or URL
+///
+/// The forge is `kind`, else guessed from the URL, else from the `origin` remote of `repo`.
+///
+/// # Errors
+///
+/// Returns an error if the forge cannot be determined or its tool cannot fetch the issue.
+pub async fn fetch_issue(reference: &str, kind: Option<ForgeKind>, repo: &Path) -> Result<Issue> {
+    let kind = match kind {
+        Some(kind) => kind,
+        None if reference.contains("://") => ForgeKind::from_remote_url(reference),
+        None => detect_forge(repo).await?,
+    };
+    kind.forge().fetch_issue(repo, reference).await
+}
+
+/// Wait until the CI checks of the pull request for `branch` finish, or `timeout` passes
+///
+/// Returns [`CiStatus::Pending`] on timeout, and [`CiStatus::NoChecks`] when no checks
+/// showed up for a while after the push.
+///
+/// # Errors
+///
+/// Returns an error if the forge does not report CI status or its tool fails.
+pub async fn wait_for_checks(
+    forge: &dyn Forge,
+    repo: &Path,
+    branch: &str,
+    timeout: Duration,
+) -> Result<CiStatus> {
+    let started = Instant::now();
+    loop {
+        let status = forge.ci_status(repo, branch).await?;
+        let waiting = match status {
+            CiStatus::Pending => true,
+            CiStatus::NoChecks => started.elapsed() < CHECKS_GRACE,
+            CiStatus::Passed | CiStatus::Failed => false,
+        };
+        if !waiting {
+            return Ok(status);
+        }
+        if started.elapsed() >= timeout {
+            return Ok(CiStatus::Pending);
+        }
+        tokio::time::sleep(CHECKS_POLL_INTERVAL).await;
+    }
+}
+
+/// Combined state of checks from their `gh` buckets (pass, fail, pending, skipping, cancel)
+fn combine_checks<'a>(buckets: impl IntoIterator<Item = &'a str>) -> CiStatus {
+    let buckets: Vec<&str> = buckets.into_iter().collect();
+    if buckets.is_empty() {
+        CiStatus::NoChecks
+    } else if buckets
+        .iter()
+        .any(|bucket| matches!(*bucket, "fail" | "cancel"))
+    {
+        CiStatus::Failed
+    } else if buckets.contains(&"pending") {
+        CiStatus::Pending
+    } else {
+        CiStatus::Passed
+    }
+}
+
+/// Run the forge's tool in `repo`, whatever its exit status
+///
+/// # Errors
+///
+/// Returns an error if the tool is not installed or cannot be started.
+async fn forge_tool_output<F: Forge + ?Sized>(
+    forge: &F,
+    repo: &Path,
+    args: &[String],
+) -> Result<Output> {
+    Command::new(forge.program())
+        .args(args)
+        .current_dir(repo)
+        .output()
+        .await
+        .map_err(|e| Error::Forge {
+            forge: forge.name().to_string(),
+            message: if e.kind() == std::io::ErrorKind::NotFound {
+                format!("`{}` was not found in PATH", forge.program())
+            } else {
+                e.to_string()
+            },
+        })
+}
+
+/// Run the forge's tool in `repo`, returning its stdout
+///
+/// # Errors
+///
+/// Returns an error if the tool is not installed or exits with a non-zero status.
+pub async fn run_forge_tool<F: Forge + ?Sized>(
+    forge: &F,
+    repo: &Path,
+    args: &[String],
+) -> Result<String> {
+    let output = forge_tool_output(forge, repo, args).await?;
+
+    if !output.status.success() {
+        return Err(Error::Forge {
+            forge: forge.name().to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    Ok(String::from_utf8_lossy(&output.stdout).to_string())
+}
+
+/// Parse the JSON printed by a forge tool
+fn parse_json<T: serde::de::DeserializeOwned, F: Forge + ?Sized>(
+    forge: &F,
+    output: &str,
+) -> Result<T> {
+    serde_json::from_str(output).map_err(|e| Error::Forge {
+        forge: forge.name().to_string(),
+        message: format!("unexpected output of `{}`: {e}", forge.program()),
+    })
+}
+
+/// The last URL printed by a forge tool, which is the created pull request
+fn url_in(output: &str) -> Option<&str> {
+    output
+        .split_whitespace()
+        .rev()
+        .find(|word| word.starts_with("https://") || word.starts_with("http://"))
+}
+
+fn to_strings(args: &[&str]) -> Vec<String> {
+    args.iter().map(ToString::to_string).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_forge_kind_from_remote_url() {
+        assert_eq!(
+            ForgeKind::from_remote_url("git@github.com:owner/project.git"),
+            ForgeKind::GitHub
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("https://gitlab.example.com/team/project.git"),
+            ForgeKind::GitLab
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("https://codeberg.org/owner/project"),
+            ForgeKind::Gitea
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("https://git.example.com/project"),
+            ForgeKind::GitHub
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("https://github.com/acme/gitlab-tools.git"),
+            ForgeKind::GitHub
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("git@github.com:acme/gitea-mirror.git"),
+            ForgeKind::GitHub
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("ssh://git@gitlab.example.com:2222/team/project.git"),
+            ForgeKind::GitLab
+        );
+        assert_eq!(
+            ForgeKind::from_remote_url("git@gitea.example.com:owner/project.git"),
+            ForgeKind::Gitea
+        );
+    }
+
+    #[test]
+    fn test_pull_request_for_result() {
+        let run = RunInfo::new("Fix the login bug\n\nIt fails on empty passwords.", "/repo");
+        let pr = PullRequest::for_result(&run, "claude", "parari/claude-1");
+        assert_eq!(pr.title, "Fix the login bug");
+        assert!(pr.body.contains("result of claude"));
+        assert!(
+            pr.body
+                .contains("> Fix the login bug\n>\n> It fails on empty passwords.\n")
+        );
+        assert!(pr.commit_message().starts_with("Fix the login bug\n\n"));
+
+        let run = RunInfo::new("\n  Fix the login bug  \n", "/repo");
+        let pr = PullRequest::for_result(&run, "claude", "parari/claude-1");
+        assert_eq!(pr.title, "Fix the login bug");
+
+        let args = GitLab.create_args(&pr);
+        assert_eq!(
+            &args[..4],
+            ["mr", "create", "--source-branch", "parari/claude-1"]
+        );
+    }
+
+    #[test]
+    fn test_issue_prompt() {
+        let issue = Issue {
+            url: "https://github.com/o/p/issues/7".to_string(),
+            title: "Crash on empty config".to_string(),
+            body: "Steps:\n1. touch config.toml\n".to_string(),
+        };
+        assert_eq!(
+            issue.prompt(None),
+            "Crash on empty config\n\nSteps:\n1. touch config.toml"
+        );
+        assert_eq!(
+            issue.prompt(Some("Add a regression test.")),
+            "Crash on empty config\n\nSteps:\n1. touch config.toml\n\nAdd a regression test."
+        );
+
+        let run = RunInfo::new(issue.prompt(None), "/repo").with_issue(Some(issue.url.clone()));
+        let pr = PullRequest::for_result(&run, "codex", "parari/codex-1");
+        assert!(
+            pr.body
+                .ends_with("Closes https://github.com/o/p/issues/7\n")
+        );
+    }
+
+    #[test]
+    fn test_combine_checks() {
+        assert_eq!(combine_checks([]), CiStatus::NoChecks);
+        assert_eq!(combine_checks(["pass", "skipping"]), CiStatus::Passed);
+        assert_eq!(combine_checks(["pass", "pending"]), CiStatus::Pending);
+        assert_eq!(combine_checks(["pending", "fail"]), CiStatus::Failed);
+    }
+
+    #[tokio::test]
+    async fn test_ci_status_unsupported() {
+        assert!(!Gitea.reports_ci());
+        let status = Gitea.ci_status(Path::new("."), "parari/claude-1").await;
+        assert!(matches!(status, Err(Error::Forge { .. })));
+    }
+
+    #[test]
+    fn test_url_in() {
+        let output = "Creating merge request for parari/claude-1 into main\n\nhttps://gitlab.com/o/p/-/merge_requests/3\n";
+        assert_eq!(
+            url_in(output),
+            Some("https://gitlab.com/o/p/-/merge_requests/3")
+        );
+        assert_eq!(url_in("created"), None);
+    }
+}
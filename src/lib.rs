@@ -10,3 +10,6 @@ pub mod domain;
 pub mod error;
 pub mod executor;
 pub mod git;
+pub mod integrations;
+#[cfg(any(test, feature = "mock"))]
+pub mod testing;
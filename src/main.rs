@@ -1,10 +1,22 @@
-use std::path::PathBuf;
+use std::io::IsTerminal;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
-use parari::cli::Args;
+use parari::cli::abort::{AbortChoice, AbortKeys};
 use parari::cli::progress::{ProgressTracker, display_completion_summary, display_header};
-use parari::domain::{self, DisplayOptions, TaskRunner, cleanup_all_registered_worktrees};
-use parari::error::{Error, Result};
+use parari::cli::{
+    Args, AutoSelect, Commands, ConfigAction, Expectation, HistoryAction, Selection, glyphs,
+};
+use parari::config::{
+    AgentAlias, Profile, REPO_CONFIG_FILE, REPO_CONFIG_TEMPLATE, RepoSettings, Settings,
+    StorageSettings, Theme, UiSettings,
+};
+use parari::domain::{
+    self, Abort, AgentPriors, Budget, DisplayOptions, EventSink, FailedAgent, JsonStream, Redactor,
+    ResultInfo, Review, RunEvent, RunOutcome, RunRecord, RunSnapshot, ScoreBreakdown, ScoreCache,
+    TaskResult, TaskRunner, WorktreeManager, cleanup_all_registered_worktrees,
+};
+use parari::error::{Error, Result, exit_code};
 #[cfg(not(feature = "mock"))]
 use parari::executor::claude::ClaudeExecutor;
 #[cfg(not(feature = "mock"))]
@@ -12,65 +24,476 @@ use parari::executor::codex::CodexExecutor;
 #[cfg(not(feature = "mock"))]
 use parari::executor::gemini::GeminiExecutor;
 #[cfg(feature = "mock")]
-use parari::executor::mock::MockExecutor;
-use parari::executor::traits::Executor;
-use parari::{cli, git};
+use parari::executor::mock::{Fixture, MockExecutor};
+use parari::executor::traits::{Executor, kill_running_agents};
+use parari::integrations::{self, CiStatus, Forge, PullRequest};
+use parari::{cli, config, git};
 
 #[tokio::main]
 async fn main() {
+    let args = Args::parse_args();
+    install_panic_hook();
+
     // Run the main task with signal handling
     let result = tokio::select! {
-        result = run() => result,
-        _ = tokio::signal::ctrl_c() => {
+        result = run(&args) => result,
+        () = interrupted() => {
             eprintln!("\nReceived interrupt signal, cleaning up worktrees...");
             cleanup_all_registered_worktrees();
-            std::process::exit(130); // Standard exit code for SIGINT
+            std::process::exit(exit_code::INTERRUPTED);
         }
     };
 
     // Cleanup any remaining worktrees on normal exit
     cleanup_all_registered_worktrees();
 
-    if let Err(e) = result {
-        if let Error::UserCancelled = e {
-            eprintln!("Cancelled.");
-        } else {
-            cli::show_error(&e);
-            std::process::exit(1);
+    let outcome = match &result {
+        Ok(outcome) => outcome.clone(),
+        Err(e) => {
+            if let Error::UserCancelled = e {
+                eprintln!("Cancelled.");
+            } else {
+                cli::show_error(e);
+            }
+            RunOutcome::failed(e)
         }
+    };
+
+    if args.json
+        && let Ok(json) = serde_json::to_string_pretty(&outcome)
+    {
+        println!("{json}");
+    }
+    if outcome.exit_code != exit_code::SUCCESS {
+        std::process::exit(outcome.exit_code);
     }
 }
 
-async fn run() -> Result<()> {
-    let args = Args::parse_args();
+/// Wait for Ctrl-C outside the split view
+///
+/// The split view asks before cancelling on Ctrl-C itself, so an interrupt signal while
+/// it is shown, e.g. from `kill -INT`, does not tear it down.
+async fn interrupted() {
+    loop {
+        if tokio::signal::ctrl_c().await.is_err() || !cli::output::tui_active() {
+            return;
+        }
+    }
+}
+
+/// Leave the terminal, agents and repository clean when parari panics
+///
+/// The panic is reported by the previous hook afterwards, so the message shows on the
+/// restored terminal. Only a panic of the main thread ends parari; one in a task of the
+/// runtime is caught there, so the run goes on with its agents and worktrees.
+fn install_panic_hook() {
+    let previous = std::panic::take_hook();
+    std::panic::set_hook(Box::new(move |info| {
+        if std::thread::current().name() == Some("main") {
+            if cli::output::tui_active() {
+                ratatui::restore();
+            }
+            kill_running_agents();
+            cleanup_all_registered_worktrees();
+        }
+        previous(info);
+    }));
+}
+
+async fn run(args: &Args) -> Result<RunOutcome> {
+    let mut settings = Settings::load()?;
+
+    cli::verbosity::set_quiet(args.quiet);
+    git::set_trace(args.trace_git);
+    git::set_dry_run(args.git_dry_run);
+    // Spinners and the split view need a terminal to draw on and read keys from,
+    // e.g. not when piped into `tee` or run from a script
+    cli::verbosity::set_plain(!std::io::stdout().is_terminal() || !std::io::stdin().is_terminal());
+
+    if args.ascii || glyphs::detect_ascii_only() {
+        glyphs::set_ascii_only(true);
+    }
+
+    if args.colors_disabled() {
+        console::set_colors_enabled(false);
+        console::set_colors_enabled_stderr(false);
+        settings.ui.theme = Theme::NoColor;
+    }
+
+    if let Some(command) = &args.command {
+        return run_command(args, &settings, command).await;
+    }
 
     // Get prompt from args or open editor
-    let prompt = match args.prompt.clone() {
-        Some(p) => p,
-        None => cli::open_editor_for_prompt()?,
+    let mut args = args.clone();
+    let issue = match &args.issue {
+        Some(reference) => {
+            cli::show_progress(&format!("Fetching issue {reference}..."));
+            Some(
+                integrations::fetch_issue(reference, args.forge, Path::new(&args.directory))
+                    .await?,
+            )
+        }
+        None => None,
     };
+    let prompt = match (&issue, args.prompt.clone()) {
+        (Some(issue), extra) => issue.prompt(extra.as_deref()),
+        (None, Some(p)) => p,
+        (None, None) if args.from_clipboard => cli::read_prompt_from_clipboard()?,
+        (None, None) => {
+            let recent = cli::pick_recent_prompt(&domain::list_runs().unwrap_or_default())?;
+            let written =
+                cli::open_editor_for_prompt(&settings.editor.template()?, recent.as_deref())?;
+            written.directives.apply_to(&mut args);
+            written.prompt
+        }
+    };
+    if cli::verbosity::plain() && args.auto_select.is_none() && !args.no_select {
+        cli::show_progress(
+            "Output is not a terminal; keeping the results without selecting one (pass --auto-select to apply one).",
+        );
+        args.no_select = true;
+    }
+    let args = &args;
 
-    // Resolve working directory
-    let working_dir = PathBuf::from(&args.directory).canonicalize()?;
+    let context = RunContext::prepare(args, &settings, &prompt, issue).await?;
+    run_agents(args, &settings, context).await
+}
 
-    // Check if it's a git repository
-    if !git::is_git_repository(&working_dir).await {
-        return Err(Error::NotGitRepository {
-            path: working_dir.clone(),
+/// Run a subcommand instead of the agents
+async fn run_command(args: &Args, settings: &Settings, command: &Commands) -> Result<RunOutcome> {
+    match command {
+        Commands::History { id, action } => match (id, action) {
+            (_, Some(HistoryAction::Search { query })) => {
+                cli::show_search_results(&domain::search_runs(&query.join(" "))?);
+            }
+            (_, Some(HistoryAction::Review { id })) => {
+                return review_run(args, settings, domain::load_run(id)?).await;
+            }
+            (Some(id), None) => {
+                cli::show_run(&domain::load_run(id)?, &domain::list_reruns(id)?);
+            }
+            (None, None) => cli::show_history(&domain::list_runs()?),
+        },
+        Commands::Export {
+            id,
+            output,
+            allow_secrets,
+        } => {
+            let secrets = domain::run_secrets(id);
+            if !secrets.is_empty() && !allow_secrets && !cli::confirm_export_secrets(&secrets)? {
+                return Err(Error::UserCancelled);
+            }
+            domain::export_run(id, output)?;
+            cli::show_exported(id, output);
+        }
+        Commands::Rerun { id, head } => {
+            return rerun(args, &domain::load_run(id)?, *head).await;
+        }
+        Commands::Import { archive, review } => {
+            let record = domain::import_run(archive)?;
+            cli::show_imported(&record);
+            if *review {
+                return review_run(args, settings, record).await;
+            }
+        }
+        Commands::Clean { yes } => clean(*yes).await?,
+        Commands::Init { gitignore, force } => {
+            init(Path::new(&args.directory), *gitignore, *force).await?;
+        }
+        Commands::Config { action } => configure(action)?,
+        Commands::Watch {
+            file,
+            auto_select,
+            agents,
+            profile,
+        } => {
+            let mut once = args.clone();
+            once.command = None;
+            once.auto_select = *auto_select;
+            once.no_select = auto_select.is_none();
+            once.agents.clone_from(agents);
+            once.profile.clone_from(profile);
+            return watch(&once, file).await;
+        }
+    }
+    Ok(RunOutcome::default())
+}
+
+/// The repository and options of a run, resolved before the agents start
+struct RunContext {
+    /// Checkout of `--repo`, removed when the run ends
+    remote: Option<domain::RemoteCheckout>,
+    working_dir: PathBuf,
+    repo_root: PathBuf,
+    repo_settings: RepoSettings,
+    /// Strips secrets from the prompt and everything saved to the history
+    redactor: Redactor,
+    /// Images attached to the prompt
+    images: Vec<PathBuf>,
+    /// Quick check command run after each agent
+    check: Option<String>,
+    /// Where the selected result is applied
+    apply_target: PathBuf,
+    /// Whether the apply target moved on from the base commit, so the result is merged
+    /// in as a patch
+    apply_as_patch: bool,
+    /// Forge to open a pull request on, with `--pr`
+    forge: Option<Box<dyn Forge>>,
+    run_info: domain::RunInfo,
+    events: Option<Arc<dyn EventSink>>,
+}
+
+/// The sink of the events of a run, with `--json-stream`
+fn event_sink(args: &Args) -> Result<Option<Arc<dyn EventSink>>> {
+    Ok(match args.json_stream.as_deref() {
+        Some(path) => Some(Arc::new(JsonStream::create(Path::new(path))?)),
+        None => None,
+    })
+}
+
+impl RunContext {
+    /// Resolve the repository, settings and targets of a run of `prompt`
+    async fn prepare(
+        args: &Args,
+        settings: &Settings,
+        prompt: &str,
+        issue: Option<integrations::Issue>,
+    ) -> Result<Self> {
+        let images = domain::resolve_images(&args.image)?;
+
+        // Clone a remote repository first; the clone lives until the run ends
+        let remote = match &args.repo {
+            Some(url) => {
+                cli::show_progress(&format!("Cloning {url}..."));
+                Some(domain::RemoteCheckout::fetch(url).await?)
+            }
+            None => None,
+        };
+
+        // Resolve working directory
+        let working_dir = match &remote {
+            Some(remote) => remote.path().to_path_buf(),
+            None => PathBuf::from(&args.directory).canonicalize()?,
+        };
+
+        // Check if it's a git repository
+        if !git::is_git_repository(&working_dir).await {
+            return Err(Error::NotGitRepository {
+                path: working_dir.clone(),
+            });
+        }
+
+        cli::show_progress(&format!("Working directory: {}", working_dir.display()));
+        let repo_root = git::get_repo_root(&working_dir).await?;
+        let repo_settings = RepoSettings::load(&repo_root)?;
+
+        // Secrets never leave the machine: strip them from the prompt, refuse secret images
+        let redactor = Redactor::new(&repo_settings.redact.patterns)?;
+        let (prompt, redacted) = redactor.redact(prompt);
+        if redacted > 0 {
+            cli::show_progress(&format!("Redacted {redacted} secret(s) from the prompt."));
+        }
+        if let Some(image) = images.iter().find(|image| {
+            let path = image.strip_prefix(&repo_root).unwrap_or(image);
+            repo_settings.is_secret(&path.to_string_lossy())
+        }) {
+            return Err(Error::InvalidImage {
+                path: image.clone(),
+                reason: format!("it matches a secret file pattern in {REPO_CONFIG_FILE}"),
+            });
+        }
+        let check = check_command(args, &repo_settings, &repo_root);
+
+        if let Some(quota) = settings.storage.quota {
+            let used = domain::disk_usage().await?.total();
+            if used > quota.bytes() {
+                cli::show_quota_warning(used, quota.bytes());
+            }
+        }
+
+        let base_commit = match &args.base {
+            Some(base) => Some(base.clone()),
+            None => git::get_head_commit(&working_dir).await.ok(),
+        };
+
+        // Validate the apply target before spending time on the agents
+        let (apply_target, apply_as_patch) = match &args.apply_to {
+            Some(path) => {
+                let target =
+                    domain::resolve_apply_target(Path::new(path), base_commit.as_deref()).await?;
+                cli::show_progress(&format!("Applying to: {}", target.root.display()));
+                if target.moved {
+                    cli::show_progress(
+                        "Its HEAD is not at the base commit, so the result is merged in as a patch.",
+                    );
+                }
+                (target.root, target.moved)
+            }
+            None => (working_dir.clone(), false),
+        };
+        let forge = pull_request_forge(args, &apply_target).await?;
+        let run_info = domain::RunInfo::new(prompt, &working_dir)
+            .with_base_commit(base_commit)
+            .with_issue(issue.map(|issue| issue.url))
+            .with_options(args.run_options())
+            .with_rerun_of(args.rerun_of.clone());
+
+        let events = event_sink(args)?;
+
+        Ok(Self {
+            remote,
+            working_dir,
+            repo_root,
+            repo_settings,
+            redactor,
+            images,
+            check,
+            apply_target,
+            apply_as_patch,
+            forge,
+            run_info,
+            events,
+        })
+    }
+
+    /// Create the task runner running the agents in worktrees of the repository
+    async fn runner(&self, args: &Args, settings: &Settings) -> Result<TaskRunner> {
+        let runner = TaskRunner::new(&self.working_dir)
+            .await?
+            .with_summaries(!args.no_summary)
+            .with_scope(args.scope.clone().unwrap_or_default())
+            .with_base(args.base.clone())
+            .with_secrets(self.repo_settings.redact.files.clone())
+            .with_retention(settings.worktrees.clone())
+            .with_setup(self.repo_settings.setup.clone())
+            .with_score(self.repo_settings.score.clone())
+            .with_policy(self.repo_settings.policy.clone())
+            .with_score_cache((!args.no_score_cache).then(ScoreCache::open))
+            .with_score_jobs(
+                args.score_jobs
+                    .map_or(settings.commands.score_jobs, usize::from),
+            )
+            .with_stall_timeout(
+                args.stall_timeout
+                    .or(settings.commands.stall_timeout)
+                    .map(config::Age::duration),
+                args.kill_stalled || settings.commands.kill_stalled,
+            )
+            .with_retries(usize::from(args.retries))
+            .with_providers(settings.providers.clone())
+            .with_images(self.images.clone())
+            .with_check(self.check.clone());
+        Ok(match &self.events {
+            Some(events) => runner.with_events(Arc::clone(events)),
+            None => runner,
+        })
+    }
+}
+
+/// The forge to open a pull request on with `--pr`
+///
+/// The pull request commits everything in the target, so it must hold only the result.
+async fn pull_request_forge(args: &Args, apply_target: &Path) -> Result<Option<Box<dyn Forge>>> {
+    if !args.pr {
+        return Ok(None);
+    }
+    if git::has_uncommitted_changes(apply_target).await? {
+        return Err(Error::InvalidApplyTarget {
+            path: apply_target.to_path_buf(),
+            reason: "--pr commits the applied result, so commit or stash local changes first"
+                .to_string(),
+        });
+    }
+    let kind = match args.forge {
+        Some(kind) => kind,
+        None => integrations::detect_forge(apply_target).await?,
+    };
+    let forge = kind.forge();
+    // Fail before the run rather than after the pull request is opened
+    if args.watch_ci.is_some() && !forge.reports_ci() {
+        return Err(Error::Forge {
+            forge: forge.name().to_string(),
+            message: format!(
+                "`{}` does not report CI status, so --watch-ci cannot be used",
+                forge.program()
+            ),
         });
     }
+    Ok(Some(forge))
+}
 
-    cli::show_progress(&format!("Working directory: {}", working_dir.display()));
+/// The check command of `--check-cmd`, where an empty command picks one for the repository
+fn check_command(args: &Args, repo_settings: &RepoSettings, repo_root: &Path) -> Option<String> {
+    match args.check_cmd.as_deref() {
+        Some("") => {
+            let check = repo_settings
+                .check
+                .clone()
+                .or_else(|| config::default_check_command(repo_root).map(str::to_string));
+            if check.is_none() {
+                cli::show_progress("No check command is known for this repository; skipping it.");
+            }
+            check
+        }
+        Some(command) => Some(command.to_string()),
+        None => repo_settings.check.clone(),
+    }
+}
 
-    // Create task runner
-    let mut runner = TaskRunner::new(&working_dir).await?;
+/// Run the agents on the prompt in worktrees of the repository, then review their results
+async fn run_agents(args: &Args, settings: &Settings, context: RunContext) -> Result<RunOutcome> {
+    let mut prompt = context.run_info.prompt.clone();
+    let mut runner = context.runner(args, settings).await?;
 
     // Collect available executors
-    let executors = get_executors(args.agents.as_deref()).await;
+    let budget = Budget {
+        max_cost: args.max_cost,
+        max_tokens: args.max_tokens,
+    };
+    runner = runner.with_budget(budget);
+    let executors = if let Some(name) = &args.profile {
+        profile_executors(settings, settings.profile(name)?, &budget)?
+    } else {
+        let agents = args
+            .agents
+            .as_deref()
+            .or(context.repo_settings.agents.as_deref());
+        select_executors(settings, agents, &budget).await?
+    };
 
     if executors.is_empty() {
         return Err(Error::NoExecutorsAvailable);
     }
+    if budget.max_tokens.is_some() {
+        let untracked: Vec<&str> = executors
+            .iter()
+            .filter(|executor| !executor.reports_usage())
+            .map(|executor| executor.name())
+            .collect();
+        if !untracked.is_empty() {
+            cli::show_untracked_tokens_warning(&untracked);
+        }
+    }
+
+    // Agents fail late and confusingly when the prompt fills their context
+    let warnings = domain::check_prompt_length(&prompt, &executors);
+    if !warnings.is_empty() {
+        let ask = args.auto_select.is_none() && !args.no_select && !args.force;
+        match cli::confirm_long_prompt(&warnings, ask)? {
+            cli::LongPrompt::Run => {}
+            cli::LongPrompt::Truncate => prompt = domain::truncate_prompt(&prompt, &warnings),
+            cli::LongPrompt::Cancel => return Err(Error::UserCancelled),
+        }
+    }
+
+    // The first agent also compares candidates on request in the split view
+    let comparer = executors.first().cloned();
+
+    // Record what the run starts from besides the base commit, to rerun it later
+    let mut context = context;
+    let snapshot = RunSnapshot::take(&context.repo_root, &executors).await;
+    context.run_info = context.run_info.with_snapshot(snapshot);
 
     // Collect executor names before moving executors
     let executor_names: Vec<String> = executors.iter().map(|e| e.name().to_string()).collect();
@@ -79,78 +502,942 @@ async fn run() -> Result<()> {
     // Display header with agent info
     display_header(&executor_name_refs);
 
+    // A key press stops all agents; read from here so the hint shows above the spinners
+    let abort = Abort::new();
+    runner = runner.with_abort(abort.clone());
+    let keys = cli::abort::listen(&abort);
+
     // Create progress tracker
     let progress = Arc::new(ProgressTracker::new(&executor_name_refs));
 
     // Run the task with progress tracking
     let results = runner
         .run_with_progress(&prompt, executors, Some(progress))
-        .await?;
+        .await;
+    let aborted = keys.and_then(AbortKeys::finish);
+    let mut results = results?;
+    if let Some(choice) = aborted {
+        let patches = if choice == AbortChoice::KeepPartial {
+            save_partial_diffs(&context.run_info.id, &results, &context.redactor).await
+        } else {
+            Vec::new()
+        };
+        runner.cleanup().await?;
+        cli::show_aborted(&patches);
+        return Err(Error::UserCancelled);
+    }
+    // A random order keeps the first listed agent from being favored out of habit
+    if args.shuffle || settings.ui.shuffle {
+        fastrand::shuffle(&mut results);
+    }
+
+    let outcome = review_results(
+        args,
+        settings,
+        &context,
+        &results,
+        &executor_names,
+        comparer,
+    )
+    .await;
+    runner.cleanup().await?;
+    let mut outcome = outcome?;
+    if let Some(agent) = &outcome.applied {
+        let (pull_request, ci) = deliver_result(args, &context, agent).await?;
+        if ci == Some(CiStatus::Failed) {
+            outcome.exit_code = exit_code::CI_FAILED;
+        }
+        outcome.pull_request = pull_request;
+        outcome.ci = ci;
+    }
+    Ok(outcome)
+}
+
+/// Rank the results of the agents, then apply the selected one
+async fn review_results(
+    args: &Args,
+    settings: &Settings,
+    context: &RunContext,
+    results: &[TaskResult],
+    executor_names: &[String],
+    comparer: Option<Arc<dyn Executor>>,
+) -> Result<RunOutcome> {
+    let run_info = &context.run_info;
 
     // Collect completed and failed agents for summary
     let completed: Vec<&str> = results
         .iter()
+        .filter(|r| r.execution.success)
         .map(|r| r.execution.executor_name.as_str())
         .collect();
     let failed: Vec<&str> = executor_names
         .iter()
         .map(String::as_str)
-        .filter(|name| !completed.iter().any(|c| c == name))
+        .filter(|name| !completed.contains(name))
         .collect();
 
     // Display completion summary
     display_completion_summary(&completed, &failed);
 
-    if results.is_empty() {
-        cli::show_progress("No results were produced.");
-        runner.cleanup().await?;
-        return Ok(());
+    // Agents that did not finish successfully, whether or not they produced a result
+    if failed.len() == executor_names.len() {
+        let failed = report_all_failed(&run_info.id, results, &context.redactor);
+        let error = Error::AllAgentsFailed;
+        return Ok(RunOutcome {
+            run_id: Some(run_info.id.clone()),
+            failed,
+            error: Some(error.to_string()),
+            exit_code: error.exit_code(),
+            ..RunOutcome::default()
+        });
+    }
+    if args.expect == Expectation::SuccessAll && !failed.is_empty() {
+        return Err(Error::AgentsFailed {
+            agents: failed.iter().map(ToString::to_string).collect(),
+        });
+    }
+
+    let mut result_infos = prepare_result_infos(args, context, results).await?;
+
+    // Score the candidates for --auto-select; the breakdown is also shown while reviewing
+    let priors = AgentPriors::from_stats(&domain::agent_stats().unwrap_or_default());
+    let scores = domain::score_candidates(&result_infos, &settings.auto_select, &priors);
+    for (info, score) in result_infos.iter_mut().zip(&scores) {
+        info.breakdown = Some(score.clone());
+    }
+
+    if let Some(events) = &context.events {
+        emit_ranking(events.as_ref(), &result_infos);
+    }
+    let failed: Vec<FailedAgent> = result_infos
+        .iter()
+        .filter(|info| !info.success)
+        .map(FailedAgent::from)
+        .collect();
+
+    if args.no_select {
+        save_run(context, results, &result_infos, &[], &settings.storage).await;
+        cli::show_ranking(&result_infos, &scores);
+        return Ok(RunOutcome {
+            run_id: Some(run_info.id.clone()),
+            scores,
+            failed,
+            ..RunOutcome::default()
+        });
+    }
+
+    // Handle selection, keeping the notes taken while reviewing in the history
+    let mut reviews = vec![Review::default(); result_infos.len()];
+    let selection = match args.auto_select {
+        Some(AutoSelect::Weighted) => auto_select(
+            &result_infos,
+            &scores,
+            &settings.ui,
+            args.allow_secrets,
+            terminal_ask(),
+        ),
+        None => cli::select_result(
+            results,
+            &result_infos,
+            run_info,
+            &settings.ui,
+            &mut reviews,
+            comparer,
+        ),
+    };
+    let mut record = save_run(context, results, &result_infos, &reviews, &settings.storage).await;
+    let selection = selection?;
+
+    let selected_info = &result_infos[selection.index];
+    let agent = &selected_info.executor_name;
+    let summary = apply_selection(
+        args,
+        context,
+        &results[selection.index],
+        &selected_info.conflicts,
+        selection,
+        &mut record,
+    )
+    .await?;
+    if summary.is_empty() {
+        return Err(Error::NothingApplied {
+            reason: format!("{agent} changed no files"),
+        });
     }
 
-    // Prepare result info for display
+    Ok(RunOutcome {
+        run_id: Some(run_info.id.clone()),
+        applied: Some(agent.clone()),
+        created: summary.created,
+        updated: summary.updated,
+        deleted: summary.deleted,
+        scores,
+        failed,
+        ..RunOutcome::default()
+    })
+}
+
+/// Prepare the results for display, checking them for conflicts with local
+/// edits in the apply target unless forced
+async fn prepare_result_infos(
+    args: &Args,
+    context: &RunContext,
+    results: &[TaskResult],
+) -> Result<Vec<ResultInfo>> {
     let display_options = DisplayOptions::default();
 
     let mut result_infos = Vec::new();
-    for result in &results {
-        let info = domain::prepare_result_info(result, &working_dir, &display_options)?;
+    for result in results {
+        let mut info =
+            domain::prepare_result_info(result, &context.working_dir, &display_options).await?;
+        if !args.force {
+            info.conflicts =
+                git::check_conflicts(&result.worktree_path, &context.apply_target).await?;
+            // Reviewing conflicts in lockfiles is pointless; the candidate's version wins
+            info.conflicts
+                .retain(|file| !context.repo_settings.is_generated(file));
+        }
         result_infos.push(info);
     }
+    domain::mark_duplicates(&mut result_infos);
+    domain::compute_similarity(&mut result_infos);
+    Ok(result_infos)
+}
 
-    // Handle selection
-    let selected_index = cli::select_result(&results, &result_infos)?;
+/// Save the run to the history, with the output and diff of each candidate
+async fn save_run(
+    context: &RunContext,
+    results: &[TaskResult],
+    result_infos: &[ResultInfo],
+    reviews: &[Review],
+    storage: &StorageSettings,
+) -> RunRecord {
+    let mut record = RunRecord::new(&context.run_info, result_infos, reviews);
+    context.redactor.redact_record(&mut record);
+    if let Err(e) = record.save() {
+        cli::show_progress(&format!("Could not save the run to the history: {e}"));
+    }
+    if storage.save_logs {
+        archive_candidates(&context.run_info.id, results, &context.redactor).await;
+    }
+    if let Some(max_age) = storage.logs_max_age
+        && let Err(e) = domain::remove_old_agent_files(max_age.duration())
+    {
+        cli::show_progress(&format!("Could not remove old logs from the history: {e}"));
+    }
+    record
+}
 
-    // Apply the selected result
-    let selected_result = &results[selected_index];
-    let selected_info = &result_infos[selected_index];
+/// Emit the ranked results as events
+fn emit_ranking(events: &dyn EventSink, result_infos: &[ResultInfo]) {
+    for (i, info) in result_infos.iter().enumerate() {
+        let (insertions, deletions) = info
+            .change_summary
+            .as_ref()
+            .map_or((0, 0), |s| (s.insertions, s.deletions));
+        events.emit(&RunEvent::ResultRanked {
+            rank: i + 1,
+            agent: info.executor_name.clone(),
+            success: info.success,
+            files_changed: info.files_changed,
+            insertions,
+            deletions,
+        });
+    }
+}
 
-    // Check for conflicts before applying
-    let conflicts = git::check_conflicts(&selected_result.worktree_path, &working_dir).await?;
+/// Apply the selected result to the target and record the decision in the history
+///
+/// `conflicts` are the files with local changes the result overwrites, which are
+/// stashed and merged back if the user chose to.
+async fn apply_selection(
+    args: &Args,
+    context: &RunContext,
+    selected: &TaskResult,
+    conflicts: &[String],
+    selection: Selection,
+    record: &mut RunRecord,
+) -> Result<git::ApplySummary> {
+    let agent = &selected.execution.executor_name;
+    let apply_target = &context.apply_target;
+    let conflicts = &match &context.repo_settings.format {
+        Some(command) => {
+            format_before_apply(args, context, command, &selected.worktree_path, conflicts).await?
+        }
+        None => conflicts.to_vec(),
+    };
+
+    // Local edits that would be overwritten were confirmed in the split view,
+    // either to be overwritten or to be stashed and merged back afterwards
+    let vcs = git::Vcs::detect(apply_target).await;
+    vcs.snapshot(apply_target).await?;
+    if selection.stash && !vcs.supports_stash() {
+        cli::show_progress(
+            "Not stashing in a jj repository; the local changes are kept in `jj op log`.",
+        );
+    }
+    let stashed = selection.stash
+        && vcs.supports_stash()
+        && git::stash_changes(apply_target, conflicts).await?;
+
+    cli::show_applying_message(agent);
+    let progress = cli::create_apply_progress();
+    let applied = if context.apply_as_patch {
+        domain::apply_result_as_patch(selected, apply_target, &context.repo_settings).await
+    } else {
+        domain::apply_result_with_progress(
+            selected,
+            apply_target,
+            &context.repo_settings,
+            |done, total| {
+                progress.set_length(u64::try_from(total).unwrap_or(u64::MAX));
+                progress.set_position(u64::try_from(done).unwrap_or(u64::MAX));
+            },
+        )
+        .await
+    };
+    progress.finish_and_clear();
+    let restored = if stashed {
+        Some(git::restore_stash(apply_target, conflicts).await?)
+    } else {
+        None
+    };
+    let summary = applied?;
+    // Record the applied result as its own jj operation
+    vcs.snapshot(apply_target).await?;
+    record.record_decision(agent, args.auto_select.is_some());
+    if let Err(e) = record.save() {
+        cli::show_progress(&format!("Could not record the applied result: {e}"));
+    }
+    cli::show_success_message();
+    cli::show_apply_summary(&summary);
+    regenerate(&context.repo_settings, &summary, apply_target).await?;
+    if let Some(events) = &context.events {
+        events.emit(&RunEvent::Applied {
+            agent: agent.clone(),
+            created: summary.created.len(),
+            updated: summary.updated.len(),
+            deleted: summary.deleted.len(),
+        });
+    }
+    if let Some(conflicts) = restored {
+        cli::show_stash_restored(&conflicts);
+    }
+    Ok(summary)
+}
+
+/// Open a pull request for the applied result, or push it to the cloned `--repo`
+///
+/// Returns the URL of the pull request and, with `--watch-ci`, how its checks ended.
+async fn deliver_result(
+    args: &Args,
+    context: &RunContext,
+    agent: &str,
+) -> Result<(Option<String>, Option<CiStatus>)> {
+    let run_info = &context.run_info;
+    let pull_request = match (&context.forge, &context.remote) {
+        (Some(forge), _) => {
+            Some(open_pull_request(forge.as_ref(), &context.apply_target, agent, run_info).await?)
+        }
+        (None, Some(remote)) => {
+            deliver_remote_result(
+                remote,
+                agent,
+                run_info,
+                args.auto_select.is_none() && !cli::verbosity::plain(),
+            )
+            .await?;
+            None
+        }
+        (None, None) => None,
+    };
+    let ci = match (&context.forge, &pull_request, args.watch_ci) {
+        (Some(forge), Some(url), Some(timeout)) => {
+            let branch = domain::result_branch(agent, &run_info.id);
+            cli::show_progress(&format!("Waiting for the CI checks of {url}..."));
+            let status = integrations::wait_for_checks(
+                forge.as_ref(),
+                &context.apply_target,
+                &branch,
+                timeout.duration(),
+            )
+            .await?;
+            cli::show_ci_status(status, url);
+            Some(status)
+        }
+        _ => None,
+    };
+    Ok((pull_request, ci))
+}
+
+/// Commit the applied result on its own branch, push it, and open a pull request for it
+///
+/// Returns the URL of the pull request.
+async fn open_pull_request(
+    forge: &dyn Forge,
+    repo: &Path,
+    agent: &str,
+    run_info: &domain::RunInfo,
+) -> Result<String> {
+    let branch = domain::result_branch(agent, &run_info.id);
+    let pull_request = PullRequest::for_result(run_info, agent, &branch);
+    git::push_branch(repo, &branch, &pull_request.commit_message()).await?;
+    cli::show_progress(&format!(
+        "Opening a pull request for {branch} on {}...",
+        forge.name()
+    ));
+    let url = forge.create_pull_request(repo, &pull_request).await?;
+    cli::show_progress(&format!("Opened {url}"));
+    Ok(url)
+}
 
-    if conflicts.is_empty() {
-        // No conflicts, but check if target has uncommitted changes
-        let uncommitted_files = git::get_uncommitted_files(&working_dir).await?;
-        if !uncommitted_files.is_empty() && !cli::confirm_overwrite_uncommitted(&uncommitted_files)?
+/// Push a result applied to a clone of a remote repository, or save it as a patch
+///
+/// Without `ask`, the patch is written; nothing is pushed without asking.
+async fn deliver_remote_result(
+    remote: &domain::RemoteCheckout,
+    agent: &str,
+    run_info: &domain::RunInfo,
+    ask: bool,
+) -> Result<()> {
+    let branch = domain::result_branch(agent, &run_info.id);
+    let patch = domain::result_patch(agent, &run_info.id);
+    let delivery = if ask {
+        cli::choose_remote_delivery(remote.url(), &branch, &patch)?
+    } else {
+        cli::RemoteDelivery::Patch
+    };
+    match delivery {
+        cli::RemoteDelivery::Push => {
+            let title = run_info.title();
+            remote
+                .push_result(
+                    &branch,
+                    &format!("{title}\n\nApplied the result of {agent} with parari."),
+                )
+                .await?;
+            cli::show_progress(&format!("Pushed {branch} to {}", remote.url()));
+        }
+        cli::RemoteDelivery::Patch => {
+            remote.write_patch(&patch).await?;
+            cli::show_progress(&format!(
+                "Wrote {} (apply it with `git apply {}`)",
+                patch.display(),
+                patch.display()
+            ));
+        }
+        cli::RemoteDelivery::Discard => {}
+    }
+    Ok(())
+}
+
+/// Run the prompt in `file` whenever the file changes, until interrupted
+///
+/// A failed run is reported and the file is watched again.
+async fn watch(args: &Args, file: &Path) -> Result<RunOutcome> {
+    let mut since = None;
+    loop {
+        cli::show_progress(&format!(
+            "Watching {} for changes (Ctrl-C to stop)...",
+            file.display()
+        ));
+        since = Some(domain::wait_for_change(file, since).await?);
+
+        let prompt = std::fs::read_to_string(file)?;
+        if prompt.trim().is_empty() {
+            continue;
+        }
+        let mut once = args.clone();
+        once.prompt = Some(prompt.trim().to_string());
+        if let Err(e) = Box::pin(run(&once)).await {
+            cli::show_error(&e);
+        }
+    }
+}
+
+/// Save the output of every failed agent and show why each one failed
+fn report_all_failed(
+    run_id: &str,
+    results: &[TaskResult],
+    redactor: &Redactor,
+) -> Vec<FailedAgent> {
+    let failed: Vec<FailedAgent> = results
+        .iter()
+        .map(|result| {
+            let mut failure = FailedAgent::new(result);
+            let lines = redactor.redact_lines(&result.execution.output_lines);
+            failure.log = domain::save_agent_log(run_id, &failure.agent, &lines).ok();
+            failure
+        })
+        .collect();
+    cli::show_all_agents_failed(&failed);
+    failed
+}
+
+/// Keep the output and diff of each candidate with the run, for `parari history search`
+///
+/// Secrets matching the `[redact]` patterns are stripped from the output and from the
+/// lines the diff adds first.
+async fn archive_candidates(run_id: &str, results: &[TaskResult], redactor: &Redactor) {
+    for result in results {
+        let agent = &result.execution.executor_name;
+        let lines = redactor.redact_lines(&result.execution.output_lines);
+        if let Err(e) = domain::save_agent_log(run_id, agent, &lines) {
+            cli::show_progress(&format!("Could not save the output of {agent}: {e}"));
+        }
+        let saved = match git::get_diff(&result.worktree_path).await {
+            Ok(diff) if diff.trim().is_empty() => Ok(()),
+            Ok(diff) => {
+                domain::save_agent_patch(run_id, agent, &redactor.redact_patch(&diff)).map(|_| ())
+            }
+            Err(e) => Err(e),
+        };
+        if let Err(e) = saved {
+            cli::show_progress(&format!("Could not save the changes of {agent}: {e}"));
+        }
+    }
+}
+
+/// Save what each agent changed before the run was aborted, skipping agents without changes
+///
+/// Secrets matching the `[redact]` patterns are stripped from the saved patches.
+async fn save_partial_diffs(
+    run_id: &str,
+    results: &[TaskResult],
+    redactor: &Redactor,
+) -> Vec<(String, PathBuf)> {
+    let mut patches = Vec::new();
+    for result in results {
+        let agent = &result.execution.executor_name;
+        let diff = match git::get_diff(&result.worktree_path).await {
+            Ok(diff) if !diff.trim().is_empty() => diff,
+            Ok(_) => continue,
+            Err(e) => {
+                cli::show_progress(&format!("Could not read the changes of {agent}: {e}"));
+                continue;
+            }
+        };
+        match domain::save_agent_patch(run_id, agent, &redactor.redact_patch(&diff)) {
+            Ok(patch) => patches.push((agent.clone(), patch)),
+            Err(e) => cli::show_progress(&format!("Could not save the changes of {agent}: {e}")),
+        }
+    }
+    patches
+}
+
+/// Run the repository's formatter in the selected worktree before applying it
+///
+/// Returns the files with local edits the formatted result overwrites. Files only
+/// the formatter touched were not confirmed yet, so they are asked about, or refused
+/// without a terminal. Without `--auto-select`, the formatted diff is shown and
+/// applying needs a final yes.
+async fn format_before_apply(
+    args: &Args,
+    context: &RunContext,
+    command: &str,
+    worktree: &Path,
+    conflicts: &[String],
+) -> Result<Vec<String>> {
+    cli::show_progress(&format!("Formatting with `{command}`..."));
+    let formatted = domain::format_result(
+        command,
+        worktree,
+        &context.apply_target,
+        &context.repo_settings,
+    )
+    .await?;
+    if !formatted.check.passed {
+        cli::show_progress(&format!(
+            "`{command}` failed; the changes are applied unformatted:\n{}",
+            formatted.check.output.trim_end()
+        ));
+    }
+    let conflicts = if args.force {
+        conflicts.to_vec()
+    } else {
+        let added: Vec<String> = formatted
+            .conflicts
+            .iter()
+            .filter(|file| !conflicts.contains(file))
+            .cloned()
+            .collect();
+        if !added.is_empty()
+            && (cli::verbosity::plain() || !cli::confirm_apply_with_conflicts(&added)?)
         {
-            cli::show_progress("Apply cancelled.");
-            runner.cleanup().await?;
-            return Err(Error::UserCancelled);
+            return Err(Error::NothingApplied {
+                reason: format!(
+                    "formatting with `{command}` changed files with local edits: {}",
+                    added.join(", ")
+                ),
+            });
+        }
+        formatted.conflicts
+    };
+    if args.auto_select.is_none() && !cli::confirm_formatted_diff(worktree, command)? {
+        return Err(Error::UserCancelled);
+    }
+    Ok(conflicts)
+}
+
+/// Run the repository's regenerate command if applying touched generated files
+async fn regenerate(
+    repo_settings: &RepoSettings,
+    summary: &git::ApplySummary,
+    target: &Path,
+) -> Result<()> {
+    let Some(command) = &repo_settings.regenerate else {
+        return Ok(());
+    };
+    let touched = summary
+        .created
+        .iter()
+        .chain(&summary.updated)
+        .chain(&summary.deleted)
+        .any(|file| repo_settings.is_generated(file));
+    if !touched {
+        return Ok(());
+    }
+    cli::show_progress(&format!("Regenerating with `{command}`..."));
+    let result = domain::run_check(command, target).await?;
+    if !result.passed {
+        cli::show_progress(&format!(
+            "`{command}` failed; the generated files are the agent's version:\n{}",
+            result.output.trim_end()
+        ));
+    }
+    Ok(())
+}
+
+/// Why an auto-selected result is only applied once the user confirms it
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Risk {
+    /// It overwrites local changes in the files of its `conflicts`
+    Conflicts,
+    /// It changes more than the large-diff limits allow
+    LargeDiff,
+}
+
+/// Asks the user whether to apply a result despite a risk
+type Ask<'a> = dyn Fn(Risk, &ResultInfo) -> Result<bool> + 'a;
+
+/// Confirmations asked on the terminal, or `None` when there is none to ask on
+fn terminal_ask() -> Option<&'static Ask<'static>> {
+    (!cli::verbosity::plain()).then_some(&confirm_risk)
+}
+
+/// Ask on the terminal whether to apply a result despite a risk
+fn confirm_risk(risk: Risk, info: &ResultInfo) -> Result<bool> {
+    match risk {
+        Risk::Conflicts => cli::confirm_apply_with_conflicts(&info.conflicts),
+        Risk::LargeDiff => cli::confirm_large_diff(info),
+    }
+}
+
+/// Choose the successful result with the highest weighted score
+///
+/// A result that looks like it adds a secret is only applied with `allow_secrets`. One
+/// that would overwrite local changes or exceeds the large-diff limits of `ui` is only
+/// applied once confirmed with `ask`, and never when there is no terminal to ask on.
+fn auto_select(
+    result_infos: &[ResultInfo],
+    scores: &[ScoreBreakdown],
+    ui: &UiSettings,
+    allow_secrets: bool,
+    ask: Option<&Ask<'_>>,
+) -> Result<Selection> {
+    let index = domain::best_candidate(result_infos, scores).ok_or(Error::AllAgentsFailed)?;
+    let info = &result_infos[index];
+    if let Some(secret) = info.secrets.first()
+        && !allow_secrets
+    {
+        return Err(Error::NothingApplied {
+            reason: format!(
+                "{} adds a possible {secret}; review it without --auto-select, or pass --allow-secrets",
+                info.executor_name
+            ),
+        });
+    }
+    if !info.conflicts.is_empty() && !ask.map_or(Ok(false), |ask| ask(Risk::Conflicts, info))? {
+        return Err(Error::NothingApplied {
+            reason: format!(
+                "{} would overwrite local changes in {} file(s); pass --force to apply anyway",
+                info.executor_name,
+                info.conflicts.len()
+            ),
+        });
+    }
+    if info.is_large_diff(ui.large_diff_files, ui.large_diff_lines) {
+        let Some(ask) = ask else {
+            return Err(Error::NothingApplied {
+                reason: format!(
+                    "{} changed {} files and {} lines, more than the large-diff limits; review it without --auto-select",
+                    info.executor_name,
+                    info.files_changed,
+                    info.changed_line_count()
+                ),
+            });
+        };
+        if !ask(Risk::LargeDiff, info)? {
+            return Err(Error::NothingApplied {
+                reason: format!("the large diff of {} was declined", info.executor_name),
+            });
         }
+    }
+    cli::show_progress(&format!(
+        "Auto-selected {}: {}",
+        info.executor_name,
+        scores[index].label()
+    ));
+    Ok(Selection {
+        index,
+        stash: false,
+    })
+}
+
+/// Run the prompt of a run of the history again with the same agents and options
+///
+/// The worktrees check out the commit the run was based on, or HEAD with `head`, and
+/// get the current uncommitted changes as usual; files that changed since the run are
+/// listed first. Results based on another commit than HEAD would undo the newer
+/// commits when applied, so they are only ranked then. The new run records which run
+/// it repeats.
+async fn rerun(args: &Args, record: &RunRecord, head: bool) -> Result<RunOutcome> {
+    let cannot_rerun = |reason: String| Error::CannotRerun {
+        id: record.id.clone(),
+        reason,
+    };
+    let working_dir = PathBuf::from(&args.directory).canonicalize()?;
+    if !git::is_git_repository(&working_dir).await {
+        return Err(Error::NotGitRepository { path: working_dir });
+    }
+    let base = if head {
+        None
     } else {
-        // There are conflicting files
-        if !cli::confirm_apply_with_conflicts(&conflicts)? {
-            cli::show_progress("Apply cancelled.");
-            runner.cleanup().await?;
-            return Err(Error::UserCancelled);
+        let base = record
+            .base_commit
+            .clone()
+            .ok_or_else(|| cannot_rerun("the run was not based on a commit".to_string()))?;
+        if !git::has_commit(&working_dir, &base).await {
+            return Err(cannot_rerun(format!(
+                "commit {base} is not in this repository; fetch it first, or use --head"
+            )));
         }
+        Some(base)
+    };
+
+    let repo_root = git::get_repo_root(&working_dir).await?;
+    let changed = record
+        .snapshot
+        .changed_files(&domain::dirty_file_hashes(&repo_root).await);
+    if !changed.is_empty() {
+        cli::show_changed_since_run(&record.id, &changed);
     }
 
-    cli::show_applying_message(&selected_info.executor_name);
-    domain::apply_result(selected_result, &working_dir).await?;
-    cli::show_success_message();
+    let mut once = args.clone();
+    once.command = None;
+    once.prompt = Some(record.prompt.clone());
+    once.agents = Some(record.candidates.iter().map(|c| c.agent.clone()).collect());
+    once.profile = None;
+    once.set_run_options(&record.options);
+    if let Some(base) = &base
+        && git::get_head_commit(&working_dir).await.ok().as_ref() != Some(base)
+    {
+        cli::show_progress(&format!(
+            "Run {} is based on {base}, not HEAD; the results are ranked but not applied \
+             (use --head to run against HEAD).",
+            record.id
+        ));
+        once.auto_select = None;
+        once.no_select = true;
+        once.pr = false;
+    }
+    once.base = base;
+    once.rerun_of = Some(record.id.clone());
+    Box::pin(run(&once)).await
+}
 
-    // Cleanup worktrees
-    runner.cleanup().await?;
+/// Open a run of the history in the split view again and apply the chosen result
+///
+/// The results are recreated from their saved diffs in new worktrees of the repository
+/// in `--directory`, at the commit the run was based on. Notes taken while reviewing
+/// are saved back to the run.
+async fn review_run(args: &Args, settings: &Settings, mut record: RunRecord) -> Result<RunOutcome> {
+    if cli::verbosity::plain() {
+        return Err(Error::CannotReview {
+            id: record.id,
+            reason: "the split view needs a terminal".to_string(),
+        });
+    }
+    let working_dir = PathBuf::from(&args.directory).canonicalize()?;
+    if !git::is_git_repository(&working_dir).await {
+        return Err(Error::NotGitRepository { path: working_dir });
+    }
+    let repo_root = git::get_repo_root(&working_dir).await?;
+    let repo_settings = RepoSettings::load(&repo_root)?;
 
+    cli::show_progress(&format!("Recreating the results of run {}...", record.id));
+    // Worktrees left behind by an error are removed when the manager is dropped
+    let mut worktrees = WorktreeManager::new(&working_dir)
+        .await?
+        .with_retention(settings.worktrees.clone());
+    let results = domain::restore_candidates(&record, &mut worktrees).await?;
+
+    let mut result_infos = Vec::new();
+    for result in &results {
+        let mut info =
+            domain::prepare_result_info(result, &working_dir, &DisplayOptions::default()).await?;
+        info.conflicts = git::check_conflicts(&result.worktree_path, &working_dir).await?;
+        info.conflicts
+            .retain(|file| !repo_settings.is_generated(file));
+        result_infos.push(info);
+    }
+    domain::mark_duplicates(&mut result_infos);
+    domain::compute_similarity(&mut result_infos);
+
+    // One of the run's agents compares candidates on request, if it is installed here
+    let agents: Vec<String> = record.candidates.iter().map(|c| c.agent.clone()).collect();
+    let comparer = select_executors(settings, Some(&agents), &Budget::default())
+        .await
+        .ok()
+        .and_then(|executors| executors.into_iter().next());
+
+    let mut reviews = record.reviews();
+    let selection = cli::select_result(
+        &results,
+        &result_infos,
+        &record.run_info(),
+        &settings.ui,
+        &mut reviews,
+        comparer,
+    );
+    record.set_reviews(&reviews);
+    if let Err(e) = record.save() {
+        cli::show_progress(&format!("Could not save the notes to the history: {e}"));
+    }
+    let selection = selection?;
+
+    // The result is applied to the repository the run is reviewed in, like a new run
+    let context = RunContext {
+        remote: None,
+        redactor: Redactor::new(&repo_settings.redact.patterns)?,
+        repo_settings,
+        images: Vec::new(),
+        check: None,
+        apply_target: working_dir.clone(),
+        apply_as_patch: false,
+        forge: None,
+        run_info: record.run_info(),
+        events: event_sink(args)?,
+        working_dir,
+        repo_root,
+    };
+    let selected = &results[selection.index];
+    let conflicts = &result_infos[selection.index].conflicts;
+    let agent = selected.execution.executor_name.clone();
+    let summary =
+        apply_selection(args, &context, selected, conflicts, selection, &mut record).await?;
+    worktrees.cleanup().await?;
+
+    Ok(RunOutcome {
+        run_id: Some(record.id.clone()),
+        applied: Some(agent),
+        created: summary.created,
+        updated: summary.updated,
+        deleted: summary.deleted,
+        exit_code: exit_code::SUCCESS,
+        ..RunOutcome::default()
+    })
+}
+
+/// Show the disk usage of the data directory and remove the worktrees not in use
+///
+/// With `--git-dry-run` the worktrees are only listed as the ones that would be removed.
+async fn clean(yes: bool) -> Result<()> {
+    let usage = domain::disk_usage().await?;
+    cli::show_disk_usage(&usage);
+
+    let idle: Vec<_> = usage.idle_worktrees().collect();
+    if idle.is_empty() {
+        println!("No worktrees to remove.");
+        return Ok(());
+    }
+    let dry_run = git::dry_run();
+    if !yes && !dry_run && !cli::confirm_clean(idle.len(), usage.reclaimable())? {
+        return Err(Error::UserCancelled);
+    }
+
+    for worktree in &idle {
+        git::remove_parari_worktree(&worktree.path).await?;
+    }
+    let size = cli::format_size(usage.reclaimable());
+    if dry_run {
+        println!("Would remove {} worktree(s), freeing {size}.", idle.len());
+    } else {
+        println!("Removed {} worktree(s), freed {size}.", idle.len());
+    }
+    Ok(())
+}
+
+/// Write a commented `.parari.toml` at the root of the repository containing `directory`
+async fn init(directory: &Path, gitignore: bool, force: bool) -> Result<()> {
+    let repo_root = git::get_repo_root(&directory.canonicalize()?).await?;
+    let path = repo_root.join(REPO_CONFIG_FILE);
+    if path.exists() && !force {
+        return Err(Error::Config {
+            message: format!(
+                "{} already exists; pass --force to overwrite it",
+                path.display()
+            ),
+        });
+    }
+    std::fs::write(&path, REPO_CONFIG_TEMPLATE)?;
+    println!("Wrote {}", path.display());
+    if gitignore && config::add_to_gitignore(&repo_root)? {
+        println!("Added {REPO_CONFIG_FILE} to .gitignore");
+    }
+    println!("Edit it to set the agents, setup and score commands for this repository.");
+    Ok(())
+}
+
+/// Read or change the user configuration file
+fn configure(action: &ConfigAction) -> Result<()> {
+    let path = config::config_file();
+    match action {
+        ConfigAction::Get { key } => {
+            let settings = Settings::load()?;
+            if let Some(value) = config::get_setting(&settings, key)? {
+                println!("{value}");
+            }
+        }
+        ConfigAction::Set { key, value } => {
+            let content = match std::fs::read_to_string(&path) {
+                Ok(content) => content,
+                Err(e) if e.kind() == std::io::ErrorKind::NotFound => String::new(),
+                Err(e) => return Err(e.into()),
+            };
+            let updated = config::set_setting(&content, key, value)?;
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            std::fs::write(&path, updated)?;
+        }
+        ConfigAction::Edit => {
+            if let Some(parent) = path.parent() {
+                std::fs::create_dir_all(parent)?;
+            }
+            cli::open_editor_for_file(&path)?;
+            Settings::load_from(&path).map_err(|e| match e {
+                Error::Config { message } => Error::Config {
+                    message: format!(
+                        "{message}\nRun `parari config edit` again to fix {}",
+                        path.display()
+                    ),
+                },
+                other => other,
+            })?;
+        }
+        ConfigAction::Path => println!("{}", path.display()),
+    }
     Ok(())
 }
 
@@ -171,14 +1458,125 @@ fn filter_executors(
     }
 }
 
+/// Build the executors of a profile, one per instance
+fn profile_executors(
+    settings: &Settings,
+    profile: &Profile,
+    budget: &Budget,
+) -> Result<Vec<Arc<dyn Executor>>> {
+    profile
+        .instances()
+        .into_iter()
+        .map(|(name, agent)| {
+            let mut resolved = settings.resolve_agent(&agent.agent);
+            resolved.args.extend(agent.args.iter().cloned());
+            resolved.env.extend(agent.env.clone());
+            create_executor(name, &resolved, budget)
+        })
+        .collect()
+}
+
+/// Get the executors for the requested agents, which may include agent aliases
+async fn select_executors(
+    settings: &Settings,
+    agent_filter: Option<&[String]>,
+    budget: &Budget,
+) -> Result<Vec<Arc<dyn Executor>>> {
+    let Some(agents) = agent_filter else {
+        return get_executors(None, budget).await;
+    };
+    let (aliases, builtin): (Vec<String>, Vec<String>) = agents
+        .iter()
+        .cloned()
+        .partition(|name| settings.agents.contains_key(name));
+
+    let mut executors = if builtin.is_empty() {
+        Vec::new()
+    } else {
+        get_executors(Some(&builtin), budget).await?
+    };
+    for name in aliases {
+        let alias = settings.resolve_agent(&name);
+        executors.push(create_executor(name, &alias, budget)?);
+    }
+    Ok(executors)
+}
+
+/// Create a mock executor standing in for a configured agent
+#[cfg(feature = "mock")]
+fn create_executor(
+    mut name: String,
+    agent: &AgentAlias,
+    _budget: &Budget,
+) -> Result<Arc<dyn Executor>> {
+    // Missing variables of an alias fail the same way as with the real agents
+    agent.environment(&name)?;
+    name.insert_str(0, "mock-");
+    let file = format!("{name}-output.txt");
+    Ok(Arc::new(MockExecutor::new(name).with_file(
+        file,
+        format!("This is mock output from {}", agent.agent),
+    )))
+}
+
+/// Create the executor of a configured agent, named `name` and passing its flags
+///
+/// Agents whose CLI has a spending limit get the `budget` passed to it.
+#[cfg(not(feature = "mock"))]
+fn create_executor(name: String, agent: &AgentAlias, budget: &Budget) -> Result<Arc<dyn Executor>> {
+    let args = agent.args.clone();
+    let env = agent.environment(&name)?;
+    match agent.agent.as_str() {
+        "claude" => Ok(Arc::new(
+            ClaudeExecutor::new()
+                .with_name(name)
+                .with_args(args)
+                .with_env(env)
+                .with_max_cost(budget.max_cost),
+        )),
+        "gemini" => Ok(Arc::new(
+            GeminiExecutor::new()
+                .with_name(name)
+                .with_args(args)
+                .with_env(env),
+        )),
+        "codex" => Ok(Arc::new(
+            CodexExecutor::new()
+                .with_name(name)
+                .with_args(args)
+                .with_env(env),
+        )),
+        other => Err(Error::Config {
+            message: format!(
+                "unknown agent \"{other}\" for \"{name}\"; available agents: claude, gemini, codex"
+            ),
+        }),
+    }
+}
+
 /// Get all available executors (mock version for development/testing)
+///
+/// The agents come from the fixture file in `PARARI_MOCK_FIXTURE` when it is set.
 #[cfg(feature = "mock")]
-async fn get_executors(agent_filter: Option<&[String]>) -> Vec<Arc<dyn Executor>> {
+async fn get_executors(
+    agent_filter: Option<&[String]>,
+    _budget: &Budget,
+) -> Result<Vec<Arc<dyn Executor>>> {
     eprintln!("[MOCK MODE] Using mock executors for development");
 
     // Yield to the async runtime to satisfy the async contract
     tokio::task::yield_now().await;
 
+    if let Some(fixture) = Fixture::from_env()? {
+        let mut executors: Vec<Arc<dyn Executor>> = Vec::new();
+        for mock in fixture.executors() {
+            if mock.is_available().await {
+                executors.push(Arc::new(mock));
+            }
+        }
+        return Ok(filter_executors(executors, agent_filter));
+    }
+
     let all_executors: Vec<Arc<dyn Executor>> = vec![
         Arc::new(
             MockExecutor::new("mock-claude")
@@ -194,15 +1592,18 @@ async fn get_executors(agent_filter: Option<&[String]>) -> Vec<Arc<dyn Executor>
         ),
     ];
 
-    filter_executors(all_executors, agent_filter)
+    Ok(filter_executors(all_executors, agent_filter))
 }
 
 /// Get all available executors (production version)
 #[cfg(not(feature = "mock"))]
-async fn get_executors(agent_filter: Option<&[String]>) -> Vec<Arc<dyn Executor>> {
+async fn get_executors(
+    agent_filter: Option<&[String]>,
+    budget: &Budget,
+) -> Result<Vec<Arc<dyn Executor>>> {
     let mut executors: Vec<Arc<dyn Executor>> = Vec::new();
 
-    let claude = Arc::new(ClaudeExecutor::new());
+    let claude = Arc::new(ClaudeExecutor::new().with_max_cost(budget.max_cost));
     if claude.is_available().await {
         executors.push(claude);
     }
@@ -217,5 +1618,101 @@ async fn get_executors(agent_filter: Option<&[String]>) -> Vec<Arc<dyn Executor>
         executors.push(codex);
     }
 
-    filter_executors(executors, agent_filter)
+    Ok(filter_executors(executors, agent_filter))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::path::PathBuf;
+
+    use parari::config::ScoringWeights;
+
+    use super::*;
+
+    fn info(files_changed: usize, conflicts: &[&str]) -> ResultInfo {
+        ResultInfo {
+            executor_name: "claude".to_string(),
+            success: true,
+            files_changed,
+            change_summary: None,
+            summary: None,
+            worktree_path: PathBuf::from("/tmp/test"),
+            stdout: String::new(),
+            stderr: String::new(),
+            output_lines: vec![],
+            conflicts: conflicts.iter().map(ToString::to_string).collect(),
+            score: None,
+            check: None,
+            duration: None,
+            breakdown: None,
+            diff_hash: None,
+            duplicate_of: None,
+            changed_lines: vec![],
+            similarity: vec![],
+            retries: vec![],
+            error_kind: None,
+            secrets: vec![],
+            policy: vec![],
+        }
+    }
+
+    /// Auto-select `info`, answering `answer` to every question and recording the risks
+    /// asked about, or without a terminal when `answer` is `None`
+    fn select(
+        info: ResultInfo,
+        ui: &UiSettings,
+        answer: Option<bool>,
+    ) -> (Result<Selection>, Vec<Risk>) {
+        let infos = [info];
+        let scores =
+            domain::score_candidates(&infos, &ScoringWeights::default(), &AgentPriors::default());
+        let asked = RefCell::new(Vec::new());
+        let ask = |risk: Risk, _: &ResultInfo| {
+            asked.borrow_mut().push(risk);
+            Ok(answer.unwrap_or_default())
+        };
+        let ask: Option<&Ask<'_>> = answer.is_some().then_some(&ask);
+        let selection = auto_select(&infos, &scores, ui, false, ask);
+        (selection, asked.into_inner())
+    }
+
+    #[test]
+    fn test_auto_select_confirms_conflicts() {
+        let ui = UiSettings::default();
+        let (selection, asked) = select(info(1, &["src/lib.rs"]), &ui, Some(true));
+        assert!(matches!(selection, Ok(Selection { index: 0, .. })));
+        assert_eq!(asked, [Risk::Conflicts]);
+
+        let (selection, _) = select(info(1, &["src/lib.rs"]), &ui, Some(false));
+        assert!(matches!(selection, Err(Error::NothingApplied { .. })));
+        let (selection, asked) = select(info(1, &["src/lib.rs"]), &ui, None);
+        assert!(matches!(selection, Err(Error::NothingApplied { .. })));
+        assert!(asked.is_empty());
+
+        let (selection, asked) = select(info(1, &[]), &ui, Some(false));
+        assert!(selection.is_ok());
+        assert!(asked.is_empty());
+    }
+
+    #[test]
+    fn test_auto_select_confirms_large_diffs() {
+        let ui = UiSettings {
+            large_diff_files: 2,
+            ..UiSettings::default()
+        };
+        let (selection, asked) = select(info(3, &[]), &ui, Some(true));
+        assert!(selection.is_ok());
+        assert_eq!(asked, [Risk::LargeDiff]);
+
+        let (selection, _) = select(info(3, &[]), &ui, Some(false));
+        assert!(
+            matches!(selection, Err(Error::NothingApplied { reason }) if reason.contains("declined"))
+        );
+        // Without a terminal the diff is refused rather than applied unseen
+        let (selection, _) = select(info(3, &[]), &ui, None);
+        assert!(
+            matches!(selection, Err(Error::NothingApplied { reason }) if reason.contains("large-diff limits"))
+        );
+    }
 }
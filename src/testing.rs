@@ -0,0 +1,364 @@
+//! Helpers for testing code built on parari
+//!
+//! Sets up throwaway git repositories, runs mock agents in them, and checks the
+//! results, so tests do not repeat the same boilerplate. Available with the `mock`
+//! feature:
+//!
+//! ```no_run
+//! # async fn example() -> parari::error::Result<()> {
+//! use parari::executor::MockExecutor;
+//! use parari::testing::{TestRepo, TestRun, assert_file_contains, assert_succeeded};
+//!
+//! let repo = TestRepo::builder().with_file("src/lib.rs", "").build()?;
+//! let run = TestRun::start(
+//!     &repo,
+//!     "Add a parser",
+//!     vec![MockExecutor::new("claude").with_file("src/lib.rs", "pub fn parse() {}")],
+//! )
+//! .await?;
+//! let result = run.result("claude");
+//! assert_succeeded(result);
+//! assert_file_contains(result, "src/lib.rs", "parse");
+//! run.cleanup().await
+//! # }
+//! ```
+
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use std::sync::Arc;
+
+use tempfile::TempDir;
+
+use crate::domain::{DisplayOptions, ResultInfo, TaskResult, TaskRunner, prepare_result_info};
+use crate::error::{Error, Result};
+use crate::executor::MockExecutor;
+use crate::executor::traits::{ErrorKind, Executor};
+
+/// A git repository in a temporary directory, removed when dropped
+#[derive(Debug)]
+pub struct TestRepo {
+    dir: TempDir,
+    path: PathBuf,
+}
+
+/// Builds a [`TestRepo`] with committed files
+#[derive(Debug, Default)]
+pub struct TestRepoBuilder {
+    files: Vec<(String, String)>,
+}
+
+impl TestRepoBuilder {
+    /// Commit a file with the given content in the initial commit
+    #[must_use]
+    pub fn with_file(mut self, path: impl Into<String>, content: impl Into<String>) -> Self {
+        self.files.push((path.into(), content.into()));
+        self
+    }
+
+    /// Create the repository with a README and the added files committed
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or a git command fails.
+    pub fn build(self) -> Result<TestRepo> {
+        let dir = tempfile::tempdir()?;
+        // Worktree paths are compared with canonical paths, e.g. /private/var on macOS
+        let path = dir.path().canonicalize()?;
+        let repo = TestRepo { dir, path };
+        repo.git(&["init", "-q"])?;
+        repo.write("README.md", "# Test Project\n")?;
+        for (file, content) in &self.files {
+            repo.write(file, content)?;
+        }
+        repo.commit("Initial commit")?;
+        Ok(repo)
+    }
+}
+
+impl TestRepo {
+    /// A repository with a committed README
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the directory cannot be created or a git command fails.
+    pub fn new() -> Result<Self> {
+        Self::builder().build()
+    }
+
+    /// Start building a repository with more committed files
+    #[must_use]
+    pub fn builder() -> TestRepoBuilder {
+        TestRepoBuilder::default()
+    }
+
+    /// Root of the repository
+    #[must_use]
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Run git in the repository as a test user, returning its stdout
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if git cannot be started or exits with a non-zero status.
+    pub fn git(&self, args: &[&str]) -> Result<String> {
+        let output = Command::new("git")
+            .args([
+                "-c",
+                "user.name=Test User",
+                "-c",
+                "user.email=test@test.com",
+            ])
+            .args(["-c", "commit.gpgsign=false"])
+            .args(args)
+            .current_dir(&self.path)
+            .output()?;
+        if !output.status.success() {
+            return Err(Error::GitCommand {
+                message: format!(
+                    "git {} failed: {}",
+                    args.join(" "),
+                    String::from_utf8_lossy(&output.stderr).trim()
+                ),
+            });
+        }
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    }
+
+    /// Write a file relative to the root, creating its directories
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be written.
+    pub fn write(&self, path: &str, content: &str) -> Result<()> {
+        let full_path = self.path.join(path);
+        if let Some(parent) = full_path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(full_path, content)?;
+        Ok(())
+    }
+
+    /// Read a file relative to the root
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the file cannot be read.
+    pub fn read(&self, path: &str) -> Result<String> {
+        Ok(std::fs::read_to_string(self.path.join(path))?)
+    }
+
+    /// Commit every change in the repository
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a git command fails.
+    pub fn commit(&self, message: &str) -> Result<()> {
+        self.git(&["add", "-A"])?;
+        self.git(&["commit", "-q", "--allow-empty", "-m", message])?;
+        Ok(())
+    }
+
+    /// Keep the directory after the repository is dropped, e.g. to look at a failed test
+    #[must_use]
+    pub fn keep(self) -> PathBuf {
+        let _ = self.dir.keep();
+        self.path
+    }
+}
+
+/// Mock agents run on a prompt in a [`TestRepo`], with their results
+pub struct TestRun {
+    /// The runner, holding the worktrees until [`TestRun::cleanup`]
+    pub runner: TaskRunner,
+    /// One result per agent
+    pub results: Vec<TaskResult>,
+}
+
+impl TestRun {
+    /// Run the agents on the prompt, without asking them for summaries
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the worktrees cannot be created or the run fails.
+    pub async fn start(repo: &TestRepo, prompt: &str, agents: Vec<MockExecutor>) -> Result<Self> {
+        let executors: Vec<Arc<dyn Executor>> = agents
+            .into_iter()
+            .map(|agent| Arc::new(agent) as Arc<dyn Executor>)
+            .collect();
+        Self::start_with(
+            TaskRunner::new(repo.path()).await?.with_summaries(false),
+            prompt,
+            executors,
+        )
+        .await
+    }
+
+    /// Run the executors on the prompt with a runner configured by the caller
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the run fails.
+    pub async fn start_with(
+        mut runner: TaskRunner,
+        prompt: &str,
+        executors: Vec<Arc<dyn Executor>>,
+    ) -> Result<Self> {
+        let results = runner.run(prompt, executors).await?;
+        Ok(Self { runner, results })
+    }
+
+    /// The result of the named agent
+    ///
+    /// # Panics
+    ///
+    /// Panics if no agent of that name was run.
+    #[must_use]
+    pub fn result(&self, agent: &str) -> &TaskResult {
+        let found = self
+            .results
+            .iter()
+            .find(|result| result.execution.executor_name == agent);
+        match found {
+            Some(result) => result,
+            None => panic!("no result of agent \"{agent}\""),
+        }
+    }
+
+    /// The results as shown in the split view, compared against `repo`
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a diff cannot be computed.
+    pub async fn result_infos(&self, repo: &TestRepo) -> Result<Vec<ResultInfo>> {
+        let mut infos = Vec::new();
+        for result in &self.results {
+            infos.push(prepare_result_info(result, repo.path(), &DisplayOptions::default()).await?);
+        }
+        Ok(infos)
+    }
+
+    /// Remove the worktrees of the run
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if a worktree cannot be removed.
+    pub async fn cleanup(mut self) -> Result<()> {
+        self.runner.cleanup().await
+    }
+}
+
+/// Assert that the agent finished successfully
+///
+/// # Panics
+///
+/// Panics with the agent's error output if it failed.
+pub fn assert_succeeded(result: &TaskResult) {
+    assert!(
+        result.execution.success,
+        "{} failed: {}",
+        result.execution.executor_name, result.execution.stderr
+    );
+}
+
+/// Assert that the agent failed for the given reason
+///
+/// # Panics
+///
+/// Panics if the agent succeeded or failed for another reason.
+pub fn assert_failed_with(result: &TaskResult, kind: ErrorKind) {
+    assert!(
+        !result.execution.success,
+        "{} succeeded",
+        result.execution.executor_name
+    );
+    assert_eq!(
+        result.execution.error_kind,
+        Some(kind),
+        "{} failed with: {}",
+        result.execution.executor_name,
+        result.execution.stderr
+    );
+}
+
+/// Assert that a file in the agent's worktree contains `expected`
+///
+/// # Panics
+///
+/// Panics if the file cannot be read or does not contain `expected`.
+pub fn assert_file_contains(result: &TaskResult, path: &str, expected: &str) {
+    let content = std::fs::read_to_string(result.worktree_path.join(path))
+        .unwrap_or_else(|e| panic!("cannot read {path}: {e}"));
+    assert!(
+        content.contains(expected),
+        "{path} does not contain {expected:?}:\n{content}"
+    );
+}
+
+/// Assert which files the result changed, in any order
+///
+/// # Panics
+///
+/// Panics if the changed files differ from `expected`.
+pub fn assert_changed_files(info: &ResultInfo, expected: &[&str]) {
+    let mut changed: Vec<&str> = info
+        .change_summary
+        .as_ref()
+        .map(|summary| {
+            summary
+                .changed_files
+                .iter()
+                .map(|file| file.path.as_str())
+                .collect()
+        })
+        .unwrap_or_default();
+    changed.sort_unstable();
+    let mut expected = expected.to_vec();
+    expected.sort_unstable();
+    assert_eq!(
+        changed, expected,
+        "{} changed other files",
+        info.executor_name
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_repo_builder() -> Result<()> {
+        let repo = TestRepo::builder()
+            .with_file("src/lib.rs", "pub fn a() {}\n")
+            .build()?;
+        assert_eq!(repo.read("src/lib.rs")?, "pub fn a() {}\n");
+        assert_eq!(repo.git(&["status", "--porcelain"])?, "");
+        assert!(repo.git(&["log", "--oneline"])?.contains("Initial commit"));
+        assert!(repo.git(&["no-such-command"]).is_err());
+        Ok(())
+    }
+
+    #[tokio::test]
+    async fn test_run_harness() -> Result<()> {
+        let repo = TestRepo::new()?;
+        let run = TestRun::start(
+            &repo,
+            "Add a file",
+            vec![
+                MockExecutor::new("writer").with_file("notes.txt", "hello\n"),
+                MockExecutor::new("limited")
+                    .with_failure("Error: 429 rate limit exceeded", Some(1)),
+            ],
+        )
+        .await?;
+
+        assert_succeeded(run.result("writer"));
+        assert_file_contains(run.result("writer"), "notes.txt", "hello");
+        assert_failed_with(run.result("limited"), ErrorKind::RateLimit);
+        let infos = run.result_infos(&repo).await?;
+        let writer = infos.iter().find(|info| info.executor_name == "writer");
+        assert_changed_files(writer.ok_or(Error::NoExecutorsAvailable)?, &["notes.txt"]);
+        run.cleanup().await
+    }
+}
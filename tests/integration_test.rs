@@ -4,59 +4,11 @@
 
 use std::sync::Arc;
 
-use parari::domain::{TaskRunner, apply_result};
+use parari::config::{RedactSettings, RepoSettings};
+use parari::domain::{ABORTED, Abort, TaskRunner, apply_result, apply_result_with_progress};
 use parari::executor::mock::MockExecutor;
 use parari::executor::traits::Executor;
-
-fn unique_temp_dir(
-    name: &str,
-) -> std::result::Result<std::path::PathBuf, Box<dyn std::error::Error>> {
-    let timestamp = std::time::SystemTime::now()
-        .duration_since(std::time::UNIX_EPOCH)?
-        .as_nanos();
-    Ok(std::env::temp_dir().join(format!("parari_test_{name}_{timestamp}")))
-}
-
-async fn setup_git_repo(
-    path: &std::path::Path,
-) -> std::result::Result<(), Box<dyn std::error::Error>> {
-    tokio::fs::create_dir_all(path).await?;
-
-    tokio::process::Command::new("git")
-        .args(["init"])
-        .current_dir(path)
-        .output()
-        .await?;
-
-    // Configure git for this test repo
-    tokio::process::Command::new("git")
-        .args(["config", "user.email", "test@test.com"])
-        .current_dir(path)
-        .output()
-        .await?;
-
-    tokio::process::Command::new("git")
-        .args(["config", "user.name", "Test User"])
-        .current_dir(path)
-        .output()
-        .await?;
-
-    tokio::fs::write(path.join("README.md"), "# Test Project\n").await?;
-
-    tokio::process::Command::new("git")
-        .args(["add", "."])
-        .current_dir(path)
-        .output()
-        .await?;
-
-    tokio::process::Command::new("git")
-        .args(["commit", "-m", "Initial commit"])
-        .current_dir(path)
-        .output()
-        .await?;
-
-    Ok(())
-}
+use parari::testing::{TestRepo, TestRun, assert_file_contains, assert_succeeded};
 
 /// Test the full workflow:
 /// 1. Create worktrees for multiple executors
@@ -66,158 +18,210 @@ async fn setup_git_repo(
 #[tokio::test]
 async fn test_full_workflow_with_mock_executors()
 -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let temp_dir = unique_temp_dir("full_workflow")?;
-    if temp_dir.exists() {
-        tokio::fs::remove_dir_all(&temp_dir).await?;
-    }
-
-    setup_git_repo(&temp_dir).await?;
+    let repo = TestRepo::new()?;
 
     // Create mock executors with different file outputs
-    let claude_mock = Arc::new(
-        MockExecutor::new("claude")
-            .with_file(
-                "src/main.rs",
-                "fn main() { println!(\"Hello from Claude!\"); }",
-            )
-            .with_file("src/lib.rs", "pub fn claude_helper() {}")
-            .with_success("Created files from Claude"),
-    ) as Arc<dyn Executor>;
-
-    let gemini_mock = Arc::new(
-        MockExecutor::new("gemini")
-            .with_file(
-                "src/main.rs",
-                "fn main() { println!(\"Hello from Gemini!\"); }",
-            )
-            .with_file("src/utils.rs", "pub fn gemini_util() {}")
-            .with_success("Created files from Gemini"),
-    ) as Arc<dyn Executor>;
-
-    let executors: Vec<Arc<dyn Executor>> = vec![claude_mock, gemini_mock];
-
-    // Create task runner and run
-    let mut runner = TaskRunner::new(&temp_dir).await?;
-    let results = runner.run("Create a Rust project", executors).await?;
+    let claude_mock = MockExecutor::new("claude")
+        .with_file(
+            "src/main.rs",
+            "fn main() { println!(\"Hello from Claude!\"); }",
+        )
+        .with_file("src/lib.rs", "pub fn claude_helper() {}")
+        .with_success("Created files from Claude");
+
+    let gemini_mock = MockExecutor::new("gemini")
+        .with_file(
+            "src/main.rs",
+            "fn main() { println!(\"Hello from Gemini!\"); }",
+        )
+        .with_file("src/utils.rs", "pub fn gemini_util() {}")
+        .with_success("Created files from Gemini");
+
+    let run = TestRun::start(
+        &repo,
+        "Create a Rust project",
+        vec![claude_mock, gemini_mock],
+    )
+    .await?;
 
     // Verify we got results from both executors
-    assert_eq!(results.len(), 2);
-
-    // Find Claude's result
-    let claude_result = results
-        .iter()
-        .find(|r| r.execution.executor_name == "claude")
-        .ok_or("Claude result not found")?;
+    assert_eq!(run.results.len(), 2);
 
     // Verify Claude's worktree has the expected files
-    let claude_main =
-        tokio::fs::read_to_string(claude_result.worktree_path.join("src/main.rs")).await?;
-    assert!(claude_main.contains("Hello from Claude!"));
-
-    let claude_lib =
-        tokio::fs::read_to_string(claude_result.worktree_path.join("src/lib.rs")).await?;
-    assert!(claude_lib.contains("claude_helper"));
+    let claude_result = run.result("claude");
+    assert_succeeded(claude_result);
+    assert_file_contains(claude_result, "src/main.rs", "Hello from Claude!");
+    assert_file_contains(claude_result, "src/lib.rs", "claude_helper");
 
     // Apply Claude's result to the original directory
-    apply_result(claude_result, &temp_dir).await?;
+    apply_result(claude_result, repo.path()).await?;
 
     // Verify the changes were applied
-    let applied_main = tokio::fs::read_to_string(temp_dir.join("src/main.rs")).await?;
-    assert!(applied_main.contains("Hello from Claude!"));
+    assert!(repo.read("src/main.rs")?.contains("Hello from Claude!"));
+    assert!(repo.read("src/lib.rs")?.contains("claude_helper"));
 
-    let applied_lib = tokio::fs::read_to_string(temp_dir.join("src/lib.rs")).await?;
-    assert!(applied_lib.contains("claude_helper"));
-
-    // Cleanup
-    runner.cleanup().await?;
-    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+    run.cleanup().await?;
+    Ok(())
+}
 
+/// Test that applying a result leaves the files matching the secret patterns alone
+#[tokio::test]
+async fn test_apply_skips_secret_files() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let repo = TestRepo::builder()
+        .with_file("keys/deploy.pem", "real key\n")
+        .build()?;
+    repo.write(".env", "TOKEN=real\n")?;
+
+    let mock = MockExecutor::new("claude")
+        .with_file(".env", "TOKEN=agent\n")
+        .with_delete("keys/deploy.pem")
+        .with_file("src/lib.rs", "pub fn f() {}\n")
+        .with_success("Done");
+    let run = TestRun::start(&repo, "Rotate the keys", vec![mock]).await?;
+
+    let repo_settings = RepoSettings {
+        redact: RedactSettings {
+            files: vec![".env".to_string(), "*.pem".to_string()],
+            ..RedactSettings::default()
+        },
+        ..RepoSettings::default()
+    };
+    let summary =
+        apply_result_with_progress(run.result("claude"), repo.path(), &repo_settings, |_, _| {})
+            .await?;
+
+    assert_eq!(summary.created, ["src/lib.rs"]);
+    assert!(summary.deleted.is_empty());
+    assert_eq!(repo.read(".env")?, "TOKEN=real\n");
+    assert_eq!(repo.read("keys/deploy.pem")?, "real key\n");
+
+    run.cleanup().await?;
     Ok(())
 }
 
 /// Test that worktrees are properly cleaned up
 #[tokio::test]
 async fn test_worktree_cleanup() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let temp_dir = unique_temp_dir("cleanup")?;
-    if temp_dir.exists() {
-        tokio::fs::remove_dir_all(&temp_dir).await?;
-    }
-
-    setup_git_repo(&temp_dir).await?;
+    let repo = TestRepo::new()?;
 
     // Create mock executor
-    let mock = Arc::new(
-        MockExecutor::new("test_cleanup")
-            .with_file("test.txt", "test content")
-            .with_success("Done"),
-    ) as Arc<dyn Executor>;
-
-    let executors: Vec<Arc<dyn Executor>> = vec![mock];
+    let mock = MockExecutor::new("test_cleanup")
+        .with_file("test.txt", "test content")
+        .with_success("Done");
 
     // Run and cleanup
-    let mut runner = TaskRunner::new(&temp_dir).await?;
-    let results = runner.run("Test", executors).await?;
+    let run = TestRun::start(&repo, "Test", vec![mock]).await?;
 
     // Get worktree path before cleanup
-    let worktree_path = results[0].worktree_path.clone();
+    let worktree_path = run.results[0].worktree_path.clone();
     assert!(worktree_path.exists());
 
-    // Cleanup
-    runner.cleanup().await?;
+    run.cleanup().await?;
 
     // Verify worktree is removed
     assert!(!worktree_path.exists());
-
-    // Final cleanup
-    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
-
     Ok(())
 }
 
 /// Test change summary detection
 #[tokio::test]
 async fn test_change_summary() -> std::result::Result<(), Box<dyn std::error::Error>> {
-    let temp_dir = unique_temp_dir("change_summary")?;
-    if temp_dir.exists() {
-        tokio::fs::remove_dir_all(&temp_dir).await?;
-    }
+    // Start from an existing file
+    let repo = TestRepo::builder()
+        .with_file("existing.txt", "existing content\n")
+        .build()?;
 
-    setup_git_repo(&temp_dir).await?;
-
-    // Add an existing file
-    tokio::fs::write(temp_dir.join("existing.txt"), "existing content\n").await?;
+    // Create mock executor that adds new files
+    let mock = MockExecutor::new("test_summary")
+        .with_file("new_file.txt", "new content")
+        .with_file("another_new.txt", "more content")
+        .with_success("Done");
 
-    tokio::process::Command::new("git")
-        .args(["add", "."])
-        .current_dir(&temp_dir)
-        .output()
-        .await?;
+    let run = TestRun::start(&repo, "Add files", vec![mock]).await?;
 
-    tokio::process::Command::new("git")
-        .args(["commit", "-m", "Add existing file"])
-        .current_dir(&temp_dir)
-        .output()
-        .await?;
+    assert_eq!(run.results.len(), 1);
 
-    // Create mock executor that adds new files
-    let mock = Arc::new(
-        MockExecutor::new("test_summary")
-            .with_file("new_file.txt", "new content")
-            .with_file("another_new.txt", "more content")
-            .with_success("Done"),
-    ) as Arc<dyn Executor>;
+    // New files are counted as added, with their lines included in the stats
+    let summary = run.results[0]
+        .change_summary
+        .as_ref()
+        .ok_or("Change summary not found")?;
+    assert_eq!(summary.files_added, 2);
+    assert_eq!(summary.insertions, 2);
+    assert_eq!(summary.deletions, 0);
 
-    let executors: Vec<Arc<dyn Executor>> = vec![mock];
+    run.cleanup().await?;
+    Ok(())
+}
 
-    // Run
-    let mut runner = TaskRunner::new(&temp_dir).await?;
-    let results = runner.run("Add files", executors).await?;
+/// Test that an aborted run stops every agent and keeps their worktrees for inspection
+#[tokio::test]
+async fn test_aborted_run() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    let repo = TestRepo::new()?;
+    let abort = Abort::new();
+    abort.trigger();
+    let runner = TaskRunner::new(repo.path())
+        .await?
+        .with_summaries(false)
+        .with_abort(abort);
+    let agents: Vec<Arc<dyn Executor>> = vec![
+        Arc::new(MockExecutor::new("claude").with_success("Done")),
+        Arc::new(MockExecutor::new("gemini").with_success("Done")),
+    ];
+
+    let run = TestRun::start_with(runner, "Test", agents).await?;
+    assert_eq!(run.results.len(), 2);
+    for result in &run.results {
+        assert!(!result.execution.success);
+        assert_eq!(result.execution.stderr, ABORTED);
+        assert!(result.worktree_path.exists());
+    }
 
-    assert_eq!(results.len(), 1);
+    run.cleanup().await?;
+    Ok(())
+}
 
-    // Cleanup
-    runner.cleanup().await?;
-    let _ = tokio::fs::remove_dir_all(&temp_dir).await;
+/// Run the parari binary with a home of its own and no `PARARI_*` variables but `env`
+fn parari(
+    home: &std::path::Path,
+    env: &[(&str, &str)],
+    args: &[&str],
+) -> std::io::Result<std::process::Output> {
+    let mut command = std::process::Command::new(env!("CARGO_BIN_EXE_parari"));
+    for (key, _) in std::env::vars_os() {
+        if key.to_string_lossy().starts_with("PARARI_") {
+            command.env_remove(key);
+        }
+    }
+    command
+        .env("HOME", home)
+        .envs(env.iter().copied())
+        .args(args);
+    command.output()
+}
 
+/// Test that a flag overrides the environment variable of an option it excludes,
+/// while two options from the same source still conflict
+#[test]
+fn test_flags_override_environment() -> std::result::Result<(), Box<dyn std::error::Error>> {
+    // The arguments are accepted when the run gets as far as checking the directory
+    let home = tempfile::tempdir()?;
+    let dir = home.path().to_string_lossy().into_owned();
+    let stderr = |env: &[(&str, &str)], flags: &[&str]| -> std::io::Result<String> {
+        let args: Vec<&str> = flags.iter().copied().chain(["-C", &dir, "x"]).collect();
+        let output = parari(home.path(), env, &args)?;
+        assert!(!output.status.success());
+        Ok(String::from_utf8_lossy(&output.stderr).into_owned())
+    };
+
+    let env = [("PARARI_AUTO_SELECT", "weighted")];
+    assert!(stderr(&env, &["--no-select"])?.contains("Not a git repository"));
+    let env = [("PARARI_AGENTS", "claude,gemini")];
+    assert!(stderr(&env, &["--profile", "thorough"])?.contains("Not a git repository"));
+
+    let env = [("PARARI_AGENTS", "claude"), ("PARARI_PROFILE", "thorough")];
+    assert!(stderr(&env, &[])?.contains("cannot be used with"));
+    let flags = ["--auto-select", "weighted", "--no-select"];
+    assert!(stderr(&[], &flags)?.contains("cannot be used with"));
     Ok(())
 }